@@ -96,6 +96,30 @@ async fn main() -> Result<()> {
         assert_eq!(file_config.local_addr, config.local_addr);
     }
 
+    if command_line_args.metrics_addr.is_some() {
+        assert_eq!(command_line_args.metrics_addr, config.metrics_addr);
+    } else {
+        assert_eq!(file_config.metrics_addr, config.metrics_addr);
+    }
+
+    if command_line_args.chunk_store.is_some() {
+        assert_eq!(command_line_args.chunk_store, config.chunk_store);
+    } else {
+        assert_eq!(file_config.chunk_store, config.chunk_store);
+    }
+
+    if command_line_args.admin_addr.is_some() {
+        assert_eq!(command_line_args.admin_addr, config.admin_addr);
+    } else {
+        assert_eq!(file_config.admin_addr, config.admin_addr);
+    }
+
+    if command_line_args.admin_token.is_some() {
+        assert_eq!(command_line_args.admin_token, config.admin_token);
+    } else {
+        assert_eq!(file_config.admin_token, config.admin_token);
+    }
+
     if command_line_args.first {
         assert!(config.first);
     }
@@ -30,19 +30,30 @@
 use color_eyre::{Section, SectionExt};
 use eyre::{eyre, Result, WrapErr};
 use safe_network::{
-    node::{add_connection_info, set_connection_info, Config, Error, Node},
+    node::{
+        add_connection_info, admin::LogFilterHandler, set_connection_info, Config, Error, Node,
+    },
     LogFormatter,
 };
 use self_update::{cargo_crate_version, Status};
 use std::{io::Write, process::exit};
 use structopt::{clap, StructOpt};
-use tokio::time::{sleep, Duration};
+use tokio::{
+    sync::mpsc,
+    time::{sleep, timeout, Duration},
+};
 use tracing::{self, error, info, trace, warn};
 use tracing_subscriber::filter::EnvFilter;
+#[cfg(not(feature = "tokio-console"))]
+use tracing_subscriber::reload;
 
 const MODULE_NAME: &str = "safe_network";
 const BOOTSTRAP_RETRY_TIME: u64 = 3; // in minutes
 
+// How long to wait for the node to leave the network gracefully, i.e. for its elders to agree on
+// it going offline, before exiting anyway.
+const LEAVE_TIMEOUT: Duration = Duration::from_secs(30);
+
 fn main() -> Result<()> {
     color_eyre::install()?;
     #[cfg(feature = "tokio-console")]
@@ -83,6 +94,10 @@ async fn run_node() -> Result<()> {
     // Set up logging
     // ==============
 
+    #[cfg(feature = "tokio-console")]
+    let log_filter: Option<LogFilterHandler> = None;
+    #[cfg(not(feature = "tokio-console"))]
+    let log_filter: Option<LogFilterHandler>;
     #[cfg(not(feature = "tokio-console"))]
     {
         let filter = match EnvFilter::try_from_env("RUST_LOG") {
@@ -121,22 +136,30 @@ async fn run_node() -> Result<()> {
                 .with_writer(non_blocking);
 
             if config.json_logs {
-                builder.json().init();
+                let builder = builder.json().with_filter_reloading();
+                log_filter = Some(log_filter_handler(builder.reload_handle()));
+                builder.init();
             } else {
-                builder.event_format(LogFormatter::default()).init();
+                let builder = builder
+                    .event_format(LogFormatter::default())
+                    .with_filter_reloading();
+                log_filter = Some(log_filter_handler(builder.reload_handle()));
+                builder.init();
             }
 
             Some(guard)
         } else {
             println!("Starting logging to stdout");
 
-            tracing_subscriber::fmt()
+            let builder = tracing_subscriber::fmt()
                 .with_thread_names(true)
                 .with_ansi(false)
                 .with_env_filter(EnvFilter::from_default_env())
                 .with_target(false)
                 .event_format(LogFormatter::default())
-                .init();
+                .with_filter_reloading();
+            log_filter = Some(log_filter_handler(builder.reload_handle()));
+            builder.init();
 
             None
         };
@@ -250,14 +273,96 @@ async fn run_node() -> Result<()> {
             });
     }
 
-    // This just keeps the node going as long as routing goes
-    while let Some(event) = event_stream.next().await {
-        trace!("Routing event! {:?}", event);
+    let mut leave_requests = match config.admin_addr {
+        Some(admin_addr) => {
+            Some(node.start_admin_endpoint(admin_addr, config.admin_token.clone(), log_filter)?)
+        }
+        None => None,
+    };
+
+    let mut terminate_signals = terminate_signals()?;
+
+    // This just keeps the node going as long as routing goes, or until asked to leave
+    loop {
+        tokio::select! {
+            event = event_stream.next() => match event {
+                Some(event) => trace!("Routing event! {:?}", event),
+                None => break,
+            },
+            Some(()) = next_leave_request(&mut leave_requests) => {
+                info!("Leaving the network as requested through the admin interface");
+                leave(&node).await;
+                break;
+            }
+            Some(()) = next_terminate_signal(&mut terminate_signals) => {
+                info!("Leaving the network as SIGTERM was received");
+                leave(&node).await;
+                break;
+            }
+        }
     }
 
     Ok(())
 }
 
+// Leaves the network gracefully, giving up on it if it takes longer than `LEAVE_TIMEOUT`.
+async fn leave(node: &Node) {
+    match timeout(LEAVE_TIMEOUT, node.leave()).await {
+        Ok(Ok(())) => {}
+        Ok(Err(error)) => error!("Failed to leave the network gracefully: {:?}", error),
+        Err(_) => error!(
+            "Timed out after {:?} leaving the network gracefully",
+            LEAVE_TIMEOUT
+        ),
+    }
+}
+
+// Resolves when a leave is requested through the admin interface, never if it's not enabled.
+async fn next_leave_request(leave_requests: &mut Option<mpsc::Receiver<()>>) -> Option<()> {
+    match leave_requests {
+        Some(leave_requests) => leave_requests.recv().await,
+        None => futures::future::pending().await,
+    }
+}
+
+#[cfg(unix)]
+type TerminateSignals = tokio::signal::unix::Signal;
+#[cfg(not(unix))]
+type TerminateSignals = ();
+
+#[cfg(unix)]
+fn terminate_signals() -> std::io::Result<TerminateSignals> {
+    use tokio::signal::unix::{signal, SignalKind};
+    signal(SignalKind::terminate())
+}
+
+#[cfg(not(unix))]
+fn terminate_signals() -> std::io::Result<TerminateSignals> {
+    Ok(())
+}
+
+// Resolves when a SIGTERM is received, never on platforms without them.
+#[cfg(unix)]
+async fn next_terminate_signal(signals: &mut TerminateSignals) -> Option<()> {
+    signals.recv().await
+}
+
+#[cfg(not(unix))]
+async fn next_terminate_signal(_signals: &mut TerminateSignals) -> Option<()> {
+    futures::future::pending().await
+}
+
+#[cfg(not(feature = "tokio-console"))]
+fn log_filter_handler<S>(handle: reload::Handle<EnvFilter, S>) -> LogFilterHandler
+where
+    S: tracing::Subscriber + Send + Sync + 'static,
+{
+    Box::new(move |directive| {
+        let filter = EnvFilter::try_new(directive).map_err(|err| err.to_string())?;
+        handle.reload(filter).map_err(|err| err.to_string())
+    })
+}
+
 fn update() -> Result<Status, Box<dyn (::std::error::Error)>> {
     info!("Checking for updates...");
     let target = self_update::get_target();
@@ -0,0 +1,159 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use eyre::{bail, Result};
+use safe_network::node::{maintenance, ChunkStoreKind, Config};
+use std::path::PathBuf;
+use structopt::{clap::AppSettings::ColoredHelp, StructOpt};
+
+#[derive(StructOpt, Debug)]
+/// Check and maintain the data dir of a node. The node must not be running.
+#[structopt(global_settings(&[ColoredHelp]))]
+struct CmdArgs {
+    /// subcommands
+    #[structopt(subcommand)]
+    pub cmd: SubCommands,
+    /// Root directory of the node, defaults to the same as sn_node's
+    #[structopt(short, long, parse(from_os_str))]
+    pub root_dir: Option<PathBuf>,
+    /// Chunk store backend the node uses: "disk" or "packed-log"
+    #[structopt(long, default_value = "disk", parse(try_from_str = parse_chunk_store))]
+    pub chunk_store: ChunkStoreKind,
+}
+
+// The memory store doesn't keep anything on disk, so there'd be nothing to maintain.
+fn parse_chunk_store(s: &str) -> Result<ChunkStoreKind, String> {
+    match s.parse()? {
+        ChunkStoreKind::Memory => Err(
+            "The memory chunk store keeps no data on disk, expected one of: disk, packed-log"
+                .to_string(),
+        ),
+        kind => Ok(kind),
+    }
+}
+
+#[derive(StructOpt, Debug)]
+enum SubCommands {
+    /// Read every chunk, reporting those whose content doesn't match their address. Corrupted
+    /// chunks are taken out of the store, as the node would do when reading them
+    Verify,
+    /// Measure the space used by the chunks and registers, as the node accounts for it on start
+    UsedSpace {
+        /// Upper limit in bytes for storage, to report the ratio used, as set with sn_node
+        #[structopt(long)]
+        max_capacity: Option<usize>,
+    },
+    /// Write all the chunks and registers to an archive file
+    Export {
+        /// Path of the archive to create
+        #[structopt(parse(from_os_str))]
+        archive: PathBuf,
+    },
+    /// Store the chunks and registers of an archive file, skipping those already stored
+    Import {
+        /// Path of the archive to read
+        #[structopt(parse(from_os_str))]
+        archive: PathBuf,
+    },
+    /// Compact the register db, reclaiming the space on disk it doesn't need anymore
+    Compact,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = CmdArgs::from_args();
+
+    let mut config = Config::default();
+    if let Some(root_dir) = args.root_dir {
+        config.set_root_dir(root_dir);
+    }
+    let root_dir = config.root_dir()?;
+    if !root_dir.is_dir() {
+        bail!("Node root dir not found at {}", root_dir.display());
+    }
+
+    match args.cmd {
+        SubCommands::Verify => {
+            let check = maintenance::verify_chunks(&root_dir, args.chunk_store).await?;
+            println!("Chunks checked: {}", check.checked);
+            for name in &check.corrupted {
+                println!("Corrupted: {:x}", name);
+            }
+            for (name, error) in &check.unreadable {
+                println!("Unreadable: {:x} ({})", name, error);
+            }
+            if !check.corrupted.is_empty() || !check.unreadable.is_empty() {
+                bail!(
+                    "{} corrupted and {} unreadable chunks found",
+                    check.corrupted.len(),
+                    check.unreadable.len()
+                );
+            }
+            println!("All chunks are valid");
+        }
+        SubCommands::UsedSpace { max_capacity } => {
+            let report = maintenance::measure_used_space(&root_dir, args.chunk_store).await?;
+            let total = report.chunks_size + report.registers_size;
+            println!(
+                "Chunks: {} using {} bytes",
+                report.chunk_count, report.chunks_size
+            );
+            println!(
+                "Registers: {} using {} bytes",
+                report.register_count, report.registers_size
+            );
+            println!("Total: {} bytes", total);
+            if let Some(max_capacity) = max_capacity {
+                println!(
+                    "Used: {:.1}% of {} bytes",
+                    100.0 * total as f64 / max_capacity as f64,
+                    max_capacity
+                );
+            }
+        }
+        SubCommands::Export { archive } => {
+            let summary =
+                maintenance::export_archive(&root_dir, args.chunk_store, &archive).await?;
+            println!(
+                "Exported {} chunks and {} registers to {}",
+                summary.chunks,
+                summary.registers,
+                archive.display()
+            );
+            for (name, error) in &summary.unreadable {
+                println!("Skipped unreadable: {:x} ({})", name, error);
+            }
+            if !summary.unreadable.is_empty() {
+                bail!(
+                    "{} unreadable chunks were not exported",
+                    summary.unreadable.len()
+                );
+            }
+        }
+        SubCommands::Import { archive } => {
+            let summary =
+                maintenance::import_archive(&root_dir, args.chunk_store, &archive).await?;
+            println!(
+                "Imported {} chunks and {} registers from {}, skipped {} already stored",
+                summary.chunks,
+                summary.registers,
+                archive.display(),
+                summary.skipped
+            );
+        }
+        SubCommands::Compact => {
+            let (before, after) = maintenance::compact_register_db(&root_dir)?;
+            println!(
+                "Register db compacted from {} to {} bytes on disk",
+                before, after
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -7,24 +7,40 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
-    data::{encrypt_blob, to_chunk, Blob, Spot},
+    data::{encrypt_blob, pack, source_size, to_chunk, Blob, Spot, StreamEncryptor},
+    upload_journal::{UploadJournal, UploadJournalId},
     Client,
 };
 use crate::{
     client::{client_api::data::DataMapLevel, utils::encryption, Error, Result},
-    messaging::data::{DataCmd, DataQuery, QueryResponse},
-    types::{BytesAddress, Chunk, ChunkAddress, Encryption, PublicKey, Scope},
+    messaging::data::{DataQuery, QueryResponse},
+    types::{BytesAddress, Chunk, ChunkAddress, Encryption, Keypair, Scope},
 };
 
 use bincode::deserialize;
 use bytes::Bytes;
-use futures::future::join_all;
+use futures::{
+    future::{self, join_all},
+    stream::{self, BoxStream},
+    StreamExt,
+};
 use itertools::Itertools;
-use self_encryption::{self, ChunkInfo, DataMap, EncryptedChunk};
-use tokio::task;
+use rand::rngs::OsRng;
+use self_encryption::{self, ChunkInfo, DataMap, EncryptedChunk, MIN_ENCRYPTABLE_BYTES};
+use std::{path::Path, sync::Arc};
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncWrite, AsyncWriteExt},
+    task,
+};
 use tracing::trace;
 use xor_name::XorName;
 
+// Number of chunks which are fetched concurrently when streaming content, or produced before
+// being flushed when streaming content without uploading it. Uploads are flushed in batches
+// of the number of chunks the client sends at once.
+const STREAMING_BATCH_SIZE: usize = 8;
+
 struct HeadChunk {
     chunk: Chunk,
     address: BytesAddress,
@@ -106,6 +122,63 @@ impl Client {
         Ok(bytes)
     }
 
+    /// Reads the content stored at `address` as a stream of [`Bytes`], in order.
+    ///
+    /// Each item of the stream is the decrypted content of a single chunk, and only
+    /// a small batch of chunks is fetched from the network at any given time,
+    /// thus the content is never held in memory as a whole.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn read_stream(
+        &self,
+        address: BytesAddress,
+    ) -> Result<BoxStream<'static, Result<Bytes>>> {
+        let chunk = self.get_chunk(address.name()).await?;
+
+        // First try to deserialize a Blob, if it works, we go and stream its chunks.
+        // If an error occurs, we consider it to be a Spot.
+        if let Ok(data_map) = self
+            .unpack_head_chunk(HeadChunk {
+                chunk: chunk.clone(),
+                address,
+            })
+            .await
+        {
+            let chunks_info = data_map.infos();
+            let data_map = Arc::new(data_map);
+            let client = self.clone();
+            let stream = stream::iter(chunks_info)
+                .map(move |chunk_info| {
+                    let client = client.clone();
+                    let data_map = data_map.clone();
+                    async move { client.read_chunk_content(&data_map, chunk_info).await }
+                })
+                .buffered(STREAMING_BATCH_SIZE);
+            Ok(stream.boxed())
+        } else {
+            let bytes = self.get_bytes(chunk, address.scope())?;
+            Ok(stream::once(future::ready(Ok(bytes))).boxed())
+        }
+    }
+
+    /// Reads the content stored at `address` and writes it to `writer` as it's being fetched
+    /// from the network. Returns the number of bytes written.
+    #[instrument(skip(self, writer), level = "debug")]
+    pub async fn read_to_writer<W>(&self, address: BytesAddress, writer: &mut W) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut stream = self.read_stream(address).await?;
+        let mut bytes_written = 0;
+        while let Some(bytes) = stream.next().await {
+            let bytes = bytes?;
+            writer.write_all(&bytes).await?;
+            bytes_written += bytes.len() as u64;
+        }
+        writer.flush().await?;
+
+        Ok(bytes_written)
+    }
+
     #[instrument(skip(self), level = "trace")]
     pub(crate) async fn get_chunk(&self, name: &XorName) -> Result<Chunk> {
         // first check it's not already in our Chunks' cache
@@ -119,6 +192,14 @@ impl Client {
             return Ok(chunk.clone());
         }
 
+        if let Some(disk_cache) = &self.chunks_disk_cache {
+            if let Some(chunk) = disk_cache.get(name).await {
+                trace!("Chunk retrieved from disk cache: {:?}", name);
+                let _ = self.chunks_cache.write().await.insert(chunk.clone());
+                return Ok(chunk);
+            }
+        }
+
         let res = self
             .send_query(DataQuery::GetChunk(ChunkAddress(*name)))
             .await?;
@@ -132,6 +213,11 @@ impl Client {
         }?;
 
         let _ = self.chunks_cache.write().await.insert(chunk.clone());
+        if let Some(disk_cache) = &self.chunks_disk_cache {
+            if let Err(error) = disk_cache.insert(&chunk).await {
+                warn!("Failed to cache chunk {:?} on disk: {}", name, error);
+            }
+        }
 
         Ok(chunk)
     }
@@ -140,10 +226,10 @@ impl Client {
     #[instrument(skip_all, level = "trace")]
     pub fn chunk_bytes(&self, bytes: Bytes, scope: Scope) -> Result<(BytesAddress, Vec<Chunk>)> {
         if let Ok(blob) = Blob::new(bytes.clone()) {
-            Self::encrypt_blob(blob, scope, self.public_key())
+            Self::encrypt_blob(blob, scope, &self.keypair())
         } else {
             let spot = Spot::new(bytes)?;
-            let (address, chunk) = Self::package_spot(spot, scope, self.public_key())?;
+            let (address, chunk) = Self::package_spot(spot, scope, &self.keypair())?;
             Ok((address, vec![chunk]))
         }
     }
@@ -154,23 +240,19 @@ impl Client {
     fn encrypt_blob(
         blob: Blob,
         scope: Scope,
-        public_key: PublicKey,
+        keypair: &Keypair,
     ) -> Result<(BytesAddress, Vec<Chunk>)> {
-        let owner = encryption(scope, public_key);
+        let owner = encryption(scope, keypair);
         encrypt_blob(blob.bytes(), owner.as_ref())
     }
 
     /// Packages a small piece of data (spot) and returns the resulting address and the chunk.
     /// The chunk content will be in plain text if it has public scope, or encrypted if it is instead private.
     /// Does not store anything to the network.
-    fn package_spot(
-        spot: Spot,
-        scope: Scope,
-        public_key: PublicKey,
-    ) -> Result<(BytesAddress, Chunk)> {
-        let encryption = encryption(scope, public_key);
+    fn package_spot(spot: Spot, scope: Scope, keypair: &Keypair) -> Result<(BytesAddress, Chunk)> {
+        let encryption = encryption(scope, keypair);
         let chunk = to_chunk(spot.bytes(), encryption.as_ref())?;
-        if chunk.value().len() >= self_encryption::MIN_ENCRYPTABLE_BYTES {
+        if chunk.value().len() >= MIN_ENCRYPTABLE_BYTES {
             return Err(Error::SpotPaddingNeeded);
         }
         let name = *chunk.name();
@@ -207,18 +289,124 @@ impl Client {
         Ok(address)
     }
 
+    /// Directly writes the content of the file at `path` to the network in the
+    /// form of immutable chunks.
+    /// The file is self-encrypted and uploaded incrementally, a batch of chunks at a time,
+    /// so it's never read into memory as a whole.
+    ///
+    /// The progress of the upload is recorded in an upload journal under the client's root dir,
+    /// so if it fails halfway it can be continued with [`Client::resume_upload`], using the id
+    /// obtained from [`Client::upload_journal_id`]. The journal is removed once the upload completes.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn upload_from_path(&self, path: &Path, scope: Scope) -> Result<BytesAddress> {
+        let mut file = File::open(path).await?;
+        if source_size(&mut file).await? < MIN_ENCRYPTABLE_BYTES {
+            // a single chunk is produced for small files, so there is nothing to resume
+            return self.upload_from_reader(file, scope).await;
+        }
+
+        let journal = UploadJournal::create(&self.root_dir, path, scope).await?;
+        self.upload_journaled(file, journal).await
+    }
+
+    /// Resumes an upload which was started with [`Client::upload_from_path`] and failed
+    /// before completing, sending only the chunks which were not stored yet.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn resume_upload(&self, journal_id: UploadJournalId) -> Result<BytesAddress> {
+        let journal = UploadJournal::load(&self.root_dir, journal_id).await?;
+        let file = File::open(journal.source()).await?;
+        self.upload_journaled(file, journal).await
+    }
+
+    /// Returns the id of the journal which records the progress of an upload
+    /// of the file at `path` with the given `scope`.
+    pub fn upload_journal_id(path: &Path, scope: Scope) -> Result<UploadJournalId> {
+        UploadJournal::id(path, scope)
+    }
+
+    /// Directly writes the content of the file at `path` to the network in the
+    /// form of immutable chunks, like [`Client::upload_from_path`].
+    /// It also attempts to verify that all the data was uploaded to the network before returning.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn upload_from_path_and_verify(
+        &self,
+        path: &Path,
+        scope: Scope,
+    ) -> Result<BytesAddress> {
+        let address = self.upload_from_path(path, scope).await?;
+
+        // let's now try to retrieve it, without keeping it in memory
+        let _bytes_read = self.read_to_writer(address, &mut tokio::io::sink()).await?;
+
+        Ok(address)
+    }
+
+    /// Directly writes the content read from `reader` to the network in the
+    /// form of immutable chunks.
+    /// The chunks are self-encrypted and sent in small batches as the content is read,
+    /// thus the content is never held in memory as a whole.
+    #[instrument(skip_all, level = "debug")]
+    pub async fn upload_from_reader<R>(&self, reader: R, scope: Scope) -> Result<BytesAddress>
+    where
+        R: AsyncRead + AsyncSeek + Unpin,
+    {
+        Self::encrypt_stream(reader, scope, &self.keypair(), Some(self), None).await
+    }
+
+    /// Stores again private content which was stored in plain text, before private data was
+    /// encrypted, returning the address of its encrypted version. Content which is already
+    /// encrypted, or public, keeps its address.
+    /// The plain text chunks are not removed from the network, as chunks cannot be deleted.
+    /// A plain text spot starting with `SNPRV`, the prefix of encrypted content, is taken for
+    /// encrypted content though, and fails to be read.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn encrypt_private_legacy(&self, address: BytesAddress) -> Result<BytesAddress> {
+        if address.scope() == Scope::Public {
+            return Ok(address);
+        }
+        let bytes = self.read_bytes(address).await?;
+        self.upload_and_verify(bytes, Scope::Private).await
+    }
+
+    /// Calculates the address of the content of the file at `path`, reading it incrementally,
+    /// without storing anything onto the network.
+    /// The address of private content depends on the keys of the client storing it, thus
+    /// [`Client::chunk_bytes`] should be used instead to know where a client stores it.
+    #[instrument(level = "debug")]
+    pub async fn calculate_address_from_path(path: &Path, scope: Scope) -> Result<BytesAddress> {
+        // we use just a random keypair as the owner
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+        let file = File::open(path).await?;
+        Self::encrypt_stream(file, scope, &keypair, None, None).await
+    }
+
+    /// Calculates the address the content of the file at `path` is stored at by this client,
+    /// reading it incrementally, without storing anything onto the network.
+    /// Unlike [`Client::calculate_address_from_path`], this is also the address of private content.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn calculate_own_address_from_path(
+        &self,
+        path: &Path,
+        scope: Scope,
+    ) -> Result<BytesAddress> {
+        let file = File::open(path).await?;
+        Self::encrypt_stream(file, scope, &self.keypair, None, None).await
+    }
+
     /// Calculates a Blob's/Spot's address from self encrypted chunks,
     /// without storing them onto the network.
+    /// The address of private content depends on the keys of the client storing it, thus
+    /// [`Client::chunk_bytes`] should be used instead to know where a client stores it.
     #[instrument(skip(bytes), level = "debug")]
     pub fn calculate_address(bytes: Bytes, scope: Scope) -> Result<BytesAddress> {
-        // we use just a random BLS public key as the owner
-        let public_key = PublicKey::Bls(bls::SecretKey::random().public_key());
+        // we use just a random keypair as the owner
+        let keypair = Keypair::new_ed25519(&mut OsRng);
         if let Ok(blob) = Blob::new(bytes.clone()) {
-            let (head_address, _all_chunks) = Self::encrypt_blob(blob, scope, public_key)?;
+            let (head_address, _all_chunks) = Self::encrypt_blob(blob, scope, &keypair)?;
             Ok(head_address)
         } else {
             let spot = Spot::new(bytes)?;
-            let (address, _chunk) = Self::package_spot(spot, scope, public_key)?;
+            let (address, _chunk) = Self::package_spot(spot, scope, &keypair)?;
             Ok(address)
         }
     }
@@ -227,18 +415,11 @@ impl Client {
     /// form of immutable self encrypted chunks, without any batching.
     #[instrument(skip_all, level = "trace")]
     async fn upload_blob(&self, blob: Blob, scope: Scope) -> Result<BytesAddress> {
-        let (head_address, all_chunks) = Self::encrypt_blob(blob, scope, self.public_key())?;
-
-        let tasks = all_chunks.into_iter().map(|chunk| {
-            let writer = self.clone();
-            task::spawn(async move { writer.send_cmd(DataCmd::StoreChunk(chunk)).await })
-        });
+        let (head_address, all_chunks) = Self::encrypt_blob(blob, scope, &self.keypair())?;
 
-        let _ = join_all(tasks)
-            .await
-            .into_iter()
-            .flatten() // swallows errors
-            .collect_vec();
+        for result in self.store_chunks(all_chunks).await {
+            result?;
+        }
 
         Ok(head_address)
     }
@@ -247,8 +428,10 @@ impl Client {
     /// form of a single chunk, without any batching.
     #[instrument(skip_all, level = "trace")]
     async fn upload_spot(&self, spot: Spot, scope: Scope) -> Result<BytesAddress> {
-        let (address, chunk) = Self::package_spot(spot, scope, self.public_key())?;
-        self.send_cmd(DataCmd::StoreChunk(chunk)).await?;
+        let (address, chunk) = Self::package_spot(spot, scope, &self.keypair())?;
+        for result in self.store_chunks(vec![chunk]).await {
+            result?;
+        }
         Ok(address)
     }
 
@@ -256,6 +439,154 @@ impl Client {
     // ---------- Private helpers -----------------
     // --------------------------------------------
 
+    // Uploads the file, recording its progress in the journal, which is removed once completed.
+    async fn upload_journaled(
+        &self,
+        file: File,
+        mut journal: UploadJournal,
+    ) -> Result<BytesAddress> {
+        let scope = journal.scope();
+        let address =
+            Self::encrypt_stream(file, scope, &self.keypair(), Some(self), Some(&mut journal))
+                .await?;
+        journal.remove().await?;
+
+        Ok(address)
+    }
+
+    // Self-encrypts the content read from `reader` and, if an `uploader` is provided,
+    // sends the resulting chunks to the network in batches as they are produced.
+    // If a `journal` is provided, the progress of the upload is recorded in it as each batch is
+    // flushed, and chunks it has already confirmed are neither read, encrypted, nor sent again.
+    // Returns the address of the content.
+    async fn encrypt_stream<R>(
+        mut reader: R,
+        scope: Scope,
+        keypair: &Keypair,
+        uploader: Option<&Client>,
+        mut journal: Option<&mut UploadJournal>,
+    ) -> Result<BytesAddress>
+    where
+        R: AsyncRead + AsyncSeek + Unpin,
+    {
+        if source_size(&mut reader).await? < MIN_ENCRYPTABLE_BYTES {
+            let mut bytes = vec![];
+            let _ = reader.read_to_end(&mut bytes).await?;
+            let spot = Spot::new(Bytes::from(bytes))?;
+            let (address, chunk) = Self::package_spot(spot, scope, keypair)?;
+            Self::flush_chunks(uploader, journal, &mut vec![chunk]).await?;
+            return Ok(address);
+        }
+
+        let batch_size = uploader.map_or(STREAMING_BATCH_SIZE, |client| {
+            client.upload_config.concurrency.max(1)
+        });
+        let mut encryptor = match journal.as_deref_mut() {
+            Some(journal) if !journal.src_hashes().is_empty() => {
+                let src_hashes = journal.src_hashes().to_vec();
+                let known_infos = journal.chunk_infos().to_vec();
+                StreamEncryptor::resume(reader, src_hashes, known_infos).await?
+            }
+            Some(journal) => {
+                let encryptor = StreamEncryptor::new(reader).await?;
+                journal
+                    .record_src_hashes(encryptor.src_hashes().to_vec())
+                    .await?;
+                encryptor
+            }
+            None => StreamEncryptor::new(reader).await?,
+        };
+        let mut batch = Vec::with_capacity(batch_size);
+        loop {
+            let confirmed = matches!(
+                (&journal, encryptor.next_known_name()),
+                (Some(journal), Some(name)) if journal.is_confirmed(&name)
+            );
+            if confirmed && encryptor.skip_chunk().await? {
+                continue;
+            }
+            match encryptor.next_chunk().await? {
+                Some(encrypted_chunk) => batch.push(Chunk::new(encrypted_chunk.content)),
+                None => break,
+            }
+            if batch.len() == batch_size {
+                if let Some(journal) = journal.as_deref_mut() {
+                    journal.set_chunk_infos(encryptor.chunk_infos());
+                }
+                Self::flush_chunks(uploader, journal.as_deref_mut(), &mut batch).await?;
+            }
+        }
+
+        if let Some(journal) = journal.as_deref_mut() {
+            journal.set_chunk_infos(encryptor.chunk_infos());
+        }
+        let data_map = encryptor.into_data_map()?;
+        let owner = encryption(scope, keypair);
+        let (address, data_map_chunks) = pack(data_map, vec![], owner.as_ref())?;
+        batch.extend(data_map_chunks);
+        Self::flush_chunks(uploader, journal, &mut batch).await?;
+
+        Ok(address)
+    }
+
+    // Drains the batch of chunks, storing them onto the network if an `uploader` is provided.
+    // Chunks already confirmed by the `journal` are skipped, and the outcome is recorded in it.
+    async fn flush_chunks(
+        uploader: Option<&Client>,
+        journal: Option<&mut UploadJournal>,
+        batch: &mut Vec<Chunk>,
+    ) -> Result<()> {
+        let chunks = std::mem::take(batch);
+        let client = match uploader {
+            Some(client) => client,
+            None => return Ok(()),
+        };
+
+        let pending = chunks
+            .into_iter()
+            .filter(
+                |chunk| !matches!(&journal, Some(journal) if journal.is_confirmed(chunk.name())),
+            )
+            .collect_vec();
+        let pending_names = pending.iter().map(|chunk| *chunk.name()).collect_vec();
+
+        let results = client.store_chunks(pending).await;
+
+        let mut confirmed = vec![];
+        let mut first_error = None;
+        for (name, result) in pending_names.into_iter().zip(results) {
+            match result {
+                Ok(()) => confirmed.push(name),
+                Err(err) => {
+                    warn!("Failed to store chunk {:?}: {:?}", name, err);
+                    let _ = first_error.get_or_insert(err);
+                }
+            }
+        }
+
+        if let Some(journal) = journal {
+            journal.record_confirmed(confirmed).await?;
+        }
+
+        first_error.map_or(Ok(()), Err)
+    }
+
+    // Gets a single chunk from the network and decrypts it using the data map it belongs to.
+    async fn read_chunk_content(&self, data_map: &DataMap, chunk_info: ChunkInfo) -> Result<Bytes> {
+        let chunk = self.get_chunk(&chunk_info.dst_hash).await?;
+        let encrypted_chunk = EncryptedChunk {
+            index: chunk_info.index,
+            content: chunk.value().clone(),
+        };
+
+        Ok(self_encryption::decrypt_range(
+            data_map,
+            &[encrypted_chunk],
+            0,
+            chunk_info.src_size,
+        )?)
+    }
+
     // Gets and decrypts chunks from the network using nothing else but the data map,
     // then returns the raw data.
     async fn read_all(&self, data_map: DataMap) -> Result<Bytes> {
@@ -361,7 +692,7 @@ impl Client {
         if matches!(scope, Scope::Public) {
             Ok(chunk.value().clone())
         } else {
-            let owner = encryption(scope, self.public_key()).ok_or(Error::NoEncryptionObject)?;
+            let owner = encryption(scope, &self.keypair).ok_or(Error::NoEncryptionObject)?;
             Ok(owner.decrypt(chunk.value().clone())?)
         }
     }
@@ -371,7 +702,7 @@ impl Client {
 mod tests {
     use crate::client::utils::test_utils::create_test_client_with;
     use crate::client::{
-        client_api::blob_apis::Blob,
+        client_api::{blob_apis::Blob, upload_journal::UploadJournal},
         utils::test_utils::{create_test_client, init_test_logger},
         Client,
     };
@@ -379,9 +710,18 @@ mod tests {
     use crate::types::{utils::random_bytes, BytesAddress, Keypair, Scope};
     use bytes::Bytes;
     use eyre::Result;
-    use futures::future::join_all;
+    use futures::{future::join_all, ready};
     use rand::rngs::OsRng;
-    use tokio::time::Instant;
+    use self_encryption::MAX_CHUNK_SIZE;
+    use std::{
+        io::{self, Cursor, SeekFrom, Write},
+        pin::Pin,
+        task::{Context, Poll},
+    };
+    use tokio::{
+        io::{AsyncRead, AsyncSeek, ReadBuf},
+        time::Instant,
+    };
     use tracing::{instrument::Instrumented, Instrument};
 
     const MIN_BLOB_SIZE: usize = self_encryption::MIN_ENCRYPTABLE_BYTES;
@@ -394,13 +734,13 @@ mod tests {
 
         use crate::client::client_api::data::encrypt_blob;
         use crate::client::utils::encryption;
-        let owner = encryption(Scope::Private, keypair.public_key());
+        let owner = encryption(Scope::Private, &keypair);
         let (first_address, mut first_chunks) = encrypt_blob(blob.clone(), owner.as_ref())?;
 
         first_chunks.sort();
 
         for _ in 0..100 {
-            let owner = encryption(Scope::Private, keypair.public_key());
+            let owner = encryption(Scope::Private, &keypair);
             let (head_address, mut all_chunks) = encrypt_blob(blob.clone(), owner.as_ref())?;
             assert_eq!(first_address, head_address);
             all_chunks.sort();
@@ -410,6 +750,44 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn private_chunks_do_not_contain_plaintext() -> Result<()> {
+        use crate::client::client_api::data::{encrypt_blob, DataMapLevel, Spot};
+        use crate::client::utils::encryption;
+        use crate::types::{Chunk, Encryption};
+
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+        let owner = encryption(Scope::Private, &keypair).expect("private data is encrypted");
+        let contains = |chunk: &Chunk, bytes: &[u8]| {
+            chunk
+                .value()
+                .windows(bytes.len())
+                .any(|window| window == bytes)
+        };
+
+        // the data map of a blob is only readable with the owner's keys
+        let blob = random_bytes(MIN_BLOB_SIZE * 4);
+        let (address, chunks) = encrypt_blob(blob.clone(), Some(&owner))?;
+        let head = chunks
+            .iter()
+            .find(|chunk| chunk.name() == address.name())
+            .expect("head chunk is produced");
+        assert!(bincode::deserialize::<DataMapLevel>(head.value()).is_err());
+        let data_map = owner.decrypt(head.value().clone())?;
+        assert!(bincode::deserialize::<DataMapLevel>(&data_map).is_ok());
+        assert!(chunks.iter().all(|chunk| !contains(chunk, &blob[..64])));
+
+        // so is the content of a spot
+        let spot = random_bytes(MIN_BLOB_SIZE / 2);
+        let (address, chunk) =
+            Client::package_spot(Spot::new(spot.clone())?, Scope::Private, &keypair)?;
+        assert_eq!(address, BytesAddress::Private(*chunk.name()));
+        assert!(!contains(&chunk, &spot[..64]));
+        assert_eq!(owner.decrypt(chunk.value().clone())?, spot);
+
+        Ok(())
+    }
+
     // Test storing and reading min size blob.
     #[tokio::test(flavor = "multi_thread")]
     async fn store_and_read_3kb() -> Result<()> {
@@ -636,6 +1014,147 @@ mod tests {
         store_and_read(&client, 1024 * 1024, Scope::Private).await
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn private_data_stored_in_plain_text_is_encrypted_again() -> Result<()> {
+        use crate::client::client_api::data::{encrypt_blob, to_chunk};
+        use crate::types::{Encryption, PublicKey};
+
+        init_test_logger();
+        let _outer_span = tracing::info_span!("private_legacy_encryption").entered();
+
+        // how private data was stored before it was encrypted
+        struct PlainText(PublicKey);
+        impl Encryption for PlainText {
+            fn public_key(&self) -> &PublicKey {
+                &self.0
+            }
+            fn encrypt(&self, data: Bytes) -> crate::types::Result<Bytes> {
+                Ok(data)
+            }
+            fn decrypt(&self, encrypted_data: Bytes) -> crate::types::Result<Bytes> {
+                Ok(encrypted_data)
+            }
+        }
+
+        let client = create_test_client().await?;
+        let plain_text = PlainText(client.public_key());
+
+        let blob = random_bytes(MIN_BLOB_SIZE * 2);
+        let (blob_address, blob_chunks) = encrypt_blob(blob.clone(), Some(&plain_text))?;
+        let spot = random_bytes(MIN_BLOB_SIZE / 2);
+        let spot_chunk = to_chunk(spot.clone(), Some(&plain_text))?;
+        let spot_address = BytesAddress::Private(*spot_chunk.name());
+        for result in client.store_chunks(blob_chunks).await {
+            result?;
+        }
+        for result in client.store_chunks(vec![spot_chunk]).await {
+            result?;
+        }
+
+        for (legacy_address, content) in [(blob_address, blob), (spot_address, spot)] {
+            // still readable as it is
+            compare(content.clone(), client.read_bytes(legacy_address).await?)?;
+
+            let address = client.encrypt_private_legacy(legacy_address).await?;
+            assert_ne!(address, legacy_address);
+            assert_eq!(
+                address,
+                client.upload(content.clone(), Scope::Private).await?
+            );
+            compare(content, client.read_bytes(address).await?)?;
+
+            // already encrypted, so kept as it is
+            assert_eq!(client.encrypt_private_legacy(address).await?, address);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn store_from_path_and_read_to_writer() -> Result<()> {
+        init_test_logger();
+        let _outer_span = tracing::info_span!("store_from_path_and_read_to_writer").entered();
+        let client = create_test_client().await?;
+
+        for size in [MIN_BLOB_SIZE / 3, 5 * 1024 * 1024 + 1] {
+            let bytes = random_bytes(size);
+            let mut file = tempfile::NamedTempFile::new()?;
+            file.write_all(&bytes)?;
+
+            // the address must be the same as if the content was uploaded from memory
+            let expected_address = Client::calculate_address(bytes.clone(), Scope::Public)?;
+            let calculated_address =
+                Client::calculate_address_from_path(file.path(), Scope::Public).await?;
+            assert_eq!(calculated_address, expected_address);
+
+            let address = client
+                .upload_from_path_and_verify(file.path(), Scope::Public)
+                .await?;
+            assert_eq!(address, expected_address);
+
+            let mut read_data = vec![];
+            let bytes_written = client.read_to_writer(address, &mut read_data).await?;
+            assert_eq!(bytes_written as usize, size);
+
+            compare(bytes, Bytes::from(read_data))?;
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn resumed_upload_skips_confirmed_chunks() -> Result<()> {
+        init_test_logger();
+        let _outer_span = tracing::info_span!("resumed_upload_skips_confirmed_chunks").entered();
+        let client = create_test_client().await?;
+
+        let batch_size = client.upload_config.concurrency.max(1);
+        let size = 3 * batch_size * MAX_CHUNK_SIZE;
+        let bytes = random_bytes(size);
+        let mut file = tempfile::NamedTempFile::new()?;
+        file.write_all(&bytes)?;
+        let expected_address = Client::calculate_address(bytes.clone(), Scope::Public)?;
+
+        // the source fails partway through the second batch, after the first pass over it
+        let mut journal =
+            UploadJournal::create(&client.root_dir, file.path(), Scope::Public).await?;
+        let reader = MeteredReader::new(bytes.to_vec(), size + (batch_size + 1) * MAX_CHUNK_SIZE);
+        let result = Client::encrypt_stream(
+            reader,
+            Scope::Public,
+            &client.keypair(),
+            Some(&client),
+            Some(&mut journal),
+        )
+        .await;
+        assert!(result.is_err());
+
+        let journal_id = UploadJournal::id(file.path(), Scope::Public)?;
+        let mut journal = UploadJournal::load(&client.root_dir, journal_id).await?;
+        let confirmed_size: usize = journal
+            .chunk_infos()
+            .iter()
+            .filter(|info| journal.is_confirmed(&info.dst_hash))
+            .map(|info| info.src_size)
+            .sum();
+        assert!(confirmed_size >= batch_size * MAX_CHUNK_SIZE);
+
+        // only the chunks which weren't confirmed are read and encrypted again
+        let mut reader = MeteredReader::new(bytes.to_vec(), usize::MAX);
+        let address = Client::encrypt_stream(
+            &mut reader,
+            Scope::Public,
+            &client.keypair(),
+            Some(&client),
+            Some(&mut journal),
+        )
+        .await?;
+        assert_eq!(address, expected_address);
+        assert_eq!(reader.read, size - confirmed_size);
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn ae_checks_blob_test() -> Result<()> {
         init_test_logger();
@@ -724,6 +1243,50 @@ mod tests {
         Ok(())
     }
 
+    // An in-memory source which counts the bytes read from it, and fails any read once
+    // `limit` bytes were read.
+    struct MeteredReader {
+        inner: Cursor<Vec<u8>>,
+        read: usize,
+        limit: usize,
+    }
+
+    impl MeteredReader {
+        fn new(bytes: Vec<u8>, limit: usize) -> Self {
+            Self {
+                inner: Cursor::new(bytes),
+                read: 0,
+                limit,
+            }
+        }
+    }
+
+    impl AsyncRead for MeteredReader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            if self.read >= self.limit {
+                return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+            }
+            let filled = buf.filled().len();
+            let result = ready!(Pin::new(&mut self.inner).poll_read(cx, buf));
+            self.read += buf.filled().len() - filled;
+            Poll::Ready(result)
+        }
+    }
+
+    impl AsyncSeek for MeteredReader {
+        fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+            Pin::new(&mut self.inner).start_seek(position)
+        }
+
+        fn poll_complete(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+            Pin::new(&mut self.inner).poll_complete(cx)
+        }
+    }
+
     async fn store_and_read(client: &Client, size: usize, scope: Scope) -> Result<()> {
         // cannot use scope as var w/ macro
         let _outer_span = if scope == Scope::Public {
@@ -735,8 +1298,12 @@ mod tests {
         // create Blob with random bytes of requested size
         let blob_bytes = random_bytes(size);
 
-        // we'll also test we can calculate address offline using `calculate_address` API
-        let expected_address = Client::calculate_address(blob_bytes.clone(), scope)?;
+        // we'll also test we can calculate address offline, using `calculate_address` API, or
+        // `chunk_bytes` for private data, as its address depends on the client's keys
+        let expected_address = match scope {
+            Scope::Public => Client::calculate_address(blob_bytes.clone(), scope)?,
+            Scope::Private => client.chunk_bytes(blob_bytes.clone(), scope)?.0,
+        };
 
         // we use upload_and_verify since it uploads and also confirms it was uploaded
         let address = client.upload_and_verify(blob_bytes.clone(), scope).await?;
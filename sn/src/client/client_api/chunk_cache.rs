@@ -0,0 +1,331 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::client::{ChunkCacheConfig, ChunkCacheEviction, Result};
+use crate::types::Chunk;
+
+use bytes::Bytes;
+use filetime::FileTime;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+use tokio::{fs, sync::Mutex};
+use tracing::{debug, trace, warn};
+use xor_name::XorName;
+
+// Name of the folder, within the client's root dir, where cached chunks are kept.
+const CHUNK_CACHE_DIR_NAME: &str = "chunk_cache";
+// Extension of chunks being written, renamed into place once complete.
+const PARTIAL_EXTENSION: &str = "partial";
+
+/// Chunks kept on disk across runs, one file per chunk, up to a total size.
+///
+/// Chunks are dropped in the order set by the eviction policy once the capacity is reached.
+/// That order is kept in memory, and restored from the modification time of the files, which
+/// is updated when a chunk is read with `ChunkCacheEviction::LeastRecentlyUsed`.
+#[derive(Debug)]
+pub(crate) struct ChunkDiskCache {
+    dir: PathBuf,
+    config: ChunkCacheConfig,
+    index: Mutex<CacheIndex>,
+}
+
+#[derive(Debug, Default)]
+struct CacheIndex {
+    // size and rank of every chunk cached
+    entries: BTreeMap<XorName, (u64, u64)>,
+    // chunks by rank, the lowest is evicted first
+    order: BTreeMap<u64, XorName>,
+    next_rank: u64,
+    size: u64,
+}
+
+impl CacheIndex {
+    fn insert(&mut self, name: XorName, size: u64) {
+        self.remove(&name);
+        let rank = self.next_rank;
+        self.next_rank += 1;
+        let _prev = self.entries.insert(name, (size, rank));
+        let _prev = self.order.insert(rank, name);
+        self.size += size;
+    }
+
+    fn remove(&mut self, name: &XorName) {
+        if let Some((size, rank)) = self.entries.remove(name) {
+            let _name = self.order.remove(&rank);
+            self.size -= size;
+        }
+    }
+
+    fn first(&self) -> Option<XorName> {
+        self.order.values().next().copied()
+    }
+}
+
+impl ChunkDiskCache {
+    /// Opens the cache within `root_dir`, indexing the chunks cached by previous runs.
+    pub(crate) async fn open(root_dir: &Path, config: ChunkCacheConfig) -> Result<Self> {
+        let dir = root_dir.join(CHUNK_CACHE_DIR_NAME);
+        fs::create_dir_all(&dir).await?;
+
+        let mut found = vec![];
+        let mut dir_entries = fs::read_dir(&dir).await?;
+        while let Some(entry) = dir_entries.next_entry().await? {
+            let path = entry.path();
+            let name = match path_to_name(&path) {
+                Some(name) => name,
+                None => {
+                    // e.g. a chunk whose write was interrupted
+                    trace!("Removing unexpected file from chunk cache: {:?}", path);
+                    let _ = fs::remove_file(&path).await;
+                    continue;
+                }
+            };
+            let metadata = entry.metadata().await?;
+            let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+            found.push((modified, name, metadata.len()));
+        }
+        found.sort();
+
+        let mut index = CacheIndex::default();
+        for (_, name, size) in found {
+            index.insert(name, size);
+        }
+        debug!(
+            "Chunk cache opened with {} chunks using {} bytes",
+            index.entries.len(),
+            index.size
+        );
+
+        let cache = Self {
+            dir,
+            config,
+            index: Mutex::new(index),
+        };
+        // the capacity may have been lowered since the last run
+        cache.evict(&mut *cache.index.lock().await).await;
+
+        Ok(cache)
+    }
+
+    /// Returns the chunk if it's cached and its content matches its address.
+    /// A chunk which doesn't match is dropped from the cache.
+    pub(crate) async fn get(&self, name: &XorName) -> Option<Chunk> {
+        // the index isn't locked while reading the file, so other chunks can be read meanwhile
+        if !self.index.lock().await.entries.contains_key(name) {
+            return None;
+        }
+
+        let path = self.chunk_path(name);
+        let chunk = match fs::read(&path).await {
+            Ok(bytes) => Chunk::new(Bytes::from(bytes)),
+            Err(error) => {
+                warn!("Failed to read chunk {:?} from cache: {}", name, error);
+                self.index.lock().await.remove(name);
+                return None;
+            }
+        };
+        if chunk.name() != name {
+            warn!(
+                "Cached chunk {:?} doesn't match its address, dropping it",
+                name
+            );
+            let mut index = self.index.lock().await;
+            index.remove(name);
+            let _ = fs::remove_file(&path).await;
+            return None;
+        }
+
+        if self.config.eviction == ChunkCacheEviction::LeastRecentlyUsed {
+            let mut index = self.index.lock().await;
+            // unless it was evicted while being read
+            if index.entries.contains_key(name) {
+                index.insert(*name, chunk.value().len() as u64);
+                drop(index);
+                if let Err(error) = touch(&path) {
+                    trace!("Failed to record use of cached chunk {:?}: {}", name, error);
+                }
+            }
+        }
+
+        Some(chunk)
+    }
+
+    /// Caches the chunk, dropping others if needed to stay within the capacity.
+    /// Chunks bigger than the capacity are not cached.
+    pub(crate) async fn insert(&self, chunk: &Chunk) -> Result<()> {
+        let size = chunk.value().len() as u64;
+        if size > self.config.capacity {
+            return Ok(());
+        }
+
+        let mut index = self.index.lock().await;
+        if index.entries.contains_key(chunk.name()) {
+            return Ok(());
+        }
+
+        let path = self.chunk_path(chunk.name());
+        let partial_path = path.with_extension(PARTIAL_EXTENSION);
+        fs::write(&partial_path, chunk.value()).await?;
+        fs::rename(&partial_path, &path).await?;
+
+        index.insert(*chunk.name(), size);
+        self.evict(&mut index).await;
+
+        Ok(())
+    }
+
+    // Drops chunks until the size of the cache is within its capacity.
+    async fn evict(&self, index: &mut CacheIndex) {
+        while index.size > self.config.capacity {
+            let name = match index.first() {
+                Some(name) => name,
+                None => break,
+            };
+            trace!("Evicting chunk {:?} from cache", name);
+            index.remove(&name);
+            if let Err(error) = fs::remove_file(self.chunk_path(&name)).await {
+                warn!("Failed to remove chunk {:?} from cache: {}", name, error);
+            }
+        }
+    }
+
+    fn chunk_path(&self, name: &XorName) -> PathBuf {
+        self.dir.join(hex::encode(name.0))
+    }
+}
+
+fn path_to_name(path: &Path) -> Option<XorName> {
+    if path.extension().is_some() {
+        return None;
+    }
+    let bytes = hex::decode(path.file_name()?.to_str()?).ok()?;
+    let mut name = XorName::default();
+    if bytes.len() != name.0.len() {
+        return None;
+    }
+    name.0.copy_from_slice(&bytes);
+    Some(name)
+}
+
+// Sets the modification time of the file to now, so it's evicted last on the next runs too.
+fn touch(path: &Path) -> std::io::Result<()> {
+    filetime::set_file_mtime(path, FileTime::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::utils::random_bytes;
+    use eyre::Result;
+
+    fn new_chunk(size: usize) -> Chunk {
+        Chunk::new(random_bytes(size))
+    }
+
+    #[tokio::test]
+    async fn chunks_are_kept_across_runs() -> Result<()> {
+        let root_dir = tempfile::tempdir()?;
+        let config = ChunkCacheConfig::default();
+
+        let cache = ChunkDiskCache::open(root_dir.path(), config).await?;
+        let chunk = new_chunk(100);
+        assert!(cache.get(chunk.name()).await.is_none());
+        cache.insert(&chunk).await?;
+        assert_eq!(cache.get(chunk.name()).await, Some(chunk.clone()));
+        drop(cache);
+
+        let cache = ChunkDiskCache::open(root_dir.path(), config).await?;
+        assert_eq!(cache.get(chunk.name()).await, Some(chunk));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn corrupted_chunks_are_dropped() -> Result<()> {
+        let root_dir = tempfile::tempdir()?;
+        let cache = ChunkDiskCache::open(root_dir.path(), ChunkCacheConfig::default()).await?;
+
+        let chunk = new_chunk(100);
+        cache.insert(&chunk).await?;
+        fs::write(cache.chunk_path(chunk.name()), random_bytes(100)).await?;
+
+        assert!(cache.get(chunk.name()).await.is_none());
+        assert!(!cache.chunk_path(chunk.name()).exists());
+
+        // it can be cached again
+        cache.insert(&chunk).await?;
+        assert_eq!(cache.get(chunk.name()).await, Some(chunk));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn least_recently_used_chunks_are_evicted() -> Result<()> {
+        let root_dir = tempfile::tempdir()?;
+        let config = ChunkCacheConfig {
+            capacity: 300,
+            eviction: ChunkCacheEviction::LeastRecentlyUsed,
+        };
+        let cache = ChunkDiskCache::open(root_dir.path(), config).await?;
+
+        let (first, second, third) = (new_chunk(100), new_chunk(100), new_chunk(100));
+        for chunk in [&first, &second, &third] {
+            cache.insert(chunk).await?;
+        }
+        // reading the first makes the second the least recently used
+        assert!(cache.get(first.name()).await.is_some());
+
+        let fourth = new_chunk(100);
+        cache.insert(&fourth).await?;
+        assert!(cache.get(second.name()).await.is_none());
+        for chunk in [&first, &third, &fourth] {
+            assert!(cache.get(chunk.name()).await.is_some());
+        }
+
+        // too big to ever fit
+        let big = new_chunk(400);
+        cache.insert(&big).await?;
+        assert!(cache.get(big.name()).await.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn first_cached_chunks_are_evicted() -> Result<()> {
+        let root_dir = tempfile::tempdir()?;
+        let config = ChunkCacheConfig {
+            capacity: 300,
+            eviction: ChunkCacheEviction::FirstInFirstOut,
+        };
+        let cache = ChunkDiskCache::open(root_dir.path(), config).await?;
+
+        let (first, second, third) = (new_chunk(100), new_chunk(100), new_chunk(100));
+        for chunk in [&first, &second, &third] {
+            cache.insert(chunk).await?;
+        }
+        assert!(cache.get(first.name()).await.is_some());
+
+        cache.insert(&new_chunk(100)).await?;
+        assert!(cache.get(first.name()).await.is_none());
+        assert!(cache.get(second.name()).await.is_some());
+
+        // a lower capacity is applied when reopening
+        drop(cache);
+        let config = ChunkCacheConfig {
+            capacity: 100,
+            ..config
+        };
+        let cache = ChunkDiskCache::open(root_dir.path(), config).await?;
+        assert_eq!(cache.index.lock().await.entries.len(), 1);
+
+        Ok(())
+    }
+}
@@ -48,7 +48,7 @@ impl Client {
 
         let targets = match &cmd {
             DataCmd::StoreChunk(_) => at_least_one_correct_elder(), // stored at Adults, so only 1 correctly functioning Elder need to relay
-            DataCmd::Register(_) => elder_count(), // only stored at Elders, all need a copy
+            DataCmd::Register(_) | DataCmd::MultiSigRegister { .. } => elder_count(), // only stored at Elders, all need a copy
         };
 
         let serialised_cmd = {
@@ -0,0 +1,286 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Self-encryption of data which is read incrementally from a source, rather than being
+//! held in memory as a whole. The output (chunks and `DataMap`) is identical to what
+//! `self_encryption::encrypt` produces for the same content, thus the resulting addresses
+//! are the same regardless of which of the APIs was used to upload the data.
+//!
+//! The per-chunk steps of `self_encryption` this relies on are in the `self_encryption_compat`
+//! module, which has to be kept in line with `self_encryption` when upgrading it.
+
+use super::self_encryption_compat::{encrypt_chunk, get_chunk_size, get_num_chunks};
+use crate::client::{Error, Result};
+
+use bytes::Bytes;
+use self_encryption::{ChunkInfo, DataMap, EncryptedChunk, MIN_ENCRYPTABLE_BYTES};
+use std::io::SeekFrom;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+use xor_name::XorName;
+
+/// Self-encrypts the content of a seekable reader one chunk at a time.
+///
+/// The source is read twice: a first pass computes the hashes of all the source chunks
+/// (which are needed to derive the keys of each chunk), and a second pass encrypts the chunks
+/// as they are requested with [`StreamEncryptor::next_chunk`]. At most one source chunk is
+/// held in memory at any given time.
+///
+/// When resuming an interrupted upload with [`StreamEncryptor::resume`], the first pass is not
+/// needed, and chunks encrypted before can be skipped with [`StreamEncryptor::skip_chunk`].
+pub(crate) struct StreamEncryptor<R> {
+    reader: R,
+    file_size: usize,
+    src_hashes: Vec<XorName>,
+    chunk_infos: Vec<ChunkInfo>,
+    // the chunks encrypted before the encryption was resumed, if any
+    known_infos: Vec<ChunkInfo>,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> StreamEncryptor<R> {
+    /// Reads the whole source once to compute the source chunk hashes, and rewinds it.
+    /// Returns an error if the content is too small to be self-encrypted.
+    pub(crate) async fn new(mut reader: R) -> Result<Self> {
+        let file_size = source_size(&mut reader).await?;
+        if file_size < MIN_ENCRYPTABLE_BYTES {
+            return Err(Error::TooSmallToBeBlob);
+        }
+
+        let num_chunks = get_num_chunks(file_size);
+        let mut src_hashes = Vec::with_capacity(num_chunks);
+        for index in 0..num_chunks {
+            let data = read_chunk(&mut reader, get_chunk_size(file_size, index)).await?;
+            src_hashes.push(XorName::from_content(&data));
+        }
+        let _ = reader.seek(SeekFrom::Start(0)).await?;
+
+        Ok(Self {
+            reader,
+            file_size,
+            src_hashes,
+            chunk_infos: Vec::with_capacity(num_chunks),
+            known_infos: vec![],
+        })
+    }
+
+    /// Resumes the encryption of a source without reading it first, given the source chunk
+    /// hashes computed by [`StreamEncryptor::new`], and the infos of the chunks which were
+    /// encrypted before, in order. Returns an error if they don't match the size of the source.
+    pub(crate) async fn resume(
+        mut reader: R,
+        src_hashes: Vec<XorName>,
+        known_infos: Vec<ChunkInfo>,
+    ) -> Result<Self> {
+        let file_size = source_size(&mut reader).await?;
+        let known_infos_match = known_infos.iter().enumerate().all(|(index, info)| {
+            info.index == index
+                && info.src_size == get_chunk_size(file_size, index)
+                && src_hashes.get(index) == Some(&info.src_hash)
+        });
+        if src_hashes.len() != get_num_chunks(file_size) || !known_infos_match {
+            return Err(Error::StreamedDataChanged);
+        }
+
+        Ok(Self {
+            reader,
+            file_size,
+            chunk_infos: Vec::with_capacity(src_hashes.len()),
+            src_hashes,
+            known_infos,
+        })
+    }
+
+    /// The source chunk hashes, which are needed to resume the encryption.
+    pub(crate) fn src_hashes(&self) -> &[XorName] {
+        &self.src_hashes
+    }
+
+    /// The infos of the chunks produced or skipped so far, in order.
+    pub(crate) fn chunk_infos(&self) -> &[ChunkInfo] {
+        &self.chunk_infos
+    }
+
+    /// The name of the next chunk, if known without encrypting it, i.e. when it was encrypted
+    /// before the encryption was resumed.
+    pub(crate) fn next_known_name(&self) -> Option<XorName> {
+        self.known_infos
+            .get(self.chunk_infos.len())
+            .map(|info| info.dst_hash)
+    }
+
+    /// Skips the next chunk, without reading it, when its name is known.
+    /// Returns `false`, not skipping it, if its name is unknown.
+    pub(crate) async fn skip_chunk(&mut self) -> Result<bool> {
+        let index = self.chunk_infos.len();
+        let info = match self.known_infos.get(index) {
+            Some(info) => info.clone(),
+            None => return Ok(false),
+        };
+
+        let _ = self
+            .reader
+            .seek(SeekFrom::Current(info.src_size as i64))
+            .await?;
+        self.chunk_infos.push(info);
+        Ok(true)
+    }
+
+    /// Reads and encrypts the next chunk of the source, returning `None` once all chunks
+    /// have been produced.
+    pub(crate) async fn next_chunk(&mut self) -> Result<Option<EncryptedChunk>> {
+        let index = self.chunk_infos.len();
+        if index == self.src_hashes.len() {
+            return Ok(None);
+        }
+
+        let data = read_chunk(&mut self.reader, get_chunk_size(self.file_size, index)).await?;
+        let src_hash = XorName::from_content(&data);
+        if src_hash != self.src_hashes[index] {
+            return Err(Error::StreamedDataChanged);
+        }
+
+        let src_size = data.len();
+        let content = encrypt_chunk(data, index, &self.src_hashes)?;
+        self.chunk_infos.push(ChunkInfo {
+            index,
+            dst_hash: XorName::from_content(&content),
+            src_hash,
+            src_size,
+        });
+
+        Ok(Some(EncryptedChunk { index, content }))
+    }
+
+    /// Returns the `DataMap` of the source, once all its chunks have been encrypted.
+    pub(crate) fn into_data_map(self) -> Result<DataMap> {
+        if self.chunk_infos.len() < self.src_hashes.len() {
+            return Err(Error::NotAllDataWasChunked {
+                expected: self.src_hashes.len(),
+                chunked: self.chunk_infos.len(),
+            });
+        }
+
+        Ok(DataMap::new(self.chunk_infos))
+    }
+}
+
+/// Returns the size of the source by seeking to its end, leaving it positioned at its start.
+pub(crate) async fn source_size<R: AsyncSeek + Unpin>(reader: &mut R) -> Result<usize> {
+    let size = reader.seek(SeekFrom::End(0)).await?;
+    let _ = reader.seek(SeekFrom::Start(0)).await?;
+    Ok(size as usize)
+}
+
+async fn read_chunk<R: AsyncRead + Unpin>(reader: &mut R, size: usize) -> Result<Bytes> {
+    let mut buffer = vec![0; size];
+    let _ = reader.read_exact(&mut buffer).await?;
+    Ok(Bytes::from(buffer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::utils::random_bytes;
+    use eyre::Result;
+    use itertools::Itertools;
+    use self_encryption::{MAX_CHUNK_SIZE, MIN_CHUNK_SIZE};
+    use std::io::Cursor;
+
+    #[tokio::test]
+    async fn stream_encryption_matches_in_memory_encryption() -> Result<()> {
+        let sizes = [
+            MIN_ENCRYPTABLE_BYTES,
+            MIN_ENCRYPTABLE_BYTES + 1,
+            3 * MAX_CHUNK_SIZE - 1,
+            3 * MAX_CHUNK_SIZE,
+            4 * MAX_CHUNK_SIZE + MIN_CHUNK_SIZE / 2,
+            5 * MAX_CHUNK_SIZE + 2 * MIN_CHUNK_SIZE,
+        ];
+
+        for size in sizes {
+            let bytes = random_bytes(size);
+            let (expected_map, expected_chunks) = self_encryption::encrypt(bytes.clone())?;
+
+            let mut encryptor = StreamEncryptor::new(Cursor::new(bytes.to_vec())).await?;
+            let mut chunks = vec![];
+            while let Some(chunk) = encryptor.next_chunk().await? {
+                chunks.push(chunk);
+            }
+            let data_map = encryptor.into_data_map()?;
+
+            assert!(
+                data_map == expected_map,
+                "DataMap mismatch for size {}",
+                size
+            );
+            let contents = |chunks: &[EncryptedChunk]| {
+                chunks
+                    .iter()
+                    .sorted_by_key(|c| c.index)
+                    .map(|c| c.content.clone())
+                    .collect_vec()
+            };
+            assert_eq!(contents(&chunks), contents(&expected_chunks));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stream_encryption_resumes_from_known_chunks() -> Result<()> {
+        let bytes = random_bytes(5 * MAX_CHUNK_SIZE + 2 * MIN_CHUNK_SIZE);
+        let (expected_map, expected_chunks) = self_encryption::encrypt(bytes.clone())?;
+        let src_hashes = StreamEncryptor::new(Cursor::new(bytes.to_vec()))
+            .await?
+            .src_hashes()
+            .to_vec();
+        let mut known_infos = expected_map.infos();
+        let _ = known_infos.pop();
+
+        // every other known chunk is skipped without being read, the last one wasn't known
+        let mut encryptor =
+            StreamEncryptor::resume(Cursor::new(bytes.to_vec()), src_hashes.clone(), known_infos)
+                .await?;
+        let mut chunks = vec![];
+        while let Some(name) = encryptor.next_known_name() {
+            if chunks.len() % 2 == 0 && encryptor.skip_chunk().await? {
+                chunks.push(None);
+                continue;
+            }
+            let chunk = encryptor.next_chunk().await?.expect("a chunk to encrypt");
+            assert_eq!(XorName::from_content(&chunk.content), name);
+            chunks.push(Some(chunk));
+        }
+        assert!(!encryptor.skip_chunk().await?);
+        chunks.push(encryptor.next_chunk().await?);
+        assert!(encryptor.next_chunk().await?.is_none());
+        assert!(encryptor.into_data_map()? == expected_map);
+
+        for chunk in chunks.into_iter().flatten() {
+            let expected = expected_chunks.iter().find(|c| c.index == chunk.index);
+            assert_eq!(Some(&chunk.content), expected.map(|c| &c.content));
+        }
+
+        // the source must still match the known chunks
+        let result = StreamEncryptor::resume(
+            Cursor::new(bytes.slice(1..).to_vec()),
+            src_hashes,
+            expected_map.infos(),
+        )
+        .await;
+        assert!(matches!(result, Err(Error::StreamedDataChanged)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stream_encryption_rejects_small_content() {
+        let bytes = random_bytes(MIN_ENCRYPTABLE_BYTES - 1);
+        let result = StreamEncryptor::new(Cursor::new(bytes.to_vec())).await;
+        assert!(matches!(result, Err(Error::TooSmallToBeBlob)));
+    }
+}
@@ -6,9 +6,12 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+mod encryptor;
 mod pac_man;
+mod self_encryption_compat;
 
-pub(crate) use pac_man::{encrypt_blob, to_chunk, DataMapLevel};
+pub(crate) use encryptor::{source_size, StreamEncryptor};
+pub(crate) use pac_man::{encrypt_blob, pack, to_chunk, DataMapLevel};
 
 use crate::client::{Error, Result};
 
@@ -0,0 +1,146 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! COMPATIBILITY CODE: a copy of the private per-chunk steps of `self_encryption` 0.27.1.
+//!
+//! `self_encryption` only encrypts content held in memory as a whole, which is why
+//! `StreamEncryptor` encrypts chunks one at a time with the steps copied here: the sizes of the
+//! chunks, the derivation of their pad, key and IV, their compression, and their encryption.
+//!
+//! Chunk addresses depend on every one of those steps, so they must stay byte for byte the same
+//! as `self_encryption`'s, or the same content would get different addresses depending on the API
+//! used to upload it. Hence `self_encryption` is pinned to an exact version, and `aes`,
+//! `block-modes` and `brotli` use the same version requirements as it does, resolving to the same
+//! versions. Upgrading any of them means checking this module against the new `self_encryption`.
+//! The test comparing the output of `StreamEncryptor` with `self_encryption::encrypt` guards it.
+
+use crate::client::{Error, Result};
+
+use aes::Aes128;
+use block_modes::{block_padding::Pkcs7, BlockMode, Cbc};
+use brotli::enc::BrotliEncoderParams;
+use bytes::Bytes;
+use self_encryption::{COMPRESSION_QUALITY, MAX_CHUNK_SIZE, MIN_CHUNK_SIZE, MIN_ENCRYPTABLE_BYTES};
+use std::io::Cursor;
+use xor_name::{XorName, XOR_NAME_LEN};
+
+type Aes128Cbc = Cbc<Aes128, Pkcs7>;
+
+const KEY_SIZE: usize = 16;
+const IV_SIZE: usize = 16;
+const PAD_SIZE: usize = (XOR_NAME_LEN * 3) - KEY_SIZE - IV_SIZE;
+
+type PadKeyIv = ([u8; PAD_SIZE], [u8; KEY_SIZE], [u8; IV_SIZE]);
+
+/// Compresses and encrypts the chunk at `chunk_index`, given the source hashes of all the chunks.
+pub(super) fn encrypt_chunk(
+    content: Bytes,
+    chunk_index: usize,
+    src_hashes: &[XorName],
+) -> Result<Bytes> {
+    let (pad, key, iv) = get_pad_key_and_iv(chunk_index, src_hashes);
+
+    let mut compressed = vec![];
+    let enc_params = BrotliEncoderParams {
+        quality: COMPRESSION_QUALITY,
+        ..Default::default()
+    };
+    let _size = brotli::BrotliCompress(
+        &mut Cursor::new(content.as_ref()),
+        &mut compressed,
+        &enc_params,
+    )
+    .map_err(|_| Error::SelfEncryption(self_encryption::Error::Compression))?;
+
+    let cipher = Aes128Cbc::new_fix(key.as_ref().into(), iv.as_ref().into());
+    let encrypted = cipher.encrypt_vec(&compressed);
+
+    Ok(encrypted
+        .iter()
+        .zip(pad.iter().cycle())
+        .map(|(&a, &b)| a ^ b)
+        .collect())
+}
+
+// Each chunk is encrypted using the source hashes of itself and its two preceding chunks,
+// wrapping around to the last chunks for the first two.
+fn get_pad_key_and_iv(chunk_index: usize, chunk_hashes: &[XorName]) -> PadKeyIv {
+    let (n_1, n_2) = match chunk_index {
+        0 => (chunk_hashes.len() - 1, chunk_hashes.len() - 2),
+        1 => (0, chunk_hashes.len() - 1),
+        n => (n - 1, n - 2),
+    };
+    let src_hash = &chunk_hashes[chunk_index];
+    let n_1_src_hash = &chunk_hashes[n_1];
+    let n_2_src_hash = &chunk_hashes[n_2];
+
+    let mut pad = [0u8; PAD_SIZE];
+    let mut key = [0u8; KEY_SIZE];
+    let mut iv = [0u8; IV_SIZE];
+
+    for (pad_el, element) in pad
+        .iter_mut()
+        .zip(src_hash.iter().chain(n_2_src_hash.iter()))
+    {
+        *pad_el = *element;
+    }
+
+    for (key_el, element) in key.iter_mut().chain(iv.iter_mut()).zip(n_1_src_hash.iter()) {
+        *key_el = *element;
+    }
+
+    (pad, key, iv)
+}
+
+/// Returns the number of chunks according to file size.
+pub(super) fn get_num_chunks(file_size: usize) -> usize {
+    if file_size < MIN_ENCRYPTABLE_BYTES {
+        return 0;
+    }
+    if file_size < 3 * MAX_CHUNK_SIZE {
+        return 3;
+    }
+    match file_size % MAX_CHUNK_SIZE {
+        0 => file_size / MAX_CHUNK_SIZE,
+        _ => (file_size / MAX_CHUNK_SIZE) + 1,
+    }
+}
+
+/// Returns the size of a chunk according to file size.
+pub(super) fn get_chunk_size(file_size: usize, chunk_index: usize) -> usize {
+    if file_size < MIN_ENCRYPTABLE_BYTES {
+        return 0;
+    }
+    if file_size < 3 * MAX_CHUNK_SIZE {
+        if chunk_index < 2 {
+            return file_size / 3;
+        } else {
+            return file_size - (2 * (file_size / 3));
+        }
+    }
+    let total_chunks = get_num_chunks(file_size);
+    if chunk_index < total_chunks - 2 {
+        return MAX_CHUNK_SIZE;
+    }
+    let remainder = file_size % MAX_CHUNK_SIZE;
+    let penultimate = (total_chunks - 2) == chunk_index;
+    if remainder == 0 {
+        return MAX_CHUNK_SIZE;
+    }
+    if remainder < MIN_CHUNK_SIZE {
+        if penultimate {
+            MAX_CHUNK_SIZE - MIN_CHUNK_SIZE
+        } else {
+            MIN_CHUNK_SIZE + remainder
+        }
+    } else if penultimate {
+        MAX_CHUNK_SIZE
+    } else {
+        remainder
+    }
+}
@@ -7,31 +7,44 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 mod blob_apis;
+mod chunk_cache;
 mod commands;
 mod data;
+mod multisig;
 mod queries;
 mod register_apis;
+mod upload_journal;
+mod upload_pipeline;
 
-use crate::client::{connections::Session, errors::Error, ClientConfig};
+use crate::client::{
+    connections::{Session, NUM_OF_ELDERS_SUBSET_FOR_QUERIES},
+    errors::Error,
+    ClientConfig, RegisterReadConsistency, UploadConfig,
+};
 use crate::messaging::data::{CmdError, DataQuery, RegisterRead, ServiceMsg};
 use crate::messaging::{ServiceAuth, WireMsg};
 use crate::peer::Peer;
 use crate::prefix_map::NetworkPrefixMap;
 use crate::types::{utils::read_prefix_map_from_disk, Chunk, Keypair, PublicKey, RegisterAddress};
+use chunk_cache::ChunkDiskCache;
 use itertools::Itertools;
 use rand::rngs::OsRng;
 use std::collections::BTreeSet;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::{
-    sync::{mpsc::Receiver, RwLock},
+    sync::{broadcast, mpsc::Receiver, RwLock},
     time::Duration,
 };
 use tracing::{debug, info};
 use uluru::LRUCache;
 use xor_name::XorName;
 
+pub use multisig::MultiSigRegisterWrite;
 pub use register_apis::RegisterWriteAheadLog;
+pub use upload_journal::UploadJournalId;
+pub use upload_pipeline::{UploadEvent, UploadProgress};
 
 // Maximum amount of Chunks to keep in our cal Chunks cache.
 // Each Chunk is maximum types::MAX_CHUNK_SIZE_IN_BYTES, i.e. ~1MB
@@ -48,7 +61,12 @@ pub struct Client {
     incoming_errors: Arc<RwLock<Receiver<CmdError>>>,
     session: Session,
     pub(crate) query_timeout: Duration,
+    register_read_consistency: RegisterReadConsistency,
     chunks_cache: Arc<RwLock<ChunksCache>>,
+    chunks_disk_cache: Option<Arc<ChunkDiskCache>>,
+    root_dir: PathBuf,
+    upload_config: UploadConfig,
+    upload_events: broadcast::Sender<UploadEvent>,
 }
 
 /// Easily manage connections to/from The Safe Network with the client and its APIs.
@@ -81,6 +99,16 @@ impl Client {
         optional_keypair: Option<Keypair>,
         read_prefixmap: bool,
     ) -> Result<Self, Error> {
+        if !config
+            .register_read_consistency
+            .is_satisfiable_by(NUM_OF_ELDERS_SUBSET_FOR_QUERIES)
+        {
+            return Err(Error::InvalidReadConsistency {
+                consistency: config.register_read_consistency,
+                elders_queried: NUM_OF_ELDERS_SUBSET_FOR_QUERIES,
+            });
+        }
+
         let mut rng = OsRng;
 
         let keypair = match optional_keypair {
@@ -149,16 +177,38 @@ impl Client {
             err_sender,
             config.local_addr,
             config.standard_wait,
+            config.register_responses_wait,
             prefix_map.clone(),
         )
         .await?;
 
+        let chunks_disk_cache = match config.chunk_cache {
+            Some(cache_config) => {
+                match ChunkDiskCache::open(&config.root_dir, cache_config).await {
+                    Ok(cache) => Some(Arc::new(cache)),
+                    Err(error) => {
+                        warn!(
+                            "Chunks won't be cached on disk, failed to open cache: {}",
+                            error
+                        );
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
         let client = Self {
             keypair,
             session,
             incoming_errors: Arc::new(RwLock::new(err_receiver)),
             query_timeout: config.query_timeout,
+            register_read_consistency: config.register_read_consistency,
             chunks_cache: Arc::new(RwLock::new(ChunksCache::default())),
+            chunks_disk_cache,
+            root_dir: config.root_dir,
+            upload_config: config.upload,
+            upload_events: broadcast::channel(upload_pipeline::UPLOAD_EVENTS_CAPACITY).0,
         };
 
         // TODO: The message being sent below is a temporary solution to fetch network info for
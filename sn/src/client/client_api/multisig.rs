@@ -0,0 +1,292 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::client::{Error, Result};
+use crate::messaging::{
+    data::{DataCmd, RegisterWrite},
+    signature_aggregator::Error as AggregatorError,
+    system::SigShare,
+};
+use crate::types::{Keypair, PublicKey, Signature};
+
+use bls::PublicKeySet;
+use bytes::Bytes;
+use std::collections::BTreeMap;
+
+/// A write to a Register owned by the members of a BLS public key set, i.e. whose owner was
+/// recorded with `Register::set_owner_key_set`, which more than its threshold of members must co-sign.
+///
+/// Each member signs the current `payload` with its share of the key, e.g. with `sign_share`, and
+/// the shares are added until there are enough of them. Edits take two rounds of signing: first
+/// their CRDT operation, whose shares are aggregated into the owner's signature the operation
+/// carries, then the write itself, whose shares are sent along with it.
+/// Once fully signed, the write is sent with `Client::publish_multisig_register_write`.
+/// The shares are kept until then, however long the members take to sign.
+///
+/// Nodes check that more than the threshold of members signed the write with their shares,
+/// thus a write signed with the set's public key alone, e.g. by whoever dealt the shares, is rejected.
+#[derive(Debug, Clone)]
+pub struct MultiSigRegisterWrite {
+    public_key_set: PublicKeySet,
+    write: RegisterWrite,
+    // shares of the current payload, by index of the member
+    shares: BTreeMap<usize, SigShare>,
+}
+
+impl MultiSigRegisterWrite {
+    /// Starts collecting the signature shares of a write on behalf of the owner made of the
+    /// members of `public_key_set`. The CRDT operation of an edit must be sourced from the owner.
+    pub fn new(public_key_set: PublicKeySet, write: RegisterWrite) -> Result<Self> {
+        let owner = PublicKey::Bls(public_key_set.public_key());
+        if let RegisterWrite::Edit(op) = &write {
+            if op.source != owner {
+                return Err(Error::NotMultiSigOwner(op.source));
+            }
+        }
+
+        Ok(Self {
+            public_key_set,
+            write,
+            shares: BTreeMap::new(),
+        })
+    }
+
+    /// The key the write is signed on behalf of.
+    pub fn owner(&self) -> PublicKey {
+        PublicKey::Bls(self.public_key_set.public_key())
+    }
+
+    /// The write being signed.
+    pub fn write(&self) -> &RegisterWrite {
+        &self.write
+    }
+
+    /// Whether enough members co-signed the write for it to be sent.
+    pub fn is_signed(&self) -> bool {
+        !self.is_op_unsigned() && self.shares.len() > self.public_key_set.threshold()
+    }
+
+    /// The bytes the members currently need to sign, or `None` once the write is fully signed.
+    pub fn payload(&self) -> Result<Option<Bytes>> {
+        if self.is_signed() {
+            return Ok(None);
+        }
+
+        let payload = match &self.write {
+            RegisterWrite::Edit(op) if op.signature.is_none() => bincode::serialize(&op.crdt_op)?,
+            write => bincode::serialize(write)?,
+        };
+
+        Ok(Some(Bytes::from(payload)))
+    }
+
+    /// Signs the current payload with a member's share of the key set.
+    /// Returns `None` if the write is already fully signed.
+    pub fn sign_share(&self, keypair: &Keypair) -> Result<Option<SigShare>> {
+        let share = match keypair {
+            Keypair::BlsShare(share) if share.public_key_set == self.public_key_set => share,
+            other => return Err(Error::NotMultiSigOwner(other.public_key())),
+        };
+
+        Ok(self.payload()?.map(|payload| {
+            SigShare::new(
+                self.public_key_set.clone(),
+                share.index,
+                &share.secret,
+                &payload,
+            )
+        }))
+    }
+
+    /// Adds a member's signature share of the current payload, returning whether the write is
+    /// now fully signed. Shares of another key set or payload, e.g. of a previous round, are rejected.
+    pub fn add_share(&mut self, sig_share: SigShare) -> Result<bool> {
+        if sig_share.public_key_set != self.public_key_set {
+            let share_pk = sig_share.public_key_set.public_key_share(sig_share.index);
+            return Err(Error::NotMultiSigOwner(PublicKey::BlsShare(share_pk)));
+        }
+        let payload = match self.payload()? {
+            Some(payload) => payload,
+            None => return Ok(true),
+        };
+
+        if !sig_share.verify(&payload) {
+            return Err(Error::SignatureAggregation(AggregatorError::InvalidShare));
+        }
+        let _prev = self.shares.insert(sig_share.index, sig_share);
+        if self.shares.len() <= self.public_key_set.threshold() {
+            return Ok(false);
+        }
+        if !self.is_op_unsigned() {
+            return Ok(true);
+        }
+
+        let shares = self
+            .shares
+            .iter()
+            .map(|(index, share)| (*index, &share.signature_share));
+        let signature = self
+            .public_key_set
+            .combine_signatures(shares)
+            .map_err(|error| Error::SignatureAggregation(AggregatorError::Combine(error)))?;
+        // the write carrying the signed op is to be co-signed next
+        self.shares.clear();
+        if let RegisterWrite::Edit(op) = &mut self.write {
+            op.signature = Some(Signature::Bls(signature));
+        }
+
+        Ok(false)
+    }
+
+    /// Returns the cmd carrying the write and the members' shares, once enough of them co-signed it.
+    pub fn into_cmd(self) -> Result<DataCmd> {
+        if !self.is_signed() {
+            return Err(Error::NotEnoughSignatureShares);
+        }
+
+        Ok(DataCmd::MultiSigRegister {
+            write: self.write,
+            sig_shares: self.shares.into_values().collect(),
+        })
+    }
+
+    // Whether the CRDT operation of an edit is still to be co-signed, before the write itself.
+    fn is_op_unsigned(&self) -> bool {
+        matches!(&self.write, RegisterWrite::Edit(op) if op.signature.is_none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::register::Register;
+    use bls::SecretKeySet;
+    use eyre::Result;
+    use rand::rngs::OsRng;
+    use std::collections::BTreeSet;
+    use xor_name::XorName;
+
+    fn members(sk_set: &SecretKeySet, count: usize) -> Vec<Keypair> {
+        (0..count)
+            .map(|index| {
+                Keypair::new_bls_share(index, sk_set.secret_key_share(index), sk_set.public_keys())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn edits_carry_the_owners_signature_and_the_members_shares() -> Result<()> {
+        let threshold = 1;
+        let sk_set = SecretKeySet::random(threshold, &mut OsRng);
+        let owner = PublicKey::Bls(sk_set.public_keys().public_key());
+        let members = members(&sk_set, 3);
+
+        let mut register = Register::new_public(owner, XorName::random(), 15_000, None);
+        let (_, op) = register.write(b"team entry".to_vec(), BTreeSet::new())?;
+        let mut multisig =
+            MultiSigRegisterWrite::new(sk_set.public_keys(), RegisterWrite::Edit(op))?;
+
+        // two rounds of threshold + 1 shares: the op, then the write
+        let mut first_round_share = None;
+        for round in 0..2 {
+            for (index, member) in members.iter().take(threshold + 1).enumerate() {
+                let share = multisig.sign_share(member)?.expect("payload to sign");
+                let _ = first_round_share.get_or_insert_with(|| share.clone());
+                let signed = multisig.add_share(share)?;
+                assert_eq!(signed, round == 1 && index == threshold);
+            }
+
+            // shares of the op don't count towards signing the write
+            if round == 0 {
+                let share = first_round_share.clone().expect("share of the first round");
+                assert!(matches!(
+                    multisig.add_share(share),
+                    Err(Error::SignatureAggregation(AggregatorError::InvalidShare))
+                ));
+            }
+        }
+        assert!(multisig.payload()?.is_none());
+
+        let (write, sig_shares) = match multisig.into_cmd()? {
+            DataCmd::MultiSigRegister { write, sig_shares } => (write, sig_shares),
+            other => panic!("Unexpected cmd: {:?}", other),
+        };
+        let payload = bincode::serialize(&write)?;
+        assert_eq!(sig_shares.len(), threshold + 1);
+        assert!(sig_shares.iter().all(|share| share.verify(&payload)));
+
+        // the op can be applied by any replica
+        let mut replica = Register::new_public(owner, *register.name(), 15_000, None);
+        match write {
+            RegisterWrite::Edit(op) => replica.apply_op(op)?,
+            other => panic!("Unexpected write: {:?}", other),
+        }
+        assert_eq!(replica.size(None)?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn writes_need_more_than_threshold_shares_of_the_owner() -> Result<()> {
+        let threshold = 2;
+        let sk_set = SecretKeySet::random(threshold, &mut OsRng);
+        let owner = PublicKey::Bls(sk_set.public_keys().public_key());
+        let members = members(&sk_set, 4);
+        let address = *Register::new_public(owner, XorName::random(), 15_000, None).address();
+        let write = RegisterWrite::TransferOwnership {
+            address,
+            new_owner: members[0].public_key(),
+        };
+        let mut multisig = MultiSigRegisterWrite::new(sk_set.public_keys(), write)?;
+
+        // the same member signing twice doesn't count twice
+        for _ in 0..threshold + 1 {
+            let share = multisig.sign_share(&members[0])?.expect("payload to sign");
+            assert!(!multisig.add_share(share)?);
+        }
+
+        // shares of another set are rejected
+        let other_set = SecretKeySet::random(threshold, &mut OsRng);
+        let outsider = &self::members(&other_set, 1)[0];
+        assert!(matches!(
+            multisig.sign_share(outsider),
+            Err(Error::NotMultiSigOwner(_))
+        ));
+        let payload = multisig.payload()?.expect("payload to sign");
+        let share = SigShare::new(
+            other_set.public_keys(),
+            0,
+            &other_set.secret_key_share(0),
+            &payload,
+        );
+        assert!(matches!(
+            multisig.add_share(share),
+            Err(Error::NotMultiSigOwner(_))
+        ));
+        assert!(!multisig.is_signed());
+        assert!(matches!(
+            multisig.clone().into_cmd(),
+            Err(Error::NotEnoughSignatureShares)
+        ));
+
+        for member in &members[1..=threshold] {
+            let share = multisig.sign_share(member)?.expect("payload to sign");
+            let _signed = multisig.add_share(share)?;
+        }
+        assert!(multisig.is_signed());
+        match multisig.into_cmd()? {
+            DataCmd::MultiSigRegister { sig_shares, .. } => {
+                let signers: Vec<_> = sig_shares.iter().map(|share| share.index).collect();
+                assert_eq!(signers, (0..=threshold).collect::<Vec<_>>());
+            }
+            other => panic!("Unexpected cmd: {:?}", other),
+        }
+
+        Ok(())
+    }
+}
@@ -7,7 +7,11 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::Client;
-use crate::client::{connections::QueryResult, errors::Error};
+use crate::client::{
+    connections::{QueryResult, NUM_OF_ELDERS_SUBSET_FOR_QUERIES},
+    errors::Error,
+    RegisterReadConsistency,
+};
 use crate::messaging::{
     data::{DataQuery, ServiceMsg},
     ServiceAuth, WireMsg,
@@ -25,7 +29,19 @@ impl Client {
     /// Queries are automatically retried using exponential backoff if the timeout is hit.
     #[instrument(skip(self), level = "debug")]
     pub async fn send_query(&self, query: DataQuery) -> Result<QueryResult, Error> {
-        self.send_query_with_retry_count(query, MAX_RETRY_COUNT)
+        self.send_query_with_retry_count(query, MAX_RETRY_COUNT, self.register_read_consistency)
+            .await
+    }
+
+    /// Send a Query to the network and await a response, overriding the
+    /// configured consistency required when reading a Register.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn send_query_with_consistency(
+        &self,
+        query: DataQuery,
+        consistency: RegisterReadConsistency,
+    ) -> Result<QueryResult, Error> {
+        self.send_query_with_retry_count(query, MAX_RETRY_COUNT, consistency)
             .await
     }
 
@@ -33,7 +49,8 @@ impl Client {
     /// Queries are not retried if the timeout is hit.
     #[instrument(skip(self), level = "debug")]
     pub async fn send_query_without_retry(&self, query: DataQuery) -> Result<QueryResult, Error> {
-        self.send_query_with_retry_count(query, 1.0).await
+        self.send_query_with_retry_count(query, 1.0, self.register_read_consistency)
+            .await
     }
 
     // Send a Query to the network and await a response.
@@ -44,7 +61,16 @@ impl Client {
         &self,
         query: DataQuery,
         retry_count: f32,
+        consistency: RegisterReadConsistency,
     ) -> Result<QueryResult, Error> {
+        // A quorum that can never be reached would otherwise only fail once all retries timed out
+        if !consistency.is_satisfiable_by(NUM_OF_ELDERS_SUBSET_FOR_QUERIES) {
+            return Err(Error::InvalidReadConsistency {
+                consistency,
+                elders_queried: NUM_OF_ELDERS_SUBSET_FOR_QUERIES,
+            });
+        }
+
         let client_pk = self.public_key();
         let msg = ServiceMsg::Query(query.clone());
         let serialised_query = WireMsg::serialize_msg_payload(&msg)?;
@@ -56,6 +82,7 @@ impl Client {
         let span = info_span!("Attempting a query");
         let _ = span.enter();
         let mut attempt = 1.0;
+        let mut last_error = None;
         loop {
             debug!(
                 "Attempting {:?} (attempt #{}) with a query timeout of {:?}",
@@ -64,23 +91,31 @@ impl Client {
 
             let res = tokio::time::timeout(
                 attempt_timeout,
-                self.send_signed_query(
+                self.send_signed_query_with_consistency(
                     query.clone(),
                     client_pk,
                     serialised_query.clone(),
                     signature.clone(),
+                    consistency,
                 ),
             )
             .await;
 
-            if let Ok(Ok(query_result)) = res {
-                break Ok(query_result);
-            } else if attempt > MAX_RETRY_COUNT {
+            match res {
+                Ok(Ok(query_result)) => break Ok(query_result),
+                // Elders may still be syncing, but if they never agree it's worth telling
+                Ok(Err(error @ Error::InsufficientMatchingResponses { .. })) => {
+                    last_error = Some(error)
+                }
+                _ => {}
+            }
+
+            if attempt > MAX_RETRY_COUNT {
                 debug!(
                     "Retries ({}) all failed returning no response for {:?}",
                     MAX_RETRY_COUNT, query
                 );
-                break Err(Error::NoResponse);
+                break Err(last_error.unwrap_or(Error::NoResponse));
             }
 
             attempt += 1.0;
@@ -96,6 +131,26 @@ impl Client {
         client_pk: PublicKey,
         serialised_query: Bytes,
         signature: Signature,
+    ) -> Result<QueryResult, Error> {
+        self.send_signed_query_with_consistency(
+            query,
+            client_pk,
+            serialised_query,
+            signature,
+            self.register_read_consistency,
+        )
+        .await
+    }
+
+    // Send an already signed Query to the network and await a response,
+    // with the given consistency required when reading a Register.
+    async fn send_signed_query_with_consistency(
+        &self,
+        query: DataQuery,
+        client_pk: PublicKey,
+        serialised_query: Bytes,
+        signature: Signature,
+        consistency: RegisterReadConsistency,
     ) -> Result<QueryResult, Error> {
         debug!("Sending Query: {:?}", query);
         let auth = ServiceAuth {
@@ -103,6 +158,8 @@ impl Client {
             signature,
         };
 
-        self.session.send_query(query, auth, serialised_query).await
+        self.session
+            .send_query(query, auth, serialised_query, consistency)
+            .await
     }
 }
@@ -6,16 +6,17 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::Client;
-use crate::client::Error;
+use super::{Client, MultiSigRegisterWrite};
+use crate::client::{Error, RegisterReadConsistency};
 use crate::messaging::data::{DataCmd, DataQuery, QueryResponse, RegisterRead, RegisterWrite};
 use crate::types::{
     register::{
-        Entry, EntryHash, Permissions, Policy, PrivatePermissions, PrivatePolicy,
+        Entry, EntryHash, HistoryEntry, Permissions, Policy, PrivatePermissions, PrivatePolicy,
         PublicPermissions, PublicPolicy, Register, User,
     },
-    PublicKey, RegisterAddress as Address,
+    OwnerType, PublicKey, RegisterAddress as Address,
 };
+use bls::PublicKeySet;
 use std::collections::{BTreeMap, BTreeSet};
 use xor_name::XorName;
 
@@ -103,6 +104,36 @@ impl Client {
         Ok((address, batch))
     }
 
+    /// Create a Public Register Data on the network, owned by the members of a BLS public key set,
+    /// more than the threshold of whom must co-sign the writes on behalf of the owner,
+    /// see `multisig_write_to_register`.
+    ///
+    /// Returns a write ahead log (WAL) of register operations, note that the changes are not uploaded to the
+    /// network until the WAL is published with `publish_register_ops`
+    #[instrument(skip(self, permissions), level = "debug")]
+    pub async fn store_multisig_public_register(
+        &self,
+        name: XorName,
+        tag: u64,
+        owner: PublicKeySet,
+        permissions: BTreeMap<User, PublicPermissions>,
+    ) -> Result<(Address, RegisterWriteAheadLog), Error> {
+        let pk = self.public_key();
+        let policy = PublicPolicy {
+            owner: PublicKey::Bls(owner.public_key()),
+            permissions,
+        };
+        let mut pub_register = Register::new_public(pk, name, tag, Some(policy));
+        pub_register.set_owner_key_set(owner)?;
+        let address = *pub_register.address();
+
+        let batch = self
+            .batch_up_pay_write_register_to_network(pub_register)
+            .await?;
+
+        Ok((address, batch))
+    }
+
     /// Delete Register
     ///
     /// Returns a write ahead log (WAL) of register operations, note that the changes are not uploaded to the
@@ -125,6 +156,9 @@ impl Client {
     ///
     /// Public or private isn't important for writing, though the data you write will
     /// be Public or Private according to the type of the targeted Register.
+    ///
+    /// The new entry supersedes all the entries given as `children`. Passing all the entries
+    /// currently returned by `read_register` thus merges any concurrent forks into it.
     #[instrument(skip(self, children), level = "debug")]
     pub async fn write_to_register(
         &self,
@@ -148,6 +182,43 @@ impl Client {
         Ok((hash, batch))
     }
 
+    /// Write to a Register owned by the members of a BLS public key set, i.e. one created with
+    /// `store_multisig_public_register`.
+    ///
+    /// The write returned must be co-signed by more than the threshold of members of the set,
+    /// see `MultiSigRegisterWrite`, before it's sent with `publish_multisig_register_write`.
+    /// Policy and ownership changes are co-signed the same way, starting from `MultiSigRegisterWrite::new`.
+    #[instrument(skip(self, children), level = "debug")]
+    pub async fn multisig_write_to_register(
+        &self,
+        address: Address,
+        entry: Entry,
+        children: BTreeSet<EntryHash>,
+    ) -> Result<(EntryHash, MultiSigRegisterWrite), Error> {
+        let mut register = self.get_register(address).await?;
+        let owner = match register.owner_type() {
+            OwnerType::Multi(key_set) => key_set,
+            OwnerType::Single(owner) => return Err(Error::NotMultiSigOwner(owner)),
+        };
+
+        // The op is sourced from the owner, as it's the owner's signature it's to carry
+        register.set_replica_authority(register.owner());
+        let (hash, op) = register.write(entry, children)?;
+
+        let write = MultiSigRegisterWrite::new(owner, RegisterWrite::Edit(op))?;
+        Ok((hash, write))
+    }
+
+    /// Send a Register write once enough members of its multi-sig owner co-signed it.
+    /// The cmd carrying it is signed by this client, while the members' shares authorise the write.
+    #[instrument(skip_all, level = "debug")]
+    pub async fn publish_multisig_register_write(
+        &self,
+        write: MultiSigRegisterWrite,
+    ) -> Result<(), Error> {
+        self.send_cmd(write.into_cmd()?).await
+    }
+
     /// Store a new Register data object
     /// Wraps msg_contents for payment validation and mutation
     ///
@@ -168,12 +239,24 @@ impl Client {
     // Get Register
     //---------------------
 
-    /// Get a Register from the Network
+    /// Get a Register from the Network, with the read consistency set in the client's config.
     #[instrument(skip(self), level = "debug")]
     pub async fn get_register(&self, address: Address) -> Result<Register, Error> {
+        self.get_register_with_consistency(address, self.register_read_consistency)
+            .await
+    }
+
+    /// Get a Register from the Network, with the given read consistency
+    /// instead of the one set in the client's config.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn get_register_with_consistency(
+        &self,
+        address: Address,
+        consistency: RegisterReadConsistency,
+    ) -> Result<Register, Error> {
         // Let's fetch the Register from the network
         let query = DataQuery::Register(RegisterRead::Get(address));
-        let query_result = self.send_query(query).await?;
+        let query_result = self.send_query_with_consistency(query, consistency).await?;
         match query_result.response {
             QueryResponse::GetRegister((res, op_id)) => {
                 res.map_err(|err| Error::ErrorMessage { source: err, op_id })
@@ -188,7 +271,21 @@ impl Client {
         &self,
         address: Address,
     ) -> Result<BTreeSet<(EntryHash, Entry)>, Error> {
-        let register = self.get_register(address).await?;
+        self.read_register_with_consistency(address, self.register_read_consistency)
+            .await
+    }
+
+    /// Get the last data entry from a Register data, with the given read consistency
+    /// instead of the one set in the client's config.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn read_register_with_consistency(
+        &self,
+        address: Address,
+        consistency: RegisterReadConsistency,
+    ) -> Result<BTreeSet<(EntryHash, Entry)>, Error> {
+        let register = self
+            .get_register_with_consistency(address, consistency)
+            .await?;
         let last = register.read(None)?;
 
         Ok(last)
@@ -209,6 +306,43 @@ impl Client {
         Ok(entry.to_owned())
     }
 
+    /// Get the history of a Register, every entry coming after the entries it was written on top of.
+    ///
+    /// If `from` is provided, the history ends with that entry, i.e. it's the Register's
+    /// content as of that version, otherwise it's the history of all the current entries.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn get_register_history(
+        &self,
+        address: Address,
+        from: Option<EntryHash>,
+    ) -> Result<Vec<HistoryEntry>, Error> {
+        let query = DataQuery::Register(RegisterRead::History { address, from });
+        let query_result = self.send_query(query).await?;
+        match query_result.response {
+            QueryResponse::GetRegisterHistory((res, op_id)) => {
+                res.map_err(|err| Error::ErrorMessage { source: err, op_id })
+            }
+            _ => Err(Error::ReceivedUnexpectedEvent),
+        }
+    }
+
+    /// Get the entries an entry of a Register was written on top of.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn get_register_children(
+        &self,
+        address: Address,
+        hash: EntryHash,
+    ) -> Result<BTreeSet<(EntryHash, Entry)>, Error> {
+        let query = DataQuery::Register(RegisterRead::GetChildren { address, hash });
+        let query_result = self.send_query(query).await?;
+        match query_result.response {
+            QueryResponse::GetRegisterChildren((res, op_id)) => {
+                res.map_err(|err| Error::ErrorMessage { source: err, op_id })
+            }
+            _ => Err(Error::ReceivedUnexpectedEvent),
+        }
+    }
+
     //----------------------
     // Ownership
     //---------------------
@@ -222,6 +356,25 @@ impl Client {
         Ok(owner)
     }
 
+    /// Transfer the ownership of a Register to another key. Only its current owner can do it.
+    ///
+    /// Returns a write ahead log (WAL) of register operations, note that the changes are not uploaded to the
+    /// network until the WAL is published with `publish_register_ops`
+    #[instrument(skip(self), level = "debug")]
+    pub async fn transfer_register_ownership(
+        &self,
+        address: Address,
+        new_owner: PublicKey,
+    ) -> Result<RegisterWriteAheadLog, Error> {
+        // Check it's allowed on our replica, so we don't send an op the network would reject
+        let mut register = self.get_register(address).await?;
+        register.transfer_ownership(new_owner, Some(self.public_key()))?;
+
+        let cmd = DataCmd::Register(RegisterWrite::TransferOwnership { address, new_owner });
+        let batch = vec![cmd];
+        Ok(batch)
+    }
+
     //----------------------
     // Permissions
     //---------------------
@@ -247,6 +400,26 @@ impl Client {
 
         Ok(policy.clone())
     }
+
+    /// Set the Policy of a Register, e.g. to add or remove writers, or to grant read access
+    /// to a private Register. Only its owner can do it, and the policy must keep the owner.
+    ///
+    /// Returns a write ahead log (WAL) of register operations, note that the changes are not uploaded to the
+    /// network until the WAL is published with `publish_register_ops`
+    #[instrument(skip(self), level = "debug")]
+    pub async fn set_register_policy(
+        &self,
+        address: Address,
+        policy: Policy,
+    ) -> Result<RegisterWriteAheadLog, Error> {
+        // Check it's allowed on our replica, so we don't send an op the network would reject
+        let mut register = self.get_register(address).await?;
+        register.set_policy(policy.clone(), Some(self.public_key()))?;
+
+        let cmd = DataCmd::Register(RegisterWrite::SetPolicy { address, policy });
+        let batch = vec![cmd];
+        Ok(batch)
+    }
 }
 
 #[cfg(test)]
@@ -259,15 +432,20 @@ mod tests {
         Error,
     };
     use crate::messaging::data::Error as ErrorMessage;
-    use crate::retry_loop_for_pattern;
     use crate::types::log_markers::LogMarker;
     use crate::types::{
-        register::{Action, EntryHash, Permissions, PrivatePermissions, PublicPermissions, User},
-        Error as DtError, PublicKey,
+        register::{
+            Action, EntryHash, Permissions, Policy, PrivatePermissions, PublicPermissions, User,
+        },
+        Error as DtError, Keypair, PublicKey,
     };
+    use crate::{retry_loop, retry_loop_for_pattern};
+    use bls::SecretKeySet;
     use eyre::{bail, eyre, Result};
+    use rand::rngs::OsRng;
     use std::{
         collections::{BTreeMap, BTreeSet},
+        iter,
         time::Instant,
     };
     use tokio::time::Duration;
@@ -676,6 +854,60 @@ mod tests {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn register_history() -> Result<()> {
+        init_test_logger();
+        let _outer_span = tracing::info_span!("test__register_history").entered();
+
+        let tag = 10;
+        let name = XorName(rand::random());
+        let client = create_test_client().await?;
+
+        let owner = client.public_key();
+        let mut perms = BTreeMap::<User, PublicPermissions>::new();
+        let _ = perms.insert(User::Key(owner), PublicPermissions::new(true));
+
+        let (address, batch) = client
+            .store_public_register(name, tag, owner, perms)
+            .await?;
+        client.publish_register_ops(batch).await?;
+
+        // write two entries, the second one on top of the first one
+        let value_1 = random_register_entry();
+        let (value1_hash, batch) = client
+            .write_to_register(address, value_1.clone(), BTreeSet::new())
+            .await?;
+        client.publish_register_ops(batch).await?;
+
+        let value_2 = random_register_entry();
+        let (value2_hash, batch) = client
+            .write_to_register(address, value_2.clone(), iter::once(value1_hash).collect())
+            .await?;
+        client.publish_register_ops(batch).await?;
+
+        let history = retry_loop_for_pattern!(client.get_register_history(address, None), Ok(history) if history.len() == 2)?;
+        let hashes: Vec<_> = history.iter().map(|item| item.hash).collect();
+        assert_eq!(hashes, vec![value1_hash, value2_hash]);
+        assert_eq!(history[1].children, iter::once(value1_hash).collect());
+
+        // the children of an entry are the entries it was written on top of
+        let children = client.get_register_children(address, value2_hash).await?;
+        assert_eq!(children, iter::once((value1_hash, value_1)).collect());
+        assert!(client
+            .get_register_children(address, value1_hash)
+            .await?
+            .is_empty());
+
+        // the history can also end with a given entry
+        let history = client
+            .get_register_history(address, Some(value1_hash))
+            .await?;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].hash, value1_hash);
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn register_owner() -> Result<()> {
         init_test_logger();
@@ -701,6 +933,101 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn register_policy_and_ownership() -> Result<()> {
+        init_test_logger();
+        let _outer_span = tracing::info_span!("test__register_policy_and_ownership").entered();
+        let tag = 10;
+
+        let name = XorName(rand::random());
+        let client = create_test_client().await?;
+
+        let owner = client.public_key();
+        let (address, batch) = client
+            .store_private_register(name, tag, owner, BTreeMap::new())
+            .await?;
+        client.publish_register_ops(batch).await?;
+
+        // grant read access to another user
+        let reader = gen_ed_keypair().public_key();
+        let mut policy = retry_loop!(client.get_register_policy(address));
+        if let Policy::Private(policy) = &mut policy {
+            let _prev = policy
+                .permissions
+                .insert(reader, PrivatePermissions::new(true, false));
+        }
+        let batch = client.set_register_policy(address, policy).await?;
+        client.publish_register_ops(batch).await?;
+
+        let permissions = retry_loop!(client.get_register_permissions_for_user(address, reader));
+        assert_eq!(
+            permissions,
+            Permissions::Private(PrivatePermissions::new(true, false))
+        );
+
+        // once transferred, the previous owner can't change the policy anymore
+        let new_owner = gen_ed_keypair().public_key();
+        let batch = client
+            .transfer_register_ownership(address, new_owner)
+            .await?;
+        client.publish_register_ops(batch).await?;
+
+        let _ = retry_loop_for_pattern!(
+            client.get_register_owner(address),
+            Ok(current_owner) if *current_owner == new_owner
+        )?;
+        let policy = client.get_register_policy(address).await?;
+        match client.set_register_policy(address, policy).await {
+            Err(Error::NetworkDataError(DtError::AccessDenied(key))) => assert_eq!(key, owner),
+            other => bail!("Unexpected result setting the policy: {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn register_multisig_write() -> Result<()> {
+        init_test_logger();
+        let _outer_span = tracing::info_span!("test__register_multisig_write").entered();
+        let tag = 10;
+
+        let threshold = 1;
+        let sk_set = SecretKeySet::random(threshold, &mut OsRng);
+        let members: Vec<_> = (0..3)
+            .map(|index| {
+                Keypair::new_bls_share(index, sk_set.secret_key_share(index), sk_set.public_keys())
+            })
+            .collect();
+
+        let name = XorName(rand::random());
+        let client = create_test_client().await?;
+        let (address, batch) = client
+            .store_multisig_public_register(name, tag, sk_set.public_keys(), BTreeMap::new())
+            .await?;
+        client.publish_register_ops(batch).await?;
+        let _ = retry_loop!(client.get_register(address));
+
+        let (hash, mut write) = client
+            .multisig_write_to_register(address, b"team entry".to_vec(), BTreeSet::new())
+            .await?;
+        while !write.is_signed() {
+            for member in &members[..=threshold] {
+                let share = write
+                    .sign_share(member)?
+                    .ok_or_else(|| eyre!("no payload"))?;
+                let _signed = write.add_share(share)?;
+            }
+        }
+        client.publish_multisig_register_write(write).await?;
+
+        let _ = retry_loop_for_pattern!(
+            client.get_register_entry(address, hash),
+            Ok(entry) if entry.as_slice() == b"team entry"
+        )?;
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn register_can_delete_private() -> Result<()> {
         init_test_logger();
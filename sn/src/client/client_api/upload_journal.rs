@@ -0,0 +1,229 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::client::{Error, Result};
+use crate::types::Scope;
+
+use bincode::{deserialize, serialize};
+use self_encryption::ChunkInfo;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+use tokio::{fs, io::AsyncWriteExt};
+use tracing::{debug, trace};
+use xor_name::XorName;
+
+// Name of the folder, within the client's root dir, where upload journals are kept.
+const UPLOAD_JOURNALS_DIR_NAME: &str = "upload_journals";
+
+/// Identifier of an upload journal.
+///
+/// It's derived from the location of the file being uploaded and the scope of the upload,
+/// thus an upload of the same file with the same scope can always be resumed with it.
+pub type UploadJournalId = XorName;
+
+/// On-disk record of the progress of an upload, used to resume it if it fails halfway.
+///
+/// It keeps track of the chunks which were already stored onto the network, so only the missing
+/// chunks are sent when resuming. It also keeps the source chunk hashes and the infos of the chunks
+/// which were self-encrypted, so the missing chunks are the only ones read and self-encrypted again.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct UploadJournal {
+    #[serde(skip)]
+    path: PathBuf,
+    /// Location of the file being uploaded.
+    source: PathBuf,
+    /// Scope of the upload.
+    scope: Scope,
+    /// Hashes of the source chunks, known once the source has been read a first time.
+    src_hashes: Vec<XorName>,
+    /// Infos of the chunks self-encrypted so far, in order.
+    chunk_infos: Vec<ChunkInfo>,
+    /// Addresses of the chunks which were already stored onto the network.
+    confirmed: BTreeSet<XorName>,
+}
+
+impl UploadJournal {
+    /// Returns the journal id of an upload of the file at `source` with the given `scope`.
+    pub(crate) fn id(source: &Path, scope: Scope) -> Result<UploadJournalId> {
+        let source = source.canonicalize()?;
+        let mut content = serialize(&scope)?;
+        content.extend(source.to_string_lossy().as_bytes());
+        Ok(XorName::from_content(&content))
+    }
+
+    /// Creates a new journal for an upload of the file at `source`, replacing any existing
+    /// journal for it, and writes it to disk.
+    pub(crate) async fn create(root_dir: &Path, source: &Path, scope: Scope) -> Result<Self> {
+        let id = Self::id(source, scope)?;
+        let journal = Self {
+            path: journal_path(root_dir, &id),
+            source: source.canonicalize()?,
+            scope,
+            src_hashes: vec![],
+            chunk_infos: vec![],
+            confirmed: BTreeSet::new(),
+        };
+        debug!("Creating upload journal {:?} for {:?}", id, journal.source);
+        journal.write().await?;
+
+        Ok(journal)
+    }
+
+    /// Reads the journal with the given id from disk.
+    pub(crate) async fn load(root_dir: &Path, id: UploadJournalId) -> Result<Self> {
+        let path = journal_path(root_dir, &id);
+        let bytes = match fs::read(&path).await {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Err(Error::NoSuchUploadJournal(id))
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut journal: Self = deserialize(&bytes)?;
+        journal.path = path;
+        debug!(
+            "Loaded upload journal {:?} for {:?}, with {} chunks confirmed",
+            id,
+            journal.source,
+            journal.confirmed.len()
+        );
+
+        Ok(journal)
+    }
+
+    /// Location of the file being uploaded.
+    pub(crate) fn source(&self) -> &Path {
+        &self.source
+    }
+
+    /// Scope of the upload.
+    pub(crate) fn scope(&self) -> Scope {
+        self.scope
+    }
+
+    /// Hashes of the source chunks, empty if the source was never read before.
+    pub(crate) fn src_hashes(&self) -> &[XorName] {
+        &self.src_hashes
+    }
+
+    /// Infos of the chunks self-encrypted before, in order.
+    pub(crate) fn chunk_infos(&self) -> &[ChunkInfo] {
+        &self.chunk_infos
+    }
+
+    /// Whether the chunk was already stored onto the network.
+    pub(crate) fn is_confirmed(&self, name: &XorName) -> bool {
+        self.confirmed.contains(name)
+    }
+
+    /// Records the chunks of a batch which were stored onto the network,
+    /// and writes the journal to disk, along with the chunk infos set before.
+    pub(crate) async fn record_confirmed(
+        &mut self,
+        confirmed: impl IntoIterator<Item = XorName>,
+    ) -> Result<()> {
+        self.confirmed.extend(confirmed);
+        trace!(
+            "Upload journal for {:?}: {} chunks confirmed",
+            self.source,
+            self.confirmed.len()
+        );
+        self.write().await
+    }
+
+    /// Records the hashes of the source chunks, once the source was read a first time,
+    /// and writes the journal to disk.
+    pub(crate) async fn record_src_hashes(&mut self, src_hashes: Vec<XorName>) -> Result<()> {
+        self.src_hashes = src_hashes;
+        self.write().await
+    }
+
+    /// Sets the infos of the chunks self-encrypted so far. They are written to disk along with
+    /// the next confirmed batch, so the infos of a confirmed chunk are never missing from disk.
+    pub(crate) fn set_chunk_infos(&mut self, chunk_infos: &[ChunkInfo]) {
+        self.chunk_infos = chunk_infos.to_vec();
+    }
+
+    /// Removes the journal from disk, once the upload has completed.
+    pub(crate) async fn remove(self) -> Result<()> {
+        debug!("Removing upload journal for {:?}", self.source);
+        fs::remove_file(&self.path).await?;
+        Ok(())
+    }
+
+    // Writes the journal to a temporary file first, then renames it, so a failure
+    // while writing never leaves a corrupted journal behind.
+    async fn write(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+
+        let tmp_path = self.path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp_path).await?;
+        file.write_all(&serialize(self)?).await?;
+        file.sync_all().await?;
+        fs::rename(&tmp_path, &self.path).await?;
+
+        Ok(())
+    }
+}
+
+fn journal_path(root_dir: &Path, id: &UploadJournalId) -> PathBuf {
+    root_dir
+        .join(UPLOAD_JOURNALS_DIR_NAME)
+        .join(format!("{:x}", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::Result;
+
+    #[tokio::test]
+    async fn journal_survives_reload() -> Result<()> {
+        let root_dir = tempfile::tempdir()?;
+        let source = tempfile::NamedTempFile::new()?;
+
+        let id = UploadJournal::id(source.path(), Scope::Public)?;
+        assert_ne!(id, UploadJournal::id(source.path(), Scope::Private)?);
+
+        let mut journal =
+            UploadJournal::create(root_dir.path(), source.path(), Scope::Public).await?;
+        let (first, second) = (XorName::random(), XorName::random());
+        let src_hashes = vec![XorName::random(), XorName::random(), XorName::random()];
+        journal.record_src_hashes(src_hashes.clone()).await?;
+        let chunk_infos = vec![ChunkInfo {
+            index: 0,
+            dst_hash: first,
+            src_hash: src_hashes[0],
+            src_size: 1024,
+        }];
+        journal.set_chunk_infos(&chunk_infos);
+        journal.record_confirmed(vec![first]).await?;
+
+        let journal = UploadJournal::load(root_dir.path(), id).await?;
+        assert_eq!(journal.scope(), Scope::Public);
+        assert_eq!(journal.source(), source.path().canonicalize()?);
+        assert_eq!(journal.src_hashes(), src_hashes);
+        assert_eq!(journal.chunk_infos(), chunk_infos);
+        assert!(journal.is_confirmed(&first));
+        assert!(!journal.is_confirmed(&second));
+
+        journal.remove().await?;
+        assert!(matches!(
+            UploadJournal::load(root_dir.path(), id).await,
+            Err(Error::NoSuchUploadJournal(_))
+        ));
+
+        Ok(())
+    }
+}
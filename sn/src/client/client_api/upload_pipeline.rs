@@ -0,0 +1,418 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Client;
+use crate::client::{Result, UploadConfig};
+use crate::messaging::{data::DataCmd, system::LoadReport};
+use crate::types::Chunk;
+
+use backoff::{backoff::Backoff, ExponentialBackoff};
+use futures::future::join_all;
+use std::{
+    future::Future,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use tokio::{
+    sync::{broadcast, Semaphore},
+    time::sleep,
+};
+use xor_name::XorName;
+
+// Number of events kept for subscribers which didn't receive them yet.
+pub(super) const UPLOAD_EVENTS_CAPACITY: usize = 1024;
+
+/// Progress of the uploads of a client, as published to the subscribers of
+/// [`Client::upload_events`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UploadEvent {
+    /// A chunk is being sent to the network.
+    ChunkSent {
+        /// Name of the chunk.
+        name: XorName,
+        /// Size of the chunk, in bytes.
+        bytes: usize,
+        /// Number of times the chunk has been sent, including this one.
+        attempt: usize,
+    },
+    /// The chunk was delivered to the Elders of its section, which relay it to the Adults
+    /// storing it. It's only known to be stored once it can be read back.
+    ChunkDelivered {
+        /// Name of the chunk.
+        name: XorName,
+        /// Size of the chunk, in bytes.
+        bytes: usize,
+    },
+    /// Sending the chunk failed, and it won't be retried, failing the upload.
+    ChunkFailed {
+        /// Name of the chunk.
+        name: XorName,
+        /// Size of the chunk, in bytes.
+        bytes: usize,
+    },
+    /// The number of chunks sent at once changed, as per the load reported by Elders.
+    ConcurrencyChanged(usize),
+}
+
+/// Running totals of upload events, e.g. to report the progress of uploads.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UploadProgress {
+    /// Number of chunks sent at least once.
+    pub chunks_sent: usize,
+    /// Number of chunks delivered to the network.
+    pub chunks_delivered: usize,
+    /// Number of chunks which couldn't be stored.
+    pub chunks_failed: usize,
+    /// Size of the chunks sent at least once, in bytes.
+    pub bytes_sent: usize,
+    /// Size of the chunks delivered to the network, in bytes.
+    pub bytes_delivered: usize,
+    /// Number of chunks currently sent at once.
+    pub concurrency: Option<usize>,
+}
+
+impl UploadProgress {
+    /// Adds an event to the totals.
+    pub fn record(&mut self, event: &UploadEvent) {
+        match *event {
+            UploadEvent::ChunkSent {
+                bytes, attempt: 1, ..
+            } => {
+                self.chunks_sent += 1;
+                self.bytes_sent += bytes;
+            }
+            UploadEvent::ChunkSent { .. } => {}
+            UploadEvent::ChunkDelivered { bytes, .. } => {
+                self.chunks_delivered += 1;
+                self.bytes_delivered += bytes;
+            }
+            UploadEvent::ChunkFailed { .. } => self.chunks_failed += 1,
+            UploadEvent::ConcurrencyChanged(concurrency) => self.concurrency = Some(concurrency),
+        }
+    }
+}
+
+impl Client {
+    /// Subscribes to the progress events of all the uploads of this client, from now on.
+    /// Events are dropped for subscribers lagging too far behind.
+    pub fn upload_events(&self) -> broadcast::Receiver<UploadEvent> {
+        self.upload_events.subscribe()
+    }
+
+    // Stores the chunks through the upload pipeline, returning the outcome for each of them,
+    // in the same order.
+    pub(crate) async fn store_chunks(&self, chunks: Vec<Chunk>) -> Vec<Result<()>> {
+        UploadPipeline::new(self.upload_config, self.upload_events.clone())
+            .run(
+                chunks,
+                |chunk| self.send_cmd(DataCmd::StoreChunk(chunk)),
+                || self.session.load_reports(),
+            )
+            .await
+    }
+}
+
+/// Sends chunks with bounded concurrency, retrying each of them with an exponential backoff,
+/// and sending fewer at once while Elders report being strained.
+///
+/// Every chunk in flight holds permits of a semaphore sized to the configured concurrency.
+/// Under load, each chunk takes a larger share of the permits, so fewer are sent at once.
+struct UploadPipeline {
+    config: UploadConfig,
+    permits: Semaphore,
+    // the number of chunks last allowed at once, to publish when it changes
+    allowed: AtomicUsize,
+    events: broadcast::Sender<UploadEvent>,
+}
+
+impl UploadPipeline {
+    fn new(config: UploadConfig, events: broadcast::Sender<UploadEvent>) -> Self {
+        let concurrency = config.concurrency.max(1);
+        Self {
+            config: UploadConfig {
+                concurrency,
+                ..config
+            },
+            permits: Semaphore::new(concurrency),
+            allowed: AtomicUsize::new(concurrency),
+            events,
+        }
+    }
+
+    async fn run<S, SFut, L, LFut>(&self, chunks: Vec<Chunk>, send: S, load: L) -> Vec<Result<()>>
+    where
+        S: Fn(Chunk) -> SFut,
+        SFut: Future<Output = Result<()>>,
+        L: Fn() -> LFut,
+        LFut: Future<Output = Vec<LoadReport>>,
+    {
+        join_all(
+            chunks
+                .into_iter()
+                .map(|chunk| self.store(chunk, &send, &load)),
+        )
+        .await
+    }
+
+    async fn store<S, SFut, L, LFut>(&self, chunk: Chunk, send: &S, load: &L) -> Result<()>
+    where
+        S: Fn(Chunk) -> SFut,
+        SFut: Future<Output = Result<()>>,
+        L: Fn() -> LFut,
+        LFut: Future<Output = Vec<LoadReport>>,
+    {
+        let name = *chunk.name();
+        let bytes = chunk.value().len();
+        let mut backoff = ExponentialBackoff {
+            initial_interval: self.config.initial_retry_interval,
+            max_elapsed_time: None,
+            ..Default::default()
+        };
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let result = {
+                let share = self.permits_share(load().await);
+                // the semaphore is never closed, so this only waits for permits
+                let _permits = self.permits.acquire_many(share as u32).await;
+                self.publish(UploadEvent::ChunkSent {
+                    name,
+                    bytes,
+                    attempt,
+                });
+                send(chunk.clone()).await
+            };
+
+            match result {
+                Ok(()) => {
+                    self.publish(UploadEvent::ChunkDelivered { name, bytes });
+                    return Ok(());
+                }
+                Err(error) if attempt <= self.config.retries => {
+                    let wait = backoff.next_backoff().unwrap_or(backoff.max_interval);
+                    warn!(
+                        "Failed to store chunk {:?}, retrying in {:?}: {:?}",
+                        name, wait, error
+                    );
+                    sleep(wait).await;
+                }
+                Err(error) => {
+                    warn!(
+                        "Failed to store chunk {:?} after {} attempts: {:?}",
+                        name, attempt, error
+                    );
+                    self.publish(UploadEvent::ChunkFailed { name, bytes });
+                    return Err(error);
+                }
+            }
+        }
+    }
+
+    // Number of permits a chunk takes as per the load reported, publishing the change
+    // in the number of chunks sent at once, if any.
+    fn permits_share(&self, reports: Vec<LoadReport>) -> usize {
+        let divisor = reports.iter().map(concurrency_divisor).max().unwrap_or(1);
+        let share = divisor.min(self.config.concurrency);
+        let allowed = self.config.concurrency / share;
+        if self.allowed.swap(allowed, Ordering::Relaxed) != allowed {
+            debug!("Sending up to {} chunks at once", allowed);
+            self.publish(UploadEvent::ConcurrencyChanged(allowed));
+        }
+        share
+    }
+
+    fn publish(&self, event: UploadEvent) {
+        // there may be no subscribers
+        let _receivers = self.events.send(event);
+    }
+}
+
+// How many times fewer chunks are sent at once under the reported load.
+fn concurrency_divisor(report: &LoadReport) -> usize {
+    let loads = [report.short_term, report.mid_term, report.long_term];
+    if loads.iter().any(|load| load.critical) {
+        8
+    } else if loads.iter().any(|load| load.very_high) {
+        4
+    } else if loads.iter().any(|load| load.high) {
+        2
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::utils::test_utils::{create_test_client, init_test_logger};
+    use crate::client::Error;
+    use crate::messaging::system::CpuLoad;
+    use crate::retry_loop;
+    use crate::types::utils::random_bytes;
+    use dashmap::DashMap;
+    use std::time::Duration;
+
+    fn config(concurrency: usize, retries: usize) -> UploadConfig {
+        UploadConfig {
+            concurrency,
+            retries,
+            initial_retry_interval: Duration::from_millis(1),
+        }
+    }
+
+    fn new_chunks(count: usize) -> Vec<Chunk> {
+        std::iter::repeat_with(|| Chunk::new(random_bytes(100)))
+            .take(count)
+            .collect()
+    }
+
+    fn report(load: CpuLoad) -> LoadReport {
+        LoadReport {
+            short_term: load,
+            mid_term: load,
+            long_term: load,
+        }
+    }
+
+    fn drain(events: &mut broadcast::Receiver<UploadEvent>) -> Vec<UploadEvent> {
+        std::iter::from_fn(|| events.try_recv().ok()).collect()
+    }
+
+    #[tokio::test]
+    async fn chunks_are_retried_until_stored() -> eyre::Result<()> {
+        let (sender, mut events) = broadcast::channel(UPLOAD_EVENTS_CAPACITY);
+        let pipeline = UploadPipeline::new(config(4, 2), sender);
+        let chunks = new_chunks(3);
+        let failures: DashMap<XorName, usize> = DashMap::new();
+        // the first chunk fails twice, the second chunk always fails
+        let _prev = failures.insert(*chunks[0].name(), 2);
+        let _prev = failures.insert(*chunks[1].name(), usize::MAX);
+
+        let results = pipeline
+            .run(
+                chunks.clone(),
+                |chunk| {
+                    let fail = match failures.get_mut(chunk.name()) {
+                        Some(mut left) if *left > 0 => {
+                            *left -= 1;
+                            true
+                        }
+                        _ => false,
+                    };
+                    async move {
+                        if fail {
+                            Err(Error::NoResponse)
+                        } else {
+                            Ok(())
+                        }
+                    }
+                },
+                || async { vec![] },
+            )
+            .await;
+
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(Error::NoResponse)));
+        assert!(results[2].is_ok());
+
+        let mut progress = UploadProgress::default();
+        let events = drain(&mut events);
+        for event in &events {
+            progress.record(event);
+        }
+        assert_eq!(
+            progress,
+            UploadProgress {
+                chunks_sent: 3,
+                chunks_delivered: 2,
+                chunks_failed: 1,
+                bytes_sent: 300,
+                bytes_delivered: 200,
+                concurrency: None,
+            }
+        );
+        // the failed chunk was sent once, then retried twice
+        let attempts = events
+            .iter()
+            .filter(|event| {
+                matches!(event, UploadEvent::ChunkSent { name, .. } if name == chunks[1].name())
+            })
+            .count();
+        assert_eq!(attempts, 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn fewer_chunks_are_sent_at_once_under_load() -> eyre::Result<()> {
+        let (sender, mut events) = broadcast::channel(UPLOAD_EVENTS_CAPACITY);
+        let pipeline = UploadPipeline::new(config(8, 0), sender);
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+        let send = |_chunk| async {
+            let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            let _prev = max_in_flight.fetch_max(current, Ordering::SeqCst);
+            sleep(Duration::from_millis(5)).await;
+            let _prev = in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        };
+
+        let results = pipeline
+            .run(new_chunks(20), send, || async { vec![] })
+            .await;
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 8);
+        assert!(!drain(&mut events)
+            .iter()
+            .any(|event| matches!(event, UploadEvent::ConcurrencyChanged(_))));
+
+        let strained = report(CpuLoad {
+            low: false,
+            moderate: true,
+            high: true,
+            very_high: true,
+            critical: false,
+        });
+        max_in_flight.store(0, Ordering::SeqCst);
+        let results = pipeline
+            .run(new_chunks(20), send, || async { vec![strained] })
+            .await;
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+        assert!(drain(&mut events).contains(&UploadEvent::ConcurrencyChanged(2)));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn chunks_are_stored_through_the_client() -> eyre::Result<()> {
+        init_test_logger();
+        let _outer_span = tracing::info_span!("chunks_are_stored_through_the_client").entered();
+        let client = create_test_client().await?;
+        let mut events = client.upload_events();
+        let chunks = new_chunks(3);
+
+        let results = client.store_chunks(chunks.clone()).await;
+        assert!(results.iter().all(Result::is_ok));
+
+        let mut progress = UploadProgress::default();
+        for event in &drain(&mut events) {
+            progress.record(event);
+        }
+        assert_eq!(progress.chunks_delivered, chunks.len());
+        assert_eq!(progress.bytes_delivered, 100 * chunks.len());
+        assert_eq!(progress.chunks_failed, 0);
+
+        for chunk in &chunks {
+            let stored = retry_loop!(client.get_chunk(chunk.name()));
+            assert_eq!(stored, *chunk);
+        }
+
+        Ok(())
+    }
+}
@@ -27,11 +27,115 @@ pub const DEFAULT_QUERY_TIMEOUT: Duration = Duration::from_secs(120);
 /// Default amount of time to wait (to keep the client alive) after sending a command. This allows AE messages to be parsed/resent.
 /// Larger PUT operations may need larger ae wait time
 pub const DEFAULT_AE_WAIT: Duration = Duration::from_secs(0);
+/// Default amount of time to wait for the rest of the Elders queried for a Register once the first
+/// one has responded, when the read consistency requires more than one of them.
+pub const DEFAULT_REGISTER_RESPONSES_WAIT: Duration = Duration::from_secs(5);
 
 const DEFAULT_ROOT_DIR_NAME: &str = "root_dir";
 const SN_QUERY_TIMEOUT: &str = "SN_QUERY_TIMEOUT";
 const SN_AE_WAIT: &str = "SN_AE_WAIT";
 
+/// How the responses of the Elders queried for a Register are turned into the Register returned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RegisterReadConsistency {
+    /// Accept the first successful response, as for any other query.
+    First,
+    /// Await the responses of all the Elders queried and merge the Registers they return,
+    /// so a single stale Elder can't hide the entries the others know of.
+    Merge,
+    /// Await the responses of all the Elders queried and require at least the given number of
+    /// them to return the same Register. Only the Registers held by that many Elders are merged,
+    /// so a single stale or malicious Elder can't decide what is read.
+    Quorum(usize),
+}
+
+// deriving it requires `#[default]` on the variant, which our minimum toolchain lacks
+#[allow(clippy::derivable_impls)]
+impl Default for RegisterReadConsistency {
+    fn default() -> Self {
+        Self::First
+    }
+}
+
+impl RegisterReadConsistency {
+    /// Whether the consistency can be satisfied by the number of Elders queried, i.e. that a
+    /// `Quorum` requires at least one and at most `elders_queried` of them to agree.
+    pub fn is_satisfiable_by(&self, elders_queried: usize) -> bool {
+        match *self {
+            Self::Quorum(required) => required > 0 && required <= elders_queried,
+            Self::First | Self::Merge => true,
+        }
+    }
+}
+
+/// Default upper limit, in bytes, for the size of the chunks kept in the on-disk chunk cache.
+pub const DEFAULT_CHUNK_CACHE_CAPACITY: u64 = 256 * 1024 * 1024;
+
+/// Which chunks the on-disk chunk cache drops first once it's full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ChunkCacheEviction {
+    /// Drop the chunks which were read the longest time ago.
+    LeastRecentlyUsed,
+    /// Drop the chunks which were cached first, whether they were read since or not.
+    FirstInFirstOut,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for ChunkCacheEviction {
+    fn default() -> Self {
+        Self::LeastRecentlyUsed
+    }
+}
+
+/// Settings of the on-disk chunk cache, kept within the client's `root_dir` across runs.
+///
+/// Chunks are immutable and their content is checked against their address when read back,
+/// so the cache never serves stale or corrupted chunks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ChunkCacheConfig {
+    /// Upper limit, in bytes, for the size of the chunks cached.
+    pub capacity: u64,
+    /// Which chunks are dropped first once the capacity is reached.
+    #[serde(default)]
+    pub eviction: ChunkCacheEviction,
+}
+
+impl Default for ChunkCacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CHUNK_CACHE_CAPACITY,
+            eviction: ChunkCacheEviction::default(),
+        }
+    }
+}
+
+/// Default upper limit for the number of chunks an upload sends at once.
+pub const DEFAULT_UPLOAD_CONCURRENCY: usize = 8;
+/// Default number of times sending a chunk is retried before an upload fails.
+pub const DEFAULT_UPLOAD_RETRIES: usize = 3;
+
+/// Settings of the pipeline chunks are uploaded through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct UploadConfig {
+    /// Upper limit for the number of chunks sent at once. Fewer are sent while Elders
+    /// report being strained.
+    pub concurrency: usize,
+    /// Number of times sending a chunk is retried before the upload fails.
+    pub retries: usize,
+    /// Time to wait before retrying to send a chunk, which grows exponentially on each retry.
+    pub initial_retry_interval: Duration,
+}
+
+impl Default for UploadConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: DEFAULT_UPLOAD_CONCURRENCY,
+            retries: DEFAULT_UPLOAD_RETRIES,
+            initial_retry_interval: Duration::from_millis(500),
+        }
+    }
+}
+
 /// Configuration for sn_client.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ClientConfig {
@@ -47,6 +151,20 @@ pub struct ClientConfig {
     pub query_timeout: Duration,
     /// The amount of time to wait after a command is sent for AE flows to complete.
     pub standard_wait: Duration,
+    /// How Register queries are answered, unless overridden on a per-call basis.
+    #[serde(default)]
+    pub register_read_consistency: RegisterReadConsistency,
+    /// The amount of time to wait for the rest of the Elders queried for a Register once the
+    /// first one has responded, when the read consistency requires more than one of them.
+    #[serde(default = "default_register_responses_wait")]
+    pub register_responses_wait: Duration,
+    /// On-disk cache of the chunks read, kept across runs. Only chunks kept in memory for the
+    /// lifetime of the client are cached if not set.
+    #[serde(default)]
+    pub chunk_cache: Option<ChunkCacheConfig>,
+    /// How chunks are uploaded.
+    #[serde(default)]
+    pub upload: UploadConfig,
 }
 
 impl ClientConfig {
@@ -60,6 +178,14 @@ impl ClientConfig {
     /// port).
     ///
     /// If `query_timeout` is not specified, [`DEFAULT_QUERY_TIMEOUT`] will be used.
+    ///
+    /// Register queries are answered by the first Elder to respond, set
+    /// `register_read_consistency` on the returned config to require more of them, and
+    /// `register_responses_wait` to change how long the rest of them are awaited for.
+    ///
+    /// The on-disk chunk cache is disabled, set `chunk_cache` on the returned config to enable it.
+    ///
+    /// Uploads use the default [`UploadConfig`], set `upload` on the returned config to tune them.
     pub async fn new(
         root_dir: Option<&Path>,
         local_addr: Option<SocketAddr>,
@@ -131,10 +257,18 @@ impl ClientConfig {
             qp2p,
             query_timeout,
             standard_wait,
+            register_read_consistency: RegisterReadConsistency::default(),
+            register_responses_wait: DEFAULT_REGISTER_RESPONSES_WAIT,
+            chunk_cache: None,
+            upload: UploadConfig::default(),
         }
     }
 }
 
+fn default_register_responses_wait() -> Duration {
+    DEFAULT_REGISTER_RESPONSES_WAIT
+}
+
 async fn read_config_file(filepath: &Path) -> Result<QuicP2pConfig, Error> {
     debug!("Reading config file '{}' ...", filepath.display());
     let mut file = File::open(filepath).await?;
@@ -246,6 +380,10 @@ mod tests {
             },
             query_timeout: expected_query_timeout,
             standard_wait: expected_standard_wait,
+            register_read_consistency: RegisterReadConsistency::First,
+            register_responses_wait: DEFAULT_REGISTER_RESPONSES_WAIT,
+            chunk_cache: None,
+            upload: UploadConfig::default(),
         };
         assert_eq!(format!("{:?}", config), format!("{:?}", expected_config));
         assert_eq!(serialize(&config)?, serialize(&expected_config)?);
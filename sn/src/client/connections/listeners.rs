@@ -14,7 +14,7 @@ use crate::client::{
 };
 use crate::messaging::{
     data::{CmdError, DataCmd, ServiceMsg},
-    system::{KeyedSig, SectionAuth, SystemMsg},
+    system::{KeyedSig, LoadReport, SectionAuth, SystemMsg},
     AuthorityProof, DstLocation, MessageId, MessageType, MsgKind, ServiceAuth, WireMsg,
 };
 use crate::node::routing::SectionAuthorityProvider;
@@ -27,8 +27,13 @@ use itertools::Itertools;
 use qp2p::ConnectionIncoming;
 use secured_linked_list::SecuredLinkedList;
 use std::net::SocketAddr;
+use tokio::time::{Duration, Instant};
 use tracing::Instrument;
 
+// Elders report their load at most once a minute while strained, so a report is
+// considered current for a bit longer than that.
+const LOAD_REPORT_TTL: Duration = Duration::from_secs(90);
+
 impl Session {
     // Listen for incoming messages on a connection
     #[instrument(skip_all, level = "debug")]
@@ -75,6 +80,13 @@ impl Session {
         }.instrument(info_span!("Listening for incoming msgs from {}", ?src))).in_current_span();
     }
 
+    /// The current load reports of the Elders which asked us to back off.
+    pub(crate) async fn load_reports(&self) -> Vec<LoadReport> {
+        let mut reports = self.load_reports.write().await;
+        reports.retain(|_, (received, _)| received.elapsed() < LOAD_REPORT_TTL);
+        reports.values().map(|(_, report)| *report).collect()
+    }
+
     #[instrument(skip_all, level = "debug")]
     pub(crate) async fn listen_for_incoming_message(
         src: SocketAddr,
@@ -148,6 +160,18 @@ impl Session {
                 }
                 result
             }
+            MessageType::System {
+                msg: SystemMsg::BackPressure(load_report),
+                ..
+            } => {
+                debug!("Elder {:?} asked us to back off: {:?}", src, load_report);
+                let _prev = session
+                    .load_reports
+                    .write()
+                    .await
+                    .insert(src, (Instant::now(), load_report));
+                Ok(())
+            }
             msg_type => {
                 warn!("Unexpected message type received: {:?}", msg_type);
                 Ok(())
@@ -423,7 +447,9 @@ impl Session {
             ServiceMsg::Cmd(cmd) => {
                 match &cmd {
                     DataCmd::StoreChunk(_) => (at_least_one_correct_elder(), cmd.dst_name()), // stored at Adults, so only 1 correctly functioning Elder need to relay
-                    DataCmd::Register(_) => (elder_count(), cmd.dst_name()), // only stored at Elders, all need a copy
+                    DataCmd::Register(_) | DataCmd::MultiSigRegister { .. } => {
+                        (elder_count(), cmd.dst_name())
+                    } // only stored at Elders, all need a copy
                 }
             }
             ServiceMsg::Query(query) => (NUM_OF_ELDERS_SUBSET_FOR_QUERIES, query.dst_name()),
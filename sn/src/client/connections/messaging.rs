@@ -9,14 +9,14 @@
 use super::{QueryResult, Session};
 
 use super::AeCache;
-use crate::client::Error;
+use crate::client::{Error, RegisterReadConsistency};
 use crate::messaging::{
-    data::{CmdError, DataQuery, QueryResponse},
+    data::{CmdError, DataQuery, OperationId, QueryResponse, RegisterRead},
     DstLocation, MessageId, MsgKind, ServiceAuth, WireMsg,
 };
 use crate::peer::Peer;
 use crate::prefix_map::NetworkPrefixMap;
-use crate::types::PublicKey;
+use crate::types::{register::Register, PublicKey};
 use backoff::{backoff::Backoff, ExponentialBackoff};
 use bytes::Bytes;
 use dashmap::DashMap;
@@ -27,9 +27,13 @@ use rand::rngs::OsRng;
 use rand::seq::SliceRandom;
 use std::path::PathBuf;
 use std::time::Duration;
-use std::{net::SocketAddr, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    sync::Arc,
+};
 use tokio::{
-    sync::mpsc::{channel, Sender},
+    sync::mpsc::{channel, Receiver, Sender},
     sync::RwLock,
     task::JoinHandle,
 };
@@ -45,6 +49,7 @@ pub(crate) const NODES_TO_CONTACT_PER_STARTUP_BATCH: usize = 3;
 impl Session {
     /// Acquire a session by bootstrapping to a section, maintaining connections to several nodes.
     #[instrument(skip(err_sender), level = "debug")]
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn new(
         client_pk: PublicKey,
         genesis_key: bls::PublicKey,
@@ -52,6 +57,7 @@ impl Session {
         err_sender: Sender<CmdError>,
         local_addr: SocketAddr,
         standard_wait: Duration,
+        register_responses_wait: Duration,
         prefix_map: NetworkPrefixMap,
     ) -> Result<Session, Error> {
         trace!("Trying to bootstrap to the network");
@@ -68,6 +74,8 @@ impl Session {
             genesis_key,
             initial_connection_check_msg_id: Arc::new(RwLock::new(None)),
             standard_wait,
+            register_responses_wait,
+            load_reports: Arc::new(RwLock::new(BTreeMap::new())),
         };
 
         Ok(session)
@@ -141,11 +149,14 @@ impl Session {
 
     #[instrument(skip_all, level = "debug")]
     /// Send a `ServiceMsg` to the network awaiting for the response.
+    /// The `consistency` only applies to queries for a whole Register, any other
+    /// query is answered with the first valid response received.
     pub(crate) async fn send_query(
         &self,
         query: DataQuery,
         auth: ServiceAuth,
         payload: Bytes,
+        consistency: RegisterReadConsistency,
     ) -> Result<QueryResult, Error> {
         let endpoint = self.endpoint.clone();
 
@@ -226,6 +237,22 @@ impl Session {
         )
         .await?;
 
+        if matches!(query, DataQuery::Register(RegisterRead::Get(_)))
+            && consistency != RegisterReadConsistency::First
+        {
+            let result = await_register_responses(
+                &mut receiver,
+                elders_len,
+                consistency,
+                self.register_responses_wait,
+            )
+            .await;
+            if let Ok(op_id) = query.operation_id() {
+                self.remove_query_listener(&op_id, msg_id);
+            }
+            return result;
+        }
+
         // TODO:
         // We are now simply accepting the very first valid response we receive,
         // but we may want to revisit this to compare multiple responses and validate them,
@@ -263,8 +290,9 @@ impl Session {
                 | (response @ Some(QueryResponse::GetRegister((Err(_), _))), None)
                 | (response @ Some(QueryResponse::GetRegisterPolicy((Err(_), _))), None)
                 | (response @ Some(QueryResponse::GetRegisterOwner((Err(_), _))), None)
-                | (response @ Some(QueryResponse::GetRegisterUserPermissions((Err(_), _))), None) =>
-                {
+                | (response @ Some(QueryResponse::GetRegisterUserPermissions((Err(_), _))), None)
+                | (response @ Some(QueryResponse::GetRegisterHistory((Err(_), _))), None)
+                | (response @ Some(QueryResponse::GetRegisterChildren((Err(_), _))), None) => {
                     debug!("QueryResponse error received (but may be overridden by a non-error response from another elder): {:#?}", &response);
                     error_response = response;
                     discarded_responses += 1;
@@ -290,20 +318,7 @@ impl Session {
 
         if let Some(query) = &response {
             if let Ok(query_op_id) = query.operation_id() {
-                // Remove the response sender
-                trace!("Removing channel for {:?}", (msg_id, &query_op_id));
-                // let _old_channel =
-                if let Some(mut entry) = self.pending_queries.get_mut(&query_op_id) {
-                    let listeners_for_op = entry.value_mut();
-                    if let Some(index) = listeners_for_op
-                        .iter()
-                        .position(|(id, _sender)| *id == msg_id)
-                    {
-                        let _old_listener = listeners_for_op.swap_remove(index);
-                    }
-                } else {
-                    warn!("No listeners found for our op_id: {:?}", query_op_id)
-                }
+                self.remove_query_listener(&query_op_id, msg_id);
             }
         }
 
@@ -321,6 +336,22 @@ impl Session {
         }
     }
 
+    // Remove the response sender of a query
+    fn remove_query_listener(&self, op_id: &OperationId, msg_id: MessageId) {
+        trace!("Removing channel for {:?}", (msg_id, op_id));
+        if let Some(mut entry) = self.pending_queries.get_mut(op_id) {
+            let listeners_for_op = entry.value_mut();
+            if let Some(index) = listeners_for_op
+                .iter()
+                .position(|(id, _sender)| *id == msg_id)
+            {
+                let _old_listener = listeners_for_op.swap_remove(index);
+            }
+        } else {
+            warn!("No listeners found for our op_id: {:?}", op_id)
+        }
+    }
+
     #[instrument(skip_all, level = "debug")]
     pub(crate) async fn make_contact_with_nodes(
         &self,
@@ -567,6 +598,118 @@ pub(super) async fn send_message(
     Ok(())
 }
 
+// Await the responses of all the Elders queried for a Register, merging the Registers
+// they return as per the read consistency required.
+async fn await_register_responses(
+    receiver: &mut Receiver<QueryResponse>,
+    elders_len: usize,
+    consistency: RegisterReadConsistency,
+    remaining_responses_wait: Duration,
+) -> Result<QueryResult, Error> {
+    let mut registers = vec![];
+    let mut error_response = None;
+    let mut operation_id = None;
+
+    for received in 0..elders_len {
+        // The first response can take as long as the query is allowed to,
+        // but we don't want a single unresponsive Elder to make us miss the rest
+        let response = if received == 0 {
+            receiver.recv().await
+        } else {
+            match tokio::time::timeout(remaining_responses_wait, receiver.recv()).await {
+                Ok(response) => response,
+                Err(_) => {
+                    debug!(
+                        "Only {} of the {} Elders queried responded with the Register",
+                        received, elders_len
+                    );
+                    break;
+                }
+            }
+        };
+
+        match response {
+            Some(QueryResponse::GetRegister((Ok(register), op_id))) => {
+                debug!("Register QueryResponse received: {:?}", register);
+                registers.push(register);
+                operation_id = Some(op_id);
+            }
+            Some(response @ QueryResponse::GetRegister((Err(_), _))) => {
+                debug!("Register QueryResponse error received (but may be overridden by a non-error response from another elder): {:#?}", &response);
+                error_response = Some(response);
+            }
+            Some(response) => {
+                warn!("Unexpected response to a Register query: {:?}", response);
+            }
+            None => {
+                debug!("QueryResponse channel closed.");
+                break;
+            }
+        }
+    }
+
+    let registers = match consistency {
+        RegisterReadConsistency::Quorum(required) if !registers.is_empty() => {
+            let (registers, matching) = registers_held_by(registers, required);
+            if registers.is_empty() {
+                return Err(Error::InsufficientMatchingResponses { matching, required });
+            }
+            registers
+        }
+        _ => registers,
+    };
+
+    let response = match (merge_registers(registers), operation_id) {
+        (Some(register), Some(op_id)) => QueryResponse::GetRegister((Ok(register), op_id)),
+        _ => error_response.ok_or(Error::NoResponse)?,
+    };
+
+    let operation_id = response
+        .operation_id()
+        .map_err(|_| Error::UnknownOperationId)?;
+
+    Ok(QueryResult {
+        response,
+        operation_id,
+    })
+}
+
+// Keep only the Registers returned by at least `required` of the Elders queried, along with
+// the largest number of Elders which returned the same Register.
+fn registers_held_by(registers: Vec<Register>, required: usize) -> (Vec<Register>, usize) {
+    let mut matching = HashMap::<Register, usize>::new();
+    for register in registers {
+        *matching.entry(register).or_default() += 1;
+    }
+
+    let most_matching = matching.values().max().copied().unwrap_or_default();
+    let registers = matching
+        .into_iter()
+        .filter(|(_, count)| *count >= required)
+        .map(|(register, _)| register)
+        .collect();
+
+    (registers, most_matching)
+}
+
+// Merge the Registers returned by the Elders queried, returning `None` if there are none to merge.
+fn merge_registers(registers: Vec<Register>) -> Option<Register> {
+    let mut registers = registers.into_iter();
+    let mut merged = registers.next();
+    if let Some(merged) = merged.as_mut() {
+        for register in registers {
+            if let Err(err) = merged.merge(register) {
+                warn!(
+                    "Discarding a Register response which can't be merged: {:?}",
+                    err
+                );
+            }
+        }
+    }
+
+    merged
+}
+
 #[instrument(skip_all, level = "trace")]
 pub(crate) async fn create_safe_dir() -> Result<PathBuf, Error> {
     let mut root_dir = dirs_next::home_dir().ok_or(Error::CouldNotReadHomeDir)?;
@@ -579,3 +722,57 @@ pub(crate) async fn create_safe_dir() -> Result<PathBuf, Error> {
 
     Ok(root_dir)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Keypair;
+    use eyre::{eyre, Result};
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn registers_are_merged_as_per_consistency() -> Result<()> {
+        let authority = Keypair::new_ed25519(&mut OsRng).public_key();
+        let mut stale = Register::new_public(authority, XorName::random(), 15000, None);
+        let (hash1, _) = stale.write(b"entry1".to_vec(), BTreeSet::new())?;
+
+        let mut synced = stale.clone();
+        let (hash2, _) = synced.write(b"entry2".to_vec(), BTreeSet::from([hash1]))?;
+
+        let mut forked = stale.clone();
+        let (hash3, _) = forked.write(b"entry3".to_vec(), BTreeSet::from([hash1]))?;
+
+        let responses = vec![stale.clone(), synced.clone(), forked.clone()];
+
+        // all the entries known by any Elder are read
+        let merged =
+            merge_registers(responses.clone()).ok_or_else(|| eyre!("Register expected"))?;
+        let latest: BTreeSet<_> = merged.read(None)?.into_iter().map(|(h, _)| h).collect();
+        assert_eq!(latest, BTreeSet::from([hash2, hash3]));
+
+        // no two Elders agree on the Register
+        let (held, matching) = registers_held_by(responses, 2);
+        assert!(held.is_empty());
+        assert_eq!(matching, 1);
+
+        // the Register two Elders agree on is read, not the one of the third
+        let responses = vec![synced.clone(), forked, synced.clone()];
+        let (held, matching) = registers_held_by(responses, 2);
+        assert_eq!(matching, 2);
+        assert_eq!(merge_registers(held), Some(synced));
+
+        assert_eq!(merge_registers(vec![]), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unsatisfiable_quorums_are_rejected() {
+        let queried = NUM_OF_ELDERS_SUBSET_FOR_QUERIES;
+        assert!(RegisterReadConsistency::Quorum(1).is_satisfiable_by(queried));
+        assert!(RegisterReadConsistency::Quorum(queried).is_satisfiable_by(queried));
+        assert!(RegisterReadConsistency::Merge.is_satisfiable_by(queried));
+        assert!(!RegisterReadConsistency::Quorum(0).is_satisfiable_by(queried));
+        assert!(!RegisterReadConsistency::Quorum(queried + 1).is_satisfiable_by(queried));
+    }
+}
@@ -8,8 +8,10 @@
 
 mod listeners;
 mod messaging;
+
 use crate::messaging::{
     data::{CmdError, OperationId, QueryResponse},
+    system::LoadReport,
     MessageId,
 };
 use crate::peer::Peer;
@@ -17,10 +19,11 @@ use crate::prefix_map::NetworkPrefixMap;
 use bls::PublicKey as BlsPublicKey;
 use bytes::Bytes;
 use dashmap::DashMap;
+pub(crate) use messaging::NUM_OF_ELDERS_SUBSET_FOR_QUERIES;
 use qp2p::Endpoint;
-use std::sync::Arc;
+use std::{collections::BTreeMap, net::SocketAddr, sync::Arc};
 use tokio::sync::{mpsc::Sender, RwLock};
-use tokio::time::Duration;
+use tokio::time::{Duration, Instant};
 
 // Here we dont track the msg_id across the network, but just use it as a local identifier to remove the correct listener
 type PendingQueryResponses = Arc<DashMap<OperationId, Vec<(MessageId, QueryResponseSender)>>>;
@@ -35,8 +38,11 @@ pub struct QueryResult {
 
 pub(crate) type AeCache = LRUCache<(Vec<Peer>, BlsPublicKey, Bytes), 100>;
 
+// Latest load reported by each Elder which asked us to back off, and when it was received.
+type LoadReports = Arc<RwLock<BTreeMap<SocketAddr, (Instant, LoadReport)>>>;
+
 #[derive(Clone, Debug)]
-pub(super) struct Session {
+pub(crate) struct Session {
     // Session endpoint.
     endpoint: Endpoint,
     // Channels for sending responses to upper layers
@@ -55,4 +61,8 @@ pub(super) struct Session {
     initial_connection_check_msg_id: Arc<RwLock<Option<MessageId>>>,
     /// Standard time to await potential AE messages:
     standard_wait: Duration,
+    /// Time to await the rest of the Elders queried for a Register once the first one responded
+    register_responses_wait: Duration,
+    /// Back-pressure reported by Elders
+    load_reports: LoadReports,
 }
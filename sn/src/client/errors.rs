@@ -6,16 +6,19 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use crate::client::RegisterReadConsistency;
 pub use crate::messaging::data::Error as ErrorMessage;
 use crate::messaging::{
     data::{CmdError, OperationId, QueryResponse},
+    signature_aggregator::Error as AggregatorError,
     Error as MessagingError,
 };
-use crate::types::Error as DtError;
+use crate::types::{Error as DtError, PublicKey as DtPublicKey};
 use bls::PublicKey;
 use std::io;
 use std::net::SocketAddr;
 use thiserror::Error;
+use xor_name::XorName;
 
 /// Specialisation of `std::Result` for Client.
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -65,6 +68,26 @@ pub enum Error {
         /// Public key of the target section
         section_pk: PublicKey,
     },
+    /// Not enough of the Elders queried returned the same Register.
+    #[error(
+        "Not enough Elders returned the same Register to satisfy the read consistency required. {matching} did, {required} needed."
+    )]
+    InsufficientMatchingResponses {
+        /// Largest number of Elders which returned the same Register
+        matching: usize,
+        /// Number of Elders required to return the same Register
+        required: usize,
+    },
+    /// The read consistency required can't be satisfied by the Elders queried.
+    #[error(
+        "The Register read consistency {consistency:?} can't be satisfied by the {elders_queried} Elders queried."
+    )]
+    InvalidReadConsistency {
+        /// Read consistency required
+        consistency: RegisterReadConsistency,
+        /// Number of Elders queried for a Register
+        elders_queried: usize,
+    },
     /// Peer connection retrieval failed
     #[error("Error with Peer's connection: {0:?}")]
     PeerConnection(SocketAddr),
@@ -80,6 +103,21 @@ pub enum Error {
     /// The provided bytes is too large to be a `Spot`.
     #[error("The provided bytes is too large to be a `Spot`")]
     TooLargeToBeSpot,
+    /// The data being streamed was modified between the passes of the self-encryption
+    #[error("The streamed data changed while it was being self-encrypted")]
+    StreamedDataChanged,
+    /// No upload journal was found with the given id
+    #[error("No upload journal was found with id {0:?}")]
+    NoSuchUploadJournal(XorName),
+    /// The key isn't the owner, nor a share of the owner, of a multi-sig Register write
+    #[error("Key {0:?} is not a share of the owner of the multi-sig Register write")]
+    NotMultiSigOwner(DtPublicKey),
+    /// Not enough members of the owner co-signed the multi-sig Register write yet
+    #[error("Not enough members of the owner co-signed the multi-sig Register write yet")]
+    NotEnoughSignatureShares,
+    /// Signature shares of a multi-sig Register write couldn't be aggregated
+    #[error(transparent)]
+    SignatureAggregation(#[from] AggregatorError),
     /// No query response before timeout
     #[error("Query timed out")]
     QueryTimedOut,
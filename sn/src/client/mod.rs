@@ -31,12 +31,22 @@ mod errors;
 
 // Export public API.
 
-pub use client_api::{Client, RegisterWriteAheadLog};
-pub use config_handler::{ClientConfig, DEFAULT_AE_WAIT, DEFAULT_QUERY_TIMEOUT};
+pub use client_api::{
+    Client, MultiSigRegisterWrite, RegisterWriteAheadLog, UploadEvent, UploadJournalId,
+    UploadProgress,
+};
+pub use config_handler::{
+    ChunkCacheConfig, ChunkCacheEviction, ClientConfig, RegisterReadConsistency, UploadConfig,
+    DEFAULT_AE_WAIT, DEFAULT_CHUNK_CACHE_CAPACITY, DEFAULT_QUERY_TIMEOUT,
+    DEFAULT_REGISTER_RESPONSES_WAIT, DEFAULT_UPLOAD_CONCURRENCY, DEFAULT_UPLOAD_RETRIES,
+};
 pub use errors::ErrorMessage;
 pub use errors::{Error, Result};
 pub use qp2p::Config as QuicP2pConfig;
 
+#[cfg(test)]
+pub(crate) use connections::Session;
+
 /// Client trait and related constants.
 pub mod client_api;
 
@@ -0,0 +1,209 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Authenticated encryption of private data maps and spots, with keys derived from the
+//! client's secret key.
+//!
+//! Encryption is deterministic, so the same content stored by the same client always results
+//! in the same chunks, and thus the same address. It uses AES-256-GCM-SIV (RFC 8452) with a fixed
+//! nonce: being resistant to nonce reuse, it then only reveals whether two chunks of the same
+//! client have the same content. The header is authenticated along with the content.
+//!
+//! Private data stored before this was in place was kept in plain text. Content without the
+//! `ENCRYPTED_MAGIC` prefix is thus returned as it is when decrypting, so it can still be read,
+//! and stored again to have it encrypted. Plain text content which happens to start with the
+//! prefix, e.g. a spot whose first bytes are `SNPRV`, is taken for encrypted content though, and
+//! fails to decrypt. Data maps can't be mistaken, as they're serialised with bincode and start
+//! with the index of their variant.
+
+use crate::types::{Encryption, Error, Keypair, PublicKey, Result};
+
+use aes_gcm_siv::{
+    aead::{Aead, NewAead, Payload},
+    Aes256GcmSiv, Key, Nonce,
+};
+use bytes::{BufMut, Bytes, BytesMut};
+use tiny_keccak::{Hasher, Sha3};
+
+// Prefix of all the content encrypted here, followed by the format version.
+const ENCRYPTED_MAGIC: &[u8; 5] = b"SNPRV";
+const FORMAT_VERSION: u8 = 1;
+const HEADER_SIZE: usize = ENCRYPTED_MAGIC.len() + 1;
+const HEADER: [u8; HEADER_SIZE] = [b'S', b'N', b'P', b'R', b'V', FORMAT_VERSION];
+// Size of the tag AES-GCM-SIV appends to the ciphertext.
+const TAG_SIZE: usize = 16;
+// The nonce is fixed for the encryption to be deterministic.
+const NONCE: [u8; 12] = [0; 12];
+
+// Domain separating the key derived from the secret key from other uses of it.
+const ENCRYPTION_KEY_DOMAIN: &[u8] = b"safe_network private data encryption key";
+
+/// Encrypts and decrypts the private data of the owner of a keypair.
+pub(crate) struct KeyedEncryption {
+    public_key: PublicKey,
+    cipher: Aes256GcmSiv,
+}
+
+impl KeyedEncryption {
+    pub(crate) fn new(keypair: &Keypair) -> Self {
+        let secret = match keypair {
+            Keypair::Ed25519(pair) => pair.secret.to_bytes().to_vec(),
+            Keypair::BlsShare(share) => share.secret.to_bytes().to_vec(),
+        };
+        let key = sha3_256(&[ENCRYPTION_KEY_DOMAIN, &secret]);
+        Self {
+            public_key: keypair.public_key(),
+            cipher: Aes256GcmSiv::new(Key::from_slice(&key)),
+        }
+    }
+}
+
+impl Encryption for KeyedEncryption {
+    fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    fn encrypt(&self, data: Bytes) -> Result<Bytes> {
+        let payload = Payload {
+            msg: &data,
+            aad: &HEADER,
+        };
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&NONCE), payload)
+            .map_err(|_| Error::FailedToEncrypt("data is too large".to_string()))?;
+
+        let mut encrypted = BytesMut::with_capacity(HEADER_SIZE + ciphertext.len());
+        encrypted.put_slice(&HEADER);
+        encrypted.put_slice(&ciphertext);
+        Ok(encrypted.freeze())
+    }
+
+    fn decrypt(&self, encrypted_data: Bytes) -> Result<Bytes> {
+        if !encrypted_data.starts_with(ENCRYPTED_MAGIC) {
+            trace!("Private data isn't encrypted, it was stored in plain text");
+            return Ok(encrypted_data);
+        }
+        if encrypted_data.len() < HEADER_SIZE + TAG_SIZE {
+            return Err(Error::FailedToDecrypt("data is too short".to_string()));
+        }
+        let version = encrypted_data[ENCRYPTED_MAGIC.len()];
+        if version != FORMAT_VERSION {
+            return Err(Error::FailedToDecrypt(format!(
+                "unknown format version {}",
+                version
+            )));
+        }
+
+        let payload = Payload {
+            msg: &encrypted_data[HEADER_SIZE..],
+            aad: &HEADER,
+        };
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(&NONCE), payload)
+            .map_err(|_| {
+                Error::FailedToDecrypt(
+                    "data was tampered with, or encrypted with a different key".to_string(),
+                )
+            })?;
+
+        Ok(Bytes::from(plaintext))
+    }
+}
+
+fn sha3_256(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Sha3::v256();
+    for part in parts {
+        hasher.update(part);
+    }
+    let mut hash = [0; 32];
+    hasher.finalize(&mut hash);
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::utils::random_bytes;
+    use eyre::Result;
+    use rand::rngs::OsRng;
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+
+    #[test]
+    fn round_trip_without_plaintext() -> Result<()> {
+        let owner = KeyedEncryption::new(&Keypair::new_ed25519(&mut OsRng));
+        let data = random_bytes(1000);
+
+        let encrypted = owner.encrypt(data.clone())?;
+        assert!(!contains(&encrypted, &data[..32]));
+        assert_eq!(owner.decrypt(encrypted.clone())?, data);
+
+        // deterministic, so the address of private data is stable
+        assert_eq!(owner.encrypt(data)?, encrypted);
+
+        Ok(())
+    }
+
+    #[test]
+    fn others_cannot_decrypt() -> Result<()> {
+        let owner = KeyedEncryption::new(&Keypair::new_ed25519(&mut OsRng));
+        let other = KeyedEncryption::new(&Keypair::new_ed25519(&mut OsRng));
+        let encrypted = owner.encrypt(random_bytes(100))?;
+
+        assert!(matches!(
+            other.decrypt(encrypted),
+            Err(Error::FailedToDecrypt(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn tampering_is_detected() -> Result<()> {
+        let owner = KeyedEncryption::new(&Keypair::new_ed25519(&mut OsRng));
+        let encrypted = owner.encrypt(random_bytes(100))?;
+
+        for index in [HEADER_SIZE, encrypted.len() - TAG_SIZE, encrypted.len() - 1] {
+            let mut tampered = encrypted.to_vec();
+            tampered[index] ^= 1;
+            assert!(owner.decrypt(Bytes::from(tampered)).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn plain_text_data_stored_before_encryption_can_be_read() -> Result<()> {
+        let owner = KeyedEncryption::new(&Keypair::new_ed25519(&mut OsRng));
+        let legacy = random_bytes(100);
+
+        assert_eq!(owner.decrypt(legacy.clone())?, legacy);
+
+        Ok(())
+    }
+
+    #[test]
+    fn plain_text_data_starting_with_the_prefix_is_misread() -> Result<()> {
+        let owner = KeyedEncryption::new(&Keypair::new_ed25519(&mut OsRng));
+        let mut legacy = ENCRYPTED_MAGIC.to_vec();
+        legacy.extend_from_slice(&random_bytes(100));
+
+        assert!(matches!(
+            owner.decrypt(Bytes::from(legacy)),
+            Err(Error::FailedToDecrypt(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -10,37 +10,18 @@
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
 
-use crate::types::{Encryption, PublicKey, Result as TypesResult, Scope};
-use bytes::Bytes;
-use rand::{self, distributions::Alphanumeric, rngs::OsRng, Rng};
-
-struct DummyEncryption {
-    public_key: PublicKey,
-}
-
-impl DummyEncryption {
-    pub(crate) fn new(public_key: PublicKey) -> Self {
-        Self { public_key }
-    }
-}
+mod encryption;
 
-impl Encryption for DummyEncryption {
-    fn public_key(&self) -> &PublicKey {
-        &self.public_key
-    }
-    fn encrypt(&self, data: Bytes) -> TypesResult<Bytes> {
-        Ok(data)
-    }
-    fn decrypt(&self, encrypted_data: Bytes) -> TypesResult<Bytes> {
-        Ok(encrypted_data)
-    }
-}
+use self::encryption::KeyedEncryption;
+use crate::types::{Encryption, Keypair, Scope};
+use rand::{self, distributions::Alphanumeric, rngs::OsRng, Rng};
 
-///
-pub fn encryption(scope: Scope, public_key: PublicKey) -> Option<impl Encryption> {
+/// Returns the encryption of private data with the keypair, or `None` for public data,
+/// which isn't encrypted.
+pub fn encryption(scope: Scope, keypair: &Keypair) -> Option<impl Encryption> {
     match scope {
         Scope::Public => None,
-        Scope::Private => Some(DummyEncryption::new(public_key)),
+        Scope::Private => Some(KeyedEncryption::new(keypair)),
     }
 }
 
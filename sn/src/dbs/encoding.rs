@@ -14,6 +14,13 @@ pub(crate) fn serialise<T: Serialize>(data: &T) -> Result<Vec<u8>> {
     bincode::serialize(data).map_err(|err| Error::Serialize(err.as_ref().to_string()))
 }
 
+/// Wrapper for bincode::serialized_size.
+pub(crate) fn serialised_size<T: Serialize>(data: &T) -> Result<usize> {
+    bincode::serialized_size(data)
+        .map(|size| size as usize)
+        .map_err(|err| Error::Serialize(err.as_ref().to_string()))
+}
+
 /// Wrapper for bincode::deserialize.
 pub(crate) fn deserialise<'a, T>(bytes: &'a [u8]) -> Result<T>
 where
@@ -53,18 +53,28 @@ where
         Ok(events)
     }
 
-    /// append a new entry
-    pub(crate) fn append(&mut self, event: TEvent) -> Result<()> {
+    /// append a new entry, returning the size of the entry as stored
+    pub(crate) fn append(&mut self, event: TEvent) -> Result<usize> {
         let key = &self.tree.len().to_string();
         if self.tree.get(key)?.is_some() {
             return Err(Error::DataExists);
         }
 
         let event = serialise(&event)?;
+        let size = event.len();
         let _old_entry = self.tree.insert(key, event).map_err(Error::Sled)?;
 
-        Ok(())
+        Ok(size)
+    }
+}
+
+/// Total size of the entries stored in a tree, ignoring the extra space used by sled.
+pub(crate) fn tree_stored_size(tree: &Tree) -> Result<usize> {
+    let mut size = 0;
+    for value in tree.iter().values() {
+        size += value?.len();
     }
+    Ok(size)
 }
 
 #[cfg(test)]
@@ -86,7 +96,7 @@ mod test {
         })?;
         let mut store = EventStore::<Token>::new(id, db)?;
 
-        store.append(Token::from_nano(10))?;
+        let _ = store.append(Token::from_nano(10))?;
 
         let events = store.get_all()?;
         assert_eq!(events.len(), 1);
@@ -6,9 +6,12 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{encoding::serialise, Result};
+use super::{
+    encoding::{deserialise, serialise},
+    Result,
+};
 use crate::types::{ChunkAddress, Keypair, PublicKey, RegisterAddress};
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 use xor_name::XorName;
 
 pub(crate) trait ToDbKey: Serialize {
@@ -20,6 +23,13 @@ pub(crate) trait ToDbKey: Serialize {
     }
 }
 
+/// Decodes an identifier from the key it was stored with, as encoded by `ToDbKey::to_db_key`.
+/// Returns `None` if the key is not such an encoding.
+pub(crate) fn from_db_key<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let serialised = hex::decode(key).ok()?;
+    deserialise(&serialised).ok()
+}
+
 impl ToDbKey for RegisterAddress {}
 impl ToDbKey for Keypair {}
 impl ToDbKey for ChunkAddress {}
@@ -6,8 +6,10 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use crate::messaging::data::StorageLevel;
+
 use std::sync::{
-    atomic::{AtomicUsize, Ordering},
+    atomic::{AtomicU8, AtomicUsize, Ordering},
     Arc,
 };
 use tracing::info;
@@ -18,6 +20,8 @@ pub struct UsedSpace {
     /// the maximum (inclusive) allocated space for storage
     max_capacity: usize,
     used_space: Arc<AtomicUsize>,
+    // the last storage level recorded with our section, shared by all the stores
+    recorded_level: Arc<AtomicU8>,
 }
 
 impl UsedSpace {
@@ -26,6 +30,7 @@ impl UsedSpace {
         Self {
             max_capacity,
             used_space: Arc::new(AtomicUsize::new(0)),
+            recorded_level: Arc::new(AtomicU8::new(0)),
         }
     }
 
@@ -37,6 +42,11 @@ impl UsedSpace {
         let _ = self.used_space.fetch_sub(size, Ordering::Relaxed);
     }
 
+    /// Space currently used, in bytes.
+    pub(crate) fn used(&self) -> usize {
+        self.used_space.load(Ordering::Relaxed)
+    }
+
     pub(crate) fn can_add(&self, size: usize) -> bool {
         let current_used_space = self.used_space.load(Ordering::Relaxed);
         current_used_space + size <= self.max_capacity
@@ -51,4 +61,21 @@ impl UsedSpace {
         info!("Used space ratio: {:?}", used_space_ratio);
         used_space_ratio
     }
+
+    /// Returns the storage level reached if it's above the one last recorded,
+    /// i.e. if we have filled another approx. 10%-points of our storage, and records it.
+    pub(crate) fn check_level(&self) -> Option<StorageLevel> {
+        // every level represents 10 percentage points
+        let used_space_level = (10.0 * self.ratio()) as u8;
+        let level = StorageLevel::from(used_space_level.min(StorageLevel::MAX)).ok()?;
+        let last_recorded = self
+            .recorded_level
+            .fetch_max(level.value(), Ordering::Relaxed);
+        if level.value() > last_recorded {
+            debug!("Next level for storage has been reached");
+            Some(level)
+        } else {
+            None
+        }
+    }
 }
@@ -7,6 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{register::RegisterWrite, CmdError, Error};
+use crate::messaging::system::SigShare;
 use crate::types::Chunk;
 use serde::{Deserialize, Serialize};
 use xor_name::XorName;
@@ -28,6 +29,16 @@ pub enum DataCmd {
     ///
     /// [`Register`]: crate::types::register::Register
     Register(RegisterWrite),
+    /// [`Register`] write operation on behalf of a multi-sig owner, authorised by the signature
+    /// shares of more than the threshold of its members rather than by the signer of the cmd.
+    ///
+    /// [`Register`]: crate::types::register::Register
+    MultiSigRegister {
+        /// The operation to perform.
+        write: RegisterWrite,
+        /// Signature shares of the serialised `write`, by distinct members of the owner.
+        sig_shares: Vec<SigShare>,
+    },
 }
 
 impl DataCmd {
@@ -37,7 +48,7 @@ impl DataCmd {
         use DataCmd::*;
         match self {
             StoreChunk(_) => CmdError::Data(error),
-            Register(c) => c.error(error),
+            Register(c) | MultiSigRegister { write: c, .. } => c.error(error),
         }
     }
 
@@ -46,7 +57,7 @@ impl DataCmd {
         use DataCmd::*;
         match self {
             StoreChunk(c) => *c.name(),
-            Register(c) => c.dst_name(),
+            Register(c) | MultiSigRegister { write: c, .. } => c.dst_name(),
         }
     }
 }
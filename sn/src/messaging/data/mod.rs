@@ -23,7 +23,7 @@ pub use self::{
 };
 
 use crate::types::{
-    register::{Entry, EntryHash, Permissions, Policy, Register},
+    register::{Entry, EntryHash, HistoryEntry, Permissions, Policy, Register},
     Chunk, ChunkAddress, DataAddress, PublicKey,
 };
 use crate::{
@@ -141,6 +141,10 @@ pub enum QueryResponse {
     GetRegisterPolicy((Result<Policy>, OperationId)),
     /// Response to [`RegisterRead::GetUserPermissions`].
     GetRegisterUserPermissions((Result<Permissions>, OperationId)),
+    /// Response to [`RegisterRead::History`].
+    GetRegisterHistory((Result<Vec<HistoryEntry>>, OperationId)),
+    /// Response to [`RegisterRead::GetChildren`].
+    GetRegisterChildren((Result<BTreeSet<(EntryHash, Entry)>>, OperationId)),
 }
 
 impl QueryResponse {
@@ -154,6 +158,8 @@ impl QueryResponse {
             ReadRegister((result, _op_id)) => result.is_ok(),
             GetRegisterPolicy((result, _op_id)) => result.is_ok(),
             GetRegisterUserPermissions((result, _op_id)) => result.is_ok(),
+            GetRegisterHistory((result, _op_id)) => result.is_ok(),
+            GetRegisterChildren((result, _op_id)) => result.is_ok(),
         }
     }
 
@@ -186,6 +192,14 @@ impl QueryResponse {
                 Ok(_) => false,
                 Err(error) => matches!(*error, ErrorMessage::DataNotFound(_)),
             },
+            GetRegisterHistory((result, _op_id)) => match result {
+                Ok(_) => false,
+                Err(error) => matches!(*error, ErrorMessage::DataNotFound(_)),
+            },
+            GetRegisterChildren((result, _op_id)) => match result {
+                Ok(_) => false,
+                Err(error) => matches!(*error, ErrorMessage::DataNotFound(_)),
+            },
         }
     }
 
@@ -219,7 +233,9 @@ impl QueryResponse {
             | GetRegisterOwner((_, operation_id))
             | ReadRegister((_, operation_id))
             | GetRegisterPolicy((_, operation_id))
-            | GetRegisterUserPermissions((_, operation_id)) => Ok(operation_id.clone()),
+            | GetRegisterUserPermissions((_, operation_id))
+            | GetRegisterHistory((_, operation_id))
+            | GetRegisterChildren((_, operation_id)) => Ok(operation_id.clone()),
         }
     }
 }
@@ -267,7 +283,12 @@ impl TryFrom<QueryResponse> for Chunk {
 
 try_from!(Register, GetRegister);
 try_from!(PublicKey, GetRegisterOwner);
-try_from!(BTreeSet<(EntryHash, Entry)>, ReadRegister);
+try_from!(
+    BTreeSet<(EntryHash, Entry)>,
+    ReadRegister,
+    GetRegisterChildren
+);
+try_from!(Vec<HistoryEntry>, GetRegisterHistory);
 try_from!(Policy, GetRegisterPolicy);
 try_from!(Permissions, GetRegisterUserPermissions);
 
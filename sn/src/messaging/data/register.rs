@@ -6,10 +6,10 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{CmdError, Error, QueryResponse, Result};
-use crate::messaging::data::OperationId;
+use super::{CmdError, DataCmd, Error, QueryResponse, Result};
+use crate::messaging::{data::OperationId, system::SigShare};
 use crate::types::{
-    register::{Entry, Register, RegisterOp, User},
+    register::{Entry, EntryHash, Policy, Register, RegisterOp, User},
     PublicKey, RegisterAddress as Address,
 };
 use serde::{Deserialize, Serialize};
@@ -54,6 +54,30 @@ pub enum RegisterRead {
     ///
     /// [`GetRegisterOwner`]: QueryResponse::GetRegisterOwner
     GetOwner(Address),
+    /// Retrieve the history of the [`Register`] at the given address, i.e. all its entries
+    /// with every entry coming after the entries it was written on top of.
+    ///
+    /// This should eventually lead to a [`GetRegisterHistory`] response.
+    ///
+    /// [`GetRegisterHistory`]: QueryResponse::GetRegisterHistory
+    History {
+        /// Register address.
+        address: Address,
+        /// Entry to end the history with, i.e. the version of the Register to retrieve the
+        /// history of. The history of all the current entries is retrieved if not set.
+        from: Option<EntryHash>,
+    },
+    /// Retrieve the entries an entry of the [`Register`] at the given address was written on top of.
+    ///
+    /// This should eventually lead to a [`GetRegisterChildren`] response.
+    ///
+    /// [`GetRegisterChildren`]: QueryResponse::GetRegisterChildren
+    GetChildren {
+        /// Register address.
+        address: Address,
+        /// Hash of the entry to get the children of.
+        hash: EntryHash,
+    },
 }
 
 /// A [`Register`] write operation.
@@ -65,6 +89,23 @@ pub struct RegisterCmd {
     ///
     /// This will be verified against the register's owner and permissions.
     pub auth: crate::messaging::ServiceAuth,
+    /// Signature shares of the serialised `write` by the members of a multi-sig owner,
+    /// empty unless the write was co-signed by them, see [`DataCmd::MultiSigRegister`].
+    pub sig_shares: Vec<SigShare>,
+}
+
+impl RegisterCmd {
+    /// Returns the data cmd the write was sent with.
+    pub fn data_cmd(&self) -> DataCmd {
+        if self.sig_shares.is_empty() {
+            DataCmd::Register(self.write.clone())
+        } else {
+            DataCmd::MultiSigRegister {
+                write: self.write.clone(),
+                sig_shares: self.sig_shares.clone(),
+            }
+        }
+    }
 }
 
 /// [`Register`] write operations.
@@ -80,6 +121,26 @@ pub enum RegisterWrite {
     /// This operation will result in an error if applied to a public register. Only private
     /// registers can be deleted, and only by their current owner(s).
     Delete(Address),
+    /// Replace the policy of a [`Register`], e.g. to add or remove writers, or to grant read
+    /// access to a private register.
+    ///
+    /// Only the current owner can do this. The policy must be of the same scope as the register,
+    /// and keep its owner, which is only changed with [`TransferOwnership`].
+    ///
+    /// [`TransferOwnership`]: RegisterWrite::TransferOwnership
+    SetPolicy {
+        /// Register address.
+        address: Address,
+        /// The new policy.
+        policy: Policy,
+    },
+    /// Make another key the owner of a [`Register`]. Only the current owner can do this.
+    TransferOwnership {
+        /// Register address.
+        address: Address,
+        /// The key of the new owner.
+        new_owner: PublicKey,
+    },
 }
 
 impl RegisterRead {
@@ -106,6 +167,14 @@ impl RegisterRead {
                 Err(error),
                 self.operation_id()?,
             ))),
+            RegisterRead::History { .. } => Ok(QueryResponse::GetRegisterHistory((
+                Err(error),
+                self.operation_id()?,
+            ))),
+            RegisterRead::GetChildren { .. } => Ok(QueryResponse::GetRegisterChildren((
+                Err(error),
+                self.operation_id()?,
+            ))),
         }
     }
 
@@ -116,7 +185,9 @@ impl RegisterRead {
             | RegisterRead::Read(ref address)
             | RegisterRead::GetPolicy(ref address)
             | RegisterRead::GetUserPermissions { ref address, .. }
-            | RegisterRead::GetOwner(ref address) => *address,
+            | RegisterRead::GetOwner(ref address)
+            | RegisterRead::History { ref address, .. }
+            | RegisterRead::GetChildren { ref address, .. } => *address,
         }
     }
 
@@ -127,7 +198,9 @@ impl RegisterRead {
             | RegisterRead::Read(ref address)
             | RegisterRead::GetPolicy(ref address)
             | RegisterRead::GetUserPermissions { ref address, .. }
-            | RegisterRead::GetOwner(ref address) => *address.name(),
+            | RegisterRead::GetOwner(ref address)
+            | RegisterRead::History { ref address, .. }
+            | RegisterRead::GetChildren { ref address, .. } => *address.name(),
         }
     }
 
@@ -167,6 +240,26 @@ impl RegisterRead {
                     .encode_to_zbase32()
                     .map_err(|_| Error::NoOperationId)?
             )),
+            RegisterRead::History {
+                ref address,
+                ref from,
+            } => Ok(format!(
+                "History-{:?}-{:?}",
+                address
+                    .encode_to_zbase32()
+                    .map_err(|_| Error::NoOperationId)?,
+                from
+            )),
+            RegisterRead::GetChildren {
+                ref address,
+                ref hash,
+            } => Ok(format!(
+                "GetChildren-{:?}-{:?}",
+                address
+                    .encode_to_zbase32()
+                    .map_err(|_| Error::NoOperationId)?,
+                hash
+            )),
         }
     }
 }
@@ -184,6 +277,8 @@ impl RegisterWrite {
             RegisterWrite::New(ref data) => *data.name(),
             RegisterWrite::Delete(ref address) => *address.name(),
             RegisterWrite::Edit(ref op) => *op.address.name(),
+            RegisterWrite::SetPolicy { ref address, .. }
+            | RegisterWrite::TransferOwnership { ref address, .. } => *address.name(),
         }
     }
 
@@ -193,6 +288,7 @@ impl RegisterWrite {
             Self::New(map) => map.address(),
             Self::Delete(address) => address,
             Self::Edit(ref op) => &op.address,
+            Self::SetPolicy { address, .. } | Self::TransferOwnership { address, .. } => address,
         }
     }
 
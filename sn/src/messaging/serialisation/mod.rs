@@ -92,7 +92,8 @@ impl MessageType {
                     | SystemMsg::JoinRequest(_)
                     | SystemMsg::JoinAsRelocatedRequest(_)
                     | SystemMsg::Propose { .. }
-                    | SystemMsg::StartConnectivityTest(_),
+                    | SystemMsg::StartConnectivityTest(_)
+                    | SystemMsg::LeaveRequest,
                 ..
             } => INFRASTRUCTURE_MSG_PRIORITY,
 
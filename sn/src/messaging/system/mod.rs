@@ -181,6 +181,10 @@ pub enum SystemMsg {
     /// Message that notifies a section to test
     /// the connectivity to a node
     StartConnectivityTest(XorName),
+    /// Sent by a node to the elders of its section when it's about to leave the network
+    /// voluntarily, so they can agree to take it `Offline` right away. The leaving node is the
+    /// one that signed the msg.
+    LeaveRequest,
     /// Cmds only sent internally in the network.
     NodeCmd(NodeCmd),
     /// Queries is a read-only operation.
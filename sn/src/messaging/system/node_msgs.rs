@@ -15,7 +15,7 @@ use crate::{
     types::ChunkAddress,
 };
 use serde::{Deserialize, Serialize};
-use xor_name::XorName;
+use xor_name::{Prefix, XorName};
 
 /// Command message sent among nodes
 #[allow(clippy::large_enum_variant)]
@@ -52,6 +52,19 @@ pub enum NodeCmd {
     ReplicateChunk(Chunk),
     /// Tells the Elders to re-publish a chunk in the data section
     RepublishChunk(Chunk),
+    /// Asks an Adult for the digest of the names of the chunks it holds within the prefix,
+    /// sent by Elders auditing the copies held by the Adults of their section
+    RequestChunkDigest(Prefix),
+    /// Reply to a `RequestChunkDigest`, sent from the Adult to the auditing Elder
+    ChunkDigest {
+        /// The prefix the digest was requested for
+        prefix: Prefix,
+        /// Merkle root of the names of the chunks held within the prefix
+        digest: XorName,
+    },
+    /// Tells an Adult to re-publish all the chunks it holds within the prefix,
+    /// sent by Elders when an audit found the holders disagreeing about them
+    RepublishChunks(Prefix),
     /// Sent to all promoted nodes (also sibling if any) after
     /// a completed transition to a new constellation.
     ReceiveExistingData {
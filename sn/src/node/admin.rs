@@ -0,0 +1,416 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Local admin interface of a running node, served as JSON-RPC over QUIC.
+//!
+//! The endpoint uses a self-signed certificate, generated on first use and stored within the
+//! node's root dir (see `ADMIN_CERT_DIR_NAME`). Clients need to trust that certificate to connect.
+//!
+//! Anyone who can reach the endpoint can make the node leave, so it's only served on a loopback
+//! address, unless a token is configured, in which case every request has to carry that token.
+
+use crate::node::{
+    routing::{Routing, XorName},
+    Error, Result,
+};
+use crate::peer::Peer;
+
+use qjsonrpc::{
+    Endpoint, IncomingJsonRpcRequest, JsonRpcRequest, JsonRpcResponse, JSONRPC_INTERNAL_ERROR,
+    JSONRPC_INVALID_PARAMS, JSONRPC_METHOD_NOT_FOUND,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{net::SocketAddr, path::Path, sync::Arc};
+use tokio::sync::mpsc;
+
+/// Name of the directory, within the node's root dir, holding the admin endpoint certificate.
+pub const ADMIN_CERT_DIR_NAME: &str = "admin";
+
+/// Method returning the node's `NodeStatus`.
+pub const ADMIN_METHOD_STATUS: &str = "status";
+/// Method setting whether the node's section allows new nodes to join, taking a bool.
+pub const ADMIN_METHOD_SET_JOINS_ALLOWED: &str = "set-joins-allowed";
+/// Method asking the section elders to test connectivity to a node, taking its name.
+pub const ADMIN_METHOD_START_CONNECTIVITY_TEST: &str = "start-connectivity-test";
+/// Method asking the node to leave the network gracefully.
+pub const ADMIN_METHOD_LEAVE: &str = "leave";
+/// Method changing the node's log filter, taking a `RUST_LOG`-like directive.
+pub const ADMIN_METHOD_SET_LOG_LEVEL: &str = "set-log-level";
+
+// JSON-RPC error code returned when a request doesn't carry the configured token.
+const ADMIN_UNAUTHORISED: isize = -32001;
+
+/// Parameters of an admin request: those of the method, along with the token the node was
+/// configured with, if any.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdminParams {
+    /// Token the node's admin interface was configured with.
+    pub token: Option<String>,
+    /// Parameters of the method.
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// Changes the node's log filter at runtime, given a `RUST_LOG`-like directive.
+pub type LogFilterHandler = Box<dyn Fn(&str) -> std::result::Result<(), String> + Send + Sync>;
+
+/// A member of a section.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionMember {
+    /// Name of the node.
+    pub name: XorName,
+    /// Address of the node.
+    pub addr: SocketAddr,
+}
+
+/// A section the node knows about, from its prefix map.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownSection {
+    /// Prefix of the section, in binary.
+    pub prefix: String,
+    /// Current public key of the section, in hex.
+    pub section_key: String,
+    /// Elders of the section.
+    pub elders: Vec<SectionMember>,
+}
+
+/// A DKG session the node is participating in, which hasn't completed yet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingDkgSession {
+    /// Generation of the session, as in the length of the section chain main branch.
+    pub generation: u64,
+    /// Prefix of the section the new elders are for, in binary.
+    pub prefix: String,
+    /// Names of the elder candidates participating in the session.
+    pub participants: Vec<XorName>,
+}
+
+/// State of a running node, as returned by `ADMIN_METHOD_STATUS`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeStatus {
+    /// Name of the node.
+    pub name: XorName,
+    /// Age of the node.
+    pub age: u8,
+    /// Prefix of the node's section, in binary.
+    pub prefix: String,
+    /// Whether the node is an elder of its section.
+    pub is_elder: bool,
+    /// Address the node can be reached at.
+    pub connection_info: SocketAddr,
+    /// Genesis key of the network, in hex.
+    pub genesis_key: String,
+    /// Elders of the node's section.
+    pub elders: Vec<SectionMember>,
+    /// Adults of the node's section.
+    pub adults: Vec<SectionMember>,
+    /// Sections the node knows about, including its own.
+    pub prefix_map: Vec<KnownSection>,
+    /// Storage levels reported by the adults of the section, only tracked by elders.
+    pub storage_levels: Vec<(XorName, u8)>,
+    /// DKG sessions the node is participating in which haven't completed yet.
+    pub pending_dkg_sessions: Vec<PendingDkgSession>,
+}
+
+struct AdminCtx {
+    network: Arc<Routing>,
+    log_filter: Option<LogFilterHandler>,
+    leave_tx: mpsc::Sender<()>,
+    token: Option<String>,
+}
+
+// Error returned to the caller, with its JSON-RPC error code.
+type RequestError = (isize, String);
+
+/// Starts serving the admin interface at `addr`, using the certificate found at, or generated
+/// into, `cert_base_path`. Graceful leave requests are sent to `leave_tx`.
+///
+/// When a `token` is given, requests not carrying it are rejected. Without one, `addr` must be a
+/// loopback address.
+pub(super) fn run_admin_endpoint(
+    addr: SocketAddr,
+    cert_base_path: &Path,
+    network: Arc<Routing>,
+    log_filter: Option<LogFilterHandler>,
+    leave_tx: mpsc::Sender<()>,
+    token: Option<String>,
+) -> Result<()> {
+    check_admin_addr(addr, &token)?;
+
+    let endpoint = Endpoint::new(cert_base_path, None)
+        .map_err(|err| Error::AdminEndpoint(format!("Failed to create endpoint: {}", err)))?;
+    let mut incoming_conns = endpoint
+        .bind(&addr)
+        .map_err(|err| Error::AdminEndpoint(format!("Failed to bind endpoint: {}", err)))?;
+
+    let ctx = Arc::new(AdminCtx {
+        network,
+        log_filter,
+        leave_tx,
+        token,
+    });
+
+    let _handle = tokio::task::spawn(async move {
+        while let Some(conn) = incoming_conns.get_next().await {
+            let _handle = tokio::task::spawn(handle_connection(conn, ctx.clone()));
+        }
+    });
+
+    Ok(())
+}
+
+fn check_admin_addr(addr: SocketAddr, token: &Option<String>) -> Result<()> {
+    if addr.ip().is_loopback() || token.is_some() {
+        Ok(())
+    } else {
+        Err(Error::AdminEndpoint(format!(
+            "Refusing to serve the admin interface on non-loopback address {} without a token",
+            addr
+        )))
+    }
+}
+
+async fn handle_connection(mut conn: IncomingJsonRpcRequest, ctx: Arc<AdminCtx>) {
+    // Each stream initiated by the client constitutes a new request.
+    while let Some((request, mut response_stream)) = conn.get_next().await {
+        let response = process_request(request, &ctx).await;
+
+        if let Err(err) = response_stream.respond(&response).await {
+            warn!("Failed to send admin response: {}", err);
+            continue;
+        }
+        if let Err(err) = response_stream.finish().await {
+            warn!("Failed to shutdown admin response stream: {}", err);
+        }
+    }
+}
+
+async fn process_request(request: JsonRpcRequest, ctx: &AdminCtx) -> JsonRpcResponse {
+    debug!("Processing admin request: {:?}", request.method);
+
+    let params = match authorise(request.params, &ctx.token) {
+        Ok(params) => params,
+        Err((code, message)) => return JsonRpcResponse::error(message, code, Some(request.id)),
+    };
+
+    let result = match request.method.as_str() {
+        ADMIN_METHOD_STATUS => Ok(json!(status(&ctx.network).await)),
+        ADMIN_METHOD_SET_JOINS_ALLOWED => match parse_params(params) {
+            Ok(joins_allowed) => ctx
+                .network
+                .set_joins_allowed(joins_allowed)
+                .await
+                .map(|()| Value::Null)
+                .map_err(internal_error),
+            Err(err) => Err(err),
+        },
+        ADMIN_METHOD_START_CONNECTIVITY_TEST => match parse_params(params) {
+            Ok(name) => ctx
+                .network
+                .start_connectivity_test(name)
+                .await
+                .map(|()| Value::Null)
+                .map_err(internal_error),
+            Err(err) => Err(err),
+        },
+        ADMIN_METHOD_LEAVE => {
+            info!("Graceful leave requested through the admin interface");
+            ctx.leave_tx
+                .send(())
+                .await
+                .map(|()| Value::Null)
+                .map_err(|_| internal_error("the node is already shutting down"))
+        }
+        ADMIN_METHOD_SET_LOG_LEVEL => match parse_params::<String>(params) {
+            Ok(directive) => match &ctx.log_filter {
+                Some(log_filter) => log_filter(&directive)
+                    .map(|()| Value::Null)
+                    .map_err(|err| (JSONRPC_INVALID_PARAMS, err)),
+                None => Err(internal_error(
+                    "log level changes are not supported by this node",
+                )),
+            },
+            Err(err) => Err(err),
+        },
+        other => Err((
+            JSONRPC_METHOD_NOT_FOUND,
+            format!("Unknown admin method: {}", other),
+        )),
+    };
+
+    match result {
+        Ok(value) => JsonRpcResponse::result(value, request.id),
+        Err((code, message)) => JsonRpcResponse::error(message, code, Some(request.id)),
+    }
+}
+
+async fn status(network: &Routing) -> NodeStatus {
+    let mut storage_levels: Vec<_> = network
+        .storage_levels()
+        .await
+        .into_iter()
+        .map(|(name, level)| (name, level.value()))
+        .collect();
+    storage_levels.sort();
+
+    NodeStatus {
+        name: network.name().await,
+        age: network.age().await,
+        prefix: format!("{:b}", network.our_prefix().await),
+        is_elder: network.is_elder().await,
+        connection_info: network.our_connection_info().await,
+        genesis_key: hex::encode(network.genesis_key().await.to_bytes()),
+        elders: members(network.our_elders().await),
+        adults: members(network.our_adults().await),
+        prefix_map: network
+            .known_sections()
+            .into_iter()
+            .map(|sap| KnownSection {
+                prefix: format!("{:b}", sap.prefix()),
+                section_key: hex::encode(sap.section_key().to_bytes()),
+                elders: members(sap.elders_vec()),
+            })
+            .collect(),
+        storage_levels,
+        pending_dkg_sessions: network
+            .pending_dkg_sessions()
+            .into_iter()
+            .map(|(session_id, candidates)| PendingDkgSession {
+                generation: session_id.generation,
+                prefix: format!("{:b}", candidates.prefix()),
+                participants: candidates.names().collect(),
+            })
+            .collect(),
+    }
+}
+
+fn members(peers: Vec<Peer>) -> Vec<SectionMember> {
+    peers
+        .into_iter()
+        .map(|peer| SectionMember {
+            name: peer.name(),
+            addr: peer.addr(),
+        })
+        .collect()
+}
+
+// Checks the request carries the configured token, if any, returning the method's parameters.
+fn authorise(params: Value, token: &Option<String>) -> Result<Value, RequestError> {
+    let AdminParams {
+        token: given,
+        params,
+    } = parse_params(params)?;
+
+    let authorised = match (token, given) {
+        (None, _) => true,
+        (Some(token), Some(given)) => constant_time_eq(token.as_bytes(), given.as_bytes()),
+        (Some(_), None) => false,
+    };
+    if authorised {
+        Ok(params)
+    } else {
+        Err((
+            ADMIN_UNAUTHORISED,
+            "Missing or invalid admin token".to_string(),
+        ))
+    }
+}
+
+// Compares the bytes without bailing out early, so the time taken doesn't leak the token.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RequestError> {
+    serde_json::from_value(params).map_err(|err| (JSONRPC_INVALID_PARAMS, err.to_string()))
+}
+
+fn internal_error(err: impl ToString) -> RequestError {
+    (JSONRPC_INTERNAL_ERROR, err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        authorise, check_admin_addr, parse_params, AdminParams, NodeStatus, SectionMember,
+        ADMIN_UNAUTHORISED, JSONRPC_INVALID_PARAMS,
+    };
+
+    use crate::node::routing::XorName;
+    use serde_json::json;
+
+    #[test]
+    fn params_are_parsed_or_rejected_as_invalid() {
+        let name = XorName::random();
+        assert_eq!(parse_params::<XorName>(json!(name)), Ok(name));
+        assert_eq!(parse_params::<bool>(json!(true)), Ok(true));
+
+        let (code, _) = parse_params::<bool>(json!("yes")).unwrap_err();
+        assert_eq!(code, JSONRPC_INVALID_PARAMS);
+    }
+
+    #[test]
+    fn requests_must_carry_the_configured_token() {
+        let token = Some("s3cr3t".to_string());
+        let request = |token: Option<&str>| {
+            json!(AdminParams {
+                token: token.map(str::to_string),
+                params: json!(true),
+            })
+        };
+
+        assert_eq!(authorise(request(None), &None), Ok(json!(true)));
+        assert_eq!(authorise(request(Some("s3cr3t")), &token), Ok(json!(true)));
+
+        for wrong in [None, Some("s3cr3"), Some("s3cr3t!"), Some("S3CR3T")] {
+            let (code, _) = authorise(request(wrong), &token).unwrap_err();
+            assert_eq!(code, ADMIN_UNAUTHORISED);
+        }
+
+        let (code, _) = authorise(json!(true), &None).unwrap_err();
+        assert_eq!(code, JSONRPC_INVALID_PARAMS);
+    }
+
+    #[test]
+    fn non_loopback_addrs_require_a_token() {
+        let token = Some("s3cr3t".to_string());
+        for addr in ["127.0.0.1:34000", "[::1]:34000"] {
+            assert!(check_admin_addr(addr.parse().unwrap(), &None).is_ok());
+        }
+        for addr in ["0.0.0.0:34000", "192.168.0.2:34000", "[::]:34000"] {
+            assert!(check_admin_addr(addr.parse().unwrap(), &None).is_err());
+            assert!(check_admin_addr(addr.parse().unwrap(), &token).is_ok());
+        }
+    }
+
+    #[test]
+    fn node_status_roundtrips_through_json() -> Result<(), serde_json::Error> {
+        let member = SectionMember {
+            name: XorName::random(),
+            addr: "127.0.0.1:12000".parse().unwrap(),
+        };
+        let status = NodeStatus {
+            name: member.name,
+            age: 5,
+            prefix: "01".to_string(),
+            is_elder: true,
+            connection_info: member.addr,
+            genesis_key: "ab".to_string(),
+            elders: vec![member.clone()],
+            adults: vec![],
+            prefix_map: vec![],
+            storage_levels: vec![(XorName::random(), 3)],
+            pending_dkg_sessions: vec![],
+        };
+
+        let json = serde_json::to_value(&status)?;
+        assert_eq!(serde_json::from_value::<NodeStatus>(json)?, status);
+        Ok(())
+    }
+}
@@ -7,9 +7,11 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::node::{
+    admin::{run_admin_endpoint, LogFilterHandler, ADMIN_CERT_DIR_NAME},
     keypair_storage::store_network_keypair,
     keypair_storage::{get_reward_pk, store_new_reward_keypair},
     logging::{log_ctx::LogCtx, run_system_logger},
+    metrics::run_metrics_server,
     routing::{Config as RoutingConfig, EventStream, Routing},
     Config as NodeConfig, Error, Result,
 };
@@ -17,8 +19,8 @@ use crate::types::PublicKey;
 use crate::UsedSpace;
 
 use rand::rngs::OsRng;
-use std::{net::SocketAddr, sync::Arc};
-use tokio::time::Duration;
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+use tokio::{sync::mpsc, time::Duration};
 use xor_name::{Prefix, XorName};
 
 type Network = Arc<Routing>;
@@ -28,6 +30,7 @@ type Network = Arc<Routing>;
 pub struct Node {
     #[debug(skip)]
     network_api: Network,
+    root_dir: PathBuf,
 }
 
 impl Node {
@@ -66,6 +69,9 @@ impl Node {
             bootstrap_nodes: config.hard_coded_contacts.clone(),
             genesis_key: config.genesis_key.clone(),
             network_config: config.network_config().clone(),
+            chunk_store_kind: config.chunk_store(),
+            #[cfg(any(test, feature = "test-utils"))]
+            simulated_network: config.simulated_network.clone(),
             ..Default::default()
         };
         if let Some(local_addr) = config.local_addr {
@@ -88,6 +94,7 @@ impl Node {
 
         let node = Self {
             network_api: network_api.clone(),
+            root_dir: root_dir_buf.clone(),
         };
 
         let our_pid = std::process::id();
@@ -106,6 +113,11 @@ impl Node {
             our_pid, node_prefix, node_name, node_age, our_conn_info_json,
         );
 
+        if let Some(metrics_addr) = config.metrics_addr {
+            let metrics_addr = run_metrics_server(metrics_addr, network_api.clone())?;
+            info!("Serving node metrics at http://{}/metrics", metrics_addr);
+        }
+
         run_system_logger(LogCtx::new(network_api), config.resource_logs).await;
 
         Ok((node, network_events))
@@ -131,8 +143,64 @@ impl Node {
         self.network_api.our_prefix().await
     }
 
+    /// Returns the current key of our section.
+    pub async fn our_section_key(&self) -> bls::PublicKey {
+        *self.network_api.section_chain().await.last_key()
+    }
+
     /// Returns the network's genesis key.
     pub async fn genesis_key(&self) -> bls::PublicKey {
         self.network_api.genesis_key().await
     }
+
+    /// Returns whether we are an Elder.
+    pub async fn is_elder(&self) -> bool {
+        self.network_api.is_elder().await
+    }
+
+    /// Starts serving the admin interface at `addr`, with the certificate stored within the node's
+    /// root dir. Log level changes are applied through `log_filter`, if any.
+    ///
+    /// Requests must carry the `token`, if one is given, without which `addr` must be a loopback
+    /// address.
+    ///
+    /// Returns a receiver notified whenever a graceful leave is requested through the interface.
+    pub fn start_admin_endpoint(
+        &self,
+        addr: SocketAddr,
+        token: Option<String>,
+        log_filter: Option<LogFilterHandler>,
+    ) -> Result<mpsc::Receiver<()>> {
+        let (leave_tx, leave_rx) = mpsc::channel(1);
+        let cert_base_path = self.root_dir.join(ADMIN_CERT_DIR_NAME);
+        run_admin_endpoint(
+            addr,
+            &cert_base_path,
+            self.network_api.clone(),
+            log_filter,
+            leave_tx,
+            token,
+        )?;
+        info!(
+            "Serving admin interface at {}, with certificate at {}",
+            addr,
+            cert_base_path.display()
+        );
+        Ok(leave_rx)
+    }
+
+    /// Stops the node, closing all its connections.
+    pub fn terminate(&self) {
+        self.network_api.terminate()
+    }
+
+    /// Leaves the network gracefully, handing off the chunks held by this node to the adults
+    /// taking over them, and then stops the node once its elders agreed on it going offline.
+    pub async fn leave(&self) -> Result<()> {
+        info!("Leaving the network...");
+        self.network_api.leave().await?;
+        self.terminate();
+        info!("Left the network");
+        Ok(())
+    }
 }
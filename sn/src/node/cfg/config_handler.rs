@@ -6,13 +6,15 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::routing::NetworkConfig;
+#[cfg(any(test, feature = "test-utils"))]
+use crate::node::routing::SimulatedNetwork;
+use crate::node::routing::{ChunkStoreKind, NetworkConfig};
 use crate::node::{Error, Result};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeSet,
     io::{self},
-    net::SocketAddr,
+    net::{Ipv4Addr, SocketAddr},
     path::PathBuf,
     time::Duration,
 };
@@ -47,6 +49,12 @@ pub struct Config {
     /// MacOS: $HOME/.safe/node/root_dir
     #[structopt(short, long, parse(from_os_str))]
     pub root_dir: Option<PathBuf>,
+    /// Backend to store chunks in, within the root directory: "disk" (one file per chunk),
+    /// "packed-log" (chunks appended to segment files), or "memory" (not persisted, for tests).
+    ///
+    /// Defaults to "disk". Chunks stored by one backend are not seen by the others.
+    #[structopt(long)]
+    pub chunk_store: Option<ChunkStoreKind>,
     /// Verbose output. `-v` is equivalent to logging with `warn`, `-vv` to `info`, `-vvv` to
     /// `debug`, `-vvvv` to `trace`. This flag overrides RUST_LOG.
     #[structopt(short, long, parse(from_occurrences))]
@@ -69,6 +77,22 @@ pub struct Config {
     /// print node resourse usage to stdout
     #[structopt(long)]
     pub resource_logs: bool,
+    /// Address to serve node metrics on, in the Prometheus text format, at `/metrics`.
+    ///
+    /// When unspecified, no metrics endpoint is started.
+    #[structopt(long)]
+    pub metrics_addr: Option<SocketAddr>,
+    /// Local address to serve the node's admin interface on, as JSON-RPC over QUIC. A port alone,
+    /// e.g. `34000`, serves it on the loopback IP.
+    ///
+    /// When unspecified, the admin interface is disabled. Non-loopback addresses are refused
+    /// unless `--admin-token` is set too.
+    #[structopt(long, parse(try_from_str = parse_admin_addr))]
+    pub admin_addr: Option<SocketAddr>,
+    /// Token the admin interface requires every request to carry, mandatory to serve it on a
+    /// non-loopback address.
+    #[structopt(long, env = "SN_ADMIN_TOKEN", hide_env_values = true)]
+    pub admin_token: Option<String>,
     /// Delete all data from a previous node running on the same PC
     #[structopt(long)]
     pub clear_data: bool,
@@ -131,6 +155,11 @@ pub struct Config {
     #[structopt(skip)]
     #[allow(missing_docs)]
     pub network_config: NetworkConfig,
+    /// In-memory network to start the node on instead of QUIC, for tests.
+    #[cfg(any(test, feature = "test-utils"))]
+    #[structopt(skip)]
+    #[serde(skip)]
+    pub simulated_network: Option<SimulatedNetwork>,
 }
 
 impl Config {
@@ -168,7 +197,16 @@ impl Config {
             tracing::error!("Error deleting data file from disk");
         });
 
-        info!("Node config to be used: {:?}", config);
+        info!(
+            "Node config to be used: {:?}",
+            Config {
+                admin_token: config
+                    .admin_token
+                    .as_ref()
+                    .map(|_| "<redacted>".to_string()),
+                ..config.clone()
+            }
+        );
         Ok(config)
     }
 
@@ -217,9 +255,25 @@ impl Config {
             self.root_dir = Some(root_dir.clone());
         }
 
+        if let Some(chunk_store) = config.chunk_store {
+            self.chunk_store = Some(chunk_store);
+        }
+
         self.json_logs = config.json_logs;
         self.resource_logs = config.resource_logs;
 
+        if let Some(metrics_addr) = config.metrics_addr {
+            self.metrics_addr = Some(metrics_addr);
+        }
+
+        if let Some(admin_addr) = config.admin_addr {
+            self.admin_addr = Some(admin_addr);
+        }
+
+        if let Some(admin_token) = config.admin_token {
+            self.admin_token = Some(admin_token);
+        }
+
         if config.verbose > 0 {
             self.verbose = config.verbose;
         }
@@ -300,6 +354,11 @@ impl Config {
         })
     }
 
+    /// Backend to store chunks in, `ChunkStoreKind::Disk` if not set.
+    pub fn chunk_store(&self) -> ChunkStoreKind {
+        self.chunk_store.unwrap_or_default()
+    }
+
     /// Set the root directory for dbs and cached state.
     pub fn set_root_dir<P: Into<PathBuf>>(&mut self, path: P) {
         self.root_dir = Some(path.into())
@@ -418,6 +477,13 @@ fn parse_public_addr(public_addr: &str) -> Result<SocketAddr, String> {
     Ok(public_addr)
 }
 
+fn parse_admin_addr(admin_addr: &str) -> Result<SocketAddr, String> {
+    match admin_addr.parse::<u16>() {
+        Ok(port) => Ok(SocketAddr::from((Ipv4Addr::LOCALHOST, port))),
+        Err(_) => admin_addr.parse().map_err(|err| format!("{}", err)),
+    }
+}
+
 /// Overwrites connection info at file.
 ///
 /// The file is written to the `current_bin_dir()` with the appropriate file name.
@@ -484,7 +550,7 @@ fn smoke() {
     // NOTE: IF this value is being changed due to a change in the config,
     // the change in config also be handled in Config::merge()
     // and in examples/config_handling.rs
-    let expected_size = 440;
+    let expected_size = 520;
 
     assert_eq!(std::mem::size_of::<Config>(), expected_size);
 }
@@ -136,6 +136,15 @@ pub enum Error {
     /// Sled error.
     #[error("Sled error:: {0}")]
     Sled(#[from] sled::Error),
+    /// Admin endpoint error.
+    #[error("Admin endpoint error:: {0}")]
+    AdminEndpoint(String),
+    /// Metrics endpoint error.
+    #[error("Metrics endpoint error:: {0}")]
+    MetricsEndpoint(#[from] hyper::Error),
+    /// Data archive error.
+    #[error("Invalid data archive: {0}")]
+    InvalidArchive(String),
 }
 
 impl From<qp2p::ClientEndpointError> for Error {
@@ -0,0 +1,307 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Operations on the data dir of a node which isn't running, e.g. to check it, or to move its
+//! data to another disk or chunk store backend.
+//!
+//! None of these must be run on a data dir in use by a running node.
+
+use crate::dbs::UsedSpace;
+use crate::messaging::data::{RegisterCmd, RegisterDataExchange};
+use crate::node::{
+    routing::{ChunkStore, ChunkStoreError, ChunkStoreKind, RegisterStorage},
+    Error, Result,
+};
+use crate::types::Chunk;
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+use xor_name::{Prefix, XorName};
+
+// Written at the start of every archive, followed by the archived entries.
+const ARCHIVE_MAGIC: &[u8; 8] = b"SNDATA01";
+
+// Entries of a data archive, each serialised with bincode.
+#[derive(Serialize, Deserialize)]
+enum ArchiveEntry {
+    Chunk(Bytes),
+    Register { key: XorName, ops: Vec<RegisterCmd> },
+}
+
+/// Outcome of checking the chunks of a data dir.
+#[derive(Debug, Default)]
+pub struct ChunkCheck {
+    /// Number of chunks checked.
+    pub checked: usize,
+    /// Chunks whose content doesn't match their address. They're taken out of the store,
+    /// as a node would do when reading them.
+    pub corrupted: Vec<XorName>,
+    /// Chunks which couldn't be read, with the error reading them.
+    pub unreadable: Vec<(XorName, String)>,
+}
+
+/// Space used by the data found in a data dir, as a node accounts for it on start.
+#[derive(Debug, Default)]
+pub struct UsedSpaceReport {
+    /// Number of chunks stored.
+    pub chunk_count: usize,
+    /// Space used by the chunks, in bytes, including what the backend needs besides their
+    /// content, e.g. the record headers, and the garbage not compacted yet, of the packed log.
+    pub chunks_size: usize,
+    /// Number of registers stored.
+    pub register_count: usize,
+    /// Size of the register ops, in bytes, ignoring the extra space used by the db.
+    pub registers_size: usize,
+}
+
+/// Number of entries written to, or read from, a data archive.
+#[derive(Debug, Default)]
+pub struct ArchiveSummary {
+    /// Number of chunks.
+    pub chunks: usize,
+    /// Number of registers.
+    pub registers: usize,
+    /// Number of chunks and registers skipped on import, as they were already stored.
+    pub skipped: usize,
+    /// Chunks skipped on export as they couldn't be read, with the error reading them.
+    /// Corrupted chunks are taken out of the store, as a node would do when reading them.
+    pub unreadable: Vec<(XorName, String)>,
+}
+
+/// Reads every chunk stored in the data dir, checking its content matches its address.
+pub async fn verify_chunks(root_dir: &Path, kind: ChunkStoreKind) -> Result<ChunkCheck> {
+    let store = ChunkStore::new(root_dir, UsedSpace::new(usize::MAX), kind)?;
+
+    let mut check = ChunkCheck::default();
+    for addr in store.keys()? {
+        check.checked += 1;
+        match store.get_chunk(&addr).await {
+            Ok(_) => {}
+            Err(ChunkStoreError::CorruptedChunk(name)) => check.corrupted.push(name),
+            Err(error) => check.unreadable.push((*addr.name(), error.to_string())),
+        }
+    }
+
+    Ok(check)
+}
+
+/// Measures the space used by the chunks and registers stored in the data dir.
+pub async fn measure_used_space(root_dir: &Path, kind: ChunkStoreKind) -> Result<UsedSpaceReport> {
+    let chunks_space = UsedSpace::new(usize::MAX);
+    let chunks = ChunkStore::new(root_dir, chunks_space.clone(), kind)?;
+    let registers_space = UsedSpace::new(usize::MAX);
+    let registers = RegisterStorage::new(root_dir, registers_space.clone())?;
+
+    Ok(UsedSpaceReport {
+        chunk_count: chunks.chunk_count(),
+        chunks_size: chunks_space.used(),
+        register_count: registers.register_count(),
+        registers_size: registers_space.used(),
+    })
+}
+
+/// Writes all the chunks and registers of the data dir to an archive file, which can be imported
+/// into any data dir, whatever its chunk store backend. Chunks which can't be read are skipped
+/// and reported in the summary, so one bad chunk doesn't prevent moving the rest of the data.
+pub async fn export_archive(
+    root_dir: &Path,
+    kind: ChunkStoreKind,
+    archive: &Path,
+) -> Result<ArchiveSummary> {
+    let chunks = ChunkStore::new(root_dir, UsedSpace::new(usize::MAX), kind)?;
+    let registers = RegisterStorage::new(root_dir, UsedSpace::new(usize::MAX))?;
+
+    let mut writer = BufWriter::new(File::create(archive)?);
+    writer.write_all(ARCHIVE_MAGIC)?;
+
+    let mut summary = ArchiveSummary::default();
+    for addr in chunks.keys()? {
+        let chunk = match chunks.get_chunk(&addr).await {
+            Ok(chunk) => chunk,
+            Err(error) => {
+                summary.unreadable.push((*addr.name(), error.to_string()));
+                continue;
+            }
+        };
+        bincode::serialize_into(&mut writer, &ArchiveEntry::Chunk(chunk.value().clone()))?;
+        summary.chunks += 1;
+    }
+
+    let RegisterDataExchange(data) = registers.get_data_of(Prefix::default()).await?;
+    for (key, ops) in data {
+        bincode::serialize_into(&mut writer, &ArchiveEntry::Register { key, ops })?;
+        summary.registers += 1;
+    }
+
+    writer.flush()?;
+    Ok(summary)
+}
+
+/// Stores the chunks and registers of an archive into the data dir, skipping those
+/// already stored. Register ops are only applied if their signatures are valid.
+pub async fn import_archive(
+    root_dir: &Path,
+    kind: ChunkStoreKind,
+    archive: &Path,
+) -> Result<ArchiveSummary> {
+    let chunks = ChunkStore::new(root_dir, UsedSpace::new(usize::MAX), kind)?;
+    let registers = RegisterStorage::new(root_dir, UsedSpace::new(usize::MAX))?;
+
+    let mut reader = BufReader::new(File::open(archive)?);
+    let mut magic = [0; ARCHIVE_MAGIC.len()];
+    reader
+        .read_exact(&mut magic)
+        .map_err(|_| Error::InvalidArchive("file is too short".to_string()))?;
+    if &magic != ARCHIVE_MAGIC {
+        return Err(Error::InvalidArchive(
+            "file is not a node data archive".to_string(),
+        ));
+    }
+
+    let mut summary = ArchiveSummary::default();
+    while !reader.fill_buf()?.is_empty() {
+        let entry: ArchiveEntry = bincode::deserialize_from(&mut reader)
+            .map_err(|error| Error::InvalidArchive(error.to_string()))?;
+        match entry {
+            ArchiveEntry::Chunk(bytes) => {
+                let chunk = Chunk::new(bytes);
+                if chunks.has_chunk(chunk.address())? {
+                    summary.skipped += 1;
+                    continue;
+                }
+                let _level = chunks.store(&chunk).await?;
+                summary.chunks += 1;
+            }
+            ArchiveEntry::Register { key, ops } => {
+                if registers.has_register(&key) {
+                    summary.skipped += 1;
+                    continue;
+                }
+                let data = RegisterDataExchange(std::iter::once((key, ops)).collect());
+                registers.update(data)?;
+                summary.registers += 1;
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Compacts the register db of the data dir, returning its size on disk before and after.
+pub fn compact_register_db(root_dir: &Path) -> Result<(u64, u64)> {
+    Ok(RegisterStorage::compact(root_dir)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::utils::random_bytes;
+    use eyre::Result;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn archive_moves_chunks_between_backends() -> Result<()> {
+        let source = tempdir()?;
+        let store = ChunkStore::new(
+            source.path(),
+            UsedSpace::new(usize::MAX),
+            ChunkStoreKind::Disk,
+        )?;
+        let chunks: Vec<Chunk> = std::iter::repeat_with(|| Chunk::new(random_bytes(100)))
+            .take(5)
+            .collect();
+        for chunk in &chunks {
+            let _level = store.store(chunk).await?;
+        }
+
+        let archive = source.path().join("data.archive");
+        let exported = export_archive(source.path(), ChunkStoreKind::Disk, &archive).await?;
+        assert_eq!(exported.chunks, chunks.len());
+
+        let target = tempdir()?;
+        let imported = import_archive(target.path(), ChunkStoreKind::PackedLog, &archive).await?;
+        assert_eq!(imported.chunks, chunks.len());
+        assert_eq!(imported.skipped, 0);
+
+        // importing again doesn't store anything twice
+        let imported = import_archive(target.path(), ChunkStoreKind::PackedLog, &archive).await?;
+        assert_eq!(imported.chunks, 0);
+        assert_eq!(imported.skipped, chunks.len());
+
+        let check = verify_chunks(target.path(), ChunkStoreKind::PackedLog).await?;
+        assert_eq!(check.checked, chunks.len());
+        assert!(check.corrupted.is_empty() && check.unreadable.is_empty());
+
+        let used = measure_used_space(target.path(), ChunkStoreKind::PackedLog).await?;
+        assert_eq!(used.chunk_count, chunks.len());
+        // the packed log charges every chunk with the header of its record too
+        assert!(used.chunks_size > 100 * chunks.len());
+        assert_eq!(used.register_count, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn export_skips_unreadable_chunks() -> Result<()> {
+        let source = tempdir()?;
+        let store = ChunkStore::new(
+            source.path(),
+            UsedSpace::new(usize::MAX),
+            ChunkStoreKind::Disk,
+        )?;
+        let chunks: Vec<Chunk> = std::iter::repeat_with(|| Chunk::new(random_bytes(100)))
+            .take(3)
+            .collect();
+        for chunk in &chunks {
+            let _level = store.store(chunk).await?;
+        }
+
+        // flip the content of one chunk file as if it had bit-rotted
+        let corrupted = &chunks[0];
+        let filepath = walkdir::WalkDir::new(source.path())
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.into_path())
+            .find(|path| {
+                path.is_file() && std::fs::read(path).ok() == Some(corrupted.value().to_vec())
+            })
+            .expect("chunk file not found");
+        std::fs::write(filepath, random_bytes(100))?;
+
+        let archive = source.path().join("data.archive");
+        let exported = export_archive(source.path(), ChunkStoreKind::Disk, &archive).await?;
+        assert_eq!(exported.chunks, chunks.len() - 1);
+        assert_eq!(exported.unreadable.len(), 1);
+        assert_eq!(exported.unreadable[0].0, *corrupted.name());
+
+        let target = tempdir()?;
+        let imported = import_archive(target.path(), ChunkStoreKind::PackedLog, &archive).await?;
+        assert_eq!(imported.chunks, chunks.len() - 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn import_rejects_other_files() -> Result<()> {
+        let dir = tempdir()?;
+        let archive = dir.path().join("not.archive");
+        std::fs::write(&archive, b"definitely not an archive")?;
+
+        assert!(matches!(
+            import_archive(dir.path(), ChunkStoreKind::Memory, &archive).await,
+            Err(Error::InvalidArchive(_))
+        ));
+
+        Ok(())
+    }
+}
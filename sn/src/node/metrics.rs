@@ -0,0 +1,398 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! HTTP endpoint exporting the node's metrics in the Prometheus text format.
+
+use crate::messaging::system::CpuLoad;
+use crate::node::{
+    routing::{Metrics, Routing},
+    Result,
+};
+
+use hyper::{
+    header::{HeaderValue, CONTENT_TYPE},
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use std::{convert::Infallible, fmt::Display, net::SocketAddr, sync::Arc};
+
+const METRICS_PATH: &str = "/metrics";
+const TEXT_FORMAT: &str = "text/plain; version=0.0.4";
+
+type Network = Arc<Routing>;
+
+/// Starts serving the node's metrics at `/metrics` on the given address.
+///
+/// Returns the address actually bound, which differs from the given one if its port was 0.
+pub(super) fn run_metrics_server(addr: SocketAddr, network: Network) -> Result<SocketAddr> {
+    let make_service = make_service_fn(move |_conn| {
+        let network = network.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle_request(request, network.clone())
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&addr)?.serve(make_service);
+    let local_addr = server.local_addr();
+
+    let _handle = tokio::task::spawn(async move {
+        if let Err(error) = server.await {
+            error!("Metrics server stopped: {:?}", error);
+        }
+    });
+
+    Ok(local_addr)
+}
+
+async fn handle_request(
+    request: Request<Body>,
+    network: Network,
+) -> Result<Response<Body>, Infallible> {
+    if request.method() != Method::GET || request.uri().path() != METRICS_PATH {
+        return Ok(empty_response(StatusCode::NOT_FOUND));
+    }
+
+    match network.metrics().await {
+        Ok(metrics) => {
+            let mut response = Response::new(Body::from(render(&metrics)));
+            let _prev = response
+                .headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static(TEXT_FORMAT));
+            Ok(response)
+        }
+        Err(error) => {
+            error!("Failed to collect node metrics: {:?}", error);
+            Ok(empty_response(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+fn empty_response(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    response
+}
+
+// Renders the metrics in the Prometheus text exposition format.
+fn render(metrics: &Metrics) -> String {
+    let mut out = Exposition::default();
+
+    out.family(
+        "sn_node_msgs_received_total",
+        "counter",
+        "Msgs received from the network, by msg variant.",
+        metrics
+            .incoming_msgs
+            .iter()
+            .map(|(kind, count)| (format!("kind=\"{}\"", kind), count)),
+    );
+    out.family(
+        "sn_node_msgs_sent_total",
+        "counter",
+        "Msgs sent to the network, by msg variant.",
+        metrics
+            .outgoing_msgs
+            .iter()
+            .map(|(kind, count)| (format!("kind=\"{}\"", kind), count)),
+    );
+
+    out.single(
+        "sn_node_used_space_ratio",
+        "Ratio of the node's max capacity currently used.",
+        metrics.used_space_ratio,
+    );
+    out.single(
+        "sn_node_chunks",
+        "Chunks stored by the node.",
+        metrics.chunk_count,
+    );
+    out.single(
+        "sn_node_registers",
+        "Registers stored by the node.",
+        metrics.register_count,
+    );
+    out.family(
+        "sn_node_chunk_cache_lookups_total",
+        "counter",
+        "Chunk queries looked up in the elder's cache of served chunks, by result.",
+        [
+            ("result=\"hit\"", metrics.chunk_cache_hits),
+            ("result=\"miss\"", metrics.chunk_cache_misses),
+        ],
+    );
+    out.single(
+        "sn_node_chunk_cache_chunks",
+        "Chunks in the elder's cache of served chunks.",
+        metrics.chunk_cache_len,
+    );
+
+    out.family(
+        "sn_node_pending_ops",
+        "gauge",
+        "Operations pending against each adult, as tracked by this node while an elder.",
+        metrics
+            .pending_ops
+            .iter()
+            .map(|(name, count)| (format!("adult=\"{:x}\"", name), count)),
+    );
+
+    out.single(
+        "sn_node_regulated_peers",
+        "Peers whose comms are being regulated due to the load they reported.",
+        metrics.regulated_peers.len(),
+    );
+    out.family(
+        "sn_node_regulated_peer_retry_interval_seconds",
+        "gauge",
+        "Initial retry interval towards each regulated peer.",
+        metrics
+            .regulated_peers
+            .iter()
+            .map(|(addr, interval)| (format!("peer=\"{}\"", addr), interval.as_secs_f64())),
+    );
+    if let Some(report) = &metrics.load_report {
+        let terms = [
+            ("short", &report.short_term),
+            ("mid", &report.mid_term),
+            ("long", &report.long_term),
+        ];
+        out.family(
+            "sn_node_cpu_load",
+            "gauge",
+            "Levels of the latest cpu load report this node evaluated for its callers.",
+            terms.iter().flat_map(|(term, load)| {
+                load_levels(load).into_iter().map(move |(level, set)| {
+                    (
+                        format!("term=\"{}\",level=\"{}\"", term, level),
+                        u8::from(set),
+                    )
+                })
+            }),
+        );
+    }
+
+    out.single(
+        "sn_node_is_elder",
+        "Whether the node is an elder of its section.",
+        u8::from(metrics.is_elder),
+    );
+    out.single(
+        "sn_node_section_elders",
+        "Elders of the node's section.",
+        metrics.elder_count,
+    );
+    out.single(
+        "sn_node_section_adults",
+        "Adults of the node's section.",
+        metrics.adult_count,
+    );
+    out.family(
+        "sn_node_section_prefix",
+        "gauge",
+        "Prefix of the node's section, as a label.",
+        std::iter::once((format!("prefix=\"{:b}\"", metrics.prefix), 1)),
+    );
+    out.single(
+        "sn_node_section_prefix_bit_count",
+        "Length of the prefix of the node's section.",
+        metrics.prefix.bit_count(),
+    );
+
+    out.family(
+        "sn_node_dkg_sessions",
+        "gauge",
+        "DKG sessions the node participates in, by state.",
+        [
+            ("in_progress", metrics.dkg_sessions_in_progress),
+            ("complete", metrics.dkg_sessions_complete),
+        ]
+        .iter()
+        .map(|(state, count)| (format!("state=\"{}\"", state), *count)),
+    );
+    out.single(
+        "sn_node_dkg_sessions_known",
+        "DKG sessions the node has been notified of, as participant or observer.",
+        metrics.dkg_sessions_known,
+    );
+
+    out.0
+}
+
+fn load_levels(load: &CpuLoad) -> [(&'static str, bool); 5] {
+    [
+        ("low", load.low),
+        ("moderate", load.moderate),
+        ("high", load.high),
+        ("very_high", load.very_high),
+        ("critical", load.critical),
+    ]
+}
+
+#[derive(Default)]
+struct Exposition(String);
+
+impl Exposition {
+    // Writes a metric family, with one sample per set of labels.
+    fn family<L: Display, V: Display>(
+        &mut self,
+        name: &str,
+        kind: &str,
+        help: &str,
+        samples: impl IntoIterator<Item = (L, V)>,
+    ) {
+        self.header(name, kind, help);
+        for (labels, value) in samples {
+            self.0
+                .push_str(&format!("{}{{{}}} {}\n", name, labels, value));
+        }
+    }
+
+    // Writes a gauge with a single, unlabelled, sample.
+    fn single<V: Display>(&mut self, name: &str, help: &str, value: V) {
+        self.header(name, "gauge", help);
+        self.0.push_str(&format!("{} {}\n", name, value));
+    }
+
+    fn header(&mut self, name: &str, kind: &str, help: &str) {
+        self.0.push_str(&format!("# HELP {} {}\n", name, help));
+        self.0.push_str(&format!("# TYPE {} {}\n", name, kind));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+
+    use crate::messaging::system::{CpuLoad, LoadReport};
+    use crate::node::routing::{Metrics, Prefix, XorName};
+
+    use std::{collections::BTreeMap, time::Duration};
+
+    #[test]
+    fn metrics_are_rendered_in_prometheus_text_format() {
+        let adult = XorName::random();
+        let peer = "127.0.0.1:12000".parse().unwrap();
+        let load = CpuLoad {
+            low: false,
+            moderate: false,
+            high: true,
+            very_high: false,
+            critical: false,
+        };
+
+        let metrics = Metrics {
+            incoming_msgs: vec![("join_request", 7), ("service_query", 3)]
+                .into_iter()
+                .collect(),
+            outgoing_msgs: vec![("join_response", 5)].into_iter().collect(),
+            used_space_ratio: 0.25,
+            chunk_count: 12,
+            register_count: 2,
+            chunk_cache_hits: 9,
+            chunk_cache_misses: 3,
+            chunk_cache_len: 4,
+            pending_ops: vec![(adult, 4)].into_iter().collect(),
+            regulated_peers: vec![(peer, Duration::from_millis(1500))]
+                .into_iter()
+                .collect(),
+            load_report: Some(LoadReport {
+                short_term: load,
+                mid_term: load,
+                long_term: load,
+            }),
+            is_elder: true,
+            elder_count: 5,
+            adult_count: 9,
+            prefix: Prefix::default().pushed(true).pushed(false),
+            dkg_sessions_in_progress: 1,
+            dkg_sessions_complete: 2,
+            dkg_sessions_known: 3,
+        };
+
+        let text = render(&metrics);
+        let lines: Vec<_> = text.lines().collect();
+
+        for expected in [
+            "# TYPE sn_node_msgs_received_total counter",
+            "sn_node_msgs_received_total{kind=\"join_request\"} 7",
+            "sn_node_msgs_received_total{kind=\"service_query\"} 3",
+            "sn_node_msgs_sent_total{kind=\"join_response\"} 5",
+            "sn_node_used_space_ratio 0.25",
+            "sn_node_chunks 12",
+            "sn_node_registers 2",
+            &format!("sn_node_pending_ops{{adult=\"{:x}\"}} 4", adult),
+            "sn_node_regulated_peers 1",
+            "sn_node_regulated_peer_retry_interval_seconds{peer=\"127.0.0.1:12000\"} 1.5",
+            "sn_node_cpu_load{term=\"mid\",level=\"high\"} 1",
+            "sn_node_cpu_load{term=\"long\",level=\"critical\"} 0",
+            "sn_node_is_elder 1",
+            "sn_node_section_elders 5",
+            "sn_node_section_adults 9",
+            "sn_node_section_prefix{prefix=\"10\"} 1",
+            "sn_node_section_prefix_bit_count 2",
+            "sn_node_dkg_sessions{state=\"in_progress\"} 1",
+            "sn_node_dkg_sessions{state=\"complete\"} 2",
+            "sn_node_dkg_sessions_known 3",
+            "# TYPE sn_node_chunk_cache_lookups_total counter",
+            "sn_node_chunk_cache_lookups_total{result=\"hit\"} 9",
+            "sn_node_chunk_cache_lookups_total{result=\"miss\"} 3",
+            "sn_node_chunk_cache_chunks 4",
+        ] {
+            assert!(
+                lines.contains(&expected),
+                "missing `{}` in:\n{}",
+                expected,
+                text
+            );
+        }
+
+        // every sample is preceded by its family's HELP and TYPE
+        let families = lines
+            .iter()
+            .filter(|line| line.starts_with("# TYPE"))
+            .count();
+        let helps = lines
+            .iter()
+            .filter(|line| line.starts_with("# HELP"))
+            .count();
+        assert_eq!(families, helps);
+        assert!(text.ends_with('\n'));
+    }
+
+    #[test]
+    fn empty_families_only_render_their_header() {
+        let metrics = Metrics {
+            incoming_msgs: BTreeMap::new(),
+            outgoing_msgs: BTreeMap::new(),
+            used_space_ratio: 0.0,
+            chunk_count: 0,
+            register_count: 0,
+            chunk_cache_hits: 0,
+            chunk_cache_misses: 0,
+            chunk_cache_len: 0,
+            pending_ops: BTreeMap::new(),
+            regulated_peers: BTreeMap::new(),
+            load_report: None,
+            is_elder: false,
+            elder_count: 1,
+            adult_count: 0,
+            prefix: Prefix::default(),
+            dkg_sessions_in_progress: 0,
+            dkg_sessions_complete: 0,
+            dkg_sessions_known: 0,
+        };
+
+        let text = render(&metrics);
+
+        assert!(!text.contains("sn_node_msgs_received_total{"));
+        assert!(!text.contains("sn_node_cpu_load"));
+        assert!(text.contains("sn_node_section_prefix{prefix=\"\"} 1\n"));
+    }
+}
@@ -8,18 +8,29 @@
 
 //! Implementation of the "Node" node for the SAFE Network.
 
+/// Admin interface
+pub mod admin;
 /// Configuration
 pub mod cfg;
+/// Offline maintenance of a node's data
+pub mod maintenance;
 /// Routing
 pub mod routing;
 
 mod api;
 mod error;
 mod logging;
+mod metrics;
+#[cfg(any(test, feature = "test-utils"))]
+mod test_network;
 
 pub use crate::node::{
     api::Node,
     cfg::{config_handler, keypair_storage},
     config_handler::{add_connection_info, set_connection_info, Config},
     error::{Error, Result},
+    routing::ChunkStoreKind,
 };
+
+#[cfg(any(test, feature = "test-utils"))]
+pub use crate::node::test_network::{TestNetwork, TestNetworkBuilder};
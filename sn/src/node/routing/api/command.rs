@@ -107,6 +107,8 @@ pub(crate) enum Command {
     StartConnectivityTest(XorName),
     /// Test Connectivity
     TestConnectivity(XorName),
+    /// Leave our section voluntarily, handing off our chunks
+    LeaveSection,
 }
 
 impl Command {
@@ -171,6 +173,7 @@ impl fmt::Display for Command {
             Command::ProposeOffline(_) => write!(f, "ProposeOffline"),
             Command::StartConnectivityTest(_) => write!(f, "StartConnectivityTest"),
             Command::TestConnectivity(_) => write!(f, "TestConnectivity"),
+            Command::LeaveSection => write!(f, "LeaveSection"),
         }
     }
 }
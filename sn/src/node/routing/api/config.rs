@@ -6,7 +6,9 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::routing::NetworkConfig;
+#[cfg(any(test, feature = "test-utils"))]
+use crate::node::routing::SimulatedNetwork;
+use crate::node::routing::{ChunkStoreKind, NetworkConfig};
 
 use ed25519_dalek::Keypair;
 use std::{
@@ -30,6 +32,11 @@ pub struct Config {
     pub genesis_key: Option<String>,
     /// Configuration for the underlying network transport.
     pub network_config: NetworkConfig,
+    /// Kind of backend to store chunks in.
+    pub chunk_store_kind: ChunkStoreKind,
+    /// In-memory network to use instead of QUIC, in which case `network_config` is ignored.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub simulated_network: Option<SimulatedNetwork>,
 }
 
 impl Default for Config {
@@ -41,6 +48,9 @@ impl Default for Config {
             bootstrap_nodes: BTreeSet::new(),
             genesis_key: None,
             network_config: NetworkConfig::default(),
+            chunk_store_kind: ChunkStoreKind::default(),
+            #[cfg(any(test, feature = "test-utils"))]
+            simulated_network: None,
         }
     }
 }
@@ -19,7 +19,7 @@ use crate::node::routing::core::{Core, Proposal, SendStatus};
 use crate::node::{Error, Result};
 use crate::peer::Peer;
 use crate::types::log_markers::LogMarker;
-use futures::future::BoxFuture;
+use futures::future::{join_all, BoxFuture};
 use futures::FutureExt;
 use std::collections::BTreeMap;
 use std::{sync::Arc, time::Duration};
@@ -31,6 +31,8 @@ use tokio::{
 use tracing::Instrument;
 
 const PROBE_INTERVAL: Duration = Duration::from_secs(30);
+const CHUNK_SCRUB_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const CHUNK_AUDIT_INTERVAL: Duration = Duration::from_secs(5 * 60);
 
 // this doesn't realistically limit concurrency
 // the prioritisation will do that, preventing lower prio messages being kicked off when
@@ -87,6 +89,26 @@ impl Dispatcher {
         }
     }
 
+    // Stop the node, cancelling all the scheduled timers and periodic tasks,
+    // and closing all its connections.
+    pub(super) fn terminate(&self) {
+        let _res = self.cancel_timer_tx.send(true);
+        self.core.comm.close();
+    }
+
+    // Await the next tick of a periodic task, or `None` if the node has been terminated.
+    async fn next_tick(&self, interval: &mut time::Interval) -> Option<time::Instant> {
+        let mut cancel_rx = self.cancel_timer_rx.clone();
+        if *cancel_rx.borrow() {
+            return None;
+        }
+
+        tokio::select! {
+            instant = interval.tick() => Some(instant),
+            _ = cancel_rx.changed() => None,
+        }
+    }
+
     /// block progress until there are no tasks pending in this semaphore
     /// intended to allow us to wait for super high priority tasks before doing others...
     /// It should only be used after checking that no permits are held by a root cmd eg
@@ -344,7 +366,8 @@ impl Dispatcher {
                                 .enqueue_and_handle_next_command_and_any_offshoots(
                                     command,
                                     Some(sub_cmd_id),
-                                );
+                                )
+                                .await;
                         }
                     }
                     Err(err) => {
@@ -367,6 +390,30 @@ impl Dispatcher {
         .boxed()
     }
 
+    /// Handles command and all the commands transitively produced during its handling,
+    /// resolving only once all of them have been handled, e.g. once all resulting msgs are sent.
+    /// Failures of the sub-commands are logged but don't abort the rest.
+    pub(super) fn handle_command_to_completion(
+        self: Arc<Self>,
+        command: Command,
+    ) -> BoxFuture<'static, Result<()>> {
+        async move {
+            let cmd_id = rand::random::<u32>().to_string();
+            let commands = self.process_command(command, &cmd_id).await?;
+            let results = join_all(
+                commands
+                    .into_iter()
+                    .map(|command| self.clone().handle_command_to_completion(command)),
+            )
+            .await;
+            for error in results.into_iter().filter_map(|result| result.err()) {
+                error!("Failed to handle sub-command of {:?}: {:?}", cmd_id, error);
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+
     pub(super) async fn start_network_probing(self: Arc<Self>) {
         info!("Starting to probe network");
         let _handle = tokio::spawn(async move {
@@ -374,9 +421,7 @@ impl Dispatcher {
             let mut interval = tokio::time::interval(PROBE_INTERVAL);
             interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
 
-            loop {
-                let _instant = interval.tick().await;
-
+            while dispatcher.next_tick(&mut interval).await.is_some() {
                 // Send a probe message if we are an elder
                 let core = &dispatcher.core;
                 if core.is_elder().await && !core.network_knowledge().prefix().await.is_empty() {
@@ -398,6 +443,67 @@ impl Dispatcher {
         });
     }
 
+    /// Periodically re-hashes all the chunks held while we are an adult,
+    /// re-fetching those found corrupted.
+    pub(super) async fn start_chunk_scrubbing(self: Arc<Self>) {
+        info!("Starting to scrub chunks");
+        let _handle = tokio::spawn(async move {
+            let dispatcher = self.clone();
+            let mut interval = time::interval(CHUNK_SCRUB_INTERVAL);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            // The first tick completes immediately, don't scrub right at startup.
+            let _instant = interval.tick().await;
+
+            while dispatcher.next_tick(&mut interval).await.is_some() {
+                if dispatcher.core.is_elder().await {
+                    continue;
+                }
+                match dispatcher.core.scrub_chunks().await {
+                    Ok(commands) => dispatcher.clone().handle_commands(commands).await,
+                    Err(error) => error!("Problem scrubbing chunks: {:?}", error),
+                }
+            }
+        });
+    }
+
+    /// Periodically audits the chunk copies held by the adults of our section while we are an elder.
+    pub(super) async fn start_chunk_auditing(self: Arc<Self>) {
+        info!("Starting to audit chunks");
+        let _handle = tokio::spawn(async move {
+            let dispatcher = self.clone();
+            let mut interval = time::interval(CHUNK_AUDIT_INTERVAL);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+            while dispatcher.next_tick(&mut interval).await.is_some() {
+                if !dispatcher.core.is_elder().await {
+                    continue;
+                }
+                match dispatcher.core.start_chunk_audit().await {
+                    Ok(commands) => dispatcher.clone().handle_commands(commands).await,
+                    Err(error) => error!("Problem starting chunk audit: {:?}", error),
+                }
+            }
+        });
+    }
+
+    /// Reports the level of storage found already used at startup to our elders.
+    pub(super) async fn report_initial_storage_level(self: Arc<Self>) {
+        let commands = self.core.report_initial_storage_level().await;
+        self.handle_commands(commands).await
+    }
+
+    async fn handle_commands(self: Arc<Self>, commands: Vec<Command>) {
+        for command in commands {
+            if let Err(e) = self
+                .clone()
+                .enqueue_and_handle_next_command_and_any_offshoots(command, None)
+                .await
+            {
+                error!("Error handling command: {:?}", e);
+            }
+        }
+    }
+
     pub(super) async fn write_prefixmap_to_disk(self: Arc<Self>) {
         info!("Writing our PrefixMap to disk");
         self.clone().core.write_prefix_map().await
@@ -602,6 +708,7 @@ impl Dispatcher {
                 }
                 Ok(commands)
             }
+            Command::LeaveSection => self.core.leave_section().await,
         }
     }
 
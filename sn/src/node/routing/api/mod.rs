@@ -24,18 +24,23 @@ use self::{
     event_stream::EventStream,
 };
 
-use crate::messaging::{system::SystemMsg, DstLocation, WireMsg};
+use crate::messaging::{
+    data::StorageLevel,
+    system::{DkgSessionId, SystemMsg},
+    DstLocation, WireMsg,
+};
 use crate::node::{
     error::{Error, Result},
     routing::{
-        core::{join_network, Comm, ConnectionEvent, Core},
+        core::{join_network, Comm, ConnectionEvent, Core, Metrics},
         ed25519,
         messages::WireMsgUtils,
-        network_knowledge::SectionAuthorityProvider,
+        network_knowledge::{ElderCandidates, SectionAuthorityProvider},
         node::Node,
         Peer, MIN_ADULT_AGE,
     },
 };
+use crate::peer::UnnamedPeer;
 use crate::types::{log_markers::LogMarker, PublicKey as TypesPublicKey};
 use crate::UsedSpace;
 
@@ -43,8 +48,16 @@ use ed25519_dalek::{PublicKey, Signature, Signer, KEYPAIR_LENGTH};
 use itertools::Itertools;
 use secured_linked_list::SecuredLinkedList;
 use std::path::PathBuf;
-use std::{collections::BTreeSet, net::SocketAddr, sync::Arc};
-use tokio::{sync::mpsc, task};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::SocketAddr,
+    sync::Arc,
+};
+use tokio::{
+    sync::mpsc,
+    task,
+    time::{sleep, Duration},
+};
 use xor_name::{Prefix, XorName};
 
 /// Interface for sending and receiving messages to and from other nodes, in the role of a full
@@ -61,6 +74,9 @@ pub struct Routing {
 
 static EVENT_CHANNEL_SIZE: usize = 20;
 
+// How often to check whether our elders agreed on us leaving.
+const LEAVE_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
 impl Routing {
     ////////////////////////////////////////////////////////////////////////////
     // Public API
@@ -72,7 +88,7 @@ impl Routing {
     /// lost in transit during bootstrapping, or other reasons. It's the responsibility of the
     /// caller to handle this case, for example by using a timeout.
     pub async fn new(
-        config: Config,
+        mut config: Config,
         used_space: UsedSpace,
         root_storage_dir: PathBuf,
     ) -> Result<(Self, EventStream)> {
@@ -90,12 +106,7 @@ impl Routing {
                 std::process::id()
             );
 
-            let comm = Comm::new(
-                config.local_addr,
-                config.network_config,
-                connection_event_tx,
-            )
-            .await?;
+            let comm = new_comm(&config, connection_event_tx).await?;
             let node = Node::new(keypair, comm.our_connection_info());
 
             let genesis_sk_set = bls::SecretKeySet::random(0, &mut rand::thread_rng());
@@ -105,6 +116,7 @@ impl Routing {
                 event_tx,
                 used_space.clone(),
                 root_storage_dir.clone(),
+                config.chunk_store_kind,
                 genesis_sk_set,
             )
             .await?;
@@ -136,10 +148,10 @@ impl Routing {
 
             core
         } else {
-            let genesis_key_str = config.genesis_key.ok_or_else(|| {
+            let genesis_key_str = config.genesis_key.as_ref().ok_or_else(|| {
                 Error::Configuration("Network's genesis key was not provided.".to_string())
             })?;
-            let genesis_key = TypesPublicKey::bls_from_hex(&genesis_key_str)?
+            let genesis_key = TypesPublicKey::bls_from_hex(genesis_key_str)?
                 .bls()
                 .ok_or_else(|| {
                     Error::Configuration(
@@ -147,24 +159,13 @@ impl Routing {
                     )
                 })?;
 
-            let keypair = config.keypair.unwrap_or_else(|| {
+            let keypair = config.keypair.take().unwrap_or_else(|| {
                 ed25519::gen_keypair(&Prefix::default().range_inclusive(), MIN_ADULT_AGE)
             });
             let node_name = ed25519::name(&keypair.public);
             info!("{} Bootstrapping a new node.", node_name);
 
-            let (comm, bootstrap_peer) = Comm::bootstrap(
-                config.local_addr,
-                config
-                    .bootstrap_nodes
-                    .iter()
-                    .copied()
-                    .collect_vec()
-                    .as_slice(),
-                config.network_config,
-                connection_event_tx,
-            )
-            .await?;
+            let (comm, bootstrap_peer) = bootstrap_comm(&config, connection_event_tx).await?;
             info!(
                 "{} Joining as a new node (PID: {}) our socket: {}, bootstrapper was: {}, network's genesis key: {:?}",
                 node_name,
@@ -192,6 +193,7 @@ impl Routing {
                 event_tx,
                 used_space.clone(),
                 root_storage_dir.to_path_buf(),
+                config.chunk_store_kind,
             )
             .await?;
             info!("{} Joined the network!", core.node.read().await.name());
@@ -210,6 +212,9 @@ impl Routing {
         ));
 
         dispatcher.clone().start_network_probing().await;
+        dispatcher.clone().start_chunk_scrubbing().await;
+        dispatcher.clone().start_chunk_auditing().await;
+        dispatcher.clone().report_initial_storage_level().await;
         dispatcher.clone().write_prefixmap_to_disk().await;
 
         let routing = Self { dispatcher };
@@ -217,6 +222,38 @@ impl Routing {
         Ok((routing, event_stream))
     }
 
+    /// Stops the node, closing all its connections and cancelling all its periodic tasks.
+    pub fn terminate(&self) {
+        self.dispatcher.terminate()
+    }
+
+    /// Leaves the network voluntarily: asks our elders to take us `Offline` and pushes our
+    /// chunks to the adults that will hold them once we're gone. Resolves once our elders agreed
+    /// on us being offline, after which the node can be terminated.
+    ///
+    /// This never resolves if our elders don't reach that agreement, so callers should bound it
+    /// with a timeout.
+    pub async fn leave(&self) -> Result<()> {
+        self.dispatcher
+            .clone()
+            .handle_command_to_completion(Command::LeaveSection)
+            .await?;
+
+        let name = self.name().await;
+        while self
+            .dispatcher
+            .core
+            .network_knowledge()
+            .members()
+            .is_joined(&name)
+        {
+            sleep(LEAVE_CHECK_INTERVAL).await;
+        }
+        info!("Our elders agreed on us leaving the section");
+
+        Ok(())
+    }
+
     /// Sets the JoinsAllowed flag.
     pub async fn set_joins_allowed(&self, joins_allowed: bool) -> Result<()> {
         let command = Command::SetJoinsAllowed(joins_allowed);
@@ -404,6 +441,26 @@ impl Routing {
             .await
     }
 
+    /// Returns the sections this node knows about, from its prefix map.
+    pub(crate) fn known_sections(&self) -> Vec<SectionAuthorityProvider> {
+        self.dispatcher.core.network_knowledge().prefix_map().all()
+    }
+
+    /// Returns the storage levels reported by the adults of our section, if we're an elder.
+    pub(crate) async fn storage_levels(&self) -> BTreeMap<XorName, StorageLevel> {
+        self.dispatcher.core.storage_levels().await
+    }
+
+    /// Returns the DKG sessions this node is participating in which haven't completed yet.
+    pub(crate) fn pending_dkg_sessions(&self) -> Vec<(DkgSessionId, ElderCandidates)> {
+        self.dispatcher.core.pending_dkg_sessions()
+    }
+
+    /// Returns a snapshot of this node's state, for the metrics endpoint.
+    pub(crate) async fn metrics(&self) -> Result<Metrics> {
+        self.dispatcher.core.metrics().await
+    }
+
     /// Returns the current BLS public key set if this node has one, or
     /// `Error::MissingSecretKeyShare` otherwise.
     pub async fn public_key_set(&self) -> Result<bls::PublicKeySet> {
@@ -468,3 +525,34 @@ async fn handle_connection_events(
 
     error!("Fatal error, the stream for incoming connections has been unexpectedly closed. No new connections or messages can be received from the network from here on.");
 }
+
+// Creates the comm of the genesis node, over the simulated network if one is configured.
+async fn new_comm(config: &Config, event_tx: mpsc::Sender<ConnectionEvent>) -> Result<Comm> {
+    #[cfg(any(test, feature = "test-utils"))]
+    if let Some(network) = &config.simulated_network {
+        return Comm::new_simulated(network, config.local_addr, event_tx);
+    }
+
+    Comm::new(config.local_addr, config.network_config.clone(), event_tx).await
+}
+
+// Creates the comm of a joining node, over the simulated network if one is configured.
+async fn bootstrap_comm(
+    config: &Config,
+    event_tx: mpsc::Sender<ConnectionEvent>,
+) -> Result<(Comm, UnnamedPeer)> {
+    let bootstrap_nodes = config.bootstrap_nodes.iter().copied().collect_vec();
+
+    #[cfg(any(test, feature = "test-utils"))]
+    if let Some(network) = &config.simulated_network {
+        return Comm::bootstrap_simulated(network, config.local_addr, &bootstrap_nodes, event_tx);
+    }
+
+    Comm::bootstrap(
+        config.local_addr,
+        &bootstrap_nodes,
+        config.network_config.clone(),
+        event_tx,
+    )
+    .await
+}
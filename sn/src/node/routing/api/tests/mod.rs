@@ -9,21 +9,24 @@
 #![allow(dead_code, unused_imports)]
 
 use super::{Comm, Command, Dispatcher};
+use crate::client::Session;
 use crate::dbs::UsedSpace;
 use crate::messaging::{
+    data::{DataQuery, ServiceMsg},
     system::{
-        JoinAsRelocatedRequest, JoinRequest, JoinResponse, KeyedSig, MembershipState,
-        NodeState as NodeStateMsg, RelocateDetails, RelocatePayload, ResourceProofResponse,
-        SectionAuth, SystemMsg,
+        CpuLoad, JoinAsRelocatedRequest, JoinRequest, JoinResponse, KeyedSig, LoadReport,
+        MembershipState, NodeQueryResponse, NodeState as NodeStateMsg, Proposal as ProposalMsg,
+        RelocateDetails, RelocatePayload, ResourceProofResponse, SectionAuth, SystemMsg,
     },
-    AuthorityProof, DstLocation, MessageId, MessageType, MsgKind, NodeAuth,
-    SectionAuth as MsgKindSectionAuth, WireMsg,
+    AuthorityProof, DstLocation, EndUser, MessageId, MessageType, MsgKind, NodeAuth,
+    SectionAuth as MsgKindSectionAuth, ServiceAuth, WireMsg,
 };
 use crate::node::{
     error::{Error, Result as RoutingResult},
     routing::{
         core::{
-            ConnectionEvent, Core, Proposal, RESOURCE_PROOF_DATA_SIZE, RESOURCE_PROOF_DIFFICULTY,
+            ChunkStoreKind, ConnectionEvent, Core, Proposal, RESOURCE_PROOF_DATA_SIZE,
+            RESOURCE_PROOF_DIFFICULTY,
         },
         create_test_max_capacity_and_root_storage,
         dkg::test_utils::{prove, section_signed},
@@ -35,13 +38,14 @@ use crate::node::{
         node::Node,
         recommended_section_size,
         relocation::{self, RelocatePayloadUtils},
-        supermajority, Event, Peer, FIRST_SECTION_MAX_AGE, FIRST_SECTION_MIN_AGE, MIN_ADULT_AGE,
-        MIN_AGE,
+        supermajority, Event, LinkRule, Peer, SimulatedNetwork, FIRST_SECTION_MAX_AGE,
+        FIRST_SECTION_MIN_AGE, MIN_ADULT_AGE, MIN_AGE,
     },
 };
 use crate::peer::UnnamedPeer;
-use crate::types::{Keypair, PublicKey};
-use crate::{elder_count, init_test_logger};
+use crate::prefix_map::NetworkPrefixMap;
+use crate::types::{utils::random_bytes, Chunk, ChunkAddress, Keypair, PublicKey};
+use crate::{chunk_copy_count, elder_count, init_test_logger};
 
 use assert_matches::assert_matches;
 use bls_dkg::message::Message;
@@ -86,6 +90,7 @@ async fn receive_join_request_without_resource_proof_response() -> Result<()> {
         mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreKind::Memory,
     )
     .await?;
     let dispatcher = Dispatcher::new(core);
@@ -166,6 +171,7 @@ async fn receive_join_request_with_resource_proof_response() -> Result<()> {
         mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreKind::Memory,
     )
     .await?;
     let dispatcher = Dispatcher::new(core);
@@ -263,6 +269,7 @@ async fn receive_join_request_from_relocated_node() -> Result<()> {
         mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreKind::Memory,
     )
     .await?;
     let dispatcher = Dispatcher::new(core);
@@ -363,6 +370,7 @@ async fn handle_agreement_on_online() -> Result<()> {
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreKind::Memory,
     )
     .await?;
     let dispatcher = Dispatcher::new(core);
@@ -425,6 +433,7 @@ async fn handle_agreement_on_online_of_elder_candidate() -> Result<()> {
         mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreKind::Memory,
     )
     .await?;
     let dispatcher = Dispatcher::new(core);
@@ -595,6 +604,7 @@ async fn handle_agreement_on_online_of_rejoined_node(phase: NetworkPhase, age: u
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreKind::Memory,
     )
     .await?;
     let dispatcher = Dispatcher::new(state);
@@ -664,6 +674,7 @@ async fn handle_agreement_on_offline_of_non_elder() -> Result<()> {
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreKind::Memory,
     )
     .await?;
     let dispatcher = Dispatcher::new(core);
@@ -720,6 +731,7 @@ async fn handle_agreement_on_offline_of_elder() -> Result<()> {
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreKind::Memory,
     )
     .await?;
     let dispatcher = Dispatcher::new(core);
@@ -797,6 +809,73 @@ enum UntrustedMessageSource {
     Accumulation,
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn receive_leave_request_from_member() -> Result<()> {
+    let (section_auth, mut nodes, sk_set) = create_section_auth();
+    let section_key = sk_set.public_keys().public_key();
+    let (section, section_key_share) = create_section(&sk_set, &section_auth).await?;
+
+    let leaving_node = create_node(MIN_ADULT_AGE, None);
+    let node_state = NodeState::joined(leaving_node.peer(), None);
+    let node_state = section_signed(sk_set.secret_key(), node_state)?;
+    let _updated = section.update_member(node_state).await;
+
+    let node = nodes.remove(0);
+    let (max_capacity, root_storage_dir) = create_test_max_capacity_and_root_storage()?;
+    let core = Core::new(
+        create_comm().await?,
+        node,
+        section,
+        Some(section_key_share),
+        mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
+        UsedSpace::new(max_capacity),
+        root_storage_dir,
+        ChunkStoreKind::Memory,
+    )
+    .await?;
+    let dispatcher = Dispatcher::new(core);
+
+    let wire_msg = WireMsg::single_src(
+        &leaving_node,
+        DstLocation::Section {
+            name: section_auth.prefix().name(),
+            section_pk: section_key,
+        },
+        SystemMsg::LeaveRequest,
+        section_key,
+    )?;
+
+    let commands = get_internal_commands(
+        Command::HandleMessage {
+            sender: UnnamedPeer::addressed(leaving_node.addr),
+            wire_msg,
+            original_bytes: None,
+        },
+        &dispatcher,
+    )
+    .await?;
+
+    // The elder proposes the leaving node offline to the rest of the elders.
+    let proposed_offline = commands.into_iter().any(|command| match command {
+        Command::SendMessage { wire_msg, .. }
+        | Command::SendMessageDeliveryGroup { wire_msg, .. } => matches!(
+            wire_msg.into_message(),
+            Ok(MessageType::System {
+                msg: SystemMsg::Propose {
+                    proposal: ProposalMsg::Offline(node_state),
+                    ..
+                },
+                ..
+            }) if node_state.name == leaving_node.name()
+                && node_state.state == MembershipState::Left
+        ),
+        _ => false,
+    });
+    assert!(proposed_offline);
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 // Checking when we get AE info that is ahead of us we should handle it.
 async fn ae_msg_from_the_future_is_handled() -> Result<()> {
@@ -830,6 +909,7 @@ async fn ae_msg_from_the_future_is_handled() -> Result<()> {
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreKind::Memory,
     )
     .await?;
 
@@ -942,6 +1022,7 @@ async fn untrusted_ae_message_msg_errors() -> Result<()> {
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreKind::Memory,
     )
     .await?;
 
@@ -978,6 +1059,68 @@ async fn untrusted_ae_message_msg_errors() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn chunk_dropped_during_churn_is_still_served() -> Result<()> {
+    init_test_logger();
+    let _span = tracing::info_span!("chunk_dropped_during_churn_is_still_served").entered();
+
+    let (section_auth, _elders, sk_set) = create_section_auth();
+    let (section, _section_key_share) = create_section(&sk_set, &section_auth).await?;
+    let (max_capacity, root_storage_dir) = create_test_max_capacity_and_root_storage()?;
+    let core = Core::new(
+        create_comm().await?,
+        create_node(MIN_ADULT_AGE, None),
+        section,
+        None,
+        mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
+        UsedSpace::new(max_capacity),
+        root_storage_dir,
+        ChunkStoreKind::Memory,
+    )
+    .await?;
+
+    let chunk = Chunk::new(random_bytes(100));
+    let _level = core.chunk_storage.store(&chunk).await?;
+
+    // adults joining closer to the chunk than us take over holding it
+    let new_adults: BTreeSet<_> = (0..chunk_copy_count())
+        .map(|index| {
+            let mut name = *chunk.name();
+            name.0[31] ^= index as u8 + 1;
+            name
+        })
+        .collect();
+    let our_name = core.node.read().await.name();
+    let commands = core
+        .reorganize_chunks(our_name, new_adults, BTreeSet::new(), BTreeSet::new())
+        .await?;
+    assert_eq!(commands.len(), chunk_copy_count());
+    assert!(!core.chunk_storage.has_chunk(chunk.address())?);
+
+    // an elder not aware of the new holders yet still gets the chunk from us
+    let elder = section_auth.elders().next().expect("no elders").name();
+    let commands = core
+        .handle_get_chunk_at_adult(
+            MessageId::new(),
+            chunk.address(),
+            EndUser(XorName::random()),
+            elder,
+        )
+        .await?;
+    assert_matches!(
+        &commands[..],
+        [Command::PrepareNodeMsgToSend {
+            msg: SystemMsg::NodeQueryResponse {
+                response: NodeQueryResponse::GetChunk(Ok(served)),
+                ..
+            },
+            dst: DstLocation::Node { name, .. },
+        }] if served == &chunk && name == &elder
+    );
+
+    Ok(())
+}
+
 /// helper to get through first command layers used for concurrency, to commands we can analyse in a useful fashion for testing
 async fn get_internal_commands(
     command: Command,
@@ -1049,6 +1192,7 @@ async fn relocation(relocated_peer_role: RelocatedPeerRole) -> Result<()> {
         mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreKind::Memory,
     )
     .await?;
     let dispatcher = Dispatcher::new(core);
@@ -1121,6 +1265,81 @@ async fn relocation(relocated_peer_role: RelocatedPeerRole) -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(start_paused = true)]
+async fn relocation_is_delivered_over_a_simulated_network() -> Result<()> {
+    let network = SimulatedNetwork::new(0);
+    network.add_rule(LinkRule::all().delay(Duration::from_millis(1), Duration::from_millis(50)));
+
+    let prefix: Prefix = "0".parse().unwrap();
+    let (section_auth, mut nodes, sk_set) = gen_section_authority_provider(prefix, elder_count());
+    let (section, section_key_share) = create_section(&sk_set, &section_auth).await?;
+
+    for _ in elder_count()..recommended_section_size() {
+        let node_state = NodeState::joined(create_peer(MIN_ADULT_AGE), None);
+        let node_state = section_signed(sk_set.secret_key(), node_state)?;
+        assert!(section.update_member(node_state).await);
+    }
+
+    let relocated_peer = create_peer(MIN_AGE);
+    let node_state = NodeState::joined(relocated_peer.clone(), None);
+    let node_state = section_signed(sk_set.secret_key(), node_state)?;
+    assert!(section.update_member(node_state).await);
+
+    let node = nodes.remove(0);
+    let (comm_tx, _comm_rx) = mpsc::channel(TEST_EVENT_CHANNEL_SIZE);
+    let comm = Comm::new_simulated(&network, node.addr, comm_tx)?;
+    let (relocated_tx, mut relocated_rx) = mpsc::channel(TEST_EVENT_CHANNEL_SIZE);
+    let _relocated_comm = Comm::new_simulated(&network, relocated_peer.addr(), relocated_tx)?;
+
+    let (max_capacity, root_storage_dir) = create_test_max_capacity_and_root_storage()?;
+    let core = Core::new(
+        comm,
+        node,
+        section,
+        Some(section_key_share),
+        mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
+        UsedSpace::new(max_capacity),
+        root_storage_dir,
+        ChunkStoreKind::Memory,
+    )
+    .await?;
+    let dispatcher = Dispatcher::new(core);
+
+    let auth = create_relocation_trigger(sk_set.secret_key(), relocated_peer.age())?;
+    let commands = dispatcher
+        .process_command(Command::HandleNewNodeOnline(auth), "cmd-id")
+        .await?;
+
+    // Only the relocated node is reachable on the simulated network, so only send what's for it.
+    for command in commands {
+        if let Command::SendMessage { recipients, .. } = &command {
+            if recipients
+                .iter()
+                .all(|peer| peer.addr() == relocated_peer.addr())
+            {
+                let _commands = dispatcher.process_command(command, "cmd-id").await?;
+            }
+        }
+    }
+
+    loop {
+        let bytes = match timeout(Duration::from_secs(5), relocated_rx.recv()).await {
+            Ok(Some(ConnectionEvent::Received((_, bytes)))) => bytes,
+            Ok(_) => continue,
+            Err(_) => bail!("The relocated node didn't receive its relocation details"),
+        };
+        if let MessageType::System {
+            msg: SystemMsg::Relocate(details),
+            ..
+        } = WireMsg::deserialize(bytes)?
+        {
+            assert_eq!(details.pub_id, relocated_peer.name());
+            assert_eq!(details.age, relocated_peer.age() + 1);
+            return Ok(());
+        }
+    }
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn node_message_to_self() -> Result<()> {
     message_to_self(MessageDst::Node).await
@@ -1150,6 +1369,7 @@ async fn message_to_self(dst: MessageDst) -> Result<()> {
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreKind::Memory,
         genesis_sk_set,
     )
     .await?;
@@ -1202,6 +1422,124 @@ async fn message_to_self(dst: MessageDst) -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn back_pressure_reaches_the_client_session() -> Result<()> {
+    let node = create_node(MIN_ADULT_AGE, None);
+    let (event_tx, _) = mpsc::channel(TEST_EVENT_CHANNEL_SIZE);
+    let (comm_tx, mut comm_rx) = mpsc::channel(TEST_EVENT_CHANNEL_SIZE);
+    let comm = Comm::new((Ipv4Addr::LOCALHOST, 0).into(), Default::default(), comm_tx).await?;
+    let node_addr = comm.our_connection_info();
+    let (max_capacity, root_storage_dir) = create_test_max_capacity_and_root_storage()?;
+
+    let genesis_sk_set = bls::SecretKeySet::random(0, &mut rand::thread_rng());
+    let genesis_key = genesis_sk_set.public_keys().public_key();
+    let core = Core::first_node(
+        comm,
+        node,
+        event_tx,
+        UsedSpace::new(max_capacity),
+        root_storage_dir,
+        ChunkStoreKind::Memory,
+        genesis_sk_set,
+    )
+    .await?;
+    let dispatcher = Dispatcher::new(core);
+
+    // A client session listening on its connection to the node, as when it sends it a msg.
+    let client_keypair = Keypair::new_ed25519(&mut OsRng);
+    let (err_tx, _err_rx) = mpsc::channel(TEST_EVENT_CHANNEL_SIZE);
+    let session = Session::new(
+        client_keypair.public_key(),
+        genesis_key,
+        Default::default(),
+        err_tx,
+        (Ipv4Addr::LOCALHOST, 0).into(),
+        Duration::from_secs(1),
+        Duration::from_secs(1),
+        NetworkPrefixMap::new(genesis_key),
+    )
+    .await?;
+    let client_endpoint = qp2p::Endpoint::new_client((Ipv4Addr::LOCALHOST, 0), Default::default())?;
+    let (connection, incoming_msgs) = client_endpoint.connect_to(&node_addr).await?;
+    Session::spawn_message_listener_thread(
+        session.clone(),
+        connection.id(),
+        node_addr,
+        incoming_msgs,
+    );
+
+    let query = ServiceMsg::Query(DataQuery::GetChunk(ChunkAddress(XorName::random())));
+    let payload = WireMsg::serialize_msg_payload(&query)?;
+    let auth = ServiceAuth {
+        public_key: client_keypair.public_key(),
+        signature: client_keypair.sign(&payload),
+    };
+    let dst_location = DstLocation::Section {
+        name: XorName::random(),
+        section_pk: genesis_key,
+    };
+    let wire_msg = WireMsg::new_msg(
+        MessageId::new(),
+        payload,
+        MsgKind::ServiceMsg(auth),
+        dst_location,
+    )?;
+    connection.send(wire_msg.serialize()?).await?;
+
+    let sender = assert_matches!(
+        timeout(Duration::from_secs(10), comm_rx.recv()).await?,
+        Some(ConnectionEvent::Received((sender, _))) => sender
+    );
+
+    // The node is strained as it handles the client's msg.
+    let load_report = LoadReport {
+        short_term: CpuLoad {
+            low: false,
+            moderate: false,
+            high: true,
+            very_high: false,
+            critical: false,
+        },
+        mid_term: CpuLoad {
+            low: false,
+            moderate: true,
+            high: false,
+            very_high: false,
+            critical: false,
+        },
+        long_term: CpuLoad {
+            low: true,
+            moderate: false,
+            high: false,
+            very_high: false,
+            critical: false,
+        },
+    };
+    let cmd = dispatcher
+        .core
+        .back_pressure_cmd(&sender, &wire_msg.msg_kind().src(), load_report)
+        .await?;
+    assert_matches!(&cmd, Command::SendMessage { recipients, .. } => {
+        assert_eq!(recipients.len(), 1);
+        assert_eq!(recipients[0].addr(), sender.addr());
+    });
+    let _ = dispatcher.process_command(cmd, "cmd-id").await?;
+
+    let reports = timeout(Duration::from_secs(10), async {
+        loop {
+            let reports = session.load_reports().await;
+            if !reports.is_empty() {
+                break reports;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await?;
+    assert_eq!(reports, vec![load_report]);
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn handle_elders_update() -> Result<()> {
     init_test_logger();
@@ -1266,6 +1604,7 @@ async fn handle_elders_update() -> Result<()> {
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreKind::Memory,
     )
     .await?;
 
@@ -1385,6 +1724,7 @@ async fn handle_demote_during_split() -> Result<()> {
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreKind::Memory,
     )
     .await?;
 
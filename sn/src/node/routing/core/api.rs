@@ -6,10 +6,11 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{delivery_group, Comm, Core};
+use super::{delivery_group, ChunkStoreKind, Comm, Core};
 
 use crate::messaging::{
-    system::{JoinResponse, MembershipState, NodeState, SigShare, SystemMsg},
+    data::StorageLevel,
+    system::{DkgSessionId, JoinResponse, MembershipState, NodeState, SigShare, SystemMsg},
     WireMsg,
 };
 use crate::node::{
@@ -17,7 +18,9 @@ use crate::node::{
     routing::{
         api::command::Command,
         core::Proposal,
-        network_knowledge::{NetworkKnowledge, SectionAuthorityProvider, SectionKeyShare},
+        network_knowledge::{
+            ElderCandidates, NetworkKnowledge, SectionAuthorityProvider, SectionKeyShare,
+        },
         node::Node,
         Event,
     },
@@ -27,7 +30,11 @@ use crate::types::log_markers::LogMarker;
 use crate::UsedSpace;
 
 use secured_linked_list::SecuredLinkedList;
-use std::{collections::BTreeSet, net::SocketAddr, path::PathBuf};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::SocketAddr,
+    path::PathBuf,
+};
 use tokio::sync::mpsc;
 use xor_name::XorName;
 
@@ -39,6 +46,7 @@ impl Core {
         event_tx: mpsc::Sender<Event>,
         used_space: UsedSpace,
         root_storage_dir: PathBuf,
+        chunk_store_kind: ChunkStoreKind,
         genesis_sk_set: bls::SecretKeySet,
     ) -> Result<Self> {
         // make sure the Node has the correct local addr as Comm
@@ -54,6 +62,7 @@ impl Core {
             event_tx,
             used_space,
             root_storage_dir,
+            chunk_store_kind,
         )
         .await
     }
@@ -94,6 +103,16 @@ impl Core {
         !self.is_elder().await
     }
 
+    /// Storage levels reported by the adults of our section, as tracked while being an elder.
+    pub(crate) async fn storage_levels(&self) -> BTreeMap<XorName, StorageLevel> {
+        self.capacity.levels().await
+    }
+
+    /// DKG sessions we're participating in which haven't completed yet.
+    pub(crate) fn pending_dkg_sessions(&self) -> Vec<(DkgSessionId, ElderCandidates)> {
+        self.dkg_voter.pending_sessions()
+    }
+
     /// Returns connection info of this node.
     pub(crate) fn our_connection_info(&self) -> SocketAddr {
         self.comm.our_connection_info()
@@ -53,6 +53,26 @@ impl BackPressure {
             .unwrap_or_default()
     }
 
+    /// Retry policies of the nodes we're regulating comms with, as per the load they reported.
+    pub(crate) async fn regulated_peers(&self) -> BTreeMap<SocketAddr, RetryConfig> {
+        self.reports
+            .read()
+            .await
+            .iter()
+            .map(|(addr, (_, cfg))| (*addr, *cfg))
+            .collect()
+    }
+
+    /// The latest load report we've evaluated for a node calling us, if any.
+    pub(crate) async fn latest_load_report(&self) -> Option<LoadReport> {
+        self.our_reports
+            .read()
+            .await
+            .values()
+            .max_by_key(|(at, _)| *at)
+            .map(|(_, report)| *report)
+    }
+
     /// Remove regulation for specific nodes when we don't need them anymore (e.g. they left).
     pub(crate) async fn remove(&self, addr: SocketAddr) {
         let _prev = self.reports.write().await.remove(&addr);
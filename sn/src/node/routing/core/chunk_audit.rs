@@ -0,0 +1,308 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Detection of lost or corrupted chunk copies outside of churn.
+//!
+//! Adults periodically scrub their store, re-hashing every chunk and fetching again from the
+//! other holders those found corrupted. Elders periodically audit a random bucket of their
+//! section's chunks: every holder of the bucket reports a digest of the chunk names it holds
+//! within it, and when the digests disagree the holders are asked to re-publish those chunks,
+//! so they are replicated again to whoever lacks them.
+
+use super::{ChunkStoreError, Command, Core};
+
+use crate::{
+    messaging::system::{NodeCmd, SystemMsg},
+    node::Result,
+    types::ChunkAddress,
+};
+
+use itertools::Itertools;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    iter,
+    time::Duration,
+};
+use tokio::time::Instant;
+use tracing::{debug, info, trace, warn};
+use xor_name::{Prefix, XorName, XOR_NAME_LEN};
+
+// How long the holders have to report their digests before an audit is dropped.
+const CHUNK_AUDIT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// An audit in progress, collecting the digests reported by the holders of a bucket.
+#[derive(Debug)]
+pub(crate) struct ChunkAudit {
+    holders: BTreeSet<XorName>,
+    digests: BTreeMap<XorName, XorName>,
+    started: Instant,
+}
+
+impl Core {
+    /// Re-hashes every chunk we hold, asking the other holders for a copy of those found corrupted.
+    pub(crate) async fn scrub_chunks(&self) -> Result<Vec<Command>> {
+        let addresses = self.chunk_storage.keys()?;
+        info!("Scrubbing {} chunks", addresses.len());
+
+        let mut commands = vec![];
+        for address in addresses {
+            match self.chunk_storage.get_chunk(&address).await {
+                Ok(_) => {}
+                Err(ChunkStoreError::CorruptedChunk(_)) => {
+                    commands.extend(self.refetch_chunk(address).await?)
+                }
+                Err(error) => warn!("Failed to scrub chunk {:?}: {:?}", address, error),
+            }
+            // This is a background job, don't hog the runtime.
+            tokio::task::yield_now().await;
+        }
+
+        Ok(commands)
+    }
+
+    /// Starts the audit of a random bucket of our section's chunks, asking its holders for
+    /// the digest of the chunk names they hold within it.
+    pub(crate) async fn start_chunk_audit(&self) -> Result<Vec<Command>> {
+        let full_adults = self.full_adults().await;
+        let adults: BTreeSet<XorName> = self
+            .network_knowledge()
+            .adults()
+            .await
+            .iter()
+            .map(|peer| peer.name())
+            .filter(|name| !full_adults.contains(name))
+            .collect();
+
+        let our_prefix = self.network_knowledge().prefix().await;
+        let bucket = Prefix::new(
+            audit_bucket_depth(&our_prefix, &adults),
+            our_prefix.substituted_in(XorName::random()),
+        );
+        let holders = self.compute_holders(&ChunkAddress(bucket.name()), &adults);
+        if holders.len() < 2 {
+            trace!("Not enough holders to audit chunks within {:?}", bucket);
+            return Ok(vec![]);
+        }
+
+        {
+            let mut audits = self.chunk_audits.write().await;
+            audits.retain(|prefix, audit| {
+                let expired = audit.started.elapsed() > CHUNK_AUDIT_TIMEOUT;
+                if expired {
+                    let missing = audit.holders.len() - audit.digests.len();
+                    warn!(
+                        "Dropping audit of chunks within {:?}, {} holders didn't report",
+                        prefix, missing
+                    );
+                }
+                !expired
+            });
+            let _prev = audits.insert(
+                bucket,
+                ChunkAudit {
+                    holders: holders.clone(),
+                    digests: BTreeMap::new(),
+                    started: Instant::now(),
+                },
+            );
+        }
+
+        debug!("Auditing chunks within {:?} at {:?}", bucket, holders);
+        let msg = SystemMsg::NodeCmd(NodeCmd::RequestChunkDigest(bucket));
+        self.send_node_msg_to_targets(msg, holders, false).await
+    }
+
+    /// Reports to an auditing Elder the digest of the chunk names we hold within the prefix.
+    pub(crate) async fn send_chunk_digest(
+        &self,
+        prefix: Prefix,
+        elder: XorName,
+    ) -> Result<Vec<Command>> {
+        let names: Vec<XorName> = self
+            .chunk_storage
+            .keys_with_prefix(prefix)?
+            .iter()
+            .map(|address| *address.name())
+            .sorted()
+            .collect();
+        let digest = merkle_root(&names);
+
+        trace!(
+            "Reporting digest {:?} of {} chunks within {:?}",
+            digest,
+            names.len(),
+            prefix
+        );
+        let msg = SystemMsg::NodeCmd(NodeCmd::ChunkDigest { prefix, digest });
+        self.send_node_msg_to_targets(msg, iter::once(elder).collect(), false)
+            .await
+    }
+
+    /// Records the digest reported by a holder. Once all the holders reported, asks one holder
+    /// of each different digest to re-publish its chunks, if they don't all agree.
+    pub(crate) async fn handle_chunk_digest(
+        &self,
+        holder: XorName,
+        prefix: Prefix,
+        digest: XorName,
+    ) -> Result<Vec<Command>> {
+        let audit = {
+            let mut audits = self.chunk_audits.write().await;
+            let completed = match audits.get_mut(&prefix) {
+                Some(audit) if audit.holders.contains(&holder) => {
+                    let _prev = audit.digests.insert(holder, digest);
+                    audit.digests.len() == audit.holders.len()
+                }
+                _ => {
+                    debug!(
+                        "Ignoring chunk digest for {:?} from {:?}, no such audit in progress",
+                        prefix, holder
+                    );
+                    return Ok(vec![]);
+                }
+            };
+            if !completed {
+                return Ok(vec![]);
+            }
+            audits.remove(&prefix)
+        };
+
+        let mut representatives = BTreeMap::new();
+        for (holder, digest) in audit.into_iter().flat_map(|audit| audit.digests) {
+            let _ = representatives.entry(digest).or_insert(holder);
+        }
+        if representatives.len() < 2 {
+            trace!("Holders agree on the chunks within {:?}", prefix);
+            return Ok(vec![]);
+        }
+
+        warn!(
+            "Holders disagree on the chunks within {:?} ({} different digests), re-publishing them",
+            prefix,
+            representatives.len()
+        );
+        let msg = SystemMsg::NodeCmd(NodeCmd::RepublishChunks(prefix));
+        self.send_node_msg_to_targets(msg, representatives.into_values().collect(), false)
+            .await
+    }
+
+    /// Sends all the chunks we hold within the prefix to our Elders, to be replicated again.
+    pub(crate) async fn republish_chunks(&self, prefix: Prefix) -> Result<Vec<Command>> {
+        let mut commands = vec![];
+        for address in self.chunk_storage.keys_with_prefix(prefix)? {
+            match self.chunk_storage.get_chunk(&address).await {
+                Ok(chunk) => {
+                    let msg = SystemMsg::NodeCmd(NodeCmd::RepublishChunk(chunk));
+                    commands.push(self.send_message_to_our_elders(msg).await?);
+                }
+                Err(ChunkStoreError::CorruptedChunk(_)) => {
+                    commands.extend(self.refetch_chunk(address).await?)
+                }
+                Err(error) => warn!("Failed to republish chunk {:?}: {:?}", address, error),
+            }
+        }
+
+        info!("Republishing {} chunks within {:?}", commands.len(), prefix);
+        Ok(commands)
+    }
+}
+
+// Root of the Merkle tree whose leaves are the given names, in order.
+fn merkle_root(names: &[XorName]) -> XorName {
+    let mut level = names.to_vec();
+    if level.is_empty() {
+        return XorName::default();
+    }
+
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => XorName::from_content_parts(&[&left.0, &right.0]),
+                _ => pair[0],
+            })
+            .collect();
+    }
+
+    level[0]
+}
+
+// Length of the buckets to audit, so that every adult has a distinct prefix of that length.
+// All the chunks within such a bucket then have the same holders, which are thus expected to
+// hold exactly the same chunks within it.
+fn audit_bucket_depth(section_prefix: &Prefix, adults: &BTreeSet<XorName>) -> usize {
+    // Being sorted, the longest common prefix between any two adults is found among neighbours.
+    adults
+        .iter()
+        .tuple_windows()
+        .map(|(lhs, rhs)| common_prefix_len(lhs, rhs) + 1)
+        .chain(iter::once(section_prefix.bit_count() + 1))
+        .max()
+        .unwrap_or_default()
+        .min(8 * XOR_NAME_LEN)
+}
+
+// Number of leading bits the two names have in common.
+fn common_prefix_len(lhs: &XorName, rhs: &XorName) -> usize {
+    lhs.0
+        .iter()
+        .zip(rhs.0.iter())
+        .position(|(lhs, rhs)| lhs != rhs)
+        .map_or(8 * XOR_NAME_LEN, |byte| {
+            8 * byte + (lhs.0[byte] ^ rhs.0[byte]).leading_zeros() as usize
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_copy_count;
+
+    #[test]
+    fn merkle_root_depends_on_every_name() {
+        let names: Vec<_> = (0..7).map(|_| XorName::random()).collect();
+        let root = merkle_root(&names);
+        assert_eq!(root, merkle_root(&names));
+
+        for i in 0..names.len() {
+            let mut altered = names.clone();
+            altered[i] = XorName::random();
+            assert_ne!(root, merkle_root(&altered));
+            let _ = altered.remove(i);
+            assert_ne!(root, merkle_root(&altered));
+        }
+
+        assert_eq!(merkle_root(&[]), XorName::default());
+    }
+
+    #[test]
+    fn chunks_within_an_audit_bucket_have_the_same_holders() {
+        let section_prefix = Prefix::default().pushed(true);
+        let adults: BTreeSet<_> = (0..20)
+            .map(|_| section_prefix.substituted_in(XorName::random()))
+            .collect();
+        let depth = audit_bucket_depth(&section_prefix, &adults);
+
+        let holders = |name: XorName| -> BTreeSet<XorName> {
+            adults
+                .iter()
+                .sorted_by(|lhs, rhs| name.cmp_distance(lhs, rhs))
+                .take(chunk_copy_count())
+                .copied()
+                .collect()
+        };
+
+        for _ in 0..10 {
+            let bucket = Prefix::new(depth, section_prefix.substituted_in(XorName::random()));
+            let expected = holders(bucket.name());
+            for _ in 0..10 {
+                assert_eq!(holders(bucket.substituted_in(XorName::random())), expected);
+            }
+        }
+    }
+}
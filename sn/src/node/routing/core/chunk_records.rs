@@ -11,9 +11,12 @@ use super::{Command, Core, Prefix};
 use crate::{
     chunk_copy_count,
     messaging::{
-        data::{operation_id, ChunkDataExchange, CmdError, Error as ErrorMessage, StorageLevel},
+        data::{
+            operation_id, ChunkDataExchange, CmdError, Error as ErrorMessage, QueryResponse,
+            ServiceMsg, StorageLevel,
+        },
         system::{NodeCmd, NodeQuery, SystemMsg},
-        AuthorityProof, EndUser, MessageId, ServiceAuth,
+        AuthorityProof, DstLocation, EndUser, MessageId, ServiceAuth, WireMsg,
     },
     node::{error::convert_to_error_message, Error, Result},
     peer::Peer,
@@ -66,6 +69,13 @@ impl Core {
         changed
     }
 
+    /// Reports to our section the level of storage we found already used at startup,
+    /// e.g. when restarting with the data stored in a previous run.
+    pub(crate) async fn report_initial_storage_level(&self) -> Vec<Command> {
+        let level = self.chunk_storage.check_storage_level();
+        self.record_if_any(level).await
+    }
+
     pub(crate) async fn full_adults(&self) -> BTreeSet<XorName> {
         self.capacity.full_adults().await
     }
@@ -128,6 +138,22 @@ impl Core {
             operation_id
         );
 
+        if let Some(chunk) = self.served_chunks.get(address.name()).await {
+            trace!("Responding with cached chunk at {:?}", address);
+            let msg = ServiceMsg::QueryResponse {
+                response: QueryResponse::GetChunk(Ok(chunk)),
+                correlation_id: msg_id,
+            };
+            let (msg_kind, payload) = Self::random_client_signature(&msg)?;
+            let dst = DstLocation::EndUser(EndUser(origin.name()));
+            let wire_msg = WireMsg::new_msg(MessageId::new(), payload, msg_kind, dst)?;
+
+            return Ok(vec![Command::SendMessage {
+                recipients: vec![origin],
+                wire_msg,
+            }]);
+        }
+
         let targets = self.get_adults_holding_chunk(address.name()).await;
 
         if targets.is_empty() {
@@ -172,6 +198,77 @@ impl Core {
             .await
     }
 
+    /// Asks the other holders of a chunk for a copy of it, to replace our corrupted one.
+    /// The response is handled by `handle_chunk_query_response_at_adult`.
+    pub(super) async fn refetch_chunk(&self, address: ChunkAddress) -> Result<Vec<Command>> {
+        let our_name = self.node.read().await.name();
+        let adults = self
+            .network_knowledge()
+            .adults()
+            .await
+            .iter()
+            .map(|peer| peer.name())
+            .collect();
+        let mut holders = self.compute_holders(&address, &adults);
+        let _ = holders.remove(&our_name);
+
+        info!("Re-fetching chunk {:?} from holders {:?}", address, holders);
+
+        let msg = SystemMsg::NodeQuery(NodeQuery::GetChunk {
+            address,
+            origin: EndUser(XorName::random()),
+        });
+        let aggregation = false;
+
+        self.send_node_msg_to_targets(msg, holders, aggregation)
+            .await
+    }
+
+    /// Pushes the chunks we hold to the adults becoming their holders once we've left the
+    /// section, i.e. those which weren't holders while we were still around.
+    pub(crate) async fn hand_off_chunks(&self) -> Result<Vec<Command>> {
+        let our_name = self.node.read().await.name();
+        let mut adults: BTreeSet<_> = self
+            .network_knowledge()
+            .adults()
+            .await
+            .iter()
+            .map(|peer| peer.name())
+            .collect();
+        let _ = adults.insert(our_name);
+        let mut remaining = adults.clone();
+        let _ = remaining.remove(&our_name);
+
+        let section_pk = self.network_knowledge.section_key().await;
+        let mut commands = vec![];
+        for addr in self.chunk_storage.keys()? {
+            let old_holders = self.compute_holders(&addr, &adults);
+            let new_holders = self.compute_holders(&addr, &remaining);
+            let targets: Vec<_> = new_holders.difference(&old_holders).copied().collect();
+            if targets.is_empty() {
+                continue;
+            }
+
+            let chunk = match self.chunk_storage.get_chunk(&addr).await {
+                Ok(chunk) => chunk,
+                Err(error) => {
+                    warn!("Failed to read chunk {:?} to hand off: {:?}", addr, error);
+                    continue;
+                }
+            };
+            trace!("Handing off chunk {:?} to {:?}", addr, targets);
+            for name in targets {
+                commands.push(Command::PrepareNodeMsgToSend {
+                    msg: SystemMsg::NodeCmd(NodeCmd::ReplicateChunk(chunk.clone())),
+                    dst: DstLocation::Node { name, section_pk },
+                })
+            }
+        }
+
+        info!("Handing off chunks with {} msgs", commands.len());
+        Ok(commands)
+    }
+
     #[allow(clippy::mutable_key_type)]
     pub(crate) async fn reorganize_chunks(
         &self,
@@ -198,7 +295,7 @@ impl Core {
             for name in targets {
                 commands.push(Command::PrepareNodeMsgToSend {
                     msg: SystemMsg::NodeCmd(NodeCmd::ReplicateChunk(chunk.clone())),
-                    dst: crate::messaging::DstLocation::Node { name, section_pk },
+                    dst: DstLocation::Node { name, section_pk },
                 })
             }
         }
@@ -233,15 +330,17 @@ impl Core {
                 if let Err(err) = chunks.remove_chunk(address).await {
                     warn!("Error deleting chunk during republish: {:?}", err);
                 }
+                // keep serving it to elders querying us for it, in case the new holders
+                // don't have it yet
+                self.served_chunks.insert(chunk.clone()).await;
             }
-            // TODO: Push to LRU cache
             Some((chunk, new_holders))
         } else {
             None
         }
     }
 
-    fn compute_holders(
+    pub(super) fn compute_holders(
         &self,
         addr: &ChunkAddress,
         adult_list: &BTreeSet<XorName>,
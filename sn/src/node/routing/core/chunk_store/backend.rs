@@ -0,0 +1,103 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Result;
+
+use crate::types::{Chunk, ChunkAddress};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+use xor_name::Prefix;
+
+/// Where and how `ChunkStore` keeps the chunks.
+///
+/// Backends account for the space their chunks use in the `UsedSpace` they're created with,
+/// including the chunks found from a previous run.
+#[async_trait]
+pub(crate) trait ChunkStorageBackend: Send + Sync {
+    /// Stores the chunk, overwriting any chunk already stored at its address.
+    async fn write_chunk(&self, chunk: &Chunk) -> Result<ChunkAddress>;
+
+    /// Reads a chunk, verifying its content matches the address it's stored at.
+    ///
+    /// Returns `Error::ChunkNotFound`, or an I/O error of kind `NotFound`, if there's no such
+    /// chunk. A chunk which doesn't match is discarded, and `Error::CorruptedChunk` is returned.
+    async fn read_chunk(&self, addr: &ChunkAddress) -> Result<Chunk>;
+
+    /// Removes the chunk, releasing the space it was using.
+    async fn delete_chunk(&self, addr: &ChunkAddress) -> Result<()>;
+
+    /// Whether a chunk is stored at the address.
+    fn chunk_exists(&self, addr: &ChunkAddress) -> Result<bool>;
+
+    /// Number of chunks stored, kept up to date as chunks are written and deleted, so it's
+    /// cheap to call however many chunks there are, e.g. on every scrape of the metrics.
+    fn chunk_count(&self) -> usize;
+
+    /// Addresses of all the chunks stored.
+    fn list_all_chunk_addresses(&self) -> Result<Vec<ChunkAddress>>;
+
+    /// Addresses of the chunks whose name matches the prefix.
+    fn list_chunk_addresses_with_prefix(&self, prefix: Prefix) -> Result<Vec<ChunkAddress>> {
+        Ok(self
+            .list_all_chunk_addresses()?
+            .into_iter()
+            .filter(|addr| prefix.matches(addr.name()))
+            .collect())
+    }
+}
+
+/// Kind of storage backend the node keeps its chunks in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChunkStoreKind {
+    /// One file per chunk, within a tree of directories following the bits of the chunk names.
+    Disk,
+    /// Chunks appended to segment files, located through an index kept in memory. Better
+    /// suited than `Disk` to large numbers of small chunks.
+    PackedLog,
+    /// Chunks kept in memory only, lost when the node stops. Meant for tests.
+    Memory,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for ChunkStoreKind {
+    fn default() -> Self {
+        Self::Disk
+    }
+}
+
+impl FromStr for ChunkStoreKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "disk" => Ok(Self::Disk),
+            "packed-log" => Ok(Self::PackedLog),
+            "memory" => Ok(Self::Memory),
+            other => Err(format!(
+                "Unknown chunk store '{}', expected one of: disk, packed-log, memory",
+                other
+            )),
+        }
+    }
+}
+
+impl Display for ChunkStoreKind {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Disk => write!(formatter, "disk"),
+            Self::PackedLog => write!(formatter, "packed-log"),
+            Self::Memory => write!(formatter, "memory"),
+        }
+    }
+}
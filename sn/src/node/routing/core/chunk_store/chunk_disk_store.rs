@@ -6,26 +6,37 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{Error, Result};
+use super::{ChunkStorageBackend, Error, Result};
 
 use crate::types::{Chunk, ChunkAddress};
 use crate::UsedSpace;
 
+use async_trait::async_trait;
 use bytes::Bytes;
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 use tokio::io::AsyncWriteExt;
 use walkdir::WalkDir;
 use xor_name::{Prefix, XorName};
 
 const BIT_TREE_DEPTH: usize = 20;
 const CHUNK_DB_DIR: &str = "chunkdb";
+const QUARANTINE_DIR: &str = "chunkdb_quarantine";
 
 /// A disk store for chunks
 #[derive(Clone)]
 pub(crate) struct ChunkDiskStore {
     bit_tree_depth: usize,
     chunk_store_path: PathBuf,
+    quarantine_path: PathBuf,
     used_space: UsedSpace,
+    // number of chunk files in the store, so they don't need to be listed to be counted
+    chunk_count: Arc<AtomicUsize>,
 }
 
 impl ChunkDiskStore {
@@ -33,15 +44,31 @@ impl ChunkDiskStore {
     ///
     /// If the location specified already contains a ChunkDiskStore, it is simply used
     ///
-    /// Used space of the dir is tracked
+    /// Used space of the dir, and the number of chunks in it, are tracked, starting with the
+    /// chunks it already contains
     pub(crate) fn new<P: AsRef<Path>>(root: P, used_space: UsedSpace) -> Result<Self> {
         let chunk_store_path = root.as_ref().join(CHUNK_DB_DIR);
+        let quarantine_path = root.as_ref().join(QUARANTINE_DIR);
 
-        Ok(ChunkDiskStore {
+        let store = ChunkDiskStore {
             bit_tree_depth: BIT_TREE_DEPTH,
             chunk_store_path,
+            quarantine_path,
             used_space,
-        })
+            chunk_count: Arc::new(AtomicUsize::new(0)),
+        };
+
+        let (count, existing) = store.measure_existing_chunks()?;
+        if count > 0 {
+            info!(
+                "ChunkDiskStore: found {} existing chunks, of {} bytes",
+                count, existing
+            );
+            store.used_space.increase(existing);
+            store.chunk_count.store(count, Ordering::Relaxed);
+        }
+
+        Ok(store)
     }
 
     // ---------------------- helper methods ----------------------
@@ -83,56 +110,114 @@ impl ChunkDiskStore {
         Ok(ChunkAddress::decode_from_zbase32(filename)?)
     }
 
-    // ---------------------- api methods ----------------------
+    // Number and total size of the chunk files found in the store, e.g. when restarting a node.
+    fn measure_existing_chunks(&self) -> Result<(usize, usize)> {
+        let files = self.list_all_files()?;
+        let mut total = 0;
+        for filepath in &files {
+            total += std::fs::metadata(filepath)?.len() as usize;
+        }
+        Ok((files.len(), total))
+    }
 
-    pub(crate) fn used_space_ratio(&self) -> f64 {
-        self.used_space.ratio()
+    // Moves a chunk file out of the store so it's not served anymore,
+    // keeping it aside for inspection, and releases the space it was using.
+    async fn quarantine_file(&self, filepath: &Path, size: usize) -> Result<()> {
+        let filename = filepath.file_name().ok_or(Error::NoFilename)?;
+        tokio::fs::create_dir_all(&self.quarantine_path).await?;
+        tokio::fs::rename(filepath, self.quarantine_path.join(filename)).await?;
+        self.used_space.decrease(size);
+        let _ = self.chunk_count.fetch_sub(1, Ordering::Relaxed);
+        Ok(())
     }
 
-    pub(crate) fn can_add(&self, size: usize) -> bool {
-        self.used_space.can_add(size)
+    pub(crate) fn list_all_files(&self) -> Result<Vec<String>> {
+        list_files_in(&self.chunk_store_path)
+    }
+
+    #[allow(unused)]
+    /// quickly find chunks related or not to a section, might be useful when adults change sections
+    /// not used yet
+    pub(crate) fn list_files_without_prefix(&self, prefix: Prefix) -> Result<Vec<String>> {
+        let all_files = self.list_all_files()?;
+        let prefix_path = self.prefix_tree_path(prefix.name(), prefix.bit_count());
+        let outside_prefix = all_files
+            .into_iter()
+            .filter(|p| !Path::new(&p).starts_with(&prefix_path))
+            .collect();
+        Ok(outside_prefix)
     }
 
-    pub(crate) async fn write_chunk(&self, data: &Chunk) -> Result<ChunkAddress> {
+    /// quickly find chunks related or not to a section, might be useful when adults change sections
+    pub(crate) fn list_files_with_prefix(&self, prefix: Prefix) -> Result<Vec<String>> {
+        let prefix_path = self.prefix_tree_path(prefix.name(), prefix.bit_count());
+        list_files_in(prefix_path.as_path())
+    }
+}
+
+#[async_trait]
+impl ChunkStorageBackend for ChunkDiskStore {
+    async fn write_chunk(&self, data: &Chunk) -> Result<ChunkAddress> {
         let addr = data.address();
         let filepath = self.address_to_filepath(addr)?;
         if let Some(dirs) = filepath.parent() {
             tokio::fs::create_dir_all(dirs).await?;
         }
 
+        let previous = tokio::fs::metadata(&filepath).await.ok();
+
         let mut file = tokio::fs::File::create(filepath).await?;
         file.write_all(data.value()).await?;
+        // tokio completes the write in the background otherwise
+        file.flush().await?;
 
         self.used_space.increase(data.value().len());
+        match previous {
+            Some(meta) => self.used_space.decrease(meta.len() as usize),
+            None => {
+                let _ = self.chunk_count.fetch_add(1, Ordering::Relaxed);
+            }
+        }
 
         Ok(*addr)
     }
 
-    pub(crate) async fn delete_chunk(&self, addr: &ChunkAddress) -> Result<()> {
+    /// A chunk which doesn't match its address is quarantined.
+    async fn read_chunk(&self, addr: &ChunkAddress) -> Result<Chunk> {
+        let file_path = self.address_to_filepath(addr)?;
+        let bytes = Bytes::from(tokio::fs::read(&file_path).await?);
+        let chunk = Chunk::new(bytes);
+        if chunk.address() != addr {
+            warn!(
+                "ChunkDiskStore: content of chunk {:?} doesn't match its address, quarantining it",
+                addr
+            );
+            self.quarantine_file(&file_path, chunk.value().len())
+                .await?;
+            return Err(Error::CorruptedChunk(*addr.name()));
+        }
+        Ok(chunk)
+    }
+
+    async fn delete_chunk(&self, addr: &ChunkAddress) -> Result<()> {
         let filepath = self.address_to_filepath(addr)?;
         let meta = tokio::fs::metadata(filepath.clone()).await?;
         tokio::fs::remove_file(filepath).await?;
         self.used_space.decrease(meta.len() as usize);
+        let _ = self.chunk_count.fetch_sub(1, Ordering::Relaxed);
         Ok(())
     }
 
-    pub(crate) async fn read_chunk(&self, addr: &ChunkAddress) -> Result<Chunk> {
-        let file_path = self.address_to_filepath(addr)?;
-        let bytes = Bytes::from(tokio::fs::read(file_path).await?);
-        let chunk = Chunk::new(bytes);
-        Ok(chunk)
-    }
-
-    pub(crate) fn chunk_file_exists(&self, addr: &ChunkAddress) -> Result<bool> {
+    fn chunk_exists(&self, addr: &ChunkAddress) -> Result<bool> {
         let filepath = self.address_to_filepath(addr)?;
         Ok(filepath.exists())
     }
 
-    pub(crate) fn list_all_files(&self) -> Result<Vec<String>> {
-        list_files_in(&self.chunk_store_path)
+    fn chunk_count(&self) -> usize {
+        self.chunk_count.load(Ordering::Relaxed)
     }
 
-    pub(crate) fn list_all_chunk_addresses(&self) -> Result<Vec<ChunkAddress>> {
+    fn list_all_chunk_addresses(&self) -> Result<Vec<ChunkAddress>> {
         let all_files = self.list_all_files()?;
         let all_addrs = all_files
             .iter()
@@ -141,25 +226,16 @@ impl ChunkDiskStore {
         all_addrs
     }
 
-    #[allow(unused)]
-    /// quickly find chunks related or not to a section, might be useful when adults change sections
-    /// not used yet
-    pub(crate) fn list_files_without_prefix(&self, prefix: Prefix) -> Result<Vec<String>> {
-        let all_files = self.list_all_files()?;
-        let prefix_path = self.prefix_tree_path(prefix.name(), prefix.bit_count());
-        let outside_prefix = all_files
-            .into_iter()
-            .filter(|p| !Path::new(&p).starts_with(&prefix_path.as_path()))
-            .collect();
-        Ok(outside_prefix)
-    }
-
-    #[allow(unused)]
-    /// quickly find chunks related or not to a section, might be useful when adults change sections
-    /// not used yet
-    pub(crate) fn list_files_with_prefix(&self, prefix: Prefix) -> Result<Vec<String>> {
-        let prefix_path = self.prefix_tree_path(prefix.name(), prefix.bit_count());
-        list_files_in(prefix_path.as_path())
+    /// The prefix can be longer than the depth of the prefix tree.
+    fn list_chunk_addresses_with_prefix(&self, prefix: Prefix) -> Result<Vec<ChunkAddress>> {
+        let mut addrs = vec![];
+        for filepath in self.list_files_with_prefix(prefix)? {
+            let addr = self.filepath_to_address(&filepath)?;
+            if prefix.matches(addr.name()) {
+                addrs.push(addr);
+            }
+        }
+        Ok(addrs)
     }
 }
 
@@ -235,6 +311,83 @@ mod tests {
         write_and_read_chunks(&chunks, store).await;
     }
 
+    #[tokio::test]
+    async fn test_read_corrupted_chunk_is_quarantined() -> Result<()> {
+        let root = tempdir()?;
+        let used_space = UsedSpace::new(usize::MAX);
+        let store = ChunkDiskStore::new(root.path(), used_space.clone())?;
+
+        let chunk = Chunk::new(random_bytes(100));
+        let addr = store.write_chunk(&chunk).await?;
+        assert!(used_space.ratio() > 0.0);
+
+        // flip the content of the chunk file as if it had bit-rotted
+        let filepath = store.address_to_filepath(&addr)?;
+        tokio::fs::write(&filepath, random_bytes(100)).await?;
+
+        assert!(matches!(
+            store.read_chunk(&addr).await,
+            Err(Error::CorruptedChunk(name)) if name == *addr.name()
+        ));
+        assert!(!store.chunk_exists(&addr)?);
+        assert!(store.list_all_chunk_addresses()?.is_empty());
+        assert_eq!(store.chunk_count(), 0);
+        assert_eq!(used_space.ratio(), 0.0);
+
+        // the same chunk can now be stored again
+        let _ = store.write_chunk(&chunk).await?;
+        assert_eq!(store.read_chunk(&addr).await?.value(), chunk.value());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_used_space_is_restored_on_reopen() -> Result<()> {
+        let root = tempdir()?;
+        let size = 100;
+        let count = 5;
+        let used_space = UsedSpace::new(2 * size * count);
+        let store = ChunkDiskStore::new(root.path(), used_space.clone())?;
+        for _ in 0..count {
+            let _ = store.write_chunk(&Chunk::new(random_bytes(size))).await?;
+        }
+        assert_eq!(used_space.ratio(), 0.5);
+
+        let used_space = UsedSpace::new(2 * size * count);
+        let reopened = ChunkDiskStore::new(root.path(), used_space.clone())?;
+        assert_eq!(used_space.ratio(), 0.5);
+        assert_eq!(reopened.list_all_chunk_addresses()?.len(), count);
+        assert_eq!(reopened.chunk_count(), count);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chunk_count_follows_writes_and_deletes() -> Result<()> {
+        let root = tempdir()?;
+        let used_space = UsedSpace::new(usize::MAX);
+        let store = ChunkDiskStore::new(root.path(), used_space.clone())?;
+
+        let chunks: Vec<_> = std::iter::repeat_with(|| Chunk::new(random_bytes(100)))
+            .take(3)
+            .collect();
+        for chunk in &chunks {
+            let _ = store.write_chunk(chunk).await?;
+        }
+        assert_eq!(store.chunk_count(), 3);
+
+        // overwriting a chunk neither counts it, nor its size, twice
+        let _ = store.write_chunk(&chunks[0]).await?;
+        assert_eq!(store.chunk_count(), 3);
+        assert_eq!(used_space.used(), 300);
+
+        store.delete_chunk(chunks[1].address()).await?;
+        assert_eq!(store.chunk_count(), 2);
+        assert_eq!(store.chunk_count(), store.list_all_chunk_addresses()?.len());
+
+        Ok(())
+    }
+
     async fn write_and_read_chunks(chunks: &[Chunk], store: ChunkDiskStore) {
         // write all chunks
         let tasks = chunks.iter().map(|c| store.write_chunk(c));
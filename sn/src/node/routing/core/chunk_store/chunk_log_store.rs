@@ -0,0 +1,691 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{ChunkStorageBackend, Error, Result};
+
+use crate::types::{Chunk, ChunkAddress};
+use crate::UsedSpace;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::{
+    collections::BTreeMap,
+    fs::OpenOptions,
+    io::{ErrorKind, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+    sync::Mutex,
+};
+use xor_name::{Prefix, XorName, XOR_NAME_LEN};
+
+const CHUNK_LOG_DIR: &str = "chunklog";
+const QUARANTINE_DIR: &str = "chunklog_quarantine";
+const SEGMENT_EXTENSION: &str = "log";
+// A new segment is started once the current one would grow beyond this size.
+const MAX_SEGMENT_SIZE: u64 = 64 * 1024 * 1024;
+
+const RECORD_PUT: u8 = 0;
+const RECORD_DELETE: u8 = 1;
+// kind, chunk name, payload length
+const RECORD_HEADER_LEN: usize = 1 + XOR_NAME_LEN + 4;
+
+// Where the content of a chunk is found in the segments.
+#[derive(Clone, Copy, Debug)]
+struct Location {
+    segment: u32,
+    offset: u64,
+    len: u32,
+}
+
+// How much of a segment is taken by records which are no longer needed,
+// i.e. overwritten or deleted chunks, and the delete records themselves.
+#[derive(Clone, Copy, Debug, Default)]
+struct SegmentUsage {
+    len: u64,
+    garbage: u64,
+}
+
+// The segment currently appended to, along with the usage of all the segments.
+struct SegmentWriter {
+    segment: u32,
+    file: File,
+    len: u64,
+    usage: BTreeMap<u32, SegmentUsage>,
+}
+
+impl SegmentWriter {
+    fn add_garbage(&mut self, segment: u32, len: u64) {
+        if let Some(usage) = self.usage.get_mut(&segment) {
+            usage.garbage += len;
+        }
+    }
+}
+
+/// A store appending chunks to segment files, with an index in memory pointing
+/// at the latest record of every chunk.
+///
+/// Every record is made of a kind byte (put or delete), the name of the chunk, the length of
+/// the payload as a little-endian u32, and the payload itself. Deletes only append a record.
+/// The space of overwritten and deleted chunks is reclaimed by compacting the segments no longer
+/// appended to once at least half of their content is garbage: the records still needed are
+/// appended again, and the segment is removed.
+///
+/// The space used is that of the segments, i.e. records are charged with their header, and
+/// garbage until it's compacted away.
+#[derive(Clone)]
+pub(crate) struct ChunkLogStore {
+    path: PathBuf,
+    quarantine_path: PathBuf,
+    max_segment_size: u64,
+    index: Arc<RwLock<BTreeMap<XorName, Location>>>,
+    writer: Arc<Mutex<SegmentWriter>>,
+    used_space: UsedSpace,
+}
+
+impl ChunkLogStore {
+    /// Creates a new `ChunkLogStore` at location `root/CHUNK_LOG_DIR`
+    ///
+    /// If the location already contains segments, the index is rebuilt from them, dropping any
+    /// record left incomplete at the end of a segment, e.g. by a crash while writing it.
+    ///
+    /// Used space is tracked, starting with the size of the segments already there
+    pub(crate) fn new<P: AsRef<Path>>(root: P, used_space: UsedSpace) -> Result<Self> {
+        Self::open(root, used_space, MAX_SEGMENT_SIZE)
+    }
+
+    fn open<P: AsRef<Path>>(root: P, used_space: UsedSpace, max_segment_size: u64) -> Result<Self> {
+        let path = root.as_ref().join(CHUNK_LOG_DIR);
+        std::fs::create_dir_all(&path)?;
+
+        let mut index = BTreeMap::new();
+        let mut usage = BTreeMap::new();
+        let segments = list_segments(&path)?;
+        for segment in &segments {
+            let len = scan_segment(&segment_path(&path, *segment), *segment, &mut index)?;
+            let _prev = usage.insert(*segment, SegmentUsage { len, garbage: len });
+        }
+        for location in index.values() {
+            if let Some(usage) = usage.get_mut(&location.segment) {
+                usage.garbage -= record_len(location.len);
+            }
+        }
+
+        let segment = segments.last().copied().unwrap_or(0);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(segment_path(&path, segment))?;
+        let len = file.metadata()?.len();
+        let _prev = usage
+            .entry(segment)
+            .or_insert(SegmentUsage { len, garbage: 0 });
+
+        let existing: u64 = usage.values().map(|usage| usage.len).sum();
+        if existing > 0 {
+            info!(
+                "ChunkLogStore: found {} existing chunks, in {} bytes of segments",
+                index.len(),
+                existing
+            );
+            used_space.increase(existing as usize);
+        }
+
+        Ok(Self {
+            path,
+            quarantine_path: root.as_ref().join(QUARANTINE_DIR),
+            max_segment_size,
+            index: Arc::new(RwLock::new(index)),
+            writer: Arc::new(Mutex::new(SegmentWriter {
+                segment,
+                file: File::from_std(file),
+                len,
+                usage,
+            })),
+            used_space,
+        })
+    }
+
+    // Appends a record to the current segment, starting a new one if it's full,
+    // and returns where its payload was written.
+    async fn append(
+        &self,
+        writer: &mut SegmentWriter,
+        kind: u8,
+        name: &XorName,
+        payload: &[u8],
+    ) -> Result<Location> {
+        let record_len = record_len(payload.len() as u32);
+        if writer.len > 0 && writer.len + record_len > self.max_segment_size {
+            let segment = writer.segment + 1;
+            writer.file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(segment_path(&self.path, segment))
+                .await?;
+            writer.segment = segment;
+            writer.len = 0;
+        }
+
+        let mut record = Vec::with_capacity(record_len as usize);
+        record.push(kind);
+        record.extend_from_slice(&name.0);
+        record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        record.extend_from_slice(payload);
+
+        writer.file.write_all(&record).await?;
+        // tokio completes the write in the background otherwise
+        writer.file.flush().await?;
+
+        let location = Location {
+            segment: writer.segment,
+            offset: writer.len + RECORD_HEADER_LEN as u64,
+            len: payload.len() as u32,
+        };
+        writer.len += record_len;
+        let segment = writer.segment;
+        writer.usage.entry(segment).or_default().len += record_len;
+        self.used_space.increase(record_len as usize);
+
+        Ok(location)
+    }
+
+    // Drops the chunk from the index, recording it in the log so it's not
+    // found again when reopening the store. Its space is released once compacted away.
+    async fn remove(&self, name: &XorName) -> Result<()> {
+        let mut writer = self.writer.lock().await;
+        if !self.index_read().contains_key(name) {
+            return Err(Error::ChunkNotFound(*name));
+        }
+
+        let location = self.append(&mut writer, RECORD_DELETE, name, &[]).await?;
+        writer.add_garbage(location.segment, record_len(0));
+        if let Some(removed) = self.index_write().remove(name) {
+            writer.add_garbage(removed.segment, record_len(removed.len));
+        }
+
+        self.compact(&mut writer).await
+    }
+
+    // Keeps the content of a chunk which doesn't match its address aside for inspection.
+    async fn quarantine(&self, addr: &ChunkAddress, content: &[u8]) -> Result<()> {
+        warn!(
+            "ChunkLogStore: content of chunk {:?} doesn't match its address, quarantining it",
+            addr
+        );
+        tokio::fs::create_dir_all(&self.quarantine_path).await?;
+        tokio::fs::write(
+            self.quarantine_path.join(addr.encode_to_zbase32()?),
+            content,
+        )
+        .await?;
+        Ok(())
+    }
+
+    // Compacts the segments no longer appended to once at least half of their content is garbage.
+    async fn compact(&self, writer: &mut SegmentWriter) -> Result<()> {
+        let segments: Vec<_> = writer
+            .usage
+            .iter()
+            .filter(|(segment, usage)| {
+                **segment != writer.segment && usage.garbage > 0 && 2 * usage.garbage >= usage.len
+            })
+            .map(|(segment, _)| *segment)
+            .collect();
+
+        for segment in segments {
+            self.compact_segment(writer, segment).await?;
+        }
+
+        Ok(())
+    }
+
+    // Appends the records of the segment which are still needed to the current segment, and then
+    // removes it: the puts of the chunks it holds the latest content of, and the deletes which
+    // could otherwise let a chunk from an older segment be found again when reopening the store.
+    async fn compact_segment(&self, writer: &mut SegmentWriter, segment: u32) -> Result<()> {
+        let path = segment_path(&self.path, segment);
+        let content = tokio::fs::read(&path).await?;
+        let has_older_segments = writer.usage.keys().next() != Some(&segment);
+
+        let mut offset = 0;
+        while offset + RECORD_HEADER_LEN <= content.len() {
+            let (kind, name, len) = decode_header(&content[offset..offset + RECORD_HEADER_LEN]);
+            let payload_offset = offset + RECORD_HEADER_LEN;
+            let payload_end = payload_offset + len as usize;
+            if payload_end > content.len() {
+                break;
+            }
+            let payload = &content[payload_offset..payload_end];
+            offset = payload_end;
+
+            if kind == RECORD_DELETE {
+                if has_older_segments && !self.index_read().contains_key(&name) {
+                    let location = self.append(writer, RECORD_DELETE, &name, &[]).await?;
+                    writer.add_garbage(location.segment, record_len(0));
+                }
+                continue;
+            }
+
+            let is_latest = matches!(
+                self.index_read().get(&name),
+                Some(location) if location.segment == segment
+                    && location.offset == payload_offset as u64
+            );
+            if !is_latest {
+                continue;
+            }
+
+            let addr = ChunkAddress(name);
+            if Chunk::new(Bytes::copy_from_slice(payload)).address() != &addr {
+                self.quarantine(&addr, payload).await?;
+                let _removed = self.index_write().remove(&name);
+                if has_older_segments {
+                    let location = self.append(writer, RECORD_DELETE, &name, &[]).await?;
+                    writer.add_garbage(location.segment, record_len(0));
+                }
+                continue;
+            }
+
+            let location = self.append(writer, RECORD_PUT, &name, payload).await?;
+            let _prev = self.index_write().insert(name, location);
+        }
+
+        tokio::fs::remove_file(&path).await?;
+        if let Some(usage) = writer.usage.remove(&segment) {
+            self.used_space.decrease(usage.len as usize);
+        }
+        debug!("ChunkLogStore: compacted segment {}", segment);
+
+        Ok(())
+    }
+
+    fn index_read(&self) -> std::sync::RwLockReadGuard<'_, BTreeMap<XorName, Location>> {
+        self.index.read().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn index_write(&self) -> std::sync::RwLockWriteGuard<'_, BTreeMap<XorName, Location>> {
+        self.index.write().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[async_trait]
+impl ChunkStorageBackend for ChunkLogStore {
+    async fn write_chunk(&self, data: &Chunk) -> Result<ChunkAddress> {
+        let addr = data.address();
+        let mut writer = self.writer.lock().await;
+        let location = self
+            .append(&mut writer, RECORD_PUT, addr.name(), data.value())
+            .await?;
+
+        if let Some(previous) = self.index_write().insert(*addr.name(), location) {
+            writer.add_garbage(previous.segment, record_len(previous.len));
+        }
+
+        self.compact(&mut writer).await?;
+        Ok(*addr)
+    }
+
+    /// A chunk which doesn't match its address is quarantined.
+    async fn read_chunk(&self, addr: &ChunkAddress) -> Result<Chunk> {
+        let bytes = loop {
+            let location = self
+                .index_read()
+                .get(addr.name())
+                .copied()
+                .ok_or(Error::ChunkNotFound(*addr.name()))?;
+
+            let mut file = match File::open(segment_path(&self.path, location.segment)).await {
+                Ok(file) => file,
+                // the segment was compacted in the meantime, moving the chunk to a newer one
+                Err(error)
+                    if error.kind() == ErrorKind::NotFound
+                        && self
+                            .index_read()
+                            .get(addr.name())
+                            .map_or(false, |latest| latest.segment != location.segment) =>
+                {
+                    continue
+                }
+                Err(error) => return Err(error.into()),
+            };
+            let _pos = file.seek(SeekFrom::Start(location.offset)).await?;
+            let mut bytes = vec![0; location.len as usize];
+            let _read = file.read_exact(&mut bytes).await?;
+            break bytes;
+        };
+
+        let chunk = Chunk::new(Bytes::from(bytes));
+        if chunk.address() != addr {
+            self.quarantine(addr, chunk.value()).await?;
+            self.remove(addr.name()).await?;
+            return Err(Error::CorruptedChunk(*addr.name()));
+        }
+        Ok(chunk)
+    }
+
+    async fn delete_chunk(&self, addr: &ChunkAddress) -> Result<()> {
+        self.remove(addr.name()).await
+    }
+
+    fn chunk_exists(&self, addr: &ChunkAddress) -> Result<bool> {
+        Ok(self.index_read().contains_key(addr.name()))
+    }
+
+    fn chunk_count(&self) -> usize {
+        self.index_read().len()
+    }
+
+    fn list_all_chunk_addresses(&self) -> Result<Vec<ChunkAddress>> {
+        Ok(self
+            .index_read()
+            .keys()
+            .map(|name| ChunkAddress(*name))
+            .collect())
+    }
+
+    fn list_chunk_addresses_with_prefix(&self, prefix: Prefix) -> Result<Vec<ChunkAddress>> {
+        Ok(self
+            .index_read()
+            .range(prefix.lower_bound()..=prefix.upper_bound())
+            .map(|(name, _)| ChunkAddress(*name))
+            .collect())
+    }
+}
+
+// Length of a record, header included, given that of its payload.
+fn record_len(payload_len: u32) -> u64 {
+    (RECORD_HEADER_LEN + payload_len as usize) as u64
+}
+
+// Kind, chunk name, and payload length of a record.
+fn decode_header(header: &[u8]) -> (u8, XorName, u32) {
+    let mut name = XorName::default();
+    name.0.copy_from_slice(&header[1..=XOR_NAME_LEN]);
+    let mut len = [0; 4];
+    len.copy_from_slice(&header[XOR_NAME_LEN + 1..RECORD_HEADER_LEN]);
+    (header[0], name, u32::from_le_bytes(len))
+}
+
+fn segment_path(dir: &Path, segment: u32) -> PathBuf {
+    dir.join(format!("segment-{:06}.{}", segment, SEGMENT_EXTENSION))
+}
+
+// Numbers of the segments found in the dir, in order.
+fn list_segments(dir: &Path) -> Result<Vec<u32>> {
+    let mut segments = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(SEGMENT_EXTENSION) {
+            continue;
+        }
+        let number = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.strip_prefix("segment-"))
+            .and_then(|number| number.parse().ok());
+        match number {
+            Some(number) => segments.push(number),
+            None => warn!("ChunkLogStore: ignoring unexpected file {}", path.display()),
+        }
+    }
+    segments.sort_unstable();
+    Ok(segments)
+}
+
+// Applies the records of a segment to the index, reading only their headers, and returns the
+// length of the segment. A record cut short at the end of the segment is truncated away.
+fn scan_segment(path: &Path, segment: u32, index: &mut BTreeMap<XorName, Location>) -> Result<u64> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let file_len = file.metadata()?.len();
+
+    let mut offset = 0;
+    let mut header = [0; RECORD_HEADER_LEN];
+    while offset < file_len {
+        let complete = match file.read_exact(&mut header) {
+            Ok(()) => true,
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof => false,
+            Err(error) => return Err(error.into()),
+        };
+
+        let (kind, name, len) = decode_header(&header);
+        let payload_offset = offset + RECORD_HEADER_LEN as u64;
+        let valid_kind = kind == RECORD_PUT || kind == RECORD_DELETE;
+        if !complete || !valid_kind || payload_offset + len as u64 > file_len {
+            warn!(
+                "ChunkLogStore: truncating incomplete record at offset {} of {}",
+                offset,
+                path.display()
+            );
+            file.set_len(offset)?;
+            return Ok(offset);
+        }
+
+        if kind == RECORD_PUT {
+            let _previous = index.insert(
+                name,
+                Location {
+                    segment,
+                    offset: payload_offset,
+                    len,
+                },
+            );
+        } else {
+            let _previous = index.remove(&name);
+        }
+
+        offset = file.seek(SeekFrom::Start(payload_offset + len as u64))?;
+    }
+
+    Ok(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::utils::random_bytes;
+
+    use super::*;
+    use tempfile::tempdir;
+
+    // Size of the segments on disk, which the store is expected to account for.
+    fn segments_len(root: &Path) -> Result<usize> {
+        let dir = root.join(CHUNK_LOG_DIR);
+        let mut len = 0;
+        for segment in list_segments(&dir)? {
+            len += std::fs::metadata(segment_path(&dir, segment))?.len() as usize;
+        }
+        Ok(len)
+    }
+
+    #[tokio::test]
+    async fn test_write_read_delete_chunks() -> Result<()> {
+        let root = tempdir()?;
+        let used_space = UsedSpace::new(usize::MAX);
+        let store = ChunkLogStore::new(root.path(), used_space.clone())?;
+
+        let chunks: Vec<Chunk> = std::iter::repeat_with(|| Chunk::new(random_bytes(100)))
+            .take(10)
+            .collect();
+        for chunk in &chunks {
+            let addr = store.write_chunk(chunk).await?;
+            assert_eq!(store.read_chunk(&addr).await?.value(), chunk.value());
+        }
+        assert_eq!(store.list_all_chunk_addresses()?.len(), chunks.len());
+
+        let addr = chunks[0].address();
+        store.delete_chunk(addr).await?;
+        assert!(!store.chunk_exists(addr)?);
+        assert!(matches!(
+            store.read_chunk(addr).await,
+            Err(Error::ChunkNotFound(name)) if name == *addr.name()
+        ));
+        assert!(matches!(
+            store.delete_chunk(addr).await,
+            Err(Error::ChunkNotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_index_and_used_space_are_restored_on_reopen() -> Result<()> {
+        let root = tempdir()?;
+        let size = 100;
+        let count = 5;
+        let used_space = UsedSpace::new(usize::MAX);
+        let store = ChunkLogStore::new(root.path(), used_space.clone())?;
+        let mut addrs = vec![];
+        for _ in 0..count + 1 {
+            addrs.push(store.write_chunk(&Chunk::new(random_bytes(size))).await?);
+        }
+        store.delete_chunk(&addrs.remove(0)).await?;
+        // headers and the deleted chunk, which isn't compacted yet, are accounted for
+        let used = segments_len(root.path())?;
+        assert_eq!(
+            used,
+            (count + 1) * record_len(size as u32) as usize + record_len(0) as usize
+        );
+        assert_eq!(used_space.used(), used);
+        drop(store);
+
+        // a record torn by a crash at the end of the segment is dropped
+        let segment = segment_path(&root.path().join(CHUNK_LOG_DIR), 0);
+        let mut file = OpenOptions::new().append(true).open(&segment)?;
+        std::io::Write::write_all(&mut file, &[RECORD_PUT, 1, 2, 3])?;
+        drop(file);
+
+        let used_space = UsedSpace::new(usize::MAX);
+        let reopened = ChunkLogStore::new(root.path(), used_space.clone())?;
+        assert_eq!(used_space.used(), used);
+        let mut listed = reopened.list_all_chunk_addresses()?;
+        listed.sort();
+        addrs.sort();
+        assert_eq!(listed, addrs);
+        for addr in &addrs {
+            assert_eq!(reopened.read_chunk(addr).await?.address(), addr);
+        }
+
+        // and it keeps appending after the last complete record
+        let chunk = Chunk::new(random_bytes(size));
+        let addr = reopened.write_chunk(&chunk).await?;
+        assert_eq!(reopened.read_chunk(&addr).await?.value(), chunk.value());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_corrupted_chunk_is_quarantined() -> Result<()> {
+        let root = tempdir()?;
+        let used_space = UsedSpace::new(usize::MAX);
+        let store = ChunkLogStore::new(root.path(), used_space.clone())?;
+
+        let chunk = Chunk::new(random_bytes(100));
+        let addr = store.write_chunk(&chunk).await?;
+        assert!(used_space.ratio() > 0.0);
+
+        // flip the content of the chunk in the segment as if it had bit-rotted
+        let segment = segment_path(&root.path().join(CHUNK_LOG_DIR), 0);
+        let mut content = std::fs::read(&segment)?;
+        let last = content.len() - 1;
+        content[last] ^= 0xff;
+        std::fs::write(&segment, content)?;
+
+        assert!(matches!(
+            store.read_chunk(&addr).await,
+            Err(Error::CorruptedChunk(name)) if name == *addr.name()
+        ));
+        assert!(!store.chunk_exists(&addr)?);
+        assert_eq!(used_space.used(), segments_len(root.path())?);
+        let quarantined = root
+            .path()
+            .join(QUARANTINE_DIR)
+            .join(addr.encode_to_zbase32()?);
+        assert_eq!(std::fs::read(quarantined)?.len(), chunk.value().len());
+
+        // it stays dropped after reopening the store
+        let reopened = ChunkLogStore::new(root.path(), UsedSpace::new(usize::MAX))?;
+        assert!(!reopened.chunk_exists(&addr)?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_space_of_deleted_and_overwritten_chunks_is_reclaimed() -> Result<()> {
+        let root = tempdir()?;
+        let dir = root.path().join(CHUNK_LOG_DIR);
+        let size = 100;
+        let max_segment_size = 4 * record_len(size as u32);
+        let used_space = UsedSpace::new(usize::MAX);
+        let store = ChunkLogStore::open(root.path(), used_space.clone(), max_segment_size)?;
+        let new_chunks = |count| {
+            std::iter::repeat_with(|| Chunk::new(random_bytes(size)))
+                .take(count)
+                .collect::<Vec<_>>()
+        };
+
+        // segment 0 is filled with chunks which are kept, but one
+        let kept = new_chunks(4);
+        let deleted = Chunk::new(random_bytes(size));
+        for chunk in [&kept[0], &kept[1], &deleted, &kept[2]] {
+            let _addr = store.write_chunk(chunk).await?;
+        }
+
+        // segment 1 ends up with garbage only: overwritten and deleted chunks, and deletes
+        let dropped = new_chunks(2);
+        for chunk in [&dropped[0], &dropped[1], &dropped[0]] {
+            let _addr = store.write_chunk(chunk).await?;
+        }
+        store.delete_chunk(deleted.address()).await?;
+        for chunk in &dropped {
+            store.delete_chunk(chunk.address()).await?;
+        }
+        assert!(segment_path(&dir, 1).exists());
+        // garbage keeps using space until it's compacted away
+        let used_with_garbage = used_space.used();
+        assert_eq!(used_with_garbage, segments_len(root.path())?);
+
+        // and is compacted away once it's no longer appended to
+        let _addr = store.write_chunk(&kept[3]).await?;
+        assert!(segment_path(&dir, 0).exists());
+        assert!(!segment_path(&dir, 1).exists());
+        assert_eq!(used_space.used(), segments_len(root.path())?);
+        assert!(used_space.used() < used_with_garbage);
+
+        // the delete of the chunk from segment 0 was kept, so it's not found again on reopening
+        drop(store);
+        let reopened = ChunkLogStore::open(root.path(), used_space.clone(), max_segment_size)?;
+        assert!(!reopened.chunk_exists(deleted.address())?);
+        for chunk in &dropped {
+            assert!(!reopened.chunk_exists(chunk.address())?);
+        }
+
+        // segment 0 is compacted too once half of it is garbage, moving its chunks to a newer one
+        reopened.delete_chunk(kept[0].address()).await?;
+        assert!(!segment_path(&dir, 0).exists());
+        for chunk in &kept[1..] {
+            assert_eq!(
+                reopened.read_chunk(chunk.address()).await?.value(),
+                chunk.value()
+            );
+        }
+
+        let used_space = UsedSpace::new(usize::MAX);
+        let reopened = ChunkLogStore::open(root.path(), used_space.clone(), max_segment_size)?;
+        let mut listed = reopened.list_all_chunk_addresses()?;
+        listed.sort();
+        let mut expected: Vec<_> = kept[1..].iter().map(|chunk| *chunk.address()).collect();
+        expected.sort();
+        assert_eq!(listed, expected);
+        assert_eq!(used_space.used(), segments_len(root.path())?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{ChunkStorageBackend, Error, Result};
+
+use crate::types::{Chunk, ChunkAddress};
+use crate::UsedSpace;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+};
+use xor_name::{Prefix, XorName};
+
+/// A store keeping chunks in memory only, e.g. for tests.
+#[derive(Clone)]
+pub(crate) struct ChunkMemoryStore {
+    chunks: Arc<RwLock<BTreeMap<XorName, Bytes>>>,
+    used_space: UsedSpace,
+}
+
+impl ChunkMemoryStore {
+    /// Creates a new, empty, `ChunkMemoryStore`.
+    pub(crate) fn new(used_space: UsedSpace) -> Self {
+        Self {
+            chunks: Arc::new(RwLock::new(BTreeMap::new())),
+            used_space,
+        }
+    }
+}
+
+#[async_trait]
+impl ChunkStorageBackend for ChunkMemoryStore {
+    async fn write_chunk(&self, chunk: &Chunk) -> Result<ChunkAddress> {
+        let addr = *chunk.address();
+        let size = chunk.value().len();
+        let previous = self
+            .chunks
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(*addr.name(), chunk.value().clone());
+
+        self.used_space.increase(size);
+        if let Some(previous) = previous {
+            self.used_space.decrease(previous.len());
+        }
+
+        Ok(addr)
+    }
+
+    async fn read_chunk(&self, addr: &ChunkAddress) -> Result<Chunk> {
+        self.chunks
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(addr.name())
+            .map(|bytes| Chunk::new(bytes.clone()))
+            .ok_or(Error::ChunkNotFound(*addr.name()))
+    }
+
+    async fn delete_chunk(&self, addr: &ChunkAddress) -> Result<()> {
+        let removed = self
+            .chunks
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(addr.name())
+            .ok_or(Error::ChunkNotFound(*addr.name()))?;
+        self.used_space.decrease(removed.len());
+        Ok(())
+    }
+
+    fn chunk_exists(&self, addr: &ChunkAddress) -> Result<bool> {
+        Ok(self
+            .chunks
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .contains_key(addr.name()))
+    }
+
+    fn chunk_count(&self) -> usize {
+        self.chunks
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .len()
+    }
+
+    fn list_all_chunk_addresses(&self) -> Result<Vec<ChunkAddress>> {
+        Ok(self
+            .chunks
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .keys()
+            .map(|name| ChunkAddress(*name))
+            .collect())
+    }
+
+    fn list_chunk_addresses_with_prefix(&self, prefix: Prefix) -> Result<Vec<ChunkAddress>> {
+        Ok(self
+            .chunks
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .range(prefix.lower_bound()..=prefix.upper_bound())
+            .map(|(name, _)| ChunkAddress(*name))
+            .collect())
+    }
+}
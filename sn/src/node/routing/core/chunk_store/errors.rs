@@ -22,6 +22,9 @@ pub enum Error {
     /// Chunk not found.
     #[error("Chunk not found: {0:?}")]
     ChunkNotFound(XorName),
+    /// Chunk content doesn't match its address.
+    #[error("Chunk content doesn't match its address: {0:?}")]
+    CorruptedChunk(XorName),
     /// Invalid filename
     #[error("Invalid chunk filename")]
     InvalidFilename,
@@ -43,7 +46,10 @@ pub enum Error {
 pub(crate) fn convert_to_error_message(error: Error) -> ErrorMessage {
     match error {
         Error::NotEnoughSpace => ErrorMessage::FailedToWriteFile,
-        Error::ChunkNotFound(xorname) => ErrorMessage::ChunkNotFound(xorname),
+        // a corrupted copy is discarded, so as far as the requester is concerned we don't hold it
+        Error::ChunkNotFound(xorname) | Error::CorruptedChunk(xorname) => {
+            ErrorMessage::ChunkNotFound(xorname)
+        }
         Error::NetworkData(error) => convert_dt_error_to_error_message(error),
         other => {
             ErrorMessage::InvalidOperation(format!("Failed to perform operation: {:?}", other))
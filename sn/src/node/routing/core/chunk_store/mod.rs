@@ -9,6 +9,7 @@
 use crate::messaging::{data::StorageLevel, system::NodeQueryResponse};
 use crate::types::{log_markers::LogMarker, Chunk, ChunkAddress};
 use std::{
+    collections::BTreeSet,
     fmt::{self, Display, Formatter},
     io::ErrorKind,
     path::Path,
@@ -16,67 +17,113 @@ use std::{
 };
 use tokio::sync::RwLock;
 use tracing::info;
+use xor_name::{Prefix, XorName};
 
 use crate::UsedSpace;
 mod errors;
 pub(crate) use errors::{convert_to_error_message, Error, Result};
 
+mod backend;
 mod chunk_disk_store;
+mod chunk_log_store;
+mod chunk_memory_store;
+pub(crate) use backend::ChunkStorageBackend;
+pub use backend::ChunkStoreKind;
 use chunk_disk_store::ChunkDiskStore;
+use chunk_log_store::ChunkLogStore;
+use chunk_memory_store::ChunkMemoryStore;
 
 /// Operations on data chunks.
 #[derive(Clone)]
 pub(crate) struct ChunkStore {
-    disk_store: ChunkDiskStore,
-    last_recorded_level: Arc<RwLock<StorageLevel>>,
+    backend: Arc<dyn ChunkStorageBackend>,
+    used_space: UsedSpace,
+    // Chunks whose local copy was found corrupted, and are being fetched again from other holders
+    refetching: Arc<RwLock<BTreeSet<XorName>>>,
 }
 
 impl ChunkStore {
-    pub(crate) fn new(path: &Path, used_space: UsedSpace) -> Result<Self> {
+    /// Creates the store with the given kind of backend, accounting for the chunks found
+    /// on disk from a previous run.
+    pub(crate) fn new(path: &Path, used_space: UsedSpace, kind: ChunkStoreKind) -> Result<Self> {
+        let backend: Arc<dyn ChunkStorageBackend> = match kind {
+            ChunkStoreKind::Disk => Arc::new(ChunkDiskStore::new(path, used_space.clone())?),
+            ChunkStoreKind::PackedLog => Arc::new(ChunkLogStore::new(path, used_space.clone())?),
+            ChunkStoreKind::Memory => Arc::new(ChunkMemoryStore::new(used_space.clone())),
+        };
+        info!("Storing chunks with the {} backend", kind);
+
         Ok(Self {
-            disk_store: ChunkDiskStore::new(path, used_space)?,
-            last_recorded_level: Arc::new(RwLock::new(StorageLevel::zero())),
+            backend,
+            used_space,
+            refetching: Arc::new(RwLock::new(BTreeSet::new())),
         })
     }
 
+    /// Returns the storage level reached if it wasn't recorded yet, e.g. when starting
+    /// with the data stored in a previous run.
+    pub(crate) fn check_storage_level(&self) -> Option<StorageLevel> {
+        self.used_space.check_level()
+    }
+
     pub(crate) fn keys(&self) -> Result<Vec<ChunkAddress>> {
-        self.disk_store.list_all_chunk_addresses()
+        self.backend.list_all_chunk_addresses()
+    }
+
+    /// Whether the chunk is stored.
+    pub(crate) fn has_chunk(&self, address: &ChunkAddress) -> Result<bool> {
+        self.backend.chunk_exists(address)
+    }
+
+    /// Number of chunks currently stored.
+    pub(crate) fn chunk_count(&self) -> usize {
+        self.backend.chunk_count()
+    }
+
+    pub(crate) fn keys_with_prefix(&self, prefix: Prefix) -> Result<Vec<ChunkAddress>> {
+        self.backend.list_chunk_addresses_with_prefix(prefix)
     }
 
     pub(crate) async fn remove_chunk(&self, address: &ChunkAddress) -> Result<()> {
         trace!("Removing chunk, {:?}", address);
-        self.disk_store.delete_chunk(address).await
+        self.backend.delete_chunk(address).await
     }
 
     pub(crate) async fn get_chunk(&self, address: &ChunkAddress) -> Result<Chunk> {
         debug!("Getting chunk {:?}", address);
 
-        match self.disk_store.read_chunk(address).await {
+        match self.backend.read_chunk(address).await {
             Ok(res) => Ok(res),
             Err(error) => match error {
                 Error::Io(io_error) if io_error.kind() == ErrorKind::NotFound => {
                     Err(Error::ChunkNotFound(*address.name()))
                 }
+                Error::CorruptedChunk(name) => {
+                    let _ = self.refetching.write().await.insert(name);
+                    Err(Error::CorruptedChunk(name))
+                }
                 something_else => Err(something_else),
             },
         }
     }
 
-    // Read chunk from local store and return NodeQueryResponse
-    pub(crate) async fn get(&self, address: &ChunkAddress) -> NodeQueryResponse {
+    // Read chunk from local store and return NodeQueryResponse, along with whether the
+    // local copy was found corrupted, in which case it needs to be fetched again
+    pub(crate) async fn get(&self, address: &ChunkAddress) -> (NodeQueryResponse, bool) {
         trace!("{:?}", LogMarker::ChunkQueryReceviedAtAdult);
-        NodeQueryResponse::GetChunk(
-            self.get_chunk(address)
-                .await
-                .map_err(convert_to_error_message),
+        let result = self.get_chunk(address).await;
+        let corrupted = matches!(result, Err(Error::CorruptedChunk(_)));
+        (
+            NodeQueryResponse::GetChunk(result.map_err(convert_to_error_message)),
+            corrupted,
         )
     }
 
-    /// Store a chunk in the local disk store
+    /// Store a chunk in the local store
     /// If that chunk was already in the local store, just overwrites it
     #[instrument(skip_all)]
-    pub(super) async fn store(&self, data: &Chunk) -> Result<Option<StorageLevel>> {
-        if self.disk_store.chunk_file_exists(data.address())? {
+    pub(crate) async fn store(&self, data: &Chunk) -> Result<Option<StorageLevel>> {
+        if self.backend.chunk_exists(data.address())? {
             info!(
                 "{}: Chunk already exists, not storing: {:?}",
                 self,
@@ -89,31 +136,18 @@ impl ChunkStore {
         // cheap extra security check for space (prone to race conditions)
         // just so we don't go too much overboard
         // should not be triggered as chunks should not be sent to full adults
-        if !self.disk_store.can_add(data.value().len()) {
+        if !self.used_space.can_add(data.value().len()) {
             return Err(Error::NotEnoughSpace);
         }
 
         // store the data
         trace!("{:?}", LogMarker::StoringChunk);
-        let _addr = self.disk_store.write_chunk(data).await?;
+        let _addr = self.backend.write_chunk(data).await?;
         trace!("{:?}", LogMarker::StoredNewChunk);
 
         // check if we've filled another apprx. 10%-points of our storage
         // if so, update the recorded level
-        let last_recorded_level = { *self.last_recorded_level.read().await };
-        if let Ok(next_level) = last_recorded_level.next() {
-            // used_space_ratio is a heavy task that's why we don't do it all the time
-            let used_space_ratio = self.disk_store.used_space_ratio();
-            let used_space_level = 10.0 * used_space_ratio;
-            // every level represents 10 percentage points
-            if used_space_level as u8 >= next_level.value() {
-                debug!("Next level for storage has been reached");
-                *self.last_recorded_level.write().await = next_level;
-                return Ok(Some(next_level));
-            }
-        }
-
-        Ok(None)
+        Ok(self.used_space.check_level())
     }
 
     /// Stores a chunk that Elders sent to it for replication.
@@ -126,6 +160,18 @@ impl ChunkStore {
         );
         self.store(&chunk).await
     }
+
+    /// Stores a chunk we fetched again from other holders after finding our copy corrupted.
+    /// Chunks we didn't ask for are ignored.
+    pub(crate) async fn store_refetched(&self, chunk: Chunk) -> Result<Option<StorageLevel>> {
+        if !self.refetching.write().await.remove(chunk.name()) {
+            debug!("Ignoring chunk we were not fetching: {:?}", chunk.name());
+            return Ok(None);
+        }
+
+        debug!("Storing re-fetched chunk: {:?}", chunk.name());
+        self.store(&chunk).await
+    }
 }
 
 impl Display for ChunkStore {
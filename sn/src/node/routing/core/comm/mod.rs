@@ -6,28 +6,34 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+mod quic;
+#[cfg(any(test, feature = "test-utils"))]
+mod simulated;
+mod transport;
+
+#[cfg(any(test, feature = "test-utils"))]
+pub use self::simulated::{LinkRule, MsgOutcome, ReplayLogEntry, SimulatedNetwork};
+
+use self::{
+    quic::QuicTransport,
+    transport::{Transport, TransportError},
+};
 use super::{msg_count::MsgCount, BackPressure};
 
-use crate::messaging::{system::LoadReport, MessageId, WireMsg};
+use crate::messaging::{system::LoadReport, MessageId, MessageType, WireMsg};
 use crate::node::error::{Error, Result};
 use crate::peer::{Peer, UnnamedPeer};
-use crate::types::log_markers::LogMarker;
 
 use bytes::Bytes;
-use futures::{
-    future::TryFutureExt,
-    stream::{FuturesUnordered, StreamExt},
-};
-use qp2p::Endpoint;
-use std::{future, net::SocketAddr};
-use tokio::{sync::mpsc, task};
-use tracing::Instrument;
+use futures::stream::{FuturesUnordered, StreamExt};
+use qp2p::config::RetryConfig;
+use std::{collections::BTreeMap, net::SocketAddr, sync::Arc};
+use tokio::sync::mpsc;
 
 // Communication component of the node to interact with other nodes.
 #[derive(Clone)]
 pub(crate) struct Comm {
-    endpoint: Endpoint,
-    event_tx: mpsc::Sender<ConnectionEvent>,
+    transport: Arc<dyn Transport>,
     msg_count: MsgCount,
     back_pressure: BackPressure,
 }
@@ -36,39 +42,26 @@ impl Drop for Comm {
     fn drop(&mut self) {
         // Close all existing connections and stop accepting new ones.
         // FIXME: this may be broken – `Comm` is clone, so this will break any clones?
-        self.endpoint.close();
+        self.transport.close();
     }
 }
 
 impl Comm {
+    // Close all existing connections and stop accepting new ones.
+    pub(crate) fn close(&self) {
+        self.transport.close();
+    }
+
     #[tracing::instrument(skip_all)]
     pub(crate) async fn new(
         local_addr: SocketAddr,
         config: qp2p::Config,
         event_tx: mpsc::Sender<ConnectionEvent>,
     ) -> Result<Self> {
-        // Don't bootstrap, just create an endpoint to listen to
-        // the incoming messages from other nodes.
-        // This also returns the a channel where we can listen for
-        // disconnection events.
-        let (endpoint, incoming_connections, _) =
-            Endpoint::new(local_addr, Default::default(), config).await?;
-
         let msg_count = MsgCount::new();
+        let transport = QuicTransport::new(local_addr, config, event_tx, msg_count.clone()).await?;
 
-        let _handle = task::spawn(
-            handle_incoming_connections(incoming_connections, event_tx.clone(), msg_count.clone())
-                .in_current_span(),
-        );
-
-        let back_pressure = BackPressure::new();
-
-        Ok(Self {
-            endpoint,
-            event_tx,
-            msg_count,
-            back_pressure,
-        })
+        Ok(Self::with_transport(Arc::new(transport), msg_count))
     }
 
     #[tracing::instrument(skip(local_addr, config, event_tx))]
@@ -78,42 +71,63 @@ impl Comm {
         config: qp2p::Config,
         event_tx: mpsc::Sender<ConnectionEvent>,
     ) -> Result<(Self, UnnamedPeer)> {
-        // Bootstrap to the network returning the connection to a node.
-        // We can use the returned channels to listen for incoming messages and disconnection events
-        let (endpoint, incoming_connections, bootstrap_peer) =
-            Endpoint::new(local_addr, bootstrap_nodes, config).await?;
-        let (bootstrap_peer, peer_incoming) = bootstrap_peer.ok_or(Error::BootstrapFailed)?;
+        let msg_count = MsgCount::new();
+        let (transport, bootstrap_peer) = QuicTransport::bootstrap(
+            local_addr,
+            bootstrap_nodes,
+            config,
+            event_tx,
+            msg_count.clone(),
+        )
+        .await?;
 
+        Ok((
+            Self::with_transport(Arc::new(transport), msg_count),
+            bootstrap_peer,
+        ))
+    }
+
+    // Same as `Comm::new`, but over the given simulated network instead of QUIC.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub(crate) fn new_simulated(
+        network: &SimulatedNetwork,
+        local_addr: SocketAddr,
+        event_tx: mpsc::Sender<ConnectionEvent>,
+    ) -> Result<Self> {
         let msg_count = MsgCount::new();
+        let transport = network.endpoint(local_addr, event_tx, msg_count.clone())?;
 
-        let _handle = task::spawn(
-            handle_incoming_connections(incoming_connections, event_tx.clone(), msg_count.clone())
-                .in_current_span(),
-        );
+        Ok(Self::with_transport(Arc::new(transport), msg_count))
+    }
 
-        let _ = task::spawn(
-            handle_incoming_messages(
-                bootstrap_peer.clone(),
-                peer_incoming,
-                event_tx.clone(),
-                msg_count.clone(),
-            )
-            .in_current_span(),
-        );
+    // Same as `Comm::bootstrap`, but over the given simulated network instead of QUIC.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub(crate) fn bootstrap_simulated(
+        network: &SimulatedNetwork,
+        local_addr: SocketAddr,
+        bootstrap_nodes: &[SocketAddr],
+        event_tx: mpsc::Sender<ConnectionEvent>,
+    ) -> Result<(Self, UnnamedPeer)> {
+        let msg_count = MsgCount::new();
+        let (transport, bootstrap_peer) =
+            network.bootstrap(local_addr, bootstrap_nodes, event_tx, msg_count.clone())?;
 
         Ok((
-            Self {
-                endpoint,
-                event_tx,
-                msg_count,
-                back_pressure: BackPressure::new(),
-            },
-            UnnamedPeer::connected(bootstrap_peer),
+            Self::with_transport(Arc::new(transport), msg_count),
+            bootstrap_peer,
         ))
     }
 
+    fn with_transport(transport: Arc<dyn Transport>, msg_count: MsgCount) -> Self {
+        Self {
+            transport,
+            msg_count,
+            back_pressure: BackPressure::new(),
+        }
+    }
+
     pub(crate) fn our_connection_info(&self) -> SocketAddr {
-        self.endpoint.public_addr()
+        self.transport.public_addr()
     }
 
     /// Sends a message on an existing connection. If no such connection exists, returns an error.
@@ -131,22 +145,12 @@ impl Comm {
 
             let bytes = wire_msg.serialize()?;
             // TODO: rework priority so this we dont need to deserialise payload to determine priority.
-            let priority = wire_msg.into_message()?.priority();
+            let msg = wire_msg.into_message()?;
+            let priority = msg.priority();
             let retries = self.back_pressure.get(&addr).await; // TODO: more laid back retries with lower priority, more aggressive with higher
 
-            let connection = if let Some(connection) = recipient.connection().await {
-                Ok(connection)
-            } else {
-                Err(None)
-            };
-
-            future::ready(connection)
-                .and_then(|connection| async move {
-                    connection
-                        .send_with(bytes, priority, Some(&retries))
-                        .await
-                        .map_err(Some)
-                })
+            self.transport
+                .send_on_existing_connection(recipient, bytes, priority, retries)
                 .await
                 .map_err(|err| {
                     error!(
@@ -160,7 +164,7 @@ impl Comm {
                 })?;
 
             // count outgoing msgs..
-            self.msg_count.increase_outgoing(addr);
+            self.msg_count.increase_outgoing(addr, &msg);
         }
 
         Ok(())
@@ -168,26 +172,7 @@ impl Comm {
 
     /// Tests whether the peer is reachable.
     pub(crate) async fn is_reachable(&self, peer: &SocketAddr) -> Result<(), Error> {
-        let qp2p_config = qp2p::Config {
-            forward_port: false,
-            ..Default::default()
-        };
-
-        let connectivity_endpoint =
-            Endpoint::new_client((self.endpoint.local_addr().ip(), 0), qp2p_config)?;
-
-        let result = connectivity_endpoint
-            .is_reachable(peer)
-            .await
-            .map_err(|err| {
-                info!("Peer {} is NOT externally reachable: {:?}", peer, err);
-                err.into()
-            })
-            .map(|()| {
-                info!("Peer {} is externally reachable.", peer);
-            });
-        connectivity_endpoint.close();
-        result
+        self.transport.is_reachable(peer).await
     }
 
     /// Sends a message to multiple recipients. Attempts to send to `delivery_group_size`
@@ -233,14 +218,15 @@ impl Comm {
         }
 
         let msg_bytes = wire_msg.serialize().map_err(Error::Messaging)?;
-        let priority = wire_msg.clone().into_message()?.priority();
+        let msg = wire_msg.clone().into_message()?;
+        let priority = msg.priority();
 
         // Run all the sends concurrently (using `FuturesUnordered`). If any of them fails, pick
         // the next recipient and try to send to them. Proceed until the needed number of sends
         // succeeds or if there are no more recipients to pick.
         let mut tasks: FuturesUnordered<_> = recipients[0..delivery_group_size]
             .iter()
-            .map(|recipient| self.send_to_one(recipient, msg_id, priority, msg_bytes.clone(), None))
+            .map(|recipient| self.send_to_one(recipient, msg_id, priority, msg_bytes.clone()))
             .collect();
 
         let mut next = delivery_group_size;
@@ -252,30 +238,13 @@ impl Comm {
                 Ok(()) => {
                     successes += 1;
                     // count outgoing msgs..
-                    self.msg_count.increase_outgoing(recipient.addr());
+                    self.msg_count.increase_outgoing(recipient.addr(), &msg);
                 }
                 Err(error) if error.is_local_close() => {
                     // The connection was closed by us which means
                     // we are terminating so let's cut this short.
                     return Err(Error::ConnectionClosed);
                 }
-                Err(SendToOneError::Send {
-                    source: qp2p::SendError::ConnectionLost(_),
-                    connection_id,
-                    reused_connection: true,
-                }) => {
-                    // We reused an existing connection, but it was lost when we tried to send. This
-                    // could indicate the connection timed out whilst it was held, or some other
-                    // transient connection issue. We don't treat this as a failed recipient, and
-                    // instead push the same recipient again, but force a reconnection.
-                    tasks.push(self.send_to_one(
-                        recipient,
-                        msg_id,
-                        priority,
-                        msg_bytes.clone(),
-                        Some(connection_id),
-                    ));
-                }
                 Err(error) => {
                     warn!("during sending, received error {:?}", error);
                     failed_recipients.push(recipient.clone());
@@ -286,7 +255,6 @@ impl Comm {
                             msg_id,
                             priority,
                             msg_bytes.clone(),
-                            None,
                         ));
                         next += 1;
                     }
@@ -321,8 +289,7 @@ impl Comm {
         msg_id: MessageId,
         msg_priority: i32,
         msg_bytes: Bytes,
-        failed_connection_id: Option<usize>,
-    ) -> (&'r Peer, Result<(), SendToOneError>) {
+    ) -> (&'r Peer, Result<(), TransportError>) {
         trace!(
             "Sending message ({} bytes, msg_id: {:?}) to {}",
             msg_bytes.len(),
@@ -333,51 +300,9 @@ impl Comm {
         // TODO: more laid back retries with lower priority, more aggressive with higher
         let retries = self.back_pressure.get(&recipient.addr()).await;
 
-        let mut reused_connection = true;
-        let connection = recipient
-            .ensure_connection(
-                |connection| Some(connection.id()) != failed_connection_id,
-                |addr| {
-                    reused_connection = false;
-                    async move {
-                        let (connection, connection_incoming) =
-                            self.endpoint.connect_to(&addr).await?;
-                        let _ = task::spawn(
-                            handle_incoming_messages(
-                                connection.clone(),
-                                connection_incoming,
-                                self.event_tx.clone(),
-                                self.msg_count.clone(),
-                            )
-                            .in_current_span(),
-                        );
-                        Ok(connection)
-                    }
-                },
-            )
-            .await
-            .map_err(SendToOneError::Connection);
-
-        if let (true, Ok(connection)) = (reused_connection, &connection) {
-            trace!(
-                connection_id = connection.id(),
-                src = %connection.remote_address(),
-                "{}",
-                LogMarker::ConnectionReused
-            );
-        }
-
-        let result = future::ready(connection)
-            .and_then(|connection| async move {
-                connection
-                    .send_with(msg_bytes, msg_priority, Some(&retries))
-                    .await
-                    .map_err(|source| SendToOneError::Send {
-                        source,
-                        connection_id: connection.id(),
-                        reused_connection,
-                    })
-            })
+        let result = self
+            .transport
+            .send(recipient, msg_bytes, msg_priority, retries)
             .await;
 
         (recipient, result)
@@ -394,6 +319,31 @@ impl Comm {
         self.back_pressure.load_report(caller).await
     }
 
+    /// Retry policies of the peers we're regulating comms with, as per the load they reported.
+    pub(crate) async fn regulated_peers(&self) -> BTreeMap<SocketAddr, RetryConfig> {
+        self.back_pressure.regulated_peers().await
+    }
+
+    /// The latest load report we've evaluated for a peer calling us, if any.
+    pub(crate) async fn latest_load_report(&self) -> Option<LoadReport> {
+        self.back_pressure.latest_load_report().await
+    }
+
+    /// Counts a msg received from the network, once its payload has been deserialised.
+    pub(crate) fn count_incoming(&self, msg: &MessageType) {
+        self.msg_count.increase_incoming_kind(msg)
+    }
+
+    /// Returns the number of incoming and outgoing msgs so far, by msg variant.
+    pub(crate) fn msg_counts_by_kind(
+        &self,
+    ) -> (BTreeMap<&'static str, usize>, BTreeMap<&'static str, usize>) {
+        (
+            self.msg_count.incoming_by_kind(),
+            self.msg_count.outgoing_by_kind(),
+        )
+    }
+
     pub(crate) fn print_stats(&self) {
         let incoming = self.msg_count.incoming();
         let outgoing = self.msg_count.outgoing();
@@ -402,94 +352,11 @@ impl Comm {
     }
 }
 
-/// Errors that can be returned from `Comm::send_to_one`.
-#[derive(Debug)]
-enum SendToOneError {
-    Connection(qp2p::ConnectionError),
-    Send {
-        source: qp2p::SendError,
-        connection_id: usize,
-        reused_connection: bool,
-    },
-}
-
-impl SendToOneError {
-    fn is_local_close(&self) -> bool {
-        matches!(
-            self,
-            SendToOneError::Connection(qp2p::ConnectionError::Closed(qp2p::Close::Local))
-                | SendToOneError::Send {
-                    source: qp2p::SendError::ConnectionLost(qp2p::ConnectionError::Closed(
-                        qp2p::Close::Local
-                    )),
-                    ..
-                }
-        )
-    }
-}
-
 #[derive(Debug)]
 pub(crate) enum ConnectionEvent {
     Received((UnnamedPeer, Bytes)),
 }
 
-#[tracing::instrument(skip_all)]
-async fn handle_incoming_connections(
-    mut incoming_connections: qp2p::IncomingConnections,
-    event_tx: mpsc::Sender<ConnectionEvent>,
-    msg_count: MsgCount,
-) {
-    while let Some((connection, connection_incoming)) = incoming_connections.next().await {
-        trace!(
-            "incoming_connection from {:?} with connection_id {:?}",
-            connection.remote_address(),
-            connection.id()
-        );
-        let _ = task::spawn(
-            handle_incoming_messages(
-                connection,
-                connection_incoming,
-                event_tx.clone(),
-                msg_count.clone(),
-            )
-            .in_current_span(),
-        );
-    }
-}
-
-#[tracing::instrument(skip(incoming_msgs, event_tx, msg_count))]
-async fn handle_incoming_messages(
-    connection: qp2p::Connection,
-    mut incoming_msgs: qp2p::ConnectionIncoming,
-    event_tx: mpsc::Sender<ConnectionEvent>,
-    msg_count: MsgCount,
-) {
-    let connection_id = connection.id();
-    let src = connection.remote_address();
-    trace!(%connection_id, %src, "{}", LogMarker::ConnectionOpened);
-
-    while let Some(result) = incoming_msgs.next().await.transpose() {
-        match result {
-            Ok(msg) => {
-                let _send_res = event_tx
-                    .send(ConnectionEvent::Received((
-                        UnnamedPeer::connected(connection.clone()),
-                        msg,
-                    )))
-                    .await;
-                // count incoming msgs..
-                msg_count.increase_incoming(src);
-            }
-            Err(error) => {
-                // TODO: should we propagate this?
-                warn!("error on connection with {}: {:?}", src, error);
-            }
-        }
-    }
-
-    trace!(%connection_id, %src, "{}", LogMarker::ConnectionClosed);
-}
-
 /// Returns the status of the send operation.
 #[derive(Debug, Clone)]
 pub(crate) enum SendStatus {
@@ -508,7 +375,7 @@ mod tests {
     use assert_matches::assert_matches;
     use eyre::Result;
     use futures::future;
-    use qp2p::Config;
+    use qp2p::{Config, Endpoint};
     use rand::rngs::OsRng;
     use std::{net::Ipv4Addr, time::Duration};
     use tokio::{net::UdpSocket, sync::mpsc, time};
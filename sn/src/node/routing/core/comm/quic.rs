@@ -0,0 +1,318 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    transport::{Transport, TransportError},
+    ConnectionEvent, MsgCount,
+};
+
+use crate::node::error::{Error, Result};
+use crate::peer::{Peer, UnnamedPeer};
+use crate::types::log_markers::LogMarker;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::future::TryFutureExt;
+use qp2p::{config::RetryConfig, Endpoint};
+use std::{future, net::SocketAddr};
+use tokio::{sync::mpsc, task};
+use tracing::Instrument;
+
+// `Transport` over QUIC, using qp2p.
+pub(super) struct QuicTransport {
+    endpoint: Endpoint,
+    event_tx: mpsc::Sender<ConnectionEvent>,
+    msg_count: MsgCount,
+}
+
+impl QuicTransport {
+    pub(super) async fn new(
+        local_addr: SocketAddr,
+        config: qp2p::Config,
+        event_tx: mpsc::Sender<ConnectionEvent>,
+        msg_count: MsgCount,
+    ) -> Result<Self> {
+        // Don't bootstrap, just create an endpoint to listen to
+        // the incoming messages from other nodes.
+        // This also returns the a channel where we can listen for
+        // disconnection events.
+        let (endpoint, incoming_connections, _) =
+            Endpoint::new(local_addr, Default::default(), config).await?;
+
+        let _handle = task::spawn(
+            handle_incoming_connections(incoming_connections, event_tx.clone(), msg_count.clone())
+                .in_current_span(),
+        );
+
+        Ok(Self {
+            endpoint,
+            event_tx,
+            msg_count,
+        })
+    }
+
+    pub(super) async fn bootstrap(
+        local_addr: SocketAddr,
+        bootstrap_nodes: &[SocketAddr],
+        config: qp2p::Config,
+        event_tx: mpsc::Sender<ConnectionEvent>,
+        msg_count: MsgCount,
+    ) -> Result<(Self, UnnamedPeer)> {
+        // Bootstrap to the network returning the connection to a node.
+        // We can use the returned channels to listen for incoming messages and disconnection events
+        let (endpoint, incoming_connections, bootstrap_peer) =
+            Endpoint::new(local_addr, bootstrap_nodes, config).await?;
+        let (bootstrap_peer, peer_incoming) = bootstrap_peer.ok_or(Error::BootstrapFailed)?;
+
+        let _handle = task::spawn(
+            handle_incoming_connections(incoming_connections, event_tx.clone(), msg_count.clone())
+                .in_current_span(),
+        );
+
+        let _handle = task::spawn(
+            handle_incoming_messages(
+                bootstrap_peer.clone(),
+                peer_incoming,
+                event_tx.clone(),
+                msg_count.clone(),
+            )
+            .in_current_span(),
+        );
+
+        Ok((
+            Self {
+                endpoint,
+                event_tx,
+                msg_count,
+            },
+            UnnamedPeer::connected(bootstrap_peer),
+        ))
+    }
+
+    // Helper to send a message to a single recipient, reusing any existing connection to it unless
+    // it's the one which previously failed.
+    async fn send_to_one(
+        &self,
+        recipient: &Peer,
+        msg: Bytes,
+        priority: i32,
+        retries: &RetryConfig,
+        failed_connection_id: Option<usize>,
+    ) -> Result<(), SendToOneError> {
+        let mut reused_connection = true;
+        let connection = recipient
+            .ensure_connection(
+                |connection| Some(connection.id()) != failed_connection_id,
+                |addr| {
+                    reused_connection = false;
+                    async move {
+                        let (connection, connection_incoming) =
+                            self.endpoint.connect_to(&addr).await?;
+                        let _handle = task::spawn(
+                            handle_incoming_messages(
+                                connection.clone(),
+                                connection_incoming,
+                                self.event_tx.clone(),
+                                self.msg_count.clone(),
+                            )
+                            .in_current_span(),
+                        );
+                        Ok(connection)
+                    }
+                },
+            )
+            .await
+            .map_err(SendToOneError::Connection);
+
+        if let (true, Ok(connection)) = (reused_connection, &connection) {
+            trace!(
+                connection_id = connection.id(),
+                src = %connection.remote_address(),
+                "{}",
+                LogMarker::ConnectionReused
+            );
+        }
+
+        future::ready(connection)
+            .and_then(|connection| async move {
+                connection
+                    .send_with(msg, priority, Some(retries))
+                    .await
+                    .map_err(|source| SendToOneError::Send {
+                        source,
+                        connection_id: connection.id(),
+                        reused_connection,
+                    })
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl Transport for QuicTransport {
+    fn public_addr(&self) -> SocketAddr {
+        self.endpoint.public_addr()
+    }
+
+    async fn send(
+        &self,
+        recipient: &Peer,
+        msg: Bytes,
+        priority: i32,
+        retries: RetryConfig,
+    ) -> Result<(), TransportError> {
+        let mut failed_connection_id = None;
+        loop {
+            match self
+                .send_to_one(
+                    recipient,
+                    msg.clone(),
+                    priority,
+                    &retries,
+                    failed_connection_id,
+                )
+                .await
+            {
+                Err(SendToOneError::Send {
+                    source: qp2p::SendError::ConnectionLost(_),
+                    connection_id,
+                    reused_connection: true,
+                }) => {
+                    // We reused an existing connection, but it was lost when we tried to send. This
+                    // could indicate the connection timed out whilst it was held, or some other
+                    // transient connection issue. We don't treat this as a failure, and instead
+                    // try the same recipient again, but force a reconnection.
+                    failed_connection_id = Some(connection_id);
+                }
+                result => return result.map_err(TransportError::from),
+            }
+        }
+    }
+
+    async fn send_on_existing_connection(
+        &self,
+        recipient: &Peer,
+        msg: Bytes,
+        priority: i32,
+        retries: RetryConfig,
+    ) -> Result<(), TransportError> {
+        let connection = recipient
+            .connection()
+            .await
+            .ok_or(TransportError::NotConnected)?;
+
+        connection
+            .send_with(msg, priority, Some(&retries))
+            .await
+            .map_err(TransportError::Send)
+    }
+
+    async fn is_reachable(&self, peer: &SocketAddr) -> Result<()> {
+        let qp2p_config = qp2p::Config {
+            forward_port: false,
+            ..Default::default()
+        };
+
+        let connectivity_endpoint =
+            Endpoint::new_client((self.endpoint.local_addr().ip(), 0), qp2p_config)?;
+
+        let result = connectivity_endpoint
+            .is_reachable(peer)
+            .await
+            .map_err(|err| {
+                info!("Peer {} is NOT externally reachable: {:?}", peer, err);
+                err.into()
+            })
+            .map(|()| {
+                info!("Peer {} is externally reachable.", peer);
+            });
+        connectivity_endpoint.close();
+        result
+    }
+
+    fn close(&self) {
+        self.endpoint.close();
+    }
+}
+
+/// Errors that can be returned from `QuicTransport::send_to_one`.
+#[derive(Debug)]
+enum SendToOneError {
+    Connection(qp2p::ConnectionError),
+    Send {
+        source: qp2p::SendError,
+        connection_id: usize,
+        reused_connection: bool,
+    },
+}
+
+impl From<SendToOneError> for TransportError {
+    fn from(error: SendToOneError) -> Self {
+        match error {
+            SendToOneError::Connection(error) => TransportError::Connection(error),
+            SendToOneError::Send { source, .. } => TransportError::Send(source),
+        }
+    }
+}
+
+#[tracing::instrument(skip_all)]
+async fn handle_incoming_connections(
+    mut incoming_connections: qp2p::IncomingConnections,
+    event_tx: mpsc::Sender<ConnectionEvent>,
+    msg_count: MsgCount,
+) {
+    while let Some((connection, connection_incoming)) = incoming_connections.next().await {
+        trace!(
+            "incoming_connection from {:?} with connection_id {:?}",
+            connection.remote_address(),
+            connection.id()
+        );
+        let _handle = task::spawn(
+            handle_incoming_messages(
+                connection,
+                connection_incoming,
+                event_tx.clone(),
+                msg_count.clone(),
+            )
+            .in_current_span(),
+        );
+    }
+}
+
+#[tracing::instrument(skip(incoming_msgs, event_tx, msg_count))]
+async fn handle_incoming_messages(
+    connection: qp2p::Connection,
+    mut incoming_msgs: qp2p::ConnectionIncoming,
+    event_tx: mpsc::Sender<ConnectionEvent>,
+    msg_count: MsgCount,
+) {
+    let connection_id = connection.id();
+    let src = connection.remote_address();
+    trace!(%connection_id, %src, "{}", LogMarker::ConnectionOpened);
+
+    while let Some(result) = incoming_msgs.next().await.transpose() {
+        match result {
+            Ok(msg) => {
+                let _send_res = event_tx
+                    .send(ConnectionEvent::Received((
+                        UnnamedPeer::connected(connection.clone()),
+                        msg,
+                    )))
+                    .await;
+                // count incoming msgs..
+                msg_count.increase_incoming(src);
+            }
+            Err(error) => {
+                // TODO: should we propagate this?
+                warn!("error on connection with {}: {:?}", src, error);
+            }
+        }
+    }
+
+    trace!(%connection_id, %src, "{}", LogMarker::ConnectionClosed);
+}
@@ -0,0 +1,819 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! An in-memory network for nodes to talk over instead of QUIC, where messages can be dropped,
+//! delayed, reordered or cut off by partitions, so that failures can be reproduced from a seed.
+
+use super::{
+    transport::{Transport, TransportError},
+    ConnectionEvent, MsgCount,
+};
+
+use crate::node::error::{Error, Result};
+use crate::peer::{Peer, UnnamedPeer};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use qp2p::config::RetryConfig;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, BinaryHeap, HashMap, VecDeque},
+    fmt::{self, Debug, Formatter},
+    hash::{Hash, Hasher},
+    io,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex, MutexGuard, PoisonError, Weak},
+    time::Duration,
+};
+use tokio::{
+    sync::{mpsc, Notify},
+    task,
+    time::{self, Instant},
+};
+
+// Port the addresses handed out by the network start from, when asked for any port.
+const FIRST_PORT: u16 = 10_000;
+
+/// Rule applied to the messages sent over the links it matches.
+///
+/// When several rules match a link, the one added last applies.
+/// Messages over links no rule matches are delivered without delay.
+#[derive(Clone, Debug, Default)]
+pub struct LinkRule {
+    from: Option<SocketAddr>,
+    to: Option<SocketAddr>,
+    drop_rate: f64,
+    min_delay: Duration,
+    max_delay: Duration,
+}
+
+impl LinkRule {
+    /// Returns a rule matching all links, which neither drops nor delays messages.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Only match the links from the given node.
+    pub fn from(mut self, addr: SocketAddr) -> Self {
+        self.from = Some(addr);
+        self
+    }
+
+    /// Only match the links to the given node.
+    pub fn to(mut self, addr: SocketAddr) -> Self {
+        self.to = Some(addr);
+        self
+    }
+
+    /// Probability, between 0 and 1, of each message being lost.
+    pub fn drop_rate(mut self, drop_rate: f64) -> Self {
+        self.drop_rate = drop_rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Range the delay of each message is picked from. Messages picking different delays can
+    /// overtake each other.
+    pub fn delay(mut self, min_delay: Duration, max_delay: Duration) -> Self {
+        self.min_delay = min_delay;
+        self.max_delay = max_delay.max(min_delay);
+        self
+    }
+
+    fn matches(&self, from: SocketAddr, to: SocketAddr) -> bool {
+        self.from.unwrap_or(from) == from && self.to.unwrap_or(to) == to
+    }
+}
+
+/// What happened to a message sent over the network.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MsgOutcome {
+    /// The message was delivered after the given delay.
+    Delivered {
+        /// Time the message spent on the way.
+        delay: Duration,
+    },
+    /// The message was lost on the way.
+    Dropped,
+    /// The sender and the recipient are on different sides of a partition.
+    Partitioned,
+    /// There's no node at the recipient's address.
+    Unreachable,
+}
+
+/// Record of a message sent over the network.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayLogEntry {
+    /// Position of the message in the log.
+    pub seq: u64,
+    /// Address of the sender.
+    pub from: SocketAddr,
+    /// Address of the recipient.
+    pub to: SocketAddr,
+    /// Size of the message in bytes.
+    pub len: usize,
+    /// What happened to the message.
+    pub outcome: MsgOutcome,
+}
+
+/// An in-memory network the nodes of a test can be started on, see `routing::Config`.
+///
+/// Whether each message is dropped and how long it's delayed is drawn from a random generator per
+/// link, seeded from the network's seed and the link's ends, so a run can be repeated from its seed
+/// as long as the nodes are started in the same order, and send in the same order over each link.
+/// Where that isn't enough, the outcomes recorded in the [`SimulatedNetwork::replay_log`] of a run
+/// can be imposed on another one with [`SimulatedNetwork::replaying`].
+///
+/// Messages are delivered on tokio's clock, so for a run to be repeatable the nodes have to be
+/// started on a current-thread runtime with the time paused, e.g. in a
+/// `#[tokio::test(start_paused = true)]`, which also lets long delays elapse instantly.
+#[derive(Clone)]
+pub struct SimulatedNetwork {
+    state: Arc<Mutex<State>>,
+    wakeup: Arc<Notify>,
+}
+
+impl Debug for SimulatedNetwork {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let state = self.lock();
+        f.debug_struct("SimulatedNetwork")
+            .field("seed", &state.seed)
+            .field("nodes", &state.endpoints.keys().collect::<Vec<_>>())
+            .field("rules", &state.rules)
+            .field("partitions", &state.partitions)
+            .finish()
+    }
+}
+
+impl SimulatedNetwork {
+    /// Creates a network drawing the fate of the messages from the given seed.
+    pub fn new(seed: u64) -> Self {
+        let wakeup = Arc::new(Notify::new());
+        let state = State {
+            seed,
+            endpoints: BTreeMap::new(),
+            next_port: FIRST_PORT,
+            connections: BTreeSet::new(),
+            rules: Vec::new(),
+            partitions: Vec::new(),
+            links: HashMap::new(),
+            replay: HashMap::new(),
+            queue: BinaryHeap::new(),
+            log: Vec::new(),
+            scheduler_started: false,
+            wakeup: wakeup.clone(),
+        };
+
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            wakeup,
+        }
+    }
+
+    /// Creates a network which gives the messages over each link the outcomes recorded in the
+    /// log, in order, before drawing them from the seed once those run out.
+    /// Partitions and unreachable nodes are those of the new run, not the recorded ones.
+    pub fn replaying(seed: u64, log: Vec<ReplayLogEntry>) -> Self {
+        let network = Self::new(seed);
+        {
+            let mut state = network.lock();
+            let mut log = log;
+            log.sort_by_key(|entry| entry.seq);
+            for entry in log {
+                if matches!(
+                    entry.outcome,
+                    MsgOutcome::Delivered { .. } | MsgOutcome::Dropped
+                ) {
+                    state
+                        .replay
+                        .entry((entry.from, entry.to))
+                        .or_default()
+                        .push_back(entry.outcome);
+                }
+            }
+        }
+        network
+    }
+
+    /// Seed the network was created with.
+    pub fn seed(&self) -> u64 {
+        self.lock().seed
+    }
+
+    /// Addresses of the nodes currently on the network.
+    pub fn nodes(&self) -> BTreeSet<SocketAddr> {
+        self.lock().endpoints.keys().copied().collect()
+    }
+
+    /// Adds a rule, taking precedence over those added before it.
+    pub fn add_rule(&self, rule: LinkRule) {
+        self.lock().rules.push(rule);
+    }
+
+    /// Removes all the rules, so messages are delivered without delay again.
+    pub fn clear_rules(&self) {
+        self.lock().rules.clear();
+    }
+
+    /// Splits the network so nodes can only reach the nodes in the same group as them.
+    /// Nodes which aren't in any of the groups, including those started later, form one more group.
+    pub fn partition(&self, groups: Vec<BTreeSet<SocketAddr>>) {
+        self.lock().partitions = groups;
+    }
+
+    /// Removes the partition, if any.
+    pub fn heal(&self) {
+        self.lock().partitions.clear();
+    }
+
+    /// Returns the messages sent so far and what happened to them.
+    pub fn replay_log(&self) -> Vec<ReplayLogEntry> {
+        self.lock().log.clone()
+    }
+
+    // Adds a node to the network, at the given address unless its port is 0.
+    pub(super) fn endpoint(
+        &self,
+        local_addr: SocketAddr,
+        event_tx: mpsc::Sender<ConnectionEvent>,
+        msg_count: MsgCount,
+    ) -> Result<SimulatedTransport> {
+        let mut state = self.lock();
+
+        let ip = if local_addr.ip().is_unspecified() {
+            IpAddr::V4(Ipv4Addr::LOCALHOST)
+        } else {
+            local_addr.ip()
+        };
+        let addr = if local_addr.port() == 0 {
+            loop {
+                let addr = SocketAddr::new(ip, state.next_port);
+                state.next_port = state.next_port.wrapping_add(1).max(FIRST_PORT);
+                if !state.endpoints.contains_key(&addr) {
+                    break addr;
+                }
+            }
+        } else {
+            SocketAddr::new(ip, local_addr.port())
+        };
+
+        if state.endpoints.contains_key(&addr) {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("{} is already taken on the simulated network", addr),
+            )));
+        }
+
+        // Messages are handed to the node in the order they arrive, without holding up the
+        // deliveries to other nodes while it's busy.
+        let (msg_tx, mut msg_rx) = mpsc::unbounded_channel::<(SocketAddr, Bytes)>();
+        let _handle = task::spawn(async move {
+            while let Some((src, msg)) = msg_rx.recv().await {
+                let _send_res = event_tx
+                    .send(ConnectionEvent::Received((
+                        UnnamedPeer::addressed(src),
+                        msg,
+                    )))
+                    .await;
+                msg_count.increase_incoming(src);
+            }
+        });
+        let _prev = state.endpoints.insert(addr, msg_tx);
+
+        if !state.scheduler_started {
+            state.scheduler_started = true;
+            let _handle = task::spawn(run_scheduler(
+                Arc::downgrade(&self.state),
+                self.wakeup.clone(),
+            ));
+        }
+
+        trace!("Node joined the simulated network at {}", addr);
+
+        Ok(SimulatedTransport {
+            network: self.clone(),
+            addr,
+        })
+    }
+
+    // Adds a node to the network and picks the first of the bootstrap nodes it can reach.
+    pub(super) fn bootstrap(
+        &self,
+        local_addr: SocketAddr,
+        bootstrap_nodes: &[SocketAddr],
+        event_tx: mpsc::Sender<ConnectionEvent>,
+        msg_count: MsgCount,
+    ) -> Result<(SimulatedTransport, UnnamedPeer)> {
+        let transport = self.endpoint(local_addr, event_tx, msg_count)?;
+
+        let mut state = self.lock();
+        let bootstrap_addr = bootstrap_nodes
+            .iter()
+            .copied()
+            .find(|addr| state.is_reachable(transport.addr, *addr));
+
+        if let Some(addr) = bootstrap_addr {
+            let _new = state.connections.insert(link(transport.addr, addr));
+            Ok((transport, UnnamedPeer::addressed(addr)))
+        } else {
+            // `transport` is closed when dropped, once we no longer hold the lock
+            drop(state);
+            Err(Error::BootstrapFailed)
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        lock(&self.state)
+    }
+
+    fn send(
+        &self,
+        from: SocketAddr,
+        to: SocketAddr,
+        msg: Bytes,
+        on_existing_connection: bool,
+    ) -> Result<(), TransportError> {
+        let mut state = self.lock();
+
+        if !state.endpoints.contains_key(&from) {
+            return Err(TransportError::Closed);
+        }
+        if on_existing_connection && !state.connections.contains(&link(from, to)) {
+            return Err(TransportError::NotConnected);
+        }
+
+        let outcome = if !state.endpoints.contains_key(&to) {
+            MsgOutcome::Unreachable
+        } else if !state.in_same_partition(from, to) {
+            MsgOutcome::Partitioned
+        } else {
+            state.draw_outcome(from, to)
+        };
+
+        let seq = state.log.len() as u64;
+        state.log.push(ReplayLogEntry {
+            seq,
+            from,
+            to,
+            len: msg.len(),
+            outcome,
+        });
+        trace!(
+            "Simulated network message #{} from {} to {}: {:?}",
+            seq,
+            from,
+            to,
+            outcome
+        );
+
+        match outcome {
+            MsgOutcome::Delivered { delay } => {
+                let _new = state.connections.insert(link(from, to));
+                state.queue.push(Scheduled {
+                    deliver_at: Instant::now() + delay,
+                    seq,
+                    from,
+                    to,
+                    msg,
+                });
+                drop(state);
+                self.wakeup.notify_one();
+                Ok(())
+            }
+            // Like a packet lost after the send succeeded as far as the sender can tell
+            MsgOutcome::Dropped => Ok(()),
+            MsgOutcome::Partitioned | MsgOutcome::Unreachable => Err(TransportError::Unreachable),
+        }
+    }
+
+    fn remove(&self, addr: SocketAddr) {
+        let mut state = self.lock();
+        if state.endpoints.remove(&addr).is_some() {
+            state
+                .connections
+                .retain(|(end0, end1)| *end0 != addr && *end1 != addr);
+            trace!("Node left the simulated network at {}", addr);
+        }
+    }
+}
+
+// Node's end of the simulated network.
+pub(super) struct SimulatedTransport {
+    network: SimulatedNetwork,
+    addr: SocketAddr,
+}
+
+impl Drop for SimulatedTransport {
+    fn drop(&mut self) {
+        self.network.remove(self.addr);
+    }
+}
+
+#[async_trait]
+impl Transport for SimulatedTransport {
+    fn public_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    async fn send(
+        &self,
+        recipient: &Peer,
+        msg: Bytes,
+        _priority: i32,
+        _retries: RetryConfig,
+    ) -> Result<(), TransportError> {
+        self.network.send(self.addr, recipient.addr(), msg, false)
+    }
+
+    async fn send_on_existing_connection(
+        &self,
+        recipient: &Peer,
+        msg: Bytes,
+        _priority: i32,
+        _retries: RetryConfig,
+    ) -> Result<(), TransportError> {
+        self.network.send(self.addr, recipient.addr(), msg, true)
+    }
+
+    async fn is_reachable(&self, peer: &SocketAddr) -> Result<()> {
+        if self.network.lock().is_reachable(self.addr, *peer) {
+            Ok(())
+        } else {
+            Err(Error::NodeNotReachable(*peer))
+        }
+    }
+
+    fn close(&self) {
+        self.network.remove(self.addr);
+    }
+}
+
+struct State {
+    seed: u64,
+    // Channel to the task handing the messages to each node
+    endpoints: BTreeMap<SocketAddr, mpsc::UnboundedSender<(SocketAddr, Bytes)>>,
+    next_port: u16,
+    // Pairs of nodes which exchanged messages, as `link` returns them
+    connections: BTreeSet<(SocketAddr, SocketAddr)>,
+    rules: Vec<LinkRule>,
+    partitions: Vec<BTreeSet<SocketAddr>>,
+    // Random generator of each link from a node to another
+    links: HashMap<(SocketAddr, SocketAddr), StdRng>,
+    // Outcomes left to replay over each link from a node to another
+    replay: HashMap<(SocketAddr, SocketAddr), VecDeque<MsgOutcome>>,
+    queue: BinaryHeap<Scheduled>,
+    log: Vec<ReplayLogEntry>,
+    scheduler_started: bool,
+    wakeup: Arc<Notify>,
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        // Let the scheduler find out the network is gone
+        self.wakeup.notify_one();
+    }
+}
+
+impl State {
+    fn is_reachable(&self, from: SocketAddr, to: SocketAddr) -> bool {
+        self.endpoints.contains_key(&to) && self.in_same_partition(from, to)
+    }
+
+    fn in_same_partition(&self, addr0: SocketAddr, addr1: SocketAddr) -> bool {
+        let group0 = self
+            .partitions
+            .iter()
+            .position(|group| group.contains(&addr0));
+        let group1 = self
+            .partitions
+            .iter()
+            .position(|group| group.contains(&addr1));
+        group0 == group1
+    }
+
+    fn draw_outcome(&mut self, from: SocketAddr, to: SocketAddr) -> MsgOutcome {
+        if let Some(outcome) = self
+            .replay
+            .get_mut(&(from, to))
+            .and_then(|outcomes| outcomes.pop_front())
+        {
+            return outcome;
+        }
+
+        let rule = self
+            .rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(from, to))
+            .cloned()
+            .unwrap_or_default();
+
+        let seed = self.seed;
+        let rng = self.links.entry((from, to)).or_insert_with(|| {
+            let mut hasher = DefaultHasher::new();
+            (seed, from, to).hash(&mut hasher);
+            StdRng::seed_from_u64(hasher.finish())
+        });
+
+        // Always draw both, so the rules don't shift what the following messages draw
+        let dropped = rng.gen_bool(rule.drop_rate);
+        let delay = if rule.max_delay > rule.min_delay {
+            let min = rule.min_delay.as_nanos() as u64;
+            let max = rule.max_delay.as_nanos() as u64;
+            Duration::from_nanos(rng.gen_range(min, max))
+        } else {
+            let _unused: u64 = rng.gen();
+            rule.min_delay
+        };
+
+        if dropped {
+            MsgOutcome::Dropped
+        } else {
+            MsgOutcome::Delivered { delay }
+        }
+    }
+
+    // Hands the messages due to their recipients, returning when the next one is due, if any.
+    fn deliver_due(&mut self, now: Instant) -> Option<Instant> {
+        while let Some(scheduled) = self.queue.peek() {
+            if scheduled.deliver_at > now {
+                return Some(scheduled.deliver_at);
+            }
+            if let Some(scheduled) = self.queue.pop() {
+                // The recipient may have left since
+                if let Some(msg_tx) = self.endpoints.get(&scheduled.to) {
+                    let _send_res = msg_tx.send((scheduled.from, scheduled.msg));
+                }
+            }
+        }
+        None
+    }
+}
+
+// Message on its way, ordered so the heap pops the one due first, and the one sent first among
+// those due at the same time.
+struct Scheduled {
+    deliver_at: Instant,
+    seq: u64,
+    from: SocketAddr,
+    to: SocketAddr,
+    msg: Bytes,
+}
+
+impl PartialEq for Scheduled {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Scheduled {}
+
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scheduled {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.deliver_at, other.seq).cmp(&(self.deliver_at, self.seq))
+    }
+}
+
+async fn run_scheduler(state: Weak<Mutex<State>>, wakeup: Arc<Notify>) {
+    loop {
+        let next_due = match state.upgrade() {
+            Some(state) => lock(&state).deliver_due(Instant::now()),
+            None => return,
+        };
+
+        if let Some(next_due) = next_due {
+            tokio::select! {
+                _ = time::sleep_until(next_due) => {}
+                _ = wakeup.notified() => {}
+            }
+        } else {
+            wakeup.notified().await;
+        }
+    }
+}
+
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    // The state is left consistent if a holder of the lock panicked
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Key of the connection between two nodes, regardless of which end initiated it.
+fn link(addr0: SocketAddr, addr1: SocketAddr) -> (SocketAddr, SocketAddr) {
+    (addr0.min(addr1), addr0.max(addr1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use eyre::Result;
+    use xor_name::XorName;
+
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    #[tokio::test(start_paused = true)]
+    async fn delivers_in_order_without_rules() -> Result<()> {
+        let network = SimulatedNetwork::new(0);
+        let (node0, _rx0) = new_node(&network)?;
+        let (node1, mut rx1) = new_node(&network)?;
+        let peer1 = Peer::new(XorName::random(), node1.public_addr());
+
+        for i in 0..10_u8 {
+            node0
+                .send(&peer1, Bytes::from(vec![i]), 0, RetryConfig::default())
+                .await
+                .map_err(|err| eyre::eyre!("{:?}", err))?;
+        }
+
+        for i in 0..10_u8 {
+            let (src, msg) = recv(&mut rx1).await?;
+            assert_eq!(src, node0.public_addr());
+            assert_eq!(msg, Bytes::from(vec![i]));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drops_all_with_full_drop_rate() -> Result<()> {
+        let network = SimulatedNetwork::new(0);
+        let (node0, _rx0) = new_node(&network)?;
+        let (node1, mut rx1) = new_node(&network)?;
+        network.add_rule(LinkRule::all().to(node1.public_addr()).drop_rate(1.0));
+
+        let peer1 = Peer::new(XorName::random(), node1.public_addr());
+        assert_matches!(
+            node0
+                .send(
+                    &peer1,
+                    Bytes::from_static(b"lost"),
+                    0,
+                    RetryConfig::default()
+                )
+                .await,
+            Ok(())
+        );
+        assert!(time::timeout(TIMEOUT, rx1.recv()).await.is_err());
+        assert_matches!(
+            network.replay_log().as_slice(),
+            [ReplayLogEntry {
+                outcome: MsgOutcome::Dropped,
+                ..
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn partition_cuts_off_until_healed() -> Result<()> {
+        let network = SimulatedNetwork::new(0);
+        let (node0, _rx0) = new_node(&network)?;
+        let (node1, mut rx1) = new_node(&network)?;
+        let peer1 = Peer::new(XorName::random(), node1.public_addr());
+
+        network.partition(vec![BTreeSet::from([node0.public_addr()])]);
+        assert_matches!(
+            node0
+                .send(
+                    &peer1,
+                    Bytes::from_static(b"cut"),
+                    0,
+                    RetryConfig::default()
+                )
+                .await,
+            Err(TransportError::Unreachable)
+        );
+        assert!(node0.is_reachable(&node1.public_addr()).await.is_err());
+
+        network.heal();
+        node0
+            .send(
+                &peer1,
+                Bytes::from_static(b"healed"),
+                0,
+                RetryConfig::default(),
+            )
+            .await
+            .map_err(|err| eyre::eyre!("{:?}", err))?;
+        assert_eq!(recv(&mut rx1).await?.1, Bytes::from_static(b"healed"));
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn same_seed_gives_same_outcomes() -> Result<()> {
+        let run = |network: SimulatedNetwork| async move {
+            network.add_rule(
+                LinkRule::all()
+                    .drop_rate(0.3)
+                    .delay(Duration::from_millis(1), Duration::from_millis(5)),
+            );
+            let (node0, _rx0) = new_node(&network)?;
+            let (node1, _rx1) = new_node(&network)?;
+            let peer1 = Peer::new(XorName::random(), node1.public_addr());
+            for _ in 0..50 {
+                node0
+                    .send(
+                        &peer1,
+                        Bytes::from_static(b"msg"),
+                        0,
+                        RetryConfig::default(),
+                    )
+                    .await
+                    .map_err(|err| eyre::eyre!("{:?}", err))?;
+            }
+            Ok::<_, eyre::Report>(network.replay_log())
+        };
+
+        let log = run(SimulatedNetwork::new(7)).await?;
+        assert!(log.iter().any(|entry| entry.outcome == MsgOutcome::Dropped));
+        assert_eq!(run(SimulatedNetwork::new(7)).await?, log);
+        assert_ne!(run(SimulatedNetwork::new(8)).await?, log);
+        assert_eq!(run(SimulatedNetwork::replaying(8, log.clone())).await?, log);
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn existing_connection_required() -> Result<()> {
+        let network = SimulatedNetwork::new(0);
+        let (node0, _rx0) = new_node(&network)?;
+        let (node1, mut rx1) = new_node(&network)?;
+        let peer0 = Peer::new(XorName::random(), node0.public_addr());
+        let peer1 = Peer::new(XorName::random(), node1.public_addr());
+
+        assert_matches!(
+            node1
+                .send_on_existing_connection(
+                    &peer0,
+                    Bytes::from_static(b"reply"),
+                    0,
+                    RetryConfig::default()
+                )
+                .await,
+            Err(TransportError::NotConnected)
+        );
+
+        node0
+            .send(
+                &peer1,
+                Bytes::from_static(b"request"),
+                0,
+                RetryConfig::default(),
+            )
+            .await
+            .map_err(|err| eyre::eyre!("{:?}", err))?;
+        let _msg = recv(&mut rx1).await?;
+        node1
+            .send_on_existing_connection(
+                &peer0,
+                Bytes::from_static(b"reply"),
+                0,
+                RetryConfig::default(),
+            )
+            .await
+            .map_err(|err| eyre::eyre!("{:?}", err))?;
+
+        node0.close();
+        assert_matches!(
+            node0
+                .send(&peer1, Bytes::from_static(b"closed"), 0, RetryConfig::default())
+                .await,
+            Err(error) if error.is_local_close()
+        );
+
+        Ok(())
+    }
+
+    fn new_node(
+        network: &SimulatedNetwork,
+    ) -> Result<(SimulatedTransport, mpsc::Receiver<ConnectionEvent>)> {
+        let (tx, rx) = mpsc::channel(100);
+        let transport = network.endpoint(
+            SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+            tx,
+            MsgCount::new(),
+        )?;
+        Ok((transport, rx))
+    }
+
+    async fn recv(rx: &mut mpsc::Receiver<ConnectionEvent>) -> Result<(SocketAddr, Bytes)> {
+        match time::timeout(TIMEOUT, rx.recv()).await? {
+            Some(ConnectionEvent::Received((src, msg))) => Ok((src.addr(), msg)),
+            None => Err(eyre::eyre!("Connection events channel closed")),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::error::Result;
+use crate::peer::Peer;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use qp2p::config::RetryConfig;
+use std::net::SocketAddr;
+
+/// The network layer `Comm` sends messages through, and receives them from.
+///
+/// Incoming messages are pushed by the transport to the `ConnectionEvent` channel it was created
+/// with, so all that's left to abstract over is how messages leave the node.
+#[async_trait]
+pub(crate) trait Transport: Send + Sync {
+    /// Address other nodes can reach us at.
+    fn public_addr(&self) -> SocketAddr;
+
+    /// Sends a message to the peer, connecting to it first if needed.
+    async fn send(
+        &self,
+        recipient: &Peer,
+        msg: Bytes,
+        priority: i32,
+        retries: RetryConfig,
+    ) -> Result<(), TransportError>;
+
+    /// Sends a message to the peer only if we're already connected to it.
+    async fn send_on_existing_connection(
+        &self,
+        recipient: &Peer,
+        msg: Bytes,
+        priority: i32,
+        retries: RetryConfig,
+    ) -> Result<(), TransportError>;
+
+    /// Tests whether the peer is reachable from outside.
+    async fn is_reachable(&self, peer: &SocketAddr) -> Result<()>;
+
+    /// Closes all existing connections and stops accepting new ones.
+    fn close(&self);
+}
+
+/// Errors that can be returned when sending through a `Transport`.
+#[derive(Debug)]
+pub(crate) enum TransportError {
+    /// Failed to connect to the peer.
+    Connection(qp2p::ConnectionError),
+    /// Failed to send the message over the connection to the peer.
+    Send(qp2p::SendError),
+    /// We're not connected to the peer.
+    NotConnected,
+    /// The peer couldn't be reached.
+    #[cfg(any(test, feature = "test-utils"))]
+    Unreachable,
+    /// Our end of the transport was closed.
+    #[cfg(any(test, feature = "test-utils"))]
+    Closed,
+}
+
+impl TransportError {
+    /// Whether the error is due to us having closed the transport.
+    pub(crate) fn is_local_close(&self) -> bool {
+        match self {
+            #[cfg(any(test, feature = "test-utils"))]
+            TransportError::Closed => true,
+            TransportError::Connection(qp2p::ConnectionError::Closed(qp2p::Close::Local))
+            | TransportError::Send(qp2p::SendError::ConnectionLost(
+                qp2p::ConnectionError::Closed(qp2p::Close::Local),
+            )) => true,
+            _ => false,
+        }
+    }
+}
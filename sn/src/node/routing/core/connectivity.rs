@@ -6,6 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use crate::messaging::system::SystemMsg;
 use crate::node::{
     error::Result,
     routing::{
@@ -36,6 +37,18 @@ impl Core {
         Ok(commands)
     }
 
+    /// Asks our elders to take us offline, while handing off our chunks to the adults
+    /// that will hold them once we're gone.
+    pub(crate) async fn leave_section(&self) -> Result<Vec<Command>> {
+        info!("Leaving the section");
+        let mut commands = vec![
+            self.send_message_to_our_elders(SystemMsg::LeaveRequest)
+                .await?,
+        ];
+        commands.extend(self.hand_off_chunks().await?);
+        Ok(commands)
+    }
+
     pub(crate) async fn propose_offline(&self, name: XorName) -> Result<Vec<Command>> {
         self.cast_offline_proposals(&iter::once(name).collect())
             .await
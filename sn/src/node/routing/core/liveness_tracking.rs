@@ -11,7 +11,7 @@ use crate::node::routing::XorName;
 use crate::messaging::data::OperationId;
 use dashmap::DashMap;
 use itertools::Itertools;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -115,6 +115,21 @@ impl Liveness {
         has_removed
     }
 
+    /// Number of operations still pending against each node.
+    pub(crate) async fn pending_ops(&self) -> BTreeMap<XorName, usize> {
+        let entries: Vec<_> = self
+            .unfulfilled_requests
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect();
+
+        let mut pending_ops = BTreeMap::new();
+        for (node, ops) in entries {
+            let _prev = pending_ops.insert(node, ops.read().await.len());
+        }
+        pending_ops
+    }
+
     pub(crate) fn recompute_closest_nodes(&self) {
         let all_known_nodes: Vec<_> = self
             .closest_nodes_to
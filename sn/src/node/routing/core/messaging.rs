@@ -343,13 +343,17 @@ impl Core {
 
     pub(crate) async fn send_ae_update_to_adults(&self) -> Vec<Command> {
         let adults = self.network_knowledge.live_adults().await;
+        self.send_ae_update_to_nodes(adults).await
+    }
 
+    // Sends an AE update, including our section's members, to the given nodes.
+    pub(crate) async fn send_ae_update_to_nodes(&self, recipients: Vec<Peer>) -> Vec<Command> {
         let dst_section_pk = self.network_knowledge.section_key().await;
         let node_msg = match self.generate_ae_update(dst_section_pk, true).await {
             Ok(node_msg) => node_msg,
             Err(err) => {
                 warn!(
-                    "Failed to generate AE-Update msg to send to our section's Adults: {:?}",
+                    "Failed to generate AE-Update msg to send to nodes of our section: {:?}",
                     err
                 );
                 return vec![];
@@ -358,7 +362,7 @@ impl Core {
 
         match self
             .send_direct_message_to_nodes_in_section(
-                adults,
+                recipients,
                 node_msg,
                 self.network_knowledge.prefix().await.name(),
                 dst_section_pk,
@@ -367,7 +371,10 @@ impl Core {
         {
             Ok(cmd) => vec![cmd],
             Err(err) => {
-                error!("Failed to send AE update to our adults: {:?}", err);
+                error!(
+                    "Failed to send AE update to nodes of our section: {:?}",
+                    err
+                );
                 vec![]
             }
         }
@@ -0,0 +1,91 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Core;
+
+use crate::messaging::system::LoadReport;
+use crate::node::error::Result;
+
+use std::{collections::BTreeMap, net::SocketAddr, time::Duration};
+use xor_name::{Prefix, XorName};
+
+/// Snapshot of the node's state, as exported by the metrics endpoint.
+#[derive(Clone, Debug)]
+pub(crate) struct Metrics {
+    /// Msgs received so far, by msg variant, e.g. `join_request`.
+    pub(crate) incoming_msgs: BTreeMap<&'static str, usize>,
+    /// Msgs sent so far, by msg variant.
+    pub(crate) outgoing_msgs: BTreeMap<&'static str, usize>,
+    /// Ratio of the node's max capacity currently used.
+    pub(crate) used_space_ratio: f64,
+    pub(crate) chunk_count: usize,
+    pub(crate) register_count: usize,
+    /// Chunk queries elders answered from their cache of served chunks.
+    pub(crate) chunk_cache_hits: usize,
+    /// Chunk queries elders had to forward to adults, as the chunk wasn't cached.
+    pub(crate) chunk_cache_misses: usize,
+    /// Chunks currently in the cache of served chunks.
+    pub(crate) chunk_cache_len: usize,
+    /// Operations still pending against each adult, as tracked by elders.
+    pub(crate) pending_ops: BTreeMap<XorName, usize>,
+    /// Initial retry interval towards each node we're regulating comms with due to their load.
+    pub(crate) regulated_peers: BTreeMap<SocketAddr, Duration>,
+    /// The latest load report we've evaluated for a node calling us.
+    pub(crate) load_report: Option<LoadReport>,
+    pub(crate) is_elder: bool,
+    pub(crate) elder_count: usize,
+    pub(crate) adult_count: usize,
+    pub(crate) prefix: Prefix,
+    /// DKG sessions we're participating in which are still in progress.
+    pub(crate) dkg_sessions_in_progress: usize,
+    /// DKG sessions we've participated in which have completed, with success or failure.
+    pub(crate) dkg_sessions_complete: usize,
+    /// DKG sessions we've been notified of, as participant or observer.
+    pub(crate) dkg_sessions_known: usize,
+}
+
+impl Core {
+    pub(crate) async fn metrics(&self) -> Result<Metrics> {
+        let (incoming_msgs, outgoing_msgs) = self.comm.msg_counts_by_kind();
+        let regulated_peers = self
+            .comm
+            .regulated_peers()
+            .await
+            .into_iter()
+            .map(|(addr, cfg)| (addr, cfg.initial_retry_interval))
+            .collect();
+        let (dkg_sessions_in_progress, dkg_sessions_complete) = self.dkg_voter.session_counts();
+        let (chunk_cache_hits, chunk_cache_misses, chunk_cache_len) =
+            self.served_chunks.stats().await;
+
+        Ok(Metrics {
+            incoming_msgs,
+            outgoing_msgs,
+            used_space_ratio: self.used_space.ratio(),
+            chunk_count: self.chunk_storage.chunk_count(),
+            register_count: self.register_storage.register_count(),
+            chunk_cache_hits,
+            chunk_cache_misses,
+            chunk_cache_len,
+            pending_ops: self.liveness.pending_ops().await,
+            regulated_peers,
+            load_report: self.comm.latest_load_report().await,
+            is_elder: self.is_elder().await,
+            elder_count: self
+                .network_knowledge
+                .authority_provider()
+                .await
+                .elder_count(),
+            adult_count: self.network_knowledge.adults().await.len(),
+            prefix: self.network_knowledge.prefix().await,
+            dkg_sessions_in_progress,
+            dkg_sessions_complete,
+            dkg_sessions_known: self.dkg_sessions.read().await.len(),
+        })
+    }
+}
@@ -10,6 +10,7 @@ mod api;
 mod back_pressure;
 mod bootstrap;
 mod capacity;
+mod chunk_audit;
 mod chunk_records;
 mod chunk_store;
 mod comm;
@@ -17,21 +18,27 @@ mod connectivity;
 mod delivery_group;
 mod liveness_tracking;
 mod messaging;
+mod metrics;
 mod msg_count;
 mod msg_handling;
 mod proposal;
 mod register_storage;
+mod served_chunks;
 mod split_barrier;
 
 pub(crate) use back_pressure::BackPressure;
 pub(crate) use bootstrap::{join_network, JoiningAsRelocated};
 pub(crate) use capacity::MIN_LEVEL_WHEN_FULL;
+pub use chunk_store::ChunkStoreKind;
 pub(crate) use chunk_store::{ChunkStore, Error as ChunkStoreError};
 pub(crate) use comm::{Comm, ConnectionEvent, SendStatus};
+#[cfg(any(test, feature = "test-utils"))]
+pub use comm::{LinkRule, MsgOutcome, ReplayLogEntry, SimulatedNetwork};
+pub(crate) use metrics::Metrics;
 pub(crate) use proposal::Proposal;
 pub(crate) use register_storage::RegisterStorage;
 
-use self::split_barrier::SplitBarrier;
+use self::{served_chunks::ServedChunks, split_barrier::SplitBarrier};
 
 use super::{
     super::error::Result,
@@ -55,6 +62,7 @@ use crate::UsedSpace;
 
 use backoff::ExponentialBackoff;
 use capacity::Capacity;
+use chunk_audit::ChunkAudit;
 use itertools::Itertools;
 use liveness_tracking::Liveness;
 use resource_proof::ResourceProof;
@@ -85,6 +93,11 @@ pub(crate) const CONCURRENT_JOINS: usize = 7;
 // the section).
 const CHUNK_QUERY_TIMEOUT: Duration = Duration::from_secs(60 * 5 /* 5 mins */);
 
+// How many chunks elders keep in `Core::served_chunks`, and for how long since last read, to answer
+// queries for popular content without querying the adults holding it.
+const SERVED_CHUNKS_CACHE_SIZE: usize = 64;
+const SERVED_CHUNKS_EXPIRY: Duration = Duration::from_secs(60 * 30 /* 30 mins */);
+
 // store up to 100 in use backoffs
 pub(crate) type AeBackoffCache =
     Arc<RwLock<LRUCache<(Peer, ExponentialBackoff), BACKOFF_CACHE_LIMIT>>>;
@@ -115,9 +128,12 @@ pub(crate) struct Core {
     resource_proof: ResourceProof,
     pub(super) register_storage: RegisterStorage,
     pub(super) chunk_storage: ChunkStore,
+    used_space: UsedSpace,
     capacity: Capacity,
     liveness: Liveness,
     pending_chunk_queries: Arc<Cache<XorName, Peer>>,
+    served_chunks: Arc<ServedChunks>,
+    chunk_audits: Arc<RwLock<BTreeMap<Prefix, ChunkAudit>>>,
     ae_backoff_cache: AeBackoffCache,
 }
 
@@ -132,6 +148,7 @@ impl Core {
         event_tx: mpsc::Sender<Event>,
         used_space: UsedSpace,
         root_storage_dir: PathBuf,
+        chunk_store_kind: ChunkStoreKind,
     ) -> Result<Self> {
         let section_keys_provider = SectionKeysProvider::new(section_key_share).await;
 
@@ -139,7 +156,8 @@ impl Core {
         node.addr = comm.our_connection_info();
 
         let register_storage = RegisterStorage::new(&root_storage_dir, used_space.clone())?;
-        let chunk_storage = ChunkStore::new(&root_storage_dir, used_space.clone())?;
+        let chunk_storage =
+            ChunkStore::new(&root_storage_dir, used_space.clone(), chunk_store_kind)?;
 
         let capacity = Capacity::new(BTreeMap::new());
         let adult_liveness = Liveness::new();
@@ -161,9 +179,15 @@ impl Core {
             resource_proof: ResourceProof::new(RESOURCE_PROOF_DATA_SIZE, RESOURCE_PROOF_DIFFICULTY),
             register_storage,
             chunk_storage,
+            used_space,
             capacity,
             liveness: adult_liveness,
             pending_chunk_queries: Arc::new(Cache::with_expiry_duration(CHUNK_QUERY_TIMEOUT)),
+            served_chunks: Arc::new(ServedChunks::new(
+                SERVED_CHUNKS_CACHE_SIZE,
+                SERVED_CHUNKS_EXPIRY,
+            )),
+            chunk_audits: Arc::new(RwLock::new(BTreeMap::new())),
             ae_backoff_cache: AeBackoffCache::default(),
         })
     }
@@ -6,15 +6,20 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use crate::messaging::{data::ServiceMsg, system::SystemMsg, MessageType};
+
 use dashmap::DashMap;
-use std::{net::SocketAddr, sync::Arc};
+use std::{collections::BTreeMap, net::SocketAddr, sync::Arc};
 
 type MsgRegistry = DashMap<SocketAddr, usize>;
+type MsgKindRegistry = DashMap<&'static str, usize>;
 
 #[derive(Clone)]
 pub(super) struct MsgCount {
     incoming: Arc<MsgRegistry>,
     outgoing: Arc<MsgRegistry>,
+    incoming_kinds: Arc<MsgKindRegistry>,
+    outgoing_kinds: Arc<MsgKindRegistry>,
 }
 
 #[derive(Debug)]
@@ -31,6 +36,8 @@ impl MsgCount {
         Self {
             incoming: Arc::new(MsgRegistry::new()),
             outgoing: Arc::new(MsgRegistry::new()),
+            incoming_kinds: Arc::new(MsgKindRegistry::new()),
+            outgoing_kinds: Arc::new(MsgKindRegistry::new()),
         }
     }
 
@@ -44,6 +51,23 @@ impl MsgCount {
         Self::get(&self.outgoing)
     }
 
+    /// Incoming msgs, by the kind of msg
+    pub(super) fn incoming_by_kind(&self) -> BTreeMap<&'static str, usize> {
+        Self::by_kind(&self.incoming_kinds)
+    }
+
+    /// Outgoing msgs, by the kind of msg
+    pub(super) fn outgoing_by_kind(&self) -> BTreeMap<&'static str, usize> {
+        Self::by_kind(&self.outgoing_kinds)
+    }
+
+    fn by_kind(registry: &MsgKindRegistry) -> BTreeMap<&'static str, usize> {
+        registry
+            .iter()
+            .map(|pair| (*pair.key(), *pair.value()))
+            .collect()
+    }
+
     fn get(registry: &MsgRegistry) -> MsgNumbers {
         let mut total = 0_usize;
         let mut max = 0_usize;
@@ -77,7 +101,53 @@ impl MsgCount {
         *self.incoming.entry(sender).or_insert(0) += 1;
     }
 
-    pub(super) fn increase_outgoing(&self, recipient: SocketAddr) {
+    pub(super) fn increase_incoming_kind(&self, msg: &MessageType) {
+        *self.incoming_kinds.entry(kind_label(msg)).or_insert(0) += 1;
+    }
+
+    pub(super) fn increase_outgoing(&self, recipient: SocketAddr, msg: &MessageType) {
         *self.outgoing.entry(recipient).or_insert(0) += 1;
+        *self.outgoing_kinds.entry(kind_label(msg)).or_insert(0) += 1;
+    }
+}
+
+// Label of the variant of the msg, so the msgs of each flow can be told apart
+fn kind_label(msg: &MessageType) -> &'static str {
+    match msg {
+        MessageType::System { msg, .. } => match msg {
+            SystemMsg::AntiEntropyRetry { .. } => "anti_entropy_retry",
+            SystemMsg::AntiEntropyRedirect { .. } => "anti_entropy_redirect",
+            SystemMsg::AntiEntropyUpdate { .. } => "anti_entropy_update",
+            SystemMsg::AntiEntropyProbe(_) => "anti_entropy_probe",
+            SystemMsg::BackPressure(_) => "back_pressure",
+            SystemMsg::Relocate(_) => "relocate",
+            SystemMsg::RelocatePromise(_) => "relocate_promise",
+            SystemMsg::JoinRequest(_) => "join_request",
+            SystemMsg::JoinResponse(_) => "join_response",
+            SystemMsg::JoinAsRelocatedRequest(_) => "join_as_relocated_request",
+            SystemMsg::JoinAsRelocatedResponse(_) => "join_as_relocated_response",
+            SystemMsg::DkgStart { .. } => "dkg_start",
+            SystemMsg::DkgSessionUnknown { .. } => "dkg_session_unknown",
+            SystemMsg::DkgSessionInfo { .. } => "dkg_session_info",
+            SystemMsg::DkgMessage { .. } => "dkg_message",
+            SystemMsg::DkgNotReady { .. } => "dkg_not_ready",
+            SystemMsg::DkgRetry { .. } => "dkg_retry",
+            SystemMsg::DkgFailureObservation { .. } => "dkg_failure_observation",
+            SystemMsg::DkgFailureAgreement(_) => "dkg_failure_agreement",
+            SystemMsg::Propose { .. } => "propose",
+            SystemMsg::StartConnectivityTest(_) => "start_connectivity_test",
+            SystemMsg::LeaveRequest => "leave_request",
+            SystemMsg::NodeCmd(_) => "node_cmd",
+            SystemMsg::NodeQuery(_) => "node_query",
+            SystemMsg::NodeQueryResponse { .. } => "node_query_response",
+            SystemMsg::NodeMsgError { .. } => "node_msg_error",
+        },
+        MessageType::Service { msg, .. } => match msg {
+            ServiceMsg::Cmd(_) => "service_cmd",
+            ServiceMsg::Query(_) => "service_query",
+            ServiceMsg::QueryResponse { .. } => "service_query_response",
+            ServiceMsg::CmdError { .. } => "service_cmd_error",
+            ServiceMsg::ServiceError(_) => "service_error",
+        },
     }
 }
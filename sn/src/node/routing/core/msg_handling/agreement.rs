@@ -173,6 +173,14 @@ impl Core {
 
         commands.extend(result);
 
+        // A node leaving voluntarily waits to learn it was taken offline before exiting.
+        if node_state.state() == MembershipState::Left {
+            commands.extend(
+                self.send_ae_update_to_nodes(vec![node_state.peer().clone()])
+                    .await,
+            );
+        }
+
         self.retain_members_only(
             self.network_knowledge
                 .adults()
@@ -475,7 +475,7 @@ mod tests {
             SectionKeysProvider,
         },
         node::Node,
-        SectionKeyShare, XorName, MIN_ADULT_AGE,
+        ChunkStoreKind, SectionKeyShare, XorName, MIN_ADULT_AGE,
     };
     use crate::UsedSpace;
 
@@ -709,6 +709,7 @@ mod tests {
                 mpsc::channel(1).0,
                 UsedSpace::new(max_capacity),
                 root_storage_dir,
+                ChunkStoreKind::Memory,
                 genesis_sk_set.clone(),
             )
             .await?;
@@ -20,10 +20,11 @@ use crate::messaging::{
     data::{ServiceMsg, StorageLevel},
     signature_aggregator::Error as AggregatorError,
     system::{
-        JoinRequest, JoinResponse, NodeCmd, NodeQuery, SectionAuth as SystemSectionAuth, SystemMsg,
+        JoinRequest, JoinResponse, LoadReport, NodeCmd, NodeQuery,
+        SectionAuth as SystemSectionAuth, SystemMsg,
     },
     AuthorityProof, DstLocation, MessageId, MessageType, MsgKind, NodeMsgAuthority, SectionAuth,
-    ServiceAuth, WireMsg,
+    ServiceAuth, SrcLocation, WireMsg,
 };
 use crate::node::{
     error::{Error, Result},
@@ -60,10 +61,10 @@ impl Core {
         // Apply backpressure if needed.
         if let Some(load_report) = self.comm.check_strain(sender.addr()).await {
             let msg_src = wire_msg.msg_kind().src();
-            cmds.push(Command::PrepareNodeMsgToSend {
-                msg: SystemMsg::BackPressure(load_report),
-                dst: msg_src.to_dst(),
-            })
+            cmds.push(
+                self.back_pressure_cmd(&sender, &msg_src, load_report)
+                    .await?,
+            );
         }
 
         // Deserialize the payload of the incoming message
@@ -80,6 +81,7 @@ impl Core {
                 return Ok(cmds);
             }
         };
+        self.comm.count_incoming(&message_type);
 
         match message_type {
             MessageType::System {
@@ -242,6 +244,34 @@ impl Core {
         }
     }
 
+    // Generate the command to report our load to the caller of a message. Clients can only be
+    // reached over the connection they called us on, as with responses to their queries.
+    pub(crate) async fn back_pressure_cmd(
+        &self,
+        sender: &UnnamedPeer,
+        src_location: &SrcLocation,
+        load_report: LoadReport,
+    ) -> Result<Command> {
+        let msg = SystemMsg::BackPressure(load_report);
+        if let SrcLocation::EndUser(_) = src_location {
+            let wire_msg = WireMsg::single_src(
+                &self.node.read().await.clone(),
+                src_location.to_dst(),
+                msg,
+                self.network_knowledge.section_key().await,
+            )?;
+            Ok(Command::SendMessage {
+                recipients: vec![sender.clone().named(src_location.name())],
+                wire_msg,
+            })
+        } else {
+            Ok(Command::PrepareNodeMsgToSend {
+                msg,
+                dst: src_location.to_dst(),
+            })
+        }
+    }
+
     // Handler for all system messages
     #[allow(clippy::too_many_arguments)]
     pub(crate) async fn handle_system_message(
@@ -347,6 +377,20 @@ impl Core {
 
                 Ok(vec![Command::TestConnectivity(name)])
             }
+            SystemMsg::LeaveRequest => {
+                trace!("Handling msg: LeaveRequest from {}: {:?}", sender, msg_id);
+                if self.is_not_elder().await {
+                    return Ok(vec![]);
+                }
+
+                // Only the node itself can ask to be taken offline.
+                if let NodeMsgAuthority::Node(_) = msg_authority {
+                    info!("{} asked to leave the section", src_name);
+                    self.propose_offline(src_name).await
+                } else {
+                    Err(Error::InvalidSrcLocation)
+                }
+            }
             SystemMsg::JoinAsRelocatedResponse(join_response) => {
                 trace!("Handling msg: JoinAsRelocatedResponse from {}", sender);
                 if let Some(RelocateState::InProgress(ref mut joining_as_relocated)) =
@@ -724,6 +768,20 @@ impl Core {
 
                 return self.republish_chunk(chunk).await;
             }
+            SystemMsg::NodeCmd(NodeCmd::RequestChunkDigest(prefix)) => {
+                trace!("Chunk digest for {:?} requested by {:?}", prefix, src_name);
+                self.send_chunk_digest(prefix, src_name).await
+            }
+            SystemMsg::NodeCmd(NodeCmd::ChunkDigest { prefix, digest }) => {
+                self.handle_chunk_digest(src_name, prefix, digest).await
+            }
+            SystemMsg::NodeCmd(NodeCmd::RepublishChunks(prefix)) => {
+                info!(
+                    "Republishing chunks within {:?} with MessageId {:?}",
+                    prefix, msg_id
+                );
+                self.republish_chunks(prefix).await
+            }
             SystemMsg::NodeCmd(node_cmd) => {
                 self.send_event(Event::MessageReceived {
                     msg_id,
@@ -770,6 +828,12 @@ impl Core {
                     _ => return Err(Error::InvalidQueryResponseAuthority),
                 };
 
+                if !self.is_elder().await {
+                    return self
+                        .handle_chunk_query_response_at_adult(response, sending_nodes_pk)
+                        .await;
+                }
+
                 self.handle_chunk_query_response_at_elder(
                     correlation_id,
                     response,
@@ -876,7 +940,7 @@ impl Core {
         }
     }
 
-    async fn record_if_any(&self, level: Option<StorageLevel>) -> Vec<Command> {
+    pub(super) async fn record_if_any(&self, level: Option<StorageLevel>) -> Vec<Command> {
         let mut cmds = vec![];
         if let Some(level) = level {
             info!("Storage has now passed {} % used.", 10 * level.value());
@@ -1017,7 +1081,7 @@ impl Core {
     }
 
     // TODO: Dedupe this w/ node
-    fn random_client_signature(client_msg: &ServiceMsg) -> Result<(MsgKind, Bytes)> {
+    pub(super) fn random_client_signature(client_msg: &ServiceMsg) -> Result<(MsgKind, Bytes)> {
         let mut rng = OsRng;
         let keypair = Keypair::new_ed25519(&mut rng);
         let payload = WireMsg::serialize_msg_payload(client_msg)?;
@@ -10,7 +10,7 @@ use crate::chunk_copy_count;
 use crate::dbs::convert_to_error_message as convert_db_error_to_error_message;
 use crate::messaging::{
     data::{CmdError, DataCmd, DataQuery, QueryResponse, RegisterRead, RegisterWrite, ServiceMsg},
-    system::{NodeQueryResponse, SystemMsg},
+    system::{NodeQueryResponse, SigShare, SystemMsg},
     AuthorityProof, DstLocation, EndUser, MessageId, MsgKind, NodeAuth, ServiceAuth, WireMsg,
 };
 use crate::node::{
@@ -53,11 +53,12 @@ impl Core {
         Ok(vec![command])
     }
 
-    /// Handle register commands
+    /// Handle register commands, along with the signature shares of a multi-sig owner if any
     pub(crate) async fn handle_register_write(
         &self,
         msg_id: MessageId,
         register_write: RegisterWrite,
+        sig_shares: Vec<SigShare>,
         user: Peer,
         auth: AuthorityProof<ServiceAuth>,
     ) -> Result<Vec<Command>> {
@@ -67,10 +68,14 @@ impl Core {
             register_write.address(),
         );
 
-        match self.register_storage.write(register_write, auth).await {
-            Ok(_) => {
+        match self
+            .register_storage
+            .write(register_write, sig_shares, auth)
+            .await
+        {
+            Ok(level_report) => {
                 info!("Successfully wrote Register from Message: {:?}", msg_id);
-                Ok(vec![])
+                Ok(self.record_if_any(level_report).await)
             }
             Err(error) => {
                 trace!("Problem on writing Register! {:?}", error);
@@ -171,8 +176,23 @@ impl Core {
         trace!("Handling chunk read at adult");
         let mut commands = vec![];
 
+        let (mut response, corrupted) = self.chunk_storage.get(address).await;
+        if corrupted {
+            // Our copy was quarantined, let's get it back from the other holders
+            commands.extend(self.refetch_chunk(*address).await?);
+        } else if matches!(response, NodeQueryResponse::GetChunk(Err(_))) {
+            // We may have dropped it during churn, with elders still unaware of the new holders
+            if let Some(chunk) = self.served_chunks.get(address.name()).await {
+                trace!(
+                    "Responding with chunk we dropped during churn {:?}",
+                    address
+                );
+                response = NodeQueryResponse::GetChunk(Ok(chunk));
+            }
+        }
+
         let msg = SystemMsg::NodeQueryResponse {
-            response: self.chunk_storage.get(address).await,
+            response,
             correlation_id: msg_id,
             user,
         };
@@ -189,6 +209,29 @@ impl Core {
         Ok(commands)
     }
 
+    /// Handle a chunk query response at an adult, which can only be
+    /// for a chunk we asked other holders for, to replace our corrupted copy.
+    pub(crate) async fn handle_chunk_query_response_at_adult(
+        &self,
+        response: NodeQueryResponse,
+        sending_nodes_pk: PublicKey,
+    ) -> Result<Vec<Command>> {
+        let NodeQueryResponse::GetChunk(response) = response;
+        match response {
+            Ok(chunk) => {
+                let level_report = self.chunk_storage.store_refetched(chunk).await?;
+                Ok(self.record_if_any(level_report).await)
+            }
+            Err(error) => {
+                debug!(
+                    "Node {:?} couldn't provide the chunk we are re-fetching: {:?}",
+                    sending_nodes_pk, error
+                );
+                Ok(vec![])
+            }
+        }
+    }
+
     /// Handle chunk read
     /// Records response in liveness tracking
     /// Forms a response to send to the requester
@@ -253,6 +296,10 @@ impl Core {
             return Ok(commands);
         }
 
+        if let QueryResponse::GetChunk(Ok(chunk)) = &query_response {
+            self.served_chunks.insert(chunk.clone()).await;
+        }
+
         let msg = ServiceMsg::QueryResponse {
             response: query_response,
             correlation_id,
@@ -305,7 +352,11 @@ impl Core {
             // Register
             // Commands to be handled at elder.
             ServiceMsg::Cmd(DataCmd::Register(register_write)) => {
-                self.handle_register_write(msg_id, register_write, user, auth)
+                self.handle_register_write(msg_id, register_write, vec![], user, auth)
+                    .await
+            }
+            ServiceMsg::Cmd(DataCmd::MultiSigRegister { write, sig_shares }) => {
+                self.handle_register_write(msg_id, write, sig_shares, user, auth)
                     .await
             }
             ServiceMsg::Query(DataQuery::Register(read)) => {
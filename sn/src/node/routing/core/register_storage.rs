@@ -7,18 +7,20 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::dbs::{
-    convert_to_error_message, Error, EventStore, Result, UsedSpace, SLED_FLUSH_TIME_MS,
+    convert_to_error_message, from_db_key, serialised_size, tree_stored_size, Error, EventStore,
+    Result, ToDbKey, UsedSpace, SLED_FLUSH_TIME_MS,
 };
 use crate::types::{
-    register::{Action, Register, User},
-    PublicKey, RegisterAddress as Address,
+    register::{Action, EntryHash, Register, User},
+    Error as DtError, OwnerType, PublicKey, RegisterAddress as Address,
 };
 use crate::{
     messaging::{
         data::{
-            DataCmd, OperationId, QueryResponse, RegisterCmd, RegisterDataExchange, RegisterRead,
-            RegisterWrite, ServiceMsg,
+            OperationId, QueryResponse, RegisterCmd, RegisterDataExchange, RegisterRead,
+            RegisterWrite, ServiceMsg, StorageLevel,
         },
+        system::SigShare,
         AuthorityProof, ServiceAuth, WireMsg,
     },
     types::DataAddress,
@@ -27,15 +29,18 @@ use crate::{
 use dashmap::DashMap;
 use sled::Db;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     fmt::{self, Display, Formatter},
-    path::Path,
+    path::{Path, PathBuf},
     sync::Arc,
 };
-use tracing::info;
+use tracing::{info, warn};
 use xor_name::{Prefix, XorName};
 
 const DATABASE_NAME: &str = "register";
+// Extensions of the dirs the db is compacted into, and the original moved aside to.
+const COMPACTING_EXTENSION: &str = "compacting";
+const OLD_EXTENSION: &str = "old";
 
 type RegisterOpStore = EventStore<RegisterCmd>;
 
@@ -57,19 +62,90 @@ struct StateEntry {
 impl RegisterStorage {
     /// Create new RegisterStorage
     pub(crate) fn new(path: &Path, used_space: UsedSpace) -> Result<Self> {
-        let db_dir = path.join("db").join(DATABASE_NAME.to_string());
-
+        recover_interrupted_compaction(&db_path(path))?;
         let db = sled::Config::default()
-            .path(&db_dir)
+            .path(db_path(path))
             .flush_every_ms(SLED_FLUSH_TIME_MS)
             .open()
             .map_err(Error::from)?;
 
-        Ok(Self {
+        let storage = Self {
             used_space,
             registers: Arc::new(DashMap::new()),
             db,
-        })
+        };
+        storage.load_existing_registers()?;
+
+        Ok(storage)
+    }
+
+    // Indexes the registers found in the db from a previous run, so they're loaded when first
+    // accessed, and accounts for the space they use.
+    fn load_existing_registers(&self) -> Result<()> {
+        let mut existing_space = 0;
+        for tree_name in self.db.tree_names() {
+            // skip trees which are not a register's, e.g. sled's default tree
+            let key = match std::str::from_utf8(&tree_name).ok().and_then(from_db_key) {
+                Some(key) => key,
+                None => continue,
+            };
+            let tree = self.db.open_tree(&tree_name)?;
+            if tree.is_empty() {
+                continue;
+            }
+
+            existing_space += tree_stored_size(&tree)?;
+            let _prev = self.registers.insert(key, None);
+        }
+
+        if !self.registers.is_empty() {
+            info!(
+                "Found {} existing registers using {} bytes",
+                self.registers.len(),
+                existing_space
+            );
+            self.used_space.increase(existing_space);
+        }
+
+        Ok(())
+    }
+
+    /// Number of registers currently stored.
+    pub(crate) fn register_count(&self) -> usize {
+        self.registers.len()
+    }
+
+    /// Whether a register is stored under the key, as used in `RegisterDataExchange`.
+    pub(crate) fn has_register(&self, key: &XorName) -> bool {
+        self.registers.contains_key(key)
+    }
+
+    /// Rewrites the db found under `path` into a new one, so it takes no more space on disk than
+    /// its content needs, and returns its size on disk before and after.
+    ///
+    /// The db must not be open anywhere else, e.g. by a running node. If the compaction is
+    /// interrupted, the db is recovered the next time it's opened or compacted.
+    pub(crate) fn compact(path: &Path) -> Result<(u64, u64)> {
+        let db_dir = db_path(path);
+        let compacted_dir = db_dir.with_extension(COMPACTING_EXTENSION);
+        let old_dir = db_dir.with_extension(OLD_EXTENSION);
+        recover_interrupted_compaction(&db_dir)?;
+
+        let db = sled::open(&db_dir)?;
+        let size_before = db.size_on_disk()?;
+
+        let compacted = sled::open(&compacted_dir)?;
+        compacted.import(db.export());
+        let _flushed = compacted.flush()?;
+        let size_after = compacted.size_on_disk()?;
+        drop(db);
+        drop(compacted);
+
+        std::fs::rename(&db_dir, &old_dir)?;
+        std::fs::rename(&compacted_dir, &db_dir)?;
+        std::fs::remove_dir_all(&old_dir)?;
+
+        Ok((size_before, size_after))
     }
 
     /// --- Synching ---
@@ -104,11 +180,8 @@ impl RegisterStorage {
         // todo: make outer loop parallel
         for (_, history) in data {
             for op in history {
-                let auth = WireMsg::verify_sig(
-                    op.auth.clone(),
-                    ServiceMsg::Cmd(DataCmd::Register(op.write.clone())),
-                )
-                .map_err(|_| Error::InvalidSignature(op.auth.public_key))?;
+                let auth = WireMsg::verify_sig(op.auth.clone(), ServiceMsg::Cmd(op.data_cmd()))
+                    .map_err(|_| Error::InvalidSignature(op.auth.public_key))?;
                 let _res = self.apply(op, auth)?;
             }
         }
@@ -118,43 +191,45 @@ impl RegisterStorage {
 
     /// --- Writing ---
 
+    /// Applies a write, along with the signature shares of the register's owner members if it's
+    /// co-signed by them, returning the storage level reached if it wasn't recorded yet.
     pub(crate) async fn write(
         &self,
         write: RegisterWrite,
+        sig_shares: Vec<SigShare>,
         auth: AuthorityProof<ServiceAuth>,
-    ) -> Result<()> {
-        // rough estimate ignoring the extra space used by sled
-        let required_space = std::mem::size_of::<RegisterCmd>();
-        if !self.used_space.can_add(required_space) {
-            return Err(Error::NotEnoughSpace);
-        }
+    ) -> Result<Option<StorageLevel>> {
         let op = RegisterCmd {
             write,
             auth: auth.clone().into_inner(),
+            sig_shares,
         };
-        self.apply(op, auth)
+        // the size of the op as stored, ignoring the extra space used by sled
+        let required_space = serialised_size(&op)?;
+        if !self.used_space.can_add(required_space) {
+            return Err(Error::NotEnoughSpace);
+        }
+        self.apply(op, auth)?;
+
+        Ok(self.used_space.check_level())
     }
 
     // helper that drops the sled tree for a given register
-    // decreases the used space by a rough estimate of the size before deletion
-    // as with addition this estimate ignores the extra space used by sled
-    // (that estimate can fall victim to a race condition if someone writes to a register that is being deleted)
+    // decreases the used space by the size of the ops stored before deletion
+    // as with addition this ignores the extra space used by sled
+    // (that size can fall victim to a race condition if someone writes to a register that is being deleted)
     fn drop_register_key(&self, key: XorName) -> Result<()> {
-        let tree = self.db.open_tree(key)?;
-        let len = tree.len();
-        let regcmd_size = std::mem::size_of::<RegisterCmd>();
+        let tree_name = key.to_db_key()?;
+        let tree = self.db.open_tree(&tree_name)?;
+        let key_used_space = tree_stored_size(&tree)?;
 
-        let _res = self.db.drop_tree(key)?;
+        let _res = self.db.drop_tree(&tree_name)?;
 
-        let key_used_space = len * regcmd_size;
         self.used_space.decrease(key_used_space);
         Ok(())
     }
 
     fn apply(&self, op: RegisterCmd, auth: AuthorityProof<ServiceAuth>) -> Result<()> {
-        // rough estimate ignoring the extra space used by sled
-        let required_space = std::mem::size_of::<RegisterCmd>();
-
         let RegisterCmd { write, .. } = op.clone();
 
         let address = *write.address();
@@ -168,12 +243,12 @@ impl RegisterStorage {
                 }
                 trace!("Creating new register");
                 let mut store = self.load_store(key)?;
-                let _res = store.append(op)?;
+                let stored_size = store.append(op)?;
                 let _prev = self
                     .registers
                     .insert(key, Some(StateEntry { state: map, store }));
 
-                self.used_space.increase(required_space);
+                self.used_space.increase(stored_size);
 
                 Ok(())
             }
@@ -194,8 +269,15 @@ impl RegisterStorage {
                             }
                             // TODO - Register::check_permission() doesn't support Delete yet in safe-nd
                             // register.check_permission(action, Some(auth.public_key))?;
-                            if auth.public_key != entry.state.owner() {
-                                Err(Error::InvalidOwner(auth.public_key))
+                            let requester = write_authority(
+                                &entry.state,
+                                &op.write,
+                                &op.sig_shares,
+                                auth.public_key,
+                            )
+                            .map_err(Error::NetworkData)?;
+                            if requester != entry.state.owner() {
+                                Err(Error::InvalidOwner(requester))
                             } else {
                                 info!("Deleting Register");
                                 self.drop_register_key(key)?;
@@ -218,31 +300,19 @@ impl RegisterStorage {
                 result
             }
             Edit(reg_op) => {
-                let mut cache = self
-                    .registers
-                    .get_mut(&key)
-                    .ok_or(Error::NoSuchData(DataAddress::Register(address)))?;
-                let entry = if let Some(cached_entry) = cache.as_mut() {
-                    cached_entry
-                } else {
-                    let fresh_entry = self.load_state(key)?;
-                    let _prev = cache.replace(fresh_entry);
-                    if let Some(entry) = cache.as_mut() {
-                        entry
-                    } else {
-                        return Err(Error::NoSuchData(DataAddress::Register(address)));
-                    }
-                };
-
                 info!("Editing Register");
-                entry
-                    .state
-                    .check_permissions(Action::Write, Some(auth.public_key))?;
-                let result = entry.state.apply_op(reg_op).map_err(Error::NetworkData);
+                let result = self.update_register(
+                    key,
+                    address,
+                    op,
+                    auth.public_key,
+                    |register, requester| {
+                        register.check_permissions(Action::Write, Some(requester))?;
+                        register.apply_op(reg_op)
+                    },
+                );
 
                 if result.is_ok() {
-                    entry.store.append(op)?;
-                    self.used_space.increase(required_space);
                     trace!("Editing Register success!");
                 } else {
                     trace!("Editing Register failed!");
@@ -250,9 +320,58 @@ impl RegisterStorage {
 
                 result
             }
+            SetPolicy { policy, .. } => {
+                info!("Setting Register policy");
+                self.update_register(key, address, op, auth.public_key, |register, requester| {
+                    register.set_policy(policy, Some(requester))
+                })
+            }
+            TransferOwnership { new_owner, .. } => {
+                info!("Transferring Register ownership to {:?}", new_owner);
+                self.update_register(key, address, op, auth.public_key, |register, requester| {
+                    register.transfer_ownership(new_owner, Some(requester))
+                })
+            }
         }
     }
 
+    // Applies a change to an existing register, loading it from disk if needed,
+    // and stores the op if the change was applied.
+    // The change is given the key the op is authorised by, see `write_authority`.
+    fn update_register(
+        &self,
+        key: XorName,
+        address: Address,
+        op: RegisterCmd,
+        signer: PublicKey,
+        change: impl FnOnce(&mut Register, PublicKey) -> crate::types::Result<()>,
+    ) -> Result<()> {
+        let mut cache = self
+            .registers
+            .get_mut(&key)
+            .ok_or(Error::NoSuchData(DataAddress::Register(address)))?;
+        let entry = if let Some(cached_entry) = cache.as_mut() {
+            cached_entry
+        } else {
+            let fresh_entry = self.load_state(key)?;
+            let _prev = cache.replace(fresh_entry);
+            if let Some(entry) = cache.as_mut() {
+                entry
+            } else {
+                return Err(Error::NoSuchData(DataAddress::Register(address)));
+            }
+        };
+
+        let requester = write_authority(&entry.state, &op.write, &op.sig_shares, signer)
+            .map_err(Error::NetworkData)?;
+        change(&mut entry.state, requester).map_err(Error::NetworkData)?;
+
+        let stored_size = entry.store.append(op)?;
+        self.used_space.increase(stored_size);
+
+        Ok(())
+    }
+
     /// --- Reading ---
 
     pub(crate) fn read(
@@ -272,6 +391,12 @@ impl RegisterStorage {
                 self.get_user_permissions(*address, *user, requester_pk, operation_id)
             }
             GetPolicy(address) => self.get_policy(*address, requester_pk, operation_id),
+            History { address, from } => {
+                self.get_history(*address, *from, requester_pk, operation_id)
+            }
+            GetChildren { address, hash } => {
+                self.get_children(*address, *hash, requester_pk, operation_id)
+            }
         }
     }
 
@@ -298,11 +423,17 @@ impl RegisterStorage {
         action: Action,
         requester_pk: PublicKey,
     ) -> Result<Register> {
-        let cache = self
+        let key = to_reg_key(address)?;
+        let mut cache = self
             .registers
-            .get(&to_reg_key(address)?)
+            .get_mut(&key)
             .ok_or_else(|| Error::NoSuchData(DataAddress::Register(*address)))?;
 
+        // registers found on disk at startup are only loaded when first accessed
+        if cache.is_none() {
+            let _prev = cache.replace(self.load_state(key)?);
+        }
+
         let StateEntry { state, .. } = cache
             .as_ref()
             .ok_or_else(|| Error::NoSuchData(DataAddress::Register(*address)))?;
@@ -394,6 +525,50 @@ impl RegisterStorage {
         Ok(QueryResponse::GetRegisterPolicy((result, operation_id)))
     }
 
+    fn get_history(
+        &self,
+        address: Address,
+        from: Option<EntryHash>,
+        requester_pk: PublicKey,
+        operation_id: OperationId,
+    ) -> Result<QueryResponse> {
+        let result = match self
+            .get_register(&address, Action::Read, requester_pk)
+            .and_then(|register| {
+                register
+                    .history(from, Some(requester_pk))
+                    .map_err(Error::from)
+            }) {
+            Ok(res) => Ok(res),
+            Err(Error::NoSuchData(addr)) => return Err(Error::NoSuchData(addr)),
+            Err(error) => Err(convert_to_error_message(error)),
+        };
+
+        Ok(QueryResponse::GetRegisterHistory((result, operation_id)))
+    }
+
+    fn get_children(
+        &self,
+        address: Address,
+        hash: EntryHash,
+        requester_pk: PublicKey,
+        operation_id: OperationId,
+    ) -> Result<QueryResponse> {
+        let result = match self
+            .get_register(&address, Action::Read, requester_pk)
+            .and_then(|register| {
+                register
+                    .children(hash, Some(requester_pk))
+                    .map_err(Error::from)
+            }) {
+            Ok(res) => Ok(res),
+            Err(Error::NoSuchData(addr)) => return Err(Error::NoSuchData(addr)),
+            Err(error) => Err(convert_to_error_message(error)),
+        };
+
+        Ok(QueryResponse::GetRegisterChildren((result, operation_id)))
+    }
+
     /// Load a register op store
     fn load_store(&self, id: XorName) -> Result<RegisterOpStore> {
         RegisterOpStore::new(id, self.db.clone()).map_err(Error::from)
@@ -410,9 +585,19 @@ impl RegisterStorage {
             if let New(register) = op.write {
                 reg = Some(register);
             } else if let Some(register) = &mut reg {
-                if let Edit(reg_op) = op.write {
-                    register.apply_op(reg_op).map_err(Error::NetworkData)?;
-                }
+                let requester =
+                    write_authority(register, &op.write, &op.sig_shares, op.auth.public_key)
+                        .map(Some)
+                        .map_err(Error::NetworkData)?;
+                let result = match op.write {
+                    Edit(reg_op) => register.apply_op(reg_op),
+                    SetPolicy { policy, .. } => register.set_policy(policy, requester),
+                    TransferOwnership { new_owner, .. } => {
+                        register.transfer_ownership(new_owner, requester)
+                    }
+                    New(_) | Delete(_) => Ok(()),
+                };
+                result.map_err(Error::NetworkData)?;
             }
         }
 
@@ -422,6 +607,77 @@ impl RegisterStorage {
     }
 }
 
+// Returns the key a write is authorised by, given the key which signed it: the owner's key if
+// more than the threshold of the members of a multi-sig owner co-signed it, the signer's otherwise.
+// The key of a multi-sig owner doesn't authorise writes on its own, as whoever dealt the shares of
+// the members may know its secret, or the members may aggregate their shares of another payload.
+fn write_authority(
+    register: &Register,
+    write: &RegisterWrite,
+    sig_shares: &[SigShare],
+    signer: PublicKey,
+) -> crate::types::Result<PublicKey> {
+    let key_set = match register.owner_type() {
+        OwnerType::Multi(key_set) => key_set,
+        OwnerType::Single(_) if sig_shares.is_empty() => return Ok(signer),
+        OwnerType::Single(_) => return Err(DtError::InvalidOwnerNotPublicKeySet),
+    };
+    if sig_shares.is_empty() && signer != register.owner() {
+        // e.g. a user the policy lets write on its own
+        return Ok(signer);
+    }
+
+    let payload = bincode::serialize(write).map_err(|e| DtError::Serialisation(e.to_string()))?;
+    let signers: BTreeSet<usize> = sig_shares
+        .iter()
+        .filter(|share| {
+            key_set
+                .public_key_share(share.index)
+                .verify(&share.signature_share, &payload)
+        })
+        .map(|share| share.index)
+        .collect();
+    if signers.len() > key_set.threshold() {
+        Ok(register.owner())
+    } else {
+        Err(DtError::AccessDenied(signer))
+    }
+}
+
+fn db_path(path: &Path) -> PathBuf {
+    path.join("db").join(DATABASE_NAME)
+}
+
+// Puts the db back in place if a compaction was interrupted while swapping the compacted db in,
+// and removes what's left of it otherwise.
+fn recover_interrupted_compaction(db_dir: &Path) -> Result<()> {
+    let compacted_dir = db_dir.with_extension(COMPACTING_EXTENSION);
+    let old_dir = db_dir.with_extension(OLD_EXTENSION);
+
+    if !db_dir.exists() {
+        // the compacted db is only swapped in once complete, but the original is preferred
+        if old_dir.exists() {
+            warn!("Restoring the register db left aside by an interrupted compaction");
+            std::fs::rename(&old_dir, db_dir)?;
+        } else if compacted_dir.exists() {
+            warn!("Moving in the register db compacted by an interrupted compaction");
+            std::fs::rename(&compacted_dir, db_dir)?;
+        } else {
+            return Ok(());
+        }
+    }
+
+    // the db is in place, so whatever else is left over isn't needed anymore
+    if compacted_dir.exists() {
+        std::fs::remove_dir_all(&compacted_dir)?;
+    }
+    if old_dir.exists() {
+        std::fs::remove_dir_all(&old_dir)?;
+    }
+
+    Ok(())
+}
+
 /// This also encodes the Public | Private scope,
 /// as well as the tag of the Address.
 fn to_reg_key(address: &Address) -> Result<XorName> {
@@ -440,21 +696,27 @@ impl Display for RegisterStorage {
 
 #[cfg(test)]
 mod test {
-    use super::RegisterOpStore;
-    use crate::messaging::data::{RegisterCmd, RegisterWrite};
-    use crate::messaging::ServiceAuth;
+    use super::{db_path, RegisterOpStore, RegisterStorage, COMPACTING_EXTENSION, OLD_EXTENSION};
+    use crate::dbs::Error as DbError;
+    use crate::messaging::data::{
+        DataCmd, QueryResponse, RegisterCmd, RegisterRead, RegisterWrite, ServiceMsg,
+    };
+    use crate::messaging::{AuthorityProof, ServiceAuth, WireMsg};
     use crate::node::Result;
+    use crate::UsedSpace;
 
+    use crate::client::MultiSigRegisterWrite;
     use crate::node::Error;
     use crate::types::{
-        register::{PublicPermissions, PublicPolicy, Register, User},
-        Keypair,
+        register::{Permissions, PublicPermissions, PublicPolicy, Register, User},
+        Error as DtError, Keypair, PublicKey, Signature,
     };
+    use bls::SecretKeySet;
     use rand::rngs::OsRng;
-    use std::collections::BTreeMap;
+    use std::collections::{BTreeMap, BTreeSet};
     use std::path::Path;
     use tempfile::tempdir;
-    use xor_name::XorName;
+    use xor_name::{Prefix, XorName};
 
     #[tokio::test(flavor = "multi_thread")]
     async fn appends_and_reads_from_store() -> Result<()> {
@@ -494,9 +756,13 @@ mod test {
             signature: authority_keypair1.sign(b""),
         };
 
-        let cmd = RegisterCmd { write, auth };
+        let cmd = RegisterCmd {
+            write,
+            auth,
+            sig_shares: vec![],
+        };
 
-        store.append(cmd.clone())?;
+        let _ = store.append(cmd.clone())?;
 
         let events = store.get_all()?;
         assert_eq!(events.len(), 1);
@@ -508,4 +774,384 @@ mod test {
 
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn registers_and_used_space_are_restored_on_reopen() -> Result<()> {
+        let tmp_dir = tempdir()?;
+        let max_capacity = 10 * 1024;
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+
+        let used_space = UsedSpace::new(max_capacity);
+        let storage = RegisterStorage::new(tmp_dir.path(), used_space.clone())?;
+        let register = new_public_register(&keypair);
+        let address = *register.address();
+        let write = RegisterWrite::New(register);
+        let _ = storage
+            .write(write.clone(), vec![], sign(&keypair, write)?)
+            .await?;
+        let _ = storage.db.flush()?;
+        let used_space_ratio = used_space.ratio();
+        assert!(used_space_ratio > 0.0);
+        drop(storage);
+
+        let used_space = UsedSpace::new(max_capacity);
+        let storage = RegisterStorage::new(tmp_dir.path(), used_space.clone())?;
+        assert_eq!(used_space.ratio(), used_space_ratio);
+
+        match storage.read(&RegisterRead::Get(address), keypair.public_key())? {
+            QueryResponse::GetRegister((Ok(register), _)) => {
+                assert_eq!(*register.address(), address)
+            }
+            other => panic!("Unexpected response: {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn registers_are_kept_when_compacting_the_db() -> Result<()> {
+        let tmp_dir = tempdir()?;
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+
+        let used_space = UsedSpace::new(usize::MAX);
+        let storage = RegisterStorage::new(tmp_dir.path(), used_space.clone())?;
+        let register = new_public_register(&keypair);
+        let address = *register.address();
+        let write = RegisterWrite::New(register);
+        let _ = storage
+            .write(write.clone(), vec![], sign(&keypair, write)?)
+            .await?;
+        let _ = storage.db.flush()?;
+        let used = used_space.used();
+        drop(storage);
+
+        let _sizes = RegisterStorage::compact(tmp_dir.path())?;
+
+        let used_space = UsedSpace::new(usize::MAX);
+        let storage = RegisterStorage::new(tmp_dir.path(), used_space.clone())?;
+        assert_eq!(used_space.used(), used);
+        assert_eq!(storage.register_count(), 1);
+        match storage.read(&RegisterRead::Get(address), keypair.public_key())? {
+            QueryResponse::GetRegister((Ok(register), _)) => {
+                assert_eq!(*register.address(), address)
+            }
+            other => panic!("Unexpected response: {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn db_is_recovered_after_an_interrupted_compaction() -> Result<()> {
+        let tmp_dir = tempdir()?;
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+
+        let storage = RegisterStorage::new(tmp_dir.path(), UsedSpace::new(usize::MAX))?;
+        let register = new_public_register(&keypair);
+        let address = *register.address();
+        let write = RegisterWrite::New(register);
+        let _ = storage
+            .write(write.clone(), vec![], sign(&keypair, write)?)
+            .await?;
+        let _ = storage.db.flush()?;
+        drop(storage);
+
+        // interrupted once the original db was moved aside, before the compacted one was moved in
+        let db_dir = db_path(tmp_dir.path());
+        let compacted_dir = db_dir.with_extension(COMPACTING_EXTENSION);
+        let old_dir = db_dir.with_extension(OLD_EXTENSION);
+        std::fs::rename(&db_dir, &old_dir)?;
+        std::fs::create_dir_all(&compacted_dir)?;
+
+        let storage = RegisterStorage::new(tmp_dir.path(), UsedSpace::new(usize::MAX))?;
+        assert!(!compacted_dir.exists());
+        assert!(!old_dir.exists());
+        match storage.read(&RegisterRead::Get(address), keypair.public_key())? {
+            QueryResponse::GetRegister((Ok(register), _)) => {
+                assert_eq!(*register.address(), address)
+            }
+            other => panic!("Unexpected response: {:?}", other),
+        }
+        drop(storage);
+
+        // interrupted before the original db was moved aside
+        std::fs::create_dir_all(&compacted_dir)?;
+        let _sizes = RegisterStorage::compact(tmp_dir.path())?;
+        assert!(!compacted_dir.exists());
+        let storage = RegisterStorage::new(tmp_dir.path(), UsedSpace::new(usize::MAX))?;
+        assert_eq!(storage.register_count(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn writes_until_not_enough_space() -> Result<()> {
+        let tmp_dir = tempdir()?;
+        let entry_size = 1024;
+        let max_capacity = 20 * entry_size;
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+
+        let used_space = UsedSpace::new(max_capacity);
+        let storage = RegisterStorage::new(tmp_dir.path(), used_space.clone())?;
+        let mut register = new_public_register(&keypair);
+        let write = RegisterWrite::New(register.clone());
+        let _ = storage
+            .write(write.clone(), vec![], sign(&keypair, write)?)
+            .await?;
+
+        let mut written = 0;
+        let mut levels = vec![];
+        let result = loop {
+            let (_, mut op) = register.write(vec![0; entry_size], BTreeSet::new())?;
+            op.signature = Some(keypair.sign(&bincode::serialize(&op.crdt_op)?));
+            let write = RegisterWrite::Edit(op);
+            match storage
+                .write(write.clone(), vec![], sign(&keypair, write)?)
+                .await
+            {
+                Ok(level) => {
+                    written += 1;
+                    levels.extend(level);
+                }
+                Err(error) => break error,
+            }
+        };
+
+        assert!(matches!(result, DbError::NotEnoughSpace));
+        // the entries' actual size is accounted for, not just the size of the op
+        assert!(written < max_capacity / entry_size);
+        assert!(written > max_capacity / entry_size / 2);
+        assert!(used_space.ratio() > 0.9);
+        // the levels were reported as storage got filled
+        assert_eq!(
+            levels.iter().map(|level| level.value()).collect::<Vec<_>>(),
+            (1..=9).collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn deleting_a_register_frees_its_space() -> Result<()> {
+        let tmp_dir = tempdir()?;
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+
+        let used_space = UsedSpace::new(10 * 1024);
+        let storage = RegisterStorage::new(tmp_dir.path(), used_space.clone())?;
+        let mut register =
+            Register::new_private(keypair.public_key(), XorName::random(), 43_000, None);
+        let address = *register.address();
+        let write = RegisterWrite::New(register.clone());
+        let _ = storage
+            .write(write.clone(), vec![], sign(&keypair, write)?)
+            .await?;
+
+        let (_, mut op) = register.write(vec![0; 1024], BTreeSet::new())?;
+        op.signature = Some(keypair.sign(&bincode::serialize(&op.crdt_op)?));
+        let write = RegisterWrite::Edit(op);
+        let _ = storage
+            .write(write.clone(), vec![], sign(&keypair, write)?)
+            .await?;
+        assert!(used_space.ratio() > 0.1);
+
+        let write = RegisterWrite::Delete(address);
+        let _ = storage
+            .write(write.clone(), vec![], sign(&keypair, write)?)
+            .await?;
+        assert_eq!(used_space.ratio(), 0.0);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn policy_updates_are_validated_and_restored_on_reopen() -> Result<()> {
+        let tmp_dir = tempdir()?;
+        let owner = Keypair::new_ed25519(&mut OsRng);
+        let other = Keypair::new_ed25519(&mut OsRng);
+
+        let storage = RegisterStorage::new(tmp_dir.path(), UsedSpace::new(usize::MAX))?;
+        let register = new_public_register(&owner);
+        let address = *register.address();
+        let write = RegisterWrite::New(register);
+        let _ = storage
+            .write(write.clone(), vec![], sign(&owner, write)?)
+            .await?;
+
+        // only the owner can let others write
+        let mut permissions = BTreeMap::default();
+        let _prev = permissions.insert(User::Anyone, PublicPermissions::new(true));
+        let write = RegisterWrite::SetPolicy {
+            address,
+            policy: PublicPolicy {
+                owner: owner.public_key(),
+                permissions,
+            }
+            .into(),
+        };
+        assert!(matches!(
+            storage
+                .write(write.clone(), vec![], sign(&other, write.clone())?)
+                .await,
+            Err(DbError::NetworkData(DtError::AccessDenied(_)))
+        ));
+        let _ = storage
+            .write(write.clone(), vec![], sign(&owner, write)?)
+            .await?;
+
+        let write = RegisterWrite::TransferOwnership {
+            address,
+            new_owner: other.public_key(),
+        };
+        let _ = storage
+            .write(write.clone(), vec![], sign(&owner, write)?)
+            .await?;
+        let _ = storage.db.flush()?;
+        drop(storage);
+
+        // the ops are replayed in order when loading the register again
+        let storage = RegisterStorage::new(tmp_dir.path(), UsedSpace::new(usize::MAX))?;
+        match storage.read(&RegisterRead::Get(address), other.public_key())? {
+            QueryResponse::GetRegister((Ok(register), _)) => {
+                assert_eq!(register.owner(), other.public_key());
+                assert_eq!(
+                    register.permissions(User::Anyone, None)?,
+                    Permissions::Public(PublicPermissions::new(true))
+                );
+            }
+            other => panic!("Unexpected response: {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn multisig_owned_register_needs_more_than_threshold_member_shares() -> eyre::Result<()> {
+        let tmp_dir = tempdir()?;
+        let threshold = 1;
+        let sk_set = SecretKeySet::random(threshold, &mut OsRng);
+        let owner = PublicKey::Bls(sk_set.public_keys().public_key());
+        let members: Vec<_> = (0..3)
+            .map(|index| {
+                Keypair::new_bls_share(index, sk_set.secret_key_share(index), sk_set.public_keys())
+            })
+            .collect();
+        let client = Keypair::new_ed25519(&mut OsRng);
+
+        let storage = RegisterStorage::new(tmp_dir.path(), UsedSpace::new(usize::MAX))?;
+        let mut register = Register::new_public(owner, XorName::random(), 43_000, None);
+        register.set_owner_key_set(sk_set.public_keys())?;
+        let address = *register.address();
+        let write = RegisterWrite::New(register.clone());
+        let _ = storage
+            .write(write.clone(), vec![], sign(&client, write)?)
+            .await?;
+
+        // a single member can't write on its own
+        let (_, mut op) = register.write(b"team entry".to_vec(), BTreeSet::new())?;
+        let unsigned_op = op.clone();
+        op.signature = Some(members[0].sign(&bincode::serialize(&op.crdt_op)?));
+        let write = RegisterWrite::Edit(op);
+        assert!(matches!(
+            storage
+                .write(write.clone(), vec![], sign(&members[0], write)?)
+                .await,
+            Err(DbError::NetworkData(DtError::AccessDenied(_)))
+        ));
+
+        // threshold + 1 members co-sign the op
+        let mut multisig =
+            MultiSigRegisterWrite::new(sk_set.public_keys(), RegisterWrite::Edit(unsigned_op))?;
+        for member in &members[..=threshold] {
+            let share = multisig.sign_share(member)?.expect("payload to sign");
+            assert!(!multisig.add_share(share)?);
+        }
+        let write = multisig.write().clone();
+
+        // the owner's key alone, e.g. known to whoever dealt the shares, can't write
+        let msg = ServiceMsg::Cmd(DataCmd::Register(write.clone()));
+        let payload = WireMsg::serialize_msg_payload(&msg)?;
+        let auth = ServiceAuth {
+            public_key: owner,
+            signature: Signature::Bls(sk_set.secret_key().sign(&payload)),
+        };
+        assert!(matches!(
+            storage
+                .write(write.clone(), vec![], WireMsg::verify_sig(auth, msg)?)
+                .await,
+            Err(DbError::NetworkData(DtError::AccessDenied(_)))
+        ));
+
+        // nor can the same member by signing more than once
+        let share = multisig.sign_share(&members[0])?.expect("payload to sign");
+        let sig_shares = vec![share; threshold + 1];
+        let cmd = DataCmd::MultiSigRegister {
+            write: write.clone(),
+            sig_shares: sig_shares.clone(),
+        };
+        assert!(matches!(
+            storage
+                .write(write.clone(), sig_shares, sign_cmd(&client, cmd)?)
+                .await,
+            Err(DbError::NetworkData(DtError::AccessDenied(_)))
+        ));
+
+        // threshold + 1 members co-sign the write
+        for member in &members[..=threshold] {
+            let share = multisig.sign_share(member)?.expect("payload to sign");
+            let _signed = multisig.add_share(share)?;
+        }
+        let cmd = multisig.into_cmd()?;
+        let auth = sign_cmd(&client, cmd.clone())?;
+        match cmd {
+            DataCmd::MultiSigRegister { write, sig_shares } => {
+                let _ = storage.write(write, sig_shares, auth).await?;
+            }
+            other => panic!("Unexpected cmd: {:?}", other),
+        }
+
+        // the shares are checked again when the ops are replicated to another elder
+        let replica_dir = tempdir()?;
+        let replica = RegisterStorage::new(replica_dir.path(), UsedSpace::new(usize::MAX))?;
+        replica.update(storage.get_data_of(Prefix::default()).await?)?;
+
+        for storage in [storage, replica] {
+            match storage.read(&RegisterRead::Get(address), owner)? {
+                QueryResponse::GetRegister((Ok(register), _)) => {
+                    assert_eq!(register.size(None)?, 1)
+                }
+                other => panic!("Unexpected response: {:?}", other),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn new_public_register(keypair: &Keypair) -> Register {
+        let pk = keypair.public_key();
+        let mut permissions = BTreeMap::default();
+        let _prev = permissions.insert(User::Key(pk), PublicPermissions::new(true));
+        Register::new_public(
+            pk,
+            XorName::random(),
+            43_000u64,
+            Some(PublicPolicy {
+                owner: pk,
+                permissions,
+            }),
+        )
+    }
+
+    fn sign(keypair: &Keypair, write: RegisterWrite) -> Result<AuthorityProof<ServiceAuth>> {
+        sign_cmd(keypair, DataCmd::Register(write))
+    }
+
+    fn sign_cmd(keypair: &Keypair, cmd: DataCmd) -> Result<AuthorityProof<ServiceAuth>> {
+        let msg = ServiceMsg::Cmd(cmd);
+        let payload = WireMsg::serialize_msg_payload(&msg)?;
+        let auth = ServiceAuth {
+            public_key: keypair.public_key(),
+            signature: keypair.sign(&payload),
+        };
+        Ok(WireMsg::verify_sig(auth, msg)?)
+    }
 }
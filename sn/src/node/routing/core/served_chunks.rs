@@ -0,0 +1,110 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::types::{Cache, Chunk};
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+use xor_name::XorName;
+
+/// Chunks recently served to clients, so elders can answer queries for popular content without a
+/// round trip to the adults holding it, or dropped by adults during churn, so they can still
+/// answer the elders querying them for it until the new holders have it.
+///
+/// Once full, the least recently used chunk is dropped: reading a chunk sets it again,
+/// restarting the time it's been in the cache for.
+pub(super) struct ServedChunks {
+    chunks: Cache<XorName, Chunk>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl ServedChunks {
+    pub(super) fn new(capacity: usize, expiry: Duration) -> Self {
+        Self {
+            // The expiry is needed for the cache to track how long items have been in there,
+            // which it evicts by, and also keeps chunks nobody asked for in a while from lingering.
+            chunks: Cache::with_expiry_duration_and_capacity(expiry, capacity),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the chunk if cached, counting the lookup as a hit or a miss.
+    pub(super) async fn get(&self, name: &XorName) -> Option<Chunk> {
+        match self.chunks.get(name).await {
+            Some(chunk) => {
+                let _prev = self.hits.fetch_add(1, Ordering::Relaxed);
+                // mark it as the most recently used
+                let _prev = self.chunks.set(*name, chunk.clone(), None).await;
+                Some(chunk)
+            }
+            None => {
+                let _prev = self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub(super) async fn insert(&self, chunk: Chunk) {
+        let _prev = self.chunks.set(*chunk.name(), chunk, None).await;
+    }
+
+    /// Lookups which found the chunk, lookups which didn't, and chunks currently cached.
+    pub(super) async fn stats(&self) -> (usize, usize, usize) {
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+            self.chunks.len().await,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::utils::random_bytes;
+    use tokio::time::sleep;
+
+    fn new_chunk() -> Chunk {
+        Chunk::new(random_bytes(10))
+    }
+
+    #[tokio::test]
+    async fn least_recently_used_chunk_is_dropped() {
+        let cache = ServedChunks::new(2, Duration::from_secs(60));
+        let (first, second, third) = (new_chunk(), new_chunk(), new_chunk());
+
+        cache.insert(first.clone()).await;
+        sleep(Duration::from_millis(5)).await;
+        cache.insert(second.clone()).await;
+        sleep(Duration::from_millis(5)).await;
+        // reading the first makes the second the least recently used
+        assert_eq!(cache.get(first.name()).await, Some(first.clone()));
+        sleep(Duration::from_millis(5)).await;
+        cache.insert(third.clone()).await;
+
+        assert_eq!(cache.get(second.name()).await, None);
+        assert_eq!(cache.get(first.name()).await, Some(first));
+        assert_eq!(cache.get(third.name()).await, Some(third));
+        assert_eq!(cache.stats().await, (3, 1, 2));
+    }
+
+    #[tokio::test]
+    async fn chunks_expire() {
+        let cache = ServedChunks::new(2, Duration::from_millis(10));
+        let chunk = new_chunk();
+        cache.insert(chunk.clone()).await;
+        sleep(Duration::from_millis(20)).await;
+
+        assert_eq!(cache.get(chunk.name()).await, None);
+        assert_eq!(cache.stats().await, (0, 1, 1));
+    }
+}
@@ -226,6 +226,26 @@ impl DkgVoter {
             .process_failure(session_id, failed_participants, signed)
     }
 
+    /// Number of sessions we're participating in which are still in progress, and which have
+    /// completed (either with success or failure).
+    pub(crate) fn session_counts(&self) -> (usize, usize) {
+        let complete = self
+            .sessions
+            .iter()
+            .filter(|session| session.complete)
+            .count();
+        (self.sessions.len() - complete, complete)
+    }
+
+    /// Sessions we're participating in which haven't completed yet, with their elder candidates.
+    pub(crate) fn pending_sessions(&self) -> Vec<(DkgSessionId, ElderCandidates)> {
+        self.sessions
+            .iter()
+            .filter(|session| !session.complete)
+            .map(|session| (*session.key(), session.elder_candidates.clone()))
+            .collect()
+    }
+
     pub(crate) fn get_cached_messages(&self, session_id: &DkgSessionId) -> Vec<DkgMessage> {
         if let Some(session) = self.sessions.get_mut(session_id) {
             session.get_cached_messages()
@@ -44,6 +44,8 @@ pub use self::{
 pub use qp2p::{Config as NetworkConfig, SendStream};
 pub use xor_name::{Prefix, XorName, XOR_NAME_LEN}; // TODO remove pub on API update
 
+#[cfg(any(test, feature = "test-utils"))]
+pub use self::core::{LinkRule, MsgOutcome, ReplayLogEntry, SimulatedNetwork};
 #[cfg(any(test, feature = "test-utils"))]
 pub use test_utils::*;
 
@@ -52,8 +54,10 @@ pub(crate) use dkg::test_utils::section_signed;
 #[cfg(test)]
 pub(crate) use network_knowledge::test_utils::gen_section_authority_provider;
 
+pub use self::core::ChunkStoreKind;
+
 pub(crate) use self::{
-    core::{ChunkStoreError, MIN_LEVEL_WHEN_FULL},
+    core::{ChunkStore, ChunkStoreError, Metrics, RegisterStorage, MIN_LEVEL_WHEN_FULL},
     network_knowledge::{section_keys::SectionKeyShare, SectionAuthorityProvider},
 };
 
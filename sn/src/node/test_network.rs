@@ -0,0 +1,549 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! An in-process network of nodes on loopback, for integration tests.
+
+use crate::client::{Client, ClientConfig};
+use crate::node::{
+    routing::{EventStream, SimulatedNetwork},
+    Config as NodeConfig, Node,
+};
+use crate::types::Keypair;
+
+use eyre::{eyre, Result, WrapErr};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::{Ipv4Addr, SocketAddr},
+};
+use tempfile::TempDir;
+use tokio::{task::JoinHandle, time::Duration};
+use xor_name::Prefix;
+
+// Max storage capacity of each node, unless set otherwise
+const DEFAULT_MAX_CAPACITY: usize = 10 * 1024 * 1024;
+// Time a node has to join the network
+const DEFAULT_JOIN_TIMEOUT: Duration = Duration::from_secs(60);
+// Time the Elders have to be elected after the network changed
+const DEFAULT_ELDERS_TIMEOUT: Duration = Duration::from_secs(120);
+// Interval between checks on the Elders elected
+const ELDERS_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Builder of a [`TestNetwork`].
+#[derive(Debug)]
+pub struct TestNetworkBuilder {
+    node_count: usize,
+    max_capacity: usize,
+    join_timeout: Duration,
+    elders_timeout: Duration,
+    simulated_network: Option<SimulatedNetwork>,
+}
+
+impl Default for TestNetworkBuilder {
+    fn default() -> Self {
+        Self {
+            node_count: crate::elder_count(),
+            max_capacity: DEFAULT_MAX_CAPACITY,
+            join_timeout: DEFAULT_JOIN_TIMEOUT,
+            elders_timeout: DEFAULT_ELDERS_TIMEOUT,
+            simulated_network: None,
+        }
+    }
+}
+
+impl TestNetworkBuilder {
+    /// Number of nodes to start the network with, including the genesis node.
+    /// Defaults to the number of Elders of a section, which is read from the `SN_ELDER_COUNT`
+    /// env var if set, so networks which split with few nodes can be tested by lowering it.
+    pub fn node_count(mut self, node_count: usize) -> Self {
+        self.node_count = node_count;
+        self
+    }
+
+    /// Upper limit in bytes for the network storage of each node.
+    pub fn max_capacity(mut self, max_capacity: usize) -> Self {
+        self.max_capacity = max_capacity;
+        self
+    }
+
+    /// Time each node has to join the network.
+    pub fn join_timeout(mut self, join_timeout: Duration) -> Self {
+        self.join_timeout = join_timeout;
+        self
+    }
+
+    /// Time the Elders have to be elected once the nodes joined, or any of them was killed.
+    pub fn elders_timeout(mut self, elders_timeout: Duration) -> Self {
+        self.elders_timeout = elders_timeout;
+        self
+    }
+
+    /// Start the nodes on the given in-memory network instead of on loopback, so the messages
+    /// between them can be dropped, delayed or partitioned as the network's rules say.
+    /// Clients can't connect to such a network.
+    pub fn simulated(mut self, network: SimulatedNetwork) -> Self {
+        self.simulated_network = Some(network);
+        self
+    }
+
+    /// Start the nodes one after the other, and wait for the Elders to be elected.
+    pub async fn build(self) -> Result<TestNetwork> {
+        if self.node_count == 0 {
+            return Err(eyre!("A test network needs at least the genesis node"));
+        }
+
+        let root_dir = tempfile::tempdir().wrap_err("Failed to create the network's dir")?;
+        let mut network = TestNetwork {
+            root_dir,
+            max_capacity: self.max_capacity,
+            join_timeout: self.join_timeout,
+            elders_timeout: self.elders_timeout,
+            simulated_network: self.simulated_network,
+            genesis_key: None,
+            nodes: BTreeMap::new(),
+            started: 0,
+        };
+
+        for _ in 0..self.node_count {
+            let _addr = network.start_node().await?;
+        }
+        network.wait_for_elders().await?;
+
+        Ok(network)
+    }
+}
+
+// A node of the network along with the task handling its events.
+struct TestNode {
+    node: Node,
+    events_handle: JoinHandle<()>,
+}
+
+/// A network of [`Node`]s running in the current tokio runtime, listening on loopback,
+/// with their storage in a temporary directory removed when the network is dropped.
+///
+/// The nodes are stopped when the network is dropped.
+#[allow(missing_debug_implementations)]
+pub struct TestNetwork {
+    root_dir: TempDir,
+    max_capacity: usize,
+    join_timeout: Duration,
+    elders_timeout: Duration,
+    simulated_network: Option<SimulatedNetwork>,
+    genesis_key: Option<bls::PublicKey>,
+    nodes: BTreeMap<SocketAddr, TestNode>,
+    // Number of nodes started so far, used to name their dirs
+    started: usize,
+}
+
+impl TestNetwork {
+    /// Returns a builder of a network.
+    pub fn builder() -> TestNetworkBuilder {
+        TestNetworkBuilder::default()
+    }
+
+    /// Returns the network's genesis key.
+    pub fn genesis_key(&self) -> Result<bls::PublicKey> {
+        self.genesis_key
+            .ok_or_else(|| eyre!("The genesis node hasn't been started"))
+    }
+
+    /// Returns the addresses of the nodes running.
+    pub fn nodes(&self) -> BTreeSet<SocketAddr> {
+        self.nodes.keys().copied().collect()
+    }
+
+    /// Returns the node running at the given address.
+    pub fn node(&self, addr: &SocketAddr) -> Option<&Node> {
+        self.nodes.get(addr).map(|test_node| &test_node.node)
+    }
+
+    /// Returns the addresses of the nodes which are currently Elders.
+    pub async fn elders(&self) -> BTreeSet<SocketAddr> {
+        let mut elders = BTreeSet::new();
+        for (addr, test_node) in &self.nodes {
+            if test_node.node.is_elder().await {
+                let _new = elders.insert(*addr);
+            }
+        }
+        elders
+    }
+
+    /// Returns the prefixes of the sections the nodes running belong to.
+    pub async fn prefixes(&self) -> BTreeSet<Prefix> {
+        let mut prefixes = BTreeSet::new();
+        for test_node in self.nodes.values() {
+            let _new = prefixes.insert(test_node.node.our_prefix().await);
+        }
+        prefixes
+    }
+
+    /// Returns a client connected to the network, with a random keypair.
+    pub async fn client(&self) -> Result<Client> {
+        self.client_with(None).await
+    }
+
+    /// Returns a client connected to the network, with the given keypair if any.
+    pub async fn client_with(&self, keypair: Option<Keypair>) -> Result<Client> {
+        if self.simulated_network.is_some() {
+            return Err(eyre!("Clients can't connect to a simulated network"));
+        }
+
+        let root_dir = self.root_dir.path().join("clients");
+        let config = ClientConfig::new(
+            Some(&root_dir),
+            Some(SocketAddr::from((Ipv4Addr::LOCALHOST, 0))),
+            self.genesis_key()?,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        // Nodes' knowledge of the network is all we need, not that of other networks on disk
+        let client = Client::create_with(config, self.nodes(), keypair, false).await?;
+
+        Ok(client)
+    }
+
+    /// Start a new node joining the network, returning its address once it joined.
+    /// This doesn't wait for the node to be promoted, see [`TestNetwork::wait_for_elders`].
+    pub async fn add_node(&mut self) -> Result<SocketAddr> {
+        self.start_node().await
+    }
+
+    /// Stop the node running at the given address, as if its process was killed,
+    /// and wait for the Elders to be elected again.
+    pub async fn kill_node(&mut self, addr: &SocketAddr) -> Result<()> {
+        let test_node = self
+            .nodes
+            .remove(addr)
+            .ok_or_else(|| eyre!("No node running at {}", addr))?;
+        info!("Killing test node at {}", addr);
+        test_node.stop();
+
+        self.wait_for_elders().await
+    }
+
+    /// Add nodes until the genesis section splits, up to a total of `max_nodes` nodes,
+    /// and wait for the Elders of the new sections to be elected.
+    /// A section only splits once both halves would have twice as many nodes as it has
+    /// Elders, so lowering their number via the `SN_ELDER_COUNT` env var keeps this quick.
+    ///
+    /// The Elders are let be elected after each node joined, as a node joining in the middle of
+    /// an election isn't always answered.
+    pub async fn force_split(&mut self, max_nodes: usize) -> Result<()> {
+        while self.prefixes().await.len() < 2 {
+            if self.nodes.len() >= max_nodes {
+                return Err(eyre!(
+                    "The network hasn't split with {} nodes",
+                    self.nodes.len()
+                ));
+            }
+            let _addr = self.start_node().await?;
+            self.wait_for_elders().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Wait for each section to have elected as many Elders as it can, i.e. the number of Elders
+    /// of a section, or all its nodes when it has fewer.
+    pub async fn wait_for_elders(&self) -> Result<()> {
+        let elder_count = crate::elder_count();
+        let check = async {
+            loop {
+                let mut sections = BTreeMap::<Prefix, (usize, usize)>::new();
+                for test_node in self.nodes.values() {
+                    let (nodes, elders) = sections
+                        .entry(test_node.node.our_prefix().await)
+                        .or_default();
+                    *nodes += 1;
+                    if test_node.node.is_elder().await {
+                        *elders += 1;
+                    }
+                }
+
+                if sections
+                    .values()
+                    .all(|(nodes, elders)| *elders == elder_count.min(*nodes))
+                {
+                    break;
+                }
+                tokio::time::sleep(ELDERS_CHECK_INTERVAL).await;
+            }
+        };
+
+        tokio::time::timeout(self.elders_timeout, check)
+            .await
+            .map_err(|_| {
+                eyre!(
+                    "The Elders weren't elected within {:?}",
+                    self.elders_timeout
+                )
+            })
+    }
+
+    // Start a node, the genesis one if there are none yet
+    async fn start_node(&mut self) -> Result<SocketAddr> {
+        let mut config = NodeConfig {
+            first: self.genesis_key.is_none(),
+            local_addr: Some(SocketAddr::from((Ipv4Addr::LOCALHOST, 0))),
+            skip_auto_port_forwarding: true,
+            max_capacity: Some(self.max_capacity),
+            hard_coded_contacts: self.nodes(),
+            genesis_key: self
+                .genesis_key
+                .map(|genesis_key| hex::encode(genesis_key.to_bytes())),
+            simulated_network: self.simulated_network.clone(),
+            ..Default::default()
+        };
+        config.set_root_dir(
+            self.root_dir
+                .path()
+                .join(format!("sn-node-{}", self.started)),
+        );
+        self.started += 1;
+
+        let (node, event_stream) = Node::new(&config, self.join_timeout)
+            .await
+            .wrap_err("Failed to start a test node")?;
+
+        let addr = node.our_connection_info().await;
+        if self.genesis_key.is_none() {
+            self.genesis_key = Some(node.genesis_key().await);
+        }
+        info!("Test node {} started at {}", node.our_name().await, addr);
+
+        let events_handle = tokio::spawn(handle_events(addr, event_stream));
+        let _old = self.nodes.insert(
+            addr,
+            TestNode {
+                node,
+                events_handle,
+            },
+        );
+
+        Ok(addr)
+    }
+}
+
+impl Drop for TestNetwork {
+    fn drop(&mut self) {
+        for test_node in self.nodes.values() {
+            test_node.stop();
+        }
+    }
+}
+
+impl TestNode {
+    fn stop(&self) {
+        self.node.terminate();
+        self.events_handle.abort();
+    }
+}
+
+// The events need to be consumed for the node to keep going
+async fn handle_events(addr: SocketAddr, mut event_stream: EventStream) {
+    while let Some(event) = event_stream.next().await {
+        trace!("Test node at {} event: {:?}", addr, event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::init_test_logger;
+    use crate::node::routing::LinkRule;
+    use crate::types::Scope;
+    use bytes::Bytes;
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore = "Spins up a network of QUIC nodes on localhost, run with --ignored"]
+    async fn data_is_retained_when_an_elder_is_killed() -> Result<()> {
+        init_test_logger();
+        let mut network = TestNetwork::builder().build().await?;
+        assert_eq!(network.elders().await.len(), crate::elder_count());
+
+        let client = network.client().await?;
+        let bytes = Bytes::from(vec![7; 1024 * 1024]);
+        let address = client
+            .upload_and_verify(bytes.clone(), Scope::Public)
+            .await?;
+
+        let elder = network
+            .elders()
+            .await
+            .into_iter()
+            .next_back()
+            .ok_or_else(|| eyre!("No Elders elected"))?;
+        network.kill_node(&elder).await?;
+        let _addr = network.add_node().await?;
+        network.wait_for_elders().await?;
+
+        let client = network.client().await?;
+        assert_eq!(client.read_bytes(address).await?, bytes);
+
+        Ok(())
+    }
+
+    // Seed of the simulated networks, unless another one is set in `SN_SIMULATION_SEED`
+    const DEFAULT_SIMULATION_SEED: u64 = 0x5afe;
+    const SN_SIMULATION_SEED: &str = "SN_SIMULATION_SEED";
+    // Upper limit of nodes started to have a section split, or a node relocated
+    const MAX_NODES: usize = 60;
+
+    // Returns a simulated network seeded from `SN_SIMULATION_SEED` if set, printing the seed so a
+    // failed run can be repeated. Runs are only repeatable on a current-thread runtime with the time
+    // paused, i.e. `#[tokio::test(start_paused = true)]`, so the nodes' tasks and timers are run
+    // in the same order every time.
+    fn simulated_network() -> SimulatedNetwork {
+        let seed = std::env::var(SN_SIMULATION_SEED)
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or(DEFAULT_SIMULATION_SEED);
+        println!(
+            "Simulated network seed: {}, set {} to repeat the run",
+            seed, SN_SIMULATION_SEED
+        );
+        SimulatedNetwork::new(seed)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn nodes_join_over_a_reordering_simulated_network() -> Result<()> {
+        init_test_logger();
+        let network = simulated_network();
+        // Messages over the same link overtake each other with such a spread of delays
+        network
+            .add_rule(LinkRule::all().delay(Duration::from_millis(1), Duration::from_millis(50)));
+
+        let mut test_network = TestNetwork::builder()
+            .node_count(1)
+            .simulated(network.clone())
+            .build()
+            .await?;
+        // A node joining while the Elders are still being elected following the previous join
+        // isn't always answered, so let each of them be promoted before the next one joins.
+        for _ in 0..3 {
+            let _addr = test_network.add_node().await?;
+            test_network.wait_for_elders().await?;
+        }
+
+        assert_eq!(network.nodes(), test_network.nodes());
+        assert_eq!(test_network.elders().await, test_network.nodes());
+        assert_eq!(
+            test_network.prefixes().await,
+            BTreeSet::from([Prefix::default()])
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn joining_nodes_learn_the_current_section_key() -> Result<()> {
+        init_test_logger();
+        let network = simulated_network();
+        network
+            .add_rule(LinkRule::all().delay(Duration::from_millis(1), Duration::from_millis(50)));
+
+        let mut test_network = TestNetwork::builder()
+            .node_count(3)
+            .simulated(network)
+            .build()
+            .await?;
+
+        // Each of the Elders elected since genesis signed a new section key, which the nodes
+        // joining, only knowing of the genesis key, are brought up to date with through AE.
+        let addr = test_network.add_node().await?;
+        test_network.wait_for_elders().await?;
+
+        let genesis_key = test_network.genesis_key()?;
+        let mut section_keys = BTreeSet::new();
+        for addr in test_network.nodes() {
+            let node = test_network
+                .node(&addr)
+                .ok_or_else(|| eyre!("No node running at {}", addr))?;
+            let _new = section_keys.insert(node.our_section_key().await);
+        }
+        assert_eq!(section_keys.len(), 1);
+        assert!(!section_keys.contains(&genesis_key));
+        assert!(test_network.elders().await.contains(&addr));
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn elders_are_elected_over_a_delaying_simulated_network() -> Result<()> {
+        init_test_logger();
+        let network = simulated_network();
+        network
+            .add_rule(LinkRule::all().delay(Duration::from_millis(1), Duration::from_millis(50)));
+
+        let mut test_network = TestNetwork::builder()
+            .node_count(3)
+            .simulated(network.clone())
+            .build()
+            .await?;
+        assert_eq!(test_network.elders().await.len(), 3);
+        assert_eq!(network.nodes(), test_network.nodes());
+
+        let elder = test_network
+            .elders()
+            .await
+            .into_iter()
+            .next_back()
+            .ok_or_else(|| eyre!("No Elders elected"))?;
+        test_network.kill_node(&elder).await?;
+        assert!(!network.nodes().contains(&elder));
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn leaving_nodes_wait_for_their_elders_to_agree() -> Result<()> {
+        init_test_logger();
+        let network = simulated_network();
+        network
+            .add_rule(LinkRule::all().delay(Duration::from_millis(1), Duration::from_millis(50)));
+
+        let mut test_network = TestNetwork::builder()
+            .node_count(3)
+            .simulated(network)
+            .build()
+            .await?;
+        let addr = test_network.add_node().await?;
+        test_network.wait_for_elders().await?;
+
+        // Only resolves once the node learnt its elders agreed on it going offline
+        let node = test_network
+            .node(&addr)
+            .ok_or_else(|| eyre!("No node running at {}", addr))?;
+        tokio::time::timeout(Duration::from_secs(60), node.leave())
+            .await
+            .wrap_err("The node wasn't taken offline")??;
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    #[ignore = "Starts dozens of simulated nodes, run with SN_ELDER_COUNT=3 and --ignored"]
+    async fn section_splits_over_a_simulated_network() -> Result<()> {
+        init_test_logger();
+        let network = simulated_network();
+        network
+            .add_rule(LinkRule::all().delay(Duration::from_millis(1), Duration::from_millis(50)));
+
+        let mut test_network = TestNetwork::builder().simulated(network).build().await?;
+        test_network.force_split(MAX_NODES).await?;
+
+        let prefixes = test_network.prefixes().await;
+        assert!(prefixes.len() >= 2);
+        assert!(!prefixes.contains(&Prefix::default()));
+
+        Ok(())
+    }
+}
@@ -116,6 +116,18 @@ pub enum Error {
     /// The CRDT operation cannot be applied as it targets a different content address.
     #[error("The CRDT operation cannot be applied as it targets a different content address.")]
     CrdtWrongAddress(RegisterAddress),
+    /// The Register replicas cannot be merged as their policies differ.
+    #[error("The Register replicas cannot be merged as their policies differ.")]
+    CrdtPolicyMismatch,
+    /// The policy can't be set on the Register.
+    #[error("Invalid Register policy: {0}")]
+    InvalidPolicy(String),
+    /// Private data could not be decrypted with the keys provided.
+    #[error("Failed to decrypt data: {0}")]
+    FailedToDecrypt(String),
+    /// Private data could not be encrypted.
+    #[error("Failed to encrypt data: {0}")]
+    FailedToEncrypt(String),
 }
 
 pub(crate) fn convert_bincode_error(err: bincode::Error) -> Error {
@@ -15,10 +15,11 @@ pub use metadata::{Action, Entry};
 pub use policy::{
     Permissions, Policy, PrivatePermissions, PrivatePolicy, PublicPermissions, PublicPolicy, User,
 };
-pub use reg_crdt::EntryHash;
+pub use reg_crdt::{EntryHash, HistoryEntry};
 
-use super::{Error, PublicKey, Result};
+use super::{Error, OwnerType, PublicKey, Result};
 use crate::{types::RegisterAddress as Address, types::Scope};
+use bls::PublicKeySet;
 use reg_crdt::{CrdtOperation, RegisterCrdt};
 use serde::{Deserialize, Serialize};
 use std::{
@@ -41,6 +42,8 @@ pub struct Register {
     authority: PublicKey,
     crdt: RegisterCrdt,
     policy: Policy,
+    // the members of the owner, if it's a multi-sig owner
+    owner_key_set: Option<PublicKeySet>,
 }
 
 impl Register {
@@ -64,6 +67,7 @@ impl Register {
             authority,
             crdt: RegisterCrdt::new(Address::Public { name, tag }),
             policy: policy.into(),
+            owner_key_set: None,
         }
     }
 
@@ -87,6 +91,7 @@ impl Register {
             authority,
             crdt: RegisterCrdt::new(Address::Private { name, tag }),
             policy: policy.into(),
+            owner_key_set: None,
         }
     }
 
@@ -147,6 +152,30 @@ impl Register {
         Ok(self.crdt.read())
     }
 
+    /// Return the entries the entry corresponding to the provided 'hash' was written on top of.
+    pub fn children(
+        &self,
+        hash: EntryHash,
+        requester: Option<PublicKey>,
+    ) -> Result<BTreeSet<(EntryHash, Entry)>> {
+        self.check_permissions(Action::Read, requester)?;
+
+        self.crdt.children(hash).ok_or(Error::NoSuchEntry)
+    }
+
+    /// Return the history of the Register, every entry coming after the entries it was
+    /// written on top of. If a 'from' hash is provided, the history ends with that entry,
+    /// i.e. it's the Register's content as of that version.
+    pub fn history(
+        &self,
+        from: Option<EntryHash>,
+        requester: Option<PublicKey>,
+    ) -> Result<Vec<HistoryEntry>> {
+        self.check_permissions(Action::Read, requester)?;
+
+        self.crdt.history(from).ok_or(Error::NoSuchEntry)
+    }
+
     /// Write an entry to the Register, returning the generated unsigned
     /// CRDT operation so the caller can sign and broadcast it to other replicas,
     /// along with the hash of the entry just written.
@@ -172,6 +201,16 @@ impl Register {
         self.crdt.apply_op(op)
     }
 
+    /// Merge the state of another replica of this Register, e.g. as held by a different Elder.
+    /// Both replicas must have the same address and policy.
+    pub fn merge(&mut self, other: Register) -> Result<()> {
+        if self.policy != other.policy {
+            return Err(Error::CrdtPolicyMismatch);
+        }
+
+        self.crdt.merge(other.crdt)
+    }
+
     /// Return user permissions, if applicable.
     pub fn permissions(&self, user: User, requester: Option<PublicKey>) -> Result<Permissions> {
         self.check_permissions(Action::Read, requester)?;
@@ -186,6 +225,64 @@ impl Register {
         Ok(&self.policy)
     }
 
+    /// Replace the policy, e.g. to add or remove writers, or to grant read access to a
+    /// private Register. Only the owner can do it, and the policy must keep the owner,
+    /// which is changed with `transfer_ownership` instead.
+    pub fn set_policy(&mut self, policy: Policy, requester: Option<PublicKey>) -> Result<()> {
+        self.check_is_owner(requester)?;
+
+        if policy.scope() != self.scope() {
+            return Err(Error::InvalidPolicy(format!(
+                "a {:?} policy can't be set on a {:?} Register",
+                policy.scope(),
+                self.scope()
+            )));
+        }
+        if policy.owner() != self.policy.owner() {
+            return Err(Error::InvalidPolicy(
+                "the owner can only be changed by transferring the ownership".to_string(),
+            ));
+        }
+
+        self.policy = policy;
+        Ok(())
+    }
+
+    /// Make another key the owner. Only the current owner can do it.
+    /// The new owner is a single key, even if the current one is a multi-sig owner.
+    pub fn transfer_ownership(
+        &mut self,
+        new_owner: PublicKey,
+        requester: Option<PublicKey>,
+    ) -> Result<()> {
+        self.check_is_owner(requester)?;
+
+        self.policy.set_owner(new_owner);
+        self.owner_key_set = None;
+        Ok(())
+    }
+
+    /// Record the members of a BLS public key set as the owner, whose writes more than the
+    /// threshold of members must co-sign. The owner must be the set's public key.
+    pub fn set_owner_key_set(&mut self, key_set: PublicKeySet) -> Result<()> {
+        if PublicKey::Bls(key_set.public_key()) != self.owner() {
+            return Err(Error::InvalidOwnerNotPublicKeySet);
+        }
+
+        self.owner_key_set = Some(key_set);
+        Ok(())
+    }
+
+    // Helper to check the requester, or the replica's authority if not provided, is the owner.
+    fn check_is_owner(&self, requester: Option<PublicKey>) -> Result<()> {
+        let requester = requester.unwrap_or(self.authority);
+        if requester == *self.policy.owner() {
+            Ok(())
+        } else {
+            Err(Error::AccessDenied(requester))
+        }
+    }
+
     /// Helper to check permissions for given `action`
     /// for the given requester's public key.
     ///
@@ -202,18 +299,34 @@ impl Register {
         *self.policy.owner()
     }
 
+    /// Return the owner of the data, along with its members if it's a multi-sig owner.
+    pub fn owner_type(&self) -> OwnerType {
+        match &self.owner_key_set {
+            Some(key_set) if PublicKey::Bls(key_set.public_key()) == self.owner() => {
+                OwnerType::Multi(key_set.clone())
+            }
+            _ => OwnerType::Single(self.owner()),
+        }
+    }
+
     /// Return the PK which the messages are expected to be signed with by this replica.
     pub fn replica_authority(&self) -> PublicKey {
         self.authority
     }
+
+    /// Set the PK which the messages are expected to be signed with by this replica, e.g. the
+    /// key of a multi-sig owner whose members are to co-sign the ops written.
+    pub fn set_replica_authority(&mut self, authority: PublicKey) {
+        self.authority = authority;
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::super::{
         register::{
-            Entry, EntryHash, Permissions, PrivatePermissions, PrivatePolicy, PublicPermissions,
-            PublicPolicy, Register, RegisterOp, User,
+            Action, Entry, EntryHash, Permissions, PrivatePermissions, PrivatePolicy,
+            PublicPermissions, PublicPolicy, Register, RegisterOp, User,
         },
         utils, Error, Keypair, Result,
     };
@@ -369,6 +482,119 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn register_history_and_children() -> eyre::Result<()> {
+        let (_, register) = &mut create_public_reg_replicas(1)[0];
+
+        let entry1 = random_register_entry();
+        let entry2 = random_register_entry();
+        let entry3 = random_register_entry();
+        let entry4 = random_register_entry();
+
+        // entry2 and entry3 are concurrent writes on top of entry1, merged by entry4
+        let (entry1_hash, _) = register.write(entry1.clone(), BTreeSet::new())?;
+        let (entry2_hash, _) = register.write(entry2, BTreeSet::from([entry1_hash]))?;
+        let (entry3_hash, _) = register.write(entry3, BTreeSet::from([entry1_hash]))?;
+        let (entry4_hash, _) =
+            register.write(entry4, BTreeSet::from([entry2_hash, entry3_hash]))?;
+
+        let history = register.history(None, None)?;
+        let hashes: Vec<_> = history.iter().map(|item| item.hash).collect();
+        let (first_fork, second_fork) = if entry2_hash < entry3_hash {
+            (entry2_hash, entry3_hash)
+        } else {
+            (entry3_hash, entry2_hash)
+        };
+        assert_eq!(
+            hashes,
+            vec![entry1_hash, first_fork, second_fork, entry4_hash]
+        );
+        assert_eq!(history[0].entry, entry1);
+        assert!(history[0].children.is_empty());
+        assert_eq!(
+            history[3].children,
+            BTreeSet::from([entry2_hash, entry3_hash])
+        );
+
+        // the register as of entry2 doesn't include the concurrent entry3, nor entry4
+        let history = register.history(Some(entry2_hash), None)?;
+        let hashes: Vec<_> = history.iter().map(|item| item.hash).collect();
+        assert_eq!(hashes, vec![entry1_hash, entry2_hash]);
+
+        let children = register.children(entry2_hash, None)?;
+        assert_eq!(children, BTreeSet::from([(entry1_hash, entry1)]));
+        assert!(register.children(entry1_hash, None)?.is_empty());
+
+        assert_eq!(
+            register.children(EntryHash::default(), None),
+            Err(Error::NoSuchEntry)
+        );
+        assert_eq!(
+            register.history(Some(EntryHash::default()), None),
+            Err(Error::NoSuchEntry)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_write_merges_forks() -> eyre::Result<()> {
+        let (_, register) = &mut create_public_reg_replicas(1)[0];
+
+        let (entry1_hash, _) = register.write(random_register_entry(), BTreeSet::new())?;
+        let (entry2_hash, _) = register.write(random_register_entry(), BTreeSet::new())?;
+        assert_eq!(register.read(None)?.len(), 2);
+
+        // writing on top of both forks converges them
+        let merged = random_register_entry();
+        let (merged_hash, _) =
+            register.write(merged.clone(), BTreeSet::from([entry1_hash, entry2_hash]))?;
+        assert_eq!(
+            register.read(None)?,
+            BTreeSet::from([(merged_hash, merged)])
+        );
+
+        // an entry can't be written on top of unknown ones
+        assert_eq!(
+            register
+                .write(
+                    random_register_entry(),
+                    BTreeSet::from([merged_hash, EntryHash::default()])
+                )
+                .map(|(hash, _)| hash),
+            Err(Error::NoSuchEntry)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_merge_replicas() -> eyre::Result<()> {
+        let (_, mut replica1) = create_public_reg_replicas(1).remove(0);
+        let (entry1_hash, _) = replica1.write(random_register_entry(), BTreeSet::new())?;
+        let mut replica2 = replica1.clone();
+
+        // concurrent writes on each replica
+        let entry2 = random_register_entry();
+        let (entry2_hash, _) = replica1.write(entry2.clone(), BTreeSet::from([entry1_hash]))?;
+        let entry3 = random_register_entry();
+        let (entry3_hash, _) = replica2.write(entry3.clone(), BTreeSet::from([entry1_hash]))?;
+
+        replica1.merge(replica2.clone())?;
+        replica2.merge(replica1.clone())?;
+        assert_eq!(
+            replica1.read(None)?,
+            BTreeSet::from([(entry2_hash, entry2), (entry3_hash, entry3)])
+        );
+        verify_data_convergence(vec![replica1.clone(), replica2], 3)?;
+
+        // replicas of Registers with a different policy can't be merged
+        let (_, other) = create_public_reg_replicas(1).remove(0);
+        assert_eq!(replica1.merge(other), Err(Error::CrdtPolicyMismatch));
+
+        Ok(())
+    }
+
     #[test]
     fn register_query_public_policy() -> eyre::Result<()> {
         let register_name = XorName::random();
@@ -640,6 +866,68 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn register_policy_can_be_set_by_owner_only() -> eyre::Result<()> {
+        let owner_keypair = Keypair::new_ed25519(&mut OsRng);
+        let owner = owner_keypair.public_key();
+        let writer_keypair = Keypair::new_ed25519(&mut OsRng);
+        let writer = writer_keypair.public_key();
+
+        let mut register =
+            create_private_reg_replica_with(XorName::random(), 43_000, Some(owner_keypair), None);
+        // an op as written by the writer on its own replica
+        let (_, mut writer_op) = register
+            .clone()
+            .write(random_register_entry(), BTreeSet::new())?;
+        writer_op.source = writer;
+        let writer_op = sign_register_op(writer_op, &writer_keypair)?;
+        check_op_not_allowed_failure(register.clone().apply_op(writer_op.clone()))?;
+
+        // let the writer write, and read
+        let mut permissions = BTreeMap::default();
+        let _prev = permissions.insert(writer, PrivatePermissions::new(true, true));
+        let policy = PrivatePolicy { owner, permissions };
+        check_op_not_allowed_failure(register.set_policy(policy.clone().into(), Some(writer)))?;
+        register.set_policy(policy.clone().into(), Some(owner))?;
+        register.apply_op(writer_op)?;
+        assert_eq!(register.read(Some(writer))?.len(), 1);
+
+        // neither the owner nor the scope can be changed with a policy
+        let mut other_owner = policy;
+        other_owner.owner = writer;
+        assert!(matches!(
+            register.set_policy(other_owner.into(), None),
+            Err(Error::InvalidPolicy(_))
+        ));
+        let public_policy = PublicPolicy {
+            owner,
+            permissions: BTreeMap::default(),
+        };
+        assert!(matches!(
+            register.set_policy(public_policy.into(), None),
+            Err(Error::InvalidPolicy(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_ownership_can_be_transferred_by_owner_only() -> eyre::Result<()> {
+        let (owner_keypair, mut register) = create_public_reg_replicas(1).remove(0);
+        let owner = owner_keypair.public_key();
+        let new_owner = Keypair::new_ed25519(&mut OsRng).public_key();
+
+        check_op_not_allowed_failure(register.transfer_ownership(new_owner, Some(new_owner)))?;
+        register.transfer_ownership(new_owner, Some(owner))?;
+        assert_eq!(register.owner(), new_owner);
+
+        // the previous owner has no more rights over it
+        check_op_not_allowed_failure(register.transfer_ownership(owner, Some(owner)))?;
+        check_op_not_allowed_failure(register.check_permissions(Action::Write, Some(owner)))?;
+
+        Ok(())
+    }
+
     // Helpers for tests
 
     fn sign_register_op(mut op: RegisterOp<Entry>, keypair: &Keypair) -> Result<RegisterOp<Entry>> {
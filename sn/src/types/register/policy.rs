@@ -7,7 +7,7 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use super::super::{Error, PublicKey, Result};
+use super::super::{Error, PublicKey, Result, Scope};
 use super::Action;
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, hash::Hash};
@@ -45,6 +45,22 @@ impl Policy {
             Policy::Private(policy) => policy.owner(),
         }
     }
+
+    /// Sets the owner.
+    pub fn set_owner(&mut self, owner: PublicKey) {
+        match self {
+            Policy::Public(policy) => policy.owner = owner,
+            Policy::Private(policy) => policy.owner = owner,
+        }
+    }
+
+    /// Returns the scope of the data this policy applies to.
+    pub fn scope(&self) -> Scope {
+        match self {
+            Policy::Public(_) => Scope::Public,
+            Policy::Private(_) => Scope::Private,
+        }
+    }
 }
 
 impl From<PrivatePolicy> for Policy {
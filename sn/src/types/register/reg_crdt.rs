@@ -13,11 +13,11 @@ use super::super::{
 use super::metadata::Entry;
 use crdts::{
     merkle_reg::{MerkleReg, Node},
-    CmRDT,
+    CmRDT, CvRDT,
 };
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeMap, BTreeSet},
     fmt::{self, Debug, Display, Formatter, Result as FmtResult},
     hash::Hash,
 };
@@ -42,6 +42,17 @@ impl Display for EntryHash {
     }
 }
 
+/// An entry of the Register's history, along with the hashes of the entries it was written on top of.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HistoryEntry {
+    /// Hash of the entry.
+    pub hash: EntryHash,
+    /// Hashes of the entries this entry was written on top of, i.e. its children in the Merkle-DAG.
+    pub children: BTreeSet<EntryHash>,
+    /// The entry.
+    pub entry: Entry,
+}
+
 /// CRDT Data operation applicable to other Register replica.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CrdtOperation<T> {
@@ -97,7 +108,9 @@ impl RegisterCrdt {
     }
 
     /// Write a new entry to the RegisterCrdt, returning the hash
-    /// of the entry and the CRDT operation without a signature
+    /// of the entry and the CRDT operation without a signature.
+    /// The entry supersedes all the given children, which must be known to this replica,
+    /// thus writing on top of all the current entries merges any concurrent forks.
     pub(super) fn write(
         &mut self,
         entry: Entry,
@@ -106,6 +119,14 @@ impl RegisterCrdt {
     ) -> Result<(EntryHash, CrdtOperation<Entry>)> {
         let address = *self.address();
 
+        // An entry written on top of unknown children would be orphaned, thus never read
+        if children
+            .iter()
+            .any(|child| self.data.node(child.0).is_none())
+        {
+            return Err(Error::NoSuchEntry);
+        }
+
         let children_array: BTreeSet<[u8; 32]> = children.iter().map(|itr| itr.0).collect();
         let crdt_op = self.data.write(entry, children_array);
         self.data.apply(crdt_op.clone());
@@ -146,11 +167,106 @@ impl RegisterCrdt {
         Ok(())
     }
 
+    /// Merge the state of another replica of the RegisterCrdt into this one.
+    pub(super) fn merge(&mut self, other: Self) -> Result<()> {
+        if self.address != other.address {
+            return Err(Error::CrdtWrongAddress(other.address));
+        }
+
+        self.data.merge(other.data);
+
+        Ok(())
+    }
+
     /// Get the entry corresponding to the provided `hash` if it exists.
     pub(super) fn get(&self, hash: EntryHash) -> Option<&Entry> {
         self.data.node(hash.0).map(|node| &node.value)
     }
 
+    /// Get the entries the entry corresponding to the provided `hash` was written on top of,
+    /// or `None` if the entry doesn't exist.
+    pub(super) fn children(&self, hash: EntryHash) -> Option<BTreeSet<(EntryHash, Entry)>> {
+        let _node = self.data.node(hash.0)?;
+        Some(
+            self.data
+                .children(hash.0)
+                .hashes_and_nodes()
+                .map(|(hash, node)| (EntryHash(hash), node.value.clone()))
+                .collect(),
+        )
+    }
+
+    /// Get the history of the register in topological order, i.e. every entry comes after the
+    /// entries it was written on top of, with concurrent entries ordered by their hash.
+    /// If `from` is provided, only the entries up to it are included, that is the register
+    /// as of the version it corresponds to. Returns `None` if such an entry doesn't exist.
+    pub(super) fn history(&self, from: Option<EntryHash>) -> Option<Vec<HistoryEntry>> {
+        let heads = match from {
+            Some(hash) => {
+                let _node = self.data.node(hash.0)?;
+                BTreeSet::from([hash.0])
+            }
+            None => self.data.read().hashes(),
+        };
+
+        // collect all the entries reachable from the heads, along with the
+        // number of their children still to be listed before them
+        let mut nodes = BTreeMap::new();
+        let mut pending = BTreeMap::new();
+        let mut to_visit: Vec<_> = heads.into_iter().collect();
+        while let Some(hash) = to_visit.pop() {
+            if nodes.contains_key(&hash) {
+                continue;
+            }
+            if let Some(node) = self.data.node(hash) {
+                // children we don't hold (yet) can't be listed
+                let children: BTreeSet<_> = node
+                    .children
+                    .iter()
+                    .copied()
+                    .filter(|child| self.data.node(*child).is_some())
+                    .collect();
+                let _prev = pending.insert(hash, children.len());
+                to_visit.extend(children);
+                let _prev = nodes.insert(hash, node);
+            }
+        }
+
+        let mut parents: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for (hash, node) in &nodes {
+            for child in &node.children {
+                parents.entry(*child).or_default().push(*hash);
+            }
+        }
+
+        let mut ready: BTreeSet<_> = pending
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(hash, _)| *hash)
+            .collect();
+        let mut history = Vec::with_capacity(nodes.len());
+        while let Some(hash) = ready.iter().next().copied() {
+            let _ = ready.remove(&hash);
+            for parent in parents.get(&hash).into_iter().flatten() {
+                if let Some(count) = pending.get_mut(parent) {
+                    *count -= 1;
+                    if *count == 0 {
+                        let _ = ready.insert(*parent);
+                    }
+                }
+            }
+
+            let node = nodes[&hash];
+            history.push(HistoryEntry {
+                hash: EntryHash(hash),
+                children: node.children.iter().copied().map(EntryHash).collect(),
+                entry: node.value.clone(),
+            });
+        }
+
+        Some(history)
+    }
+
     /// Read current entries (multiple entries occur on concurrent writes).
     pub(super) fn read(&self) -> BTreeSet<(EntryHash, Entry)> {
         self.data
@@ -8,37 +8,39 @@
 // Software.
 
 use super::{metadata::get_metadata, FilesMapChange, ProcessedFiles};
-use crate::{Error, Result, Safe, XorUrl};
-use bytes::Bytes;
+use crate::{Error, Result, Safe, Scope, XorUrl};
 use log::info;
 use safe_network::client::Error as ClientError;
-use std::{
-    fs,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 use walkdir::{DirEntry, WalkDir};
 
 const MAX_RECURSIVE_DEPTH: usize = 10_000;
 
-// Upload a files to the Network as a Public Blob
-pub(crate) async fn upload_file_to_net(safe: &mut Safe, path: &Path) -> Result<XorUrl> {
-    let data = fs::read(path).map_err(|err| {
-        Error::InvalidInput(format!("Failed to read file from local location: {}", err))
-    })?;
-    let data = Bytes::from(data);
+// Upload a files to the Network as a Public or Private Blob
+pub(crate) async fn upload_file_to_net(
+    safe: &mut Safe,
+    path: &Path,
+    scope: Scope,
+) -> Result<XorUrl> {
+    if !path.is_file() {
+        return Err(Error::InvalidInput(format!(
+            "Failed to read file from local location: '{}' is not a file",
+            path.display()
+        )));
+    }
 
     let mut mime_type_for_xorurl = mime_guess::from_path(&path).first_raw();
-    let result = match safe
-        .store_public_bytes(data.to_owned(), mime_type_for_xorurl)
-        .await
-    {
+    let result = match safe.store_file(path, mime_type_for_xorurl, scope).await {
         Ok(xorurl) => Ok(xorurl),
         Err(Error::InvalidMediaType(_)) => {
             // Let's then upload it and set media-type to be simply raw content
             mime_type_for_xorurl = None;
-            safe.store_public_bytes(data.clone(), mime_type_for_xorurl)
-                .await
+            safe.store_file(path, mime_type_for_xorurl, scope).await
         }
+        Err(Error::ClientError(ClientError::IoError(err))) => Err(Error::InvalidInput(format!(
+            "Failed to read file from local location: {}",
+            err
+        ))),
         other_err => other_err,
     };
 
@@ -49,7 +51,7 @@ pub(crate) async fn upload_file_to_net(safe: &mut Safe, path: &Path) -> Result<X
         // Switch dry run mode ON only for this next operation
         let prev_dry_run_mode = safe.dry_run_mode;
         safe.dry_run_mode = true;
-        let result = safe.store_public_bytes(data, mime_type_for_xorurl).await;
+        let result = safe.store_file(path, mime_type_for_xorurl, scope).await;
         safe.dry_run_mode = prev_dry_run_mode;
         let xorurl = result?;
 
@@ -72,6 +74,7 @@ pub(crate) async fn file_system_dir_walk(
     location: &Path,
     recursive: bool,
     follow_links: bool,
+    scope: Scope,
 ) -> Result<ProcessedFiles> {
     info!("Reading files from {}", location.display());
 
@@ -128,7 +131,7 @@ pub(crate) async fn file_system_dir_walk(
                     }
 
                     if metadata.file_type().is_file() {
-                        match upload_file_to_net(safe, current_file_path).await {
+                        match upload_file_to_net(safe, current_file_path, scope).await {
                             Ok(xorurl) => {
                                 processed_files
                                     .insert(normalised_path, FilesMapChange::Added(xorurl));
@@ -180,6 +183,7 @@ fn valid_depth(entry: &DirEntry, max_depth: usize) -> bool {
 pub(crate) async fn file_system_single_file(
     safe: &mut Safe,
     location: &Path,
+    scope: Scope,
 ) -> Result<ProcessedFiles> {
     info!("Reading file {}", location.display());
     let (metadata, _) = get_metadata(location, true)?; // follows symlinks.
@@ -193,7 +197,7 @@ pub(crate) async fn file_system_single_file(
             location.display()
         )))
     } else {
-        match upload_file_to_net(safe, location).await {
+        match upload_file_to_net(safe, location, scope).await {
             Ok(xorurl) => {
                 processed_files.insert(normalised_path, FilesMapChange::Added(xorurl));
             }
@@ -12,7 +12,7 @@ use super::{
     metadata::FileMeta,
     ProcessedFiles, RealPath,
 };
-use crate::{app::consts::*, Error, Result, Safe, XorUrl};
+use crate::{app::consts::*, Error, Result, Safe, Scope, VersionHash, XorUrl};
 use log::{debug, info};
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, fs, path::Path};
@@ -23,6 +23,9 @@ pub type FilesMap = BTreeMap<String, FileInfo>;
 // Each FileInfo contains file metadata and the link to the file's Blob XOR-URL
 pub type FileInfo = BTreeMap<String, String>;
 
+// Number of characters of the conflicting version to suffix the conflicting paths with
+const CONFLICT_MARKER_LEN: usize = 8;
+
 // Type of changes made to each item of a FilesMap
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Serialize, Deserialize)]
@@ -86,6 +89,69 @@ impl GetAttr for FileInfo {
     }
 }
 
+// Helper function to merge the FilesMaps of concurrent versions of a FilesContainer.
+// All the paths found in any of the versions are kept. When the versions disagree on the
+// content of a path, the most recently modified item keeps the path, and each of the other
+// items is kept next to it, under the same path suffixed with a conflict marker.
+pub(crate) fn merge_files_maps(versions: &[(VersionHash, FilesMap)]) -> FilesMap {
+    let mut items_by_path = BTreeMap::<&str, Vec<(&VersionHash, &FileInfo)>>::new();
+    for (version, files_map) in versions {
+        for (path, file_info) in files_map {
+            items_by_path
+                .entry(path)
+                .or_default()
+                .push((version, file_info));
+        }
+    }
+
+    let mut merged = FilesMap::new();
+    for (path, mut items) in items_by_path {
+        // the winner is the most recently modified, ties broken by version
+        items.sort_by(|(lhs_version, lhs), (rhs_version, rhs)| {
+            (lhs.get(PREDICATE_MODIFIED), lhs_version)
+                .cmp(&(rhs.get(PREDICATE_MODIFIED), rhs_version))
+        });
+        let (_, winner) = match items.pop() {
+            Some(item) => item,
+            None => continue,
+        };
+
+        let mut conflicting = BTreeMap::new();
+        for (version, file_info) in items {
+            // only the metadata differs if they link to the same content
+            if same_content(file_info, winner) {
+                continue;
+            }
+            let _ = conflicting
+                .entry(file_info.get(PREDICATE_LINK))
+                .or_insert((version, file_info));
+        }
+
+        for (version, file_info) in conflicting.into_values() {
+            let version = version.to_string();
+            let mut conflict_info = file_info.clone();
+            conflict_info.insert(PREDICATE_CONFLICT.to_string(), version.clone());
+            debug!(
+                "Conflicting FileInfo for {} found in version {}",
+                path, version
+            );
+            merged.insert(
+                format!("{}.conflict-{}", path, &version[..CONFLICT_MARKER_LEN]),
+                conflict_info,
+            );
+        }
+
+        merged.insert(path.to_string(), winner.clone());
+    }
+
+    merged
+}
+
+fn same_content(lhs: &FileInfo, rhs: &FileInfo) -> bool {
+    lhs.get(PREDICATE_TYPE) == rhs.get(PREDICATE_TYPE)
+        && lhs.get(PREDICATE_LINK) == rhs.get(PREDICATE_LINK)
+}
+
 // Helper function to add or update a FileInfo in a FilesMap
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn add_or_update_file_item(
@@ -98,9 +164,10 @@ pub(crate) async fn add_or_update_file_item(
     name_exists: bool,
     files_map: &mut FilesMap,
     processed_files: &mut ProcessedFiles,
+    scope: Scope,
 ) -> bool {
     // We need to add a new FileInfo, let's generate the FileInfo first
-    match gen_new_file_item(safe, file_path, file_meta, file_link).await {
+    match gen_new_file_item(safe, file_path, file_meta, file_link, scope).await {
         Ok(new_file_item) => {
             // note: files have link property, dirs and symlinks do not
             let xorurl = new_file_item
@@ -140,11 +207,12 @@ async fn gen_new_file_item(
     file_path: &Path,
     file_meta: &FileMeta,
     link: Option<&str>, // must be symlink target or None if FileMeta::is_symlink() is true.
+    scope: Scope,       // of the file uploaded if no link is provided
 ) -> Result<FileInfo> {
     let mut file_item = file_meta.to_file_item();
     if file_meta.is_file() {
         let xorurl = match link {
-            None => upload_file_to_net(safe, file_path).await?,
+            None => upload_file_to_net(safe, file_path, scope).await?,
             Some(link) => link.to_string(),
         };
         file_item.insert(PREDICATE_LINK.to_string(), xorurl);
@@ -20,16 +20,17 @@ use bytes::{Buf, Bytes};
 use file_system::{
     file_system_dir_walk, file_system_single_file, normalise_path_separator, upload_file_to_net,
 };
-use files_map::add_or_update_file_item;
+use files_map::{add_or_update_file_item, merge_files_maps};
 use log::{debug, info, warn};
 use relative_path::RelativePath;
-use safe_network::types::BytesAddress;
+use safe_network::types::{BytesAddress, DataAddress};
 use std::{
     collections::{BTreeMap, HashSet},
-    iter::FromIterator,
     path::{Path, PathBuf},
     str,
 };
+use tokio::io::AsyncWrite;
+use xor_name::XorName;
 
 pub(crate) use files_map::{file_map_for_path, get_file_link_and_metadata};
 pub(crate) use metadata::FileMeta;
@@ -60,6 +61,32 @@ impl Safe {
     /// # });
     /// ```
     pub async fn files_container_create(&mut self) -> Result<XorUrl> {
+        self.create_files_container(Scope::Public).await
+    }
+
+    /// # Create an empty private FilesContainer.
+    ///
+    /// The FilesContainer is stored on a private Register, and the files added to it,
+    /// as well as its FilesMaps, are stored as private data, only readable by us.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    ///     safe.connect(None, None, None).await.unwrap();
+    ///     let xorurl = safe.files_container_create_private().await.unwrap();
+    ///     assert!(xorurl.contains("safe://"))
+    /// # });
+    /// ```
+    pub async fn files_container_create_private(&mut self) -> Result<XorUrl> {
+        self.create_files_container(Scope::Private).await
+    }
+
+    // Private helper to create an empty FilesContainer with the given scope
+    async fn create_files_container(&mut self, scope: Scope) -> Result<XorUrl> {
         // Build a Register creation operation
         let (xorname, reg_op) = self
             .safe_client
@@ -67,7 +94,7 @@ impl Safe {
                 None,
                 FILES_CONTAINER_TYPE_TAG,
                 None,
-                false,
+                scope == Scope::Private,
                 self.dry_run_mode,
             )
             .await?;
@@ -75,7 +102,7 @@ impl Safe {
         let xorurl = SafeUrl::encode_register(
             xorname,
             FILES_CONTAINER_TYPE_TAG,
-            Scope::Public,
+            scope,
             ContentType::FilesContainer,
             self.xorurl_base,
         )?;
@@ -108,30 +135,84 @@ impl Safe {
         dst: Option<&Path>,
         recursive: bool,
         follow_links: bool,
+    ) -> Result<(XorUrl, ProcessedFiles, FilesMap)> {
+        self.create_files_container_from(
+            location.as_ref(),
+            dst,
+            recursive,
+            follow_links,
+            Scope::Public,
+        )
+        .await
+    }
+
+    /// # Create a private FilesContainer containing files uploaded from a local folder.
+    ///
+    /// As with `files_container_create_private`, the FilesContainer and the files uploaded
+    /// are stored as private data, only readable by us.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    ///     safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_private_from("./testdata", None, true, true).await.unwrap();
+    ///     assert!(xorurl.contains("safe://"))
+    /// # });
+    /// ```
+    pub async fn files_container_create_private_from<P: AsRef<Path>>(
+        &mut self,
+        location: P,
+        dst: Option<&Path>,
+        recursive: bool,
+        follow_links: bool,
+    ) -> Result<(XorUrl, ProcessedFiles, FilesMap)> {
+        self.create_files_container_from(
+            location.as_ref(),
+            dst,
+            recursive,
+            follow_links,
+            Scope::Private,
+        )
+        .await
+    }
+
+    // Private helper to create a FilesContainer with the given scope, from a local folder
+    async fn create_files_container_from(
+        &mut self,
+        location: &Path,
+        dst: Option<&Path>,
+        recursive: bool,
+        follow_links: bool,
+        scope: Scope,
     ) -> Result<(XorUrl, ProcessedFiles, FilesMap)> {
         // Let's upload the files (if not dry_run) and generate the list of local files paths
         let mut processed_files =
-            file_system_dir_walk(self, location.as_ref(), recursive, follow_links).await?;
+            file_system_dir_walk(self, location, recursive, follow_links, scope).await?;
 
         // The FilesContainer is stored on a Register
         // and the link to the serialised FilesMap as the entry's value
         let files_map = files_map_create(
             self,
             &mut processed_files,
-            location.as_ref(),
+            location,
             dst,
             follow_links,
+            scope,
         )
         .await?;
 
         // Create a Register
-        let xorurl = self.files_container_create().await?;
+        let xorurl = self.create_files_container(scope).await?;
 
         if self.dry_run_mode {
             Ok((xorurl.to_string(), processed_files, files_map))
         } else {
             // Store files map on network
-            let files_map_xorurl = self.store_files_map(&files_map).await?;
+            let files_map_xorurl = self.store_files_map(&files_map, scope).await?;
 
             let mut reg_url = SafeUrl::from_xorurl(&xorurl)?;
 
@@ -154,6 +235,10 @@ impl Safe {
 
     /// # Fetch an existing FilesContainer.
     ///
+    /// Returns the latest versions of the FilesContainer along with their FilesMap. When it was
+    /// updated concurrently, it has several latest versions whose FilesMaps are merged, as with
+    /// `files_container_merge`, and none of them alone identifies the FilesMap returned.
+    ///
     /// ## Example
     ///
     /// ```no_run
@@ -163,26 +248,79 @@ impl Safe {
     /// # rt.block_on(async {
     /// #   safe.connect(None, None, None).await.unwrap();
     ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, true).await.unwrap();
-    ///     let (version, files_map) = safe.files_container_get(&xorurl).await.unwrap().unwrap();
-    ///     println!("FilesContainer fetched is at version: {}", version);
-    ///     println!("FilesMap of fetched version is: {:?}", files_map);
+    ///     let (versions, files_map) = safe.files_container_get(&xorurl).await.unwrap().unwrap();
+    ///     println!("FilesContainer fetched is at versions: {:?}", versions);
+    ///     println!("FilesMap of fetched versions is: {:?}", files_map);
     /// # });
     /// ```
     pub async fn files_container_get(
         &mut self,
         url: &str,
-    ) -> Result<Option<(VersionHash, FilesMap)>> {
+    ) -> Result<Option<(HashSet<VersionHash>, FilesMap)>> {
         debug!("Getting files container from: {:?}", url);
         let safe_url = self.parse_and_resolve_url(url).await?;
 
         self.fetch_files_container(&safe_url).await
     }
 
-    /// Fetch a FilesContainer from a SafeUrl without performing any type of URL resolution
+    /// # Merge the concurrent versions of a FilesContainer.
+    ///
+    /// When several clients update a FilesContainer concurrently, it ends up with several
+    /// latest versions. This writes a new version on top of all of them, with the union of
+    /// their FilesMaps. If they disagree on the content of a path, the most recently modified
+    /// file keeps it, while the others are kept under the same path suffixed with `.conflict-`
+    /// and the beginning of the version they come from.
+    /// Nothing is written if the FilesContainer has a single latest version.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, true).await.unwrap();
+    ///     let (version, files_map) = safe.files_container_merge(&xorurl).await.unwrap().unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    ///     println!("The FilesMap of the merged FilesContainer is: {:?}", files_map);
+    /// # });
+    /// ```
+    pub async fn files_container_merge(
+        &mut self,
+        url: &str,
+    ) -> Result<Option<(VersionHash, FilesMap)>> {
+        debug!("Merging FilesContainer at: {:?}", url);
+        let mut safe_url = self.parse_and_resolve_url(url).await?;
+
+        // We need all the latest versions, not the one an NRS name may be resolved to
+        safe_url.set_content_version(None);
+
+        let (current_versions, files_map) = match self.fetch_files_container(&safe_url).await? {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+
+        if current_versions.len() == 1 {
+            return Ok(current_versions
+                .into_iter()
+                .next()
+                .map(|version| (version, files_map)));
+        }
+
+        let new_version = self
+            .append_version_to_files_container(current_versions, &files_map, url, safe_url, false)
+            .await?;
+
+        Ok(Some((new_version, files_map)))
+    }
+
+    /// Fetch all the latest versions of a FilesContainer from a SafeUrl, without performing
+    /// any type of URL resolution, along with their merged FilesMap
     pub(crate) async fn fetch_files_container(
         &self,
         safe_url: &SafeUrl,
-    ) -> Result<Option<(VersionHash, FilesMap)>> {
+    ) -> Result<Option<(HashSet<VersionHash>, FilesMap)>> {
         // fetch register entries and wrap errors
         debug!(
             "Fetching FilesContainer from {}, address type: {:?}",
@@ -208,35 +346,49 @@ impl Safe {
                 err => Error::NetDataError(format!("Failed to get current version: {}", err)),
             })?;
 
-        // take the 1st entry (TODO Multiple entries)
         debug!(
             "Retrieved {} entries for register at {}",
             entries.len(),
             safe_url.to_string()
         );
-        if entries.len() > 1 {
-            return Err(Error::NotImplementedError("Multiple file container entries not managed, this happends when 2 clients write concurrently to a file container".to_string()));
-        }
-        let first_entry = entries.iter().next();
-        let (version, files_map_xorurl) = if let Some((v, m)) = first_entry {
-            (v.into(), str::from_utf8(m)?)
-        } else {
+        if entries.is_empty() {
             warn!("FilesContainer found at \"{:?}\" was empty", safe_url);
             return Ok(None);
+        }
+
+        let mut versions = Vec::with_capacity(entries.len());
+        for (hash, entry) in &entries {
+            // Using the FilesMap XOR-URL we can now fetch the FilesMap and deserialise it
+            let files_map = self.fetch_files_map(str::from_utf8(entry)?).await?;
+            versions.push((VersionHash::from(hash), files_map));
+        }
+
+        let files_map = if versions.len() == 1 {
+            versions[0].1.clone()
+        } else {
+            warn!(
+                "FilesContainer found at \"{}\" has {} concurrent versions, merging them",
+                safe_url,
+                versions.len()
+            );
+            merge_files_maps(&versions)
         };
+        debug!("Files map retrieved.... {:?}", &entries);
+
+        let versions = versions.into_iter().map(|(version, _)| version).collect();
+        Ok(Some((versions, files_map)))
+    }
 
-        // Using the FilesMap XOR-URL we can now fetch the FilesMap and deserialise it
+    // Fetch and deserialise the FilesMap stored at the given XOR-URL
+    async fn fetch_files_map(&self, files_map_xorurl: &str) -> Result<FilesMap> {
         let files_map_url = SafeUrl::from_xorurl(files_map_xorurl)?;
-        let serialised_files_map = self.fetch_public_data(&files_map_url, None).await?;
-        let files_map = serde_json::from_slice(serialised_files_map.chunk()).map_err(|err| {
+        let serialised_files_map = self.fetch_data(&files_map_url, None).await?;
+        serde_json::from_slice(serialised_files_map.chunk()).map_err(|err| {
             Error::ContentError(format!(
                 "Couldn't deserialise the FilesMap stored in the FilesContainer: {:?}",
                 err
             ))
-        })?;
-        debug!("Files map retrieved.... {:?}", &version);
-
-        Ok(Some((version, files_map)))
+        })
     }
 
     /// # Sync up local folder with the content on a FilesContainer.
@@ -287,17 +439,23 @@ impl Safe {
         // the version from it so we can fetch latest version of it for sync-ing
         safe_url.set_content_version(None);
 
-        let (current_version, current_files_map) =
-            match self.fetch_files_container(&safe_url).await? {
-                Some((version, files_map)) => (Some(version), files_map),
-                None => (None, FilesMap::default()),
-            };
+        let (current_versions, current_files_map) = self
+            .fetch_files_container(&safe_url)
+            .await?
+            .unwrap_or_default();
 
         // Let's generate the list of local files paths, without uploading any new file yet.
         // Switch dry run mode ON only for this next operation
         let prev_dry_run_mode = self.dry_run_mode;
         self.dry_run_mode = true;
-        let result = file_system_dir_walk(self, location.as_ref(), recursive, follow_links).await;
+        let result = file_system_dir_walk(
+            self,
+            location.as_ref(),
+            recursive,
+            follow_links,
+            safe_url.scope(),
+        )
+        .await;
         self.dry_run_mode = prev_dry_run_mode;
         let processed_files = result?;
 
@@ -313,12 +471,13 @@ impl Safe {
             false,
             true,
             follow_links,
+            safe_url.scope(),
         )
         .await?;
 
         self.update_files_container(
             success_count,
-            current_version,
+            current_versions,
             new_files_map,
             processed_files,
             url,
@@ -355,7 +514,7 @@ impl Safe {
         follow_links: bool,
     ) -> Result<(Option<(VersionHash, FilesMap)>, ProcessedFiles)> {
         debug!("Adding file to FilesContainer at {}", url);
-        let (safe_url, current_version, current_files_map) =
+        let (safe_url, current_versions, current_files_map) =
             validate_files_add_params(self, source_file, url, update_nrs).await?;
 
         let dst_path = Path::new(safe_url.path());
@@ -372,7 +531,7 @@ impl Safe {
             // Switch dry run mode ON only for this next operation
             let prev_dry_run_mode = self.dry_run_mode;
             self.dry_run_mode = true;
-            let result = file_system_single_file(self, source_path).await;
+            let result = file_system_single_file(self, source_path, safe_url.scope()).await;
             self.dry_run_mode = prev_dry_run_mode;
             let processed_files = result?;
 
@@ -386,13 +545,14 @@ impl Safe {
                 force,
                 false,
                 follow_links,
+                safe_url.scope(),
             )
             .await?
         };
 
         self.update_files_container(
             success_count,
-            current_version,
+            current_versions,
             new_files_map,
             processed_files,
             url,
@@ -427,10 +587,10 @@ impl Safe {
         force: bool,
         update_nrs: bool,
     ) -> Result<(Option<(VersionHash, FilesMap)>, ProcessedFiles)> {
-        let (safe_url, current_version, current_files_map) =
+        let (safe_url, current_versions, current_files_map) =
             validate_files_add_params(self, "", url, update_nrs).await?;
 
-        let new_file_xorurl = self.store_public_bytes(data, None).await?;
+        let new_file_xorurl = self.store_bytes(data, None, safe_url.scope()).await?;
 
         let dst_path = Path::new(safe_url.path());
         let (processed_files, new_files_map, success_count) =
@@ -438,7 +598,7 @@ impl Safe {
 
         self.update_files_container(
             success_count,
-            current_version,
+            current_versions,
             new_files_map,
             processed_files,
             url,
@@ -493,7 +653,7 @@ impl Safe {
         // the version from it so we can fetch latest version of it
         safe_url.set_content_version(None);
 
-        let (current_version, files_map) = match self.fetch_files_container(&safe_url).await? {
+        let (current_versions, files_map) = match self.fetch_files_container(&safe_url).await? {
             Some(info) => info,
             None => {
                 return Err(Error::EmptyContent(format!(
@@ -506,17 +666,22 @@ impl Safe {
         let (processed_files, new_files_map, success_count) =
             files_map_remove_path(Path::new(dst_path), files_map, recursive)?;
 
-        let version = if success_count == 0 {
-            current_version
-        } else {
-            self.append_version_to_files_container(
-                HashSet::from_iter([current_version]),
-                &new_files_map,
-                url,
-                safe_url,
-                update_nrs,
-            )
-            .await?
+        let single_version = match current_versions.iter().next() {
+            Some(version) if current_versions.len() == 1 => Some(*version),
+            _ => None,
+        };
+        let version = match single_version {
+            Some(version) if success_count == 0 => version,
+            _ => {
+                self.append_version_to_files_container(
+                    current_versions,
+                    &new_files_map,
+                    url,
+                    safe_url,
+                    update_nrs,
+                )
+                .await?
+            }
         };
 
         Ok((version, processed_files, new_files_map))
@@ -528,15 +693,16 @@ impl Safe {
     async fn update_files_container(
         &mut self,
         files_map_changes_count: u64,
-        current_version: Option<VersionHash>,
+        current_versions: HashSet<VersionHash>,
         new_files_map: FilesMap,
         processed_files: ProcessedFiles,
         url: &str,
         safe_url: SafeUrl,
         update_nrs: bool,
     ) -> Result<(Option<(VersionHash, FilesMap)>, ProcessedFiles)> {
-        if files_map_changes_count == 0 {
-            if let Some(version) = current_version {
+        // Even without changes, concurrent versions are merged into a new one
+        if files_map_changes_count == 0 && current_versions.len() <= 1 {
+            if let Some(version) = current_versions.into_iter().next() {
                 // We had a FilesMap but there were no changes to it, so let's
                 // return the existing version and files map, along with
                 // details about the processed files.
@@ -548,16 +714,11 @@ impl Safe {
                 Ok((None, processed_files))
             }
         } else {
-            // There were changes to current FilesMap, so append new version to the container
-            let parent_versions = if let Some(version) = current_version {
-                HashSet::from_iter([version])
-            } else {
-                HashSet::new()
-            };
-
+            // There were changes to current FilesMap, so append new version to the container,
+            // on top of all its current versions
             let new_version = self
                 .append_version_to_files_container(
-                    parent_versions,
+                    current_versions,
                     &new_files_map,
                     url,
                     safe_url,
@@ -574,7 +735,7 @@ impl Safe {
     #[allow(clippy::too_many_arguments)]
     async fn append_version_to_files_container(
         &mut self,
-        current_version: HashSet<VersionHash>,
+        current_versions: HashSet<VersionHash>,
         new_files_map: &FilesMap,
         url: &str,
         mut safe_url: SafeUrl,
@@ -583,14 +744,15 @@ impl Safe {
         // The FilesContainer is updated by adding an entry containing the link to
         // the Blob with the serialised new version of the FilesMap.
         let files_map_xorurl = if !self.dry_run_mode {
-            self.store_files_map(new_files_map).await?
+            self.store_files_map(new_files_map, safe_url.scope())
+                .await?
         } else {
             "".to_string()
         };
 
         // append entry to register
         let entry = files_map_xorurl.as_bytes().to_vec();
-        let replace = current_version.iter().map(|e| e.entry_hash()).collect();
+        let replace = current_versions.iter().map(|e| e.entry_hash()).collect();
         let entry_hash = &self
             .register_write(&safe_url.to_string(), entry, replace)
             .await?;
@@ -632,31 +794,76 @@ impl Safe {
         bytes: Bytes,
         media_type: Option<&str>,
     ) -> Result<XorUrl> {
-        let content_type = media_type.map_or_else(
-            || Ok(ContentType::Raw),
-            |media_type_str| {
-                if SafeUrl::is_media_type_supported(media_type_str) {
-                    Ok(ContentType::MediaType(media_type_str.to_string()))
-                } else {
-                    Err(Error::InvalidMediaType(format!(
-                        "Media-type '{}' not supported. You can pass 'None' as the 'media_type' for this content to be treated as raw",
-                        media_type_str
-                    )))
-                }
-            },
-        )?;
+        self.store_bytes(bytes, media_type, Scope::Public).await
+    }
 
-        let xorname = self
-            .safe_client
-            .store_bytes(bytes.clone(), self.dry_run_mode)
-            .await?;
-        let xorurl = SafeUrl::encode_bytes(
-            BytesAddress::Public(xorname),
-            content_type,
-            self.xorurl_base,
-        )?;
+    /// # Store a private file
+    ///
+    /// As with `store_public_bytes`, but the data is stored encrypted with our keys, thus
+    /// only we can read it. Its XOR-URL is different to that of the same data stored as public.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use sn_api::{Safe, SafeData};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let data = bytes::Bytes::from("Something super secret");
+    ///     let xorurl = safe.store_private_bytes(data.clone(), Some("text/plain")).await.unwrap();
+    ///     match safe.fetch(&xorurl, None).await.unwrap() {
+    ///         SafeData::PrivateBlob { data: received_data, .. } => assert_eq!(received_data, data),
+    ///         other => panic!("unexpected content: {:?}", other),
+    ///     }
+    /// # });
+    /// ```
+    pub async fn store_private_bytes(
+        &self,
+        bytes: Bytes,
+        media_type: Option<&str>,
+    ) -> Result<XorUrl> {
+        self.store_bytes(bytes, media_type, Scope::Private).await
+    }
 
-        Ok(xorurl)
+    /// # Store a public file
+    ///
+    /// Put the content of a local file onto the network. The file is read, self-encrypted
+    /// and uploaded incrementally, thus it's never held in memory as a whole.
+    /// As with `store_public_bytes`, it'll be stored as a spot if it's less than 3072 bytes,
+    /// otherwise, it'll be stored as a blob.
+    ///
+    /// If `resume_uploads_mode` is set and a previous upload of the same file failed halfway,
+    /// only the chunks which were not stored by it are sent to the network.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use sn_api::Safe;
+    /// # use std::path::Path;
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let path = Path::new("./testdata/test.md");
+    ///     let xorurl = safe.store_public_file(path, Some("text/markdown")).await.unwrap();
+    ///     let mut received_data = vec![];
+    ///     safe.files_get_public_data_to_writer(&xorurl, &mut received_data).await.unwrap();
+    ///     assert_eq!(received_data, std::fs::read(path).unwrap());
+    /// # });
+    /// ```
+    pub async fn store_public_file(&self, path: &Path, media_type: Option<&str>) -> Result<XorUrl> {
+        self.store_file(path, media_type, Scope::Public).await
+    }
+
+    /// # Store a private file
+    ///
+    /// As with `store_public_file`, but the content is stored encrypted with our keys,
+    /// thus only we can read it.
+    pub async fn store_private_file(
+        &self,
+        path: &Path,
+        media_type: Option<&str>,
+    ) -> Result<XorUrl> {
+        self.store_file(path, media_type, Scope::Private).await
     }
 
     /// # Get a Public Blob
@@ -678,30 +885,86 @@ impl Safe {
     pub async fn files_get_public_data(&mut self, url: &str, range: Range) -> Result<Bytes> {
         // TODO: do we want ownership from other PKs yet?
         let safe_url = self.parse_and_resolve_url(url).await?;
-        self.fetch_public_data(&safe_url, range).await
+        self.fetch_data(&safe_url, range).await
     }
 
-    /// Fetch an Blob from a SafeUrl without performing any type of URL resolution
-    pub(crate) async fn fetch_public_data(
-        &self,
-        safe_url: &SafeUrl,
-        range: Range,
-    ) -> Result<Bytes> {
-        let data = match safe_url.data_type() {
-            DataType::Bytes => {
-                self.safe_client
-                    .get_bytes(BytesAddress::Public(safe_url.xorname()), range)
-                    .await?
-            }
-            other => {
-                return Err(Error::ContentError(format!("{}", other)));
+    /// # Get a Public Blob into a writer
+    /// Get blob from the network, writing its content to `writer` as it's being fetched,
+    /// rather than holding it in memory as a whole. Returns the number of bytes written.
+    /// Private blobs we stored, e.g. the files of our private FilesContainers, can be read too.
+    pub async fn files_get_public_data_to_writer<W>(
+        &mut self,
+        url: &str,
+        writer: &mut W,
+    ) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let safe_url = self.parse_and_resolve_url(url).await?;
+        match safe_url.address() {
+            DataAddress::Bytes(address) => {
+                self.safe_client.get_bytes_to_writer(address, writer).await
             }
-        };
-        Ok(data)
+            _ => Err(Error::ContentError(format!("{}", safe_url.data_type()))),
+        }
+    }
+
+    /// Fetch a public or private Blob from a SafeUrl without performing any type of URL resolution
+    pub(crate) async fn fetch_data(&self, safe_url: &SafeUrl, range: Range) -> Result<Bytes> {
+        match safe_url.address() {
+            DataAddress::Bytes(address) => self.safe_client.get_bytes(address, range).await,
+            _ => Err(Error::ContentError(format!("{}", safe_url.data_type()))),
+        }
+    }
+
+    // Private helper to store bytes with the given scope
+    async fn store_bytes(
+        &self,
+        bytes: Bytes,
+        media_type: Option<&str>,
+        scope: Scope,
+    ) -> Result<XorUrl> {
+        let content_type = content_type_from(media_type)?;
+
+        let xorname = self
+            .safe_client
+            .store_bytes(bytes, scope, self.dry_run_mode)
+            .await?;
+
+        let xorurl = SafeUrl::encode_bytes(
+            bytes_address(xorname, scope),
+            content_type,
+            self.xorurl_base,
+        )?;
+
+        Ok(xorurl)
     }
 
-    // Private helper to serialise a FilesMap and store it in a Public Blob
-    async fn store_files_map(&mut self, files_map: &FilesMap) -> Result<String> {
+    // Private helper to store the content of a local file with the given scope
+    async fn store_file(
+        &self,
+        path: &Path,
+        media_type: Option<&str>,
+        scope: Scope,
+    ) -> Result<XorUrl> {
+        let content_type = content_type_from(media_type)?;
+
+        let xorname = self
+            .safe_client
+            .store_file(path, scope, self.dry_run_mode, self.resume_uploads_mode)
+            .await?;
+
+        let xorurl = SafeUrl::encode_bytes(
+            bytes_address(xorname, scope),
+            content_type,
+            self.xorurl_base,
+        )?;
+
+        Ok(xorurl)
+    }
+
+    // Private helper to serialise a FilesMap and store it in a Blob with the given scope
+    async fn store_files_map(&mut self, files_map: &FilesMap, scope: Scope) -> Result<String> {
         // The FilesMapContainer is a Register where each NRS Map version is
         // an entry containing the XOR-URL of the Blob that contains the serialised NrsMap.
         let serialised_files_map = serde_json::to_string(&files_map).map_err(|err| {
@@ -712,7 +975,7 @@ impl Safe {
         })?;
 
         let files_map_xorurl = self
-            .store_public_bytes(Bytes::from(serialised_files_map), None)
+            .store_bytes(Bytes::from(serialised_files_map), None, scope)
             .await?;
 
         Ok(files_map_xorurl)
@@ -721,13 +984,37 @@ impl Safe {
 
 // Helper functions
 
+fn bytes_address(xorname: XorName, scope: Scope) -> BytesAddress {
+    match scope {
+        Scope::Public => BytesAddress::Public(xorname),
+        Scope::Private => BytesAddress::Private(xorname),
+    }
+}
+
+// Obtain the ContentType for the given media-type, if it's supported
+fn content_type_from(media_type: Option<&str>) -> Result<ContentType> {
+    media_type.map_or_else(
+        || Ok(ContentType::Raw),
+        |media_type_str| {
+            if SafeUrl::is_media_type_supported(media_type_str) {
+                Ok(ContentType::MediaType(media_type_str.to_string()))
+            } else {
+                Err(Error::InvalidMediaType(format!(
+                    "Media-type '{}' not supported. You can pass 'None' as the 'media_type' for this content to be treated as raw",
+                    media_type_str
+                )))
+            }
+        },
+    )
+}
+
 // Make sure the input params are valid for a files_container_add operation
 async fn validate_files_add_params(
     safe: &mut Safe,
     source_file: &str,
     url: &str,
     update_nrs: bool,
-) -> Result<(SafeUrl, Option<VersionHash>, FilesMap)> {
+) -> Result<(SafeUrl, HashSet<VersionHash>, FilesMap)> {
     let safe_url = Safe::parse_url(url)?;
 
     // If NRS name shall be updated then the URL has to be an NRS-URL
@@ -762,12 +1049,12 @@ async fn validate_files_add_params(
         }
     }
 
-    let (current_version, current_files_map) = match safe.fetch_files_container(&safe_url).await? {
-        Some((version, files_map)) => (Some(version), files_map),
-        None => (None, FilesMap::default()),
-    };
+    let (current_versions, current_files_map) = safe
+        .fetch_files_container(&safe_url)
+        .await?
+        .unwrap_or_default();
 
-    Ok((safe_url, current_version, current_files_map))
+    Ok((safe_url, current_versions, current_files_map))
 }
 
 // From the location path and the destination path chosen by the user, calculate
@@ -824,6 +1111,7 @@ async fn files_map_sync(
     force: bool,
     compare_file_content: bool,
     follow_links: bool,
+    scope: Scope,
 ) -> Result<(ProcessedFiles, FilesMap, u64)> {
     let (location_base_path, dst_base_path) = get_base_paths(location, dst_path);
     let mut updated_files_map = FilesMap::new();
@@ -864,6 +1152,7 @@ async fn files_map_sync(
                     false,
                     &mut updated_files_map,
                     &mut processed_files,
+                    scope,
                 )
                 .await
                 {
@@ -890,7 +1179,7 @@ async fn files_map_sync(
             }
             Some(file_item) => {
                 let is_modified =
-                    is_file_item_modified(safe, Path::new(local_file_name), file_item).await;
+                    is_file_item_modified(safe, Path::new(local_file_name), file_item, scope).await;
                 if force || (compare_file_content && is_modified) {
                     // We need to update the current FileInfo
                     if add_or_update_file_item(
@@ -903,6 +1192,7 @@ async fn files_map_sync(
                         true,
                         &mut updated_files_map,
                         &mut processed_files,
+                        scope,
                     )
                     .await
                     {
@@ -981,12 +1271,13 @@ async fn is_file_item_modified(
     safe: &mut Safe,
     local_filename: &Path,
     file_item: &FileInfo,
+    scope: Scope,
 ) -> bool {
     if FileMeta::filetype_is_file(&file_item[PREDICATE_TYPE]) {
         // Switch dry run mode ON only for this next operation
         let prev_dry_run_mode = safe.dry_run_mode;
         safe.dry_run_mode = true;
-        let is_uploaded = match upload_file_to_net(safe, local_filename).await {
+        let is_uploaded = match upload_file_to_net(safe, local_filename, scope).await {
             Ok(local_xorurl) => file_item[PREDICATE_LINK] != local_xorurl,
             Err(_) => false,
         };
@@ -1061,6 +1352,7 @@ async fn files_map_add_link(
                                 true,
                                 &mut files_map,
                                 &mut processed_files,
+                                safe_url.scope(),
                             )
                             .await
                             {
@@ -1102,6 +1394,7 @@ async fn files_map_add_link(
                         false,
                         &mut files_map,
                         &mut processed_files,
+                        safe_url.scope(),
                     )
                     .await
                     {
@@ -1178,6 +1471,7 @@ async fn files_map_create(
     location: &Path,
     dst_path: Option<&Path>,
     follow_links: bool,
+    scope: Scope,
 ) -> Result<FilesMap> {
     let mut files_map = FilesMap::default();
 
@@ -1223,6 +1517,7 @@ async fn files_map_create(
             false,
             &mut files_map,
             content,
+            scope,
         )
         .await;
     }
@@ -1233,13 +1528,15 @@ async fn files_map_create(
 mod tests {
     use super::*;
     use crate::{
-        app::test_helpers::{new_safe_instance, random_nrs_name},
+        app::test_helpers::{new_safe_instance, random_nrs_name, single_version},
         register::EntryHash,
+        resolver::SafeData,
         retry_loop, retry_loop_for_pattern,
     };
     use anyhow::{anyhow, bail, Result};
     use assert_matches::assert_matches;
     use rand::{distributions::Alphanumeric, thread_rng, Rng};
+    use std::fs;
 
     const TEST_DATA_FOLDER: &str = "./testdata/";
     const TEST_DATA_FOLDER_NO_SLASH: &str = "./testdata";
@@ -1288,6 +1585,7 @@ mod tests {
             Path::new(TEST_DATA_FOLDER_NO_SLASH),
             Some(Path::new("")),
             true,
+            Scope::Public,
         )
         .await?;
         assert_eq!(files_map.len(), 2);
@@ -1352,6 +1650,49 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_files_store_private_blob() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let random_blob_content: String =
+            thread_rng().sample_iter(&Alphanumeric).take(20).collect();
+        let data = Bytes::from(random_blob_content);
+
+        let file_xorurl = safe.store_private_bytes(data.clone(), None).await?;
+        assert_eq!(SafeUrl::from_url(&file_xorurl)?.scope(), Scope::Private);
+
+        match retry_loop!(safe.fetch(&file_xorurl, None)) {
+            SafeData::PrivateBlob {
+                data: retrieved, ..
+            } => assert_eq!(retrieved, data),
+            other => bail!("Content fetched is not a PrivateBlob: {:?}", other),
+        }
+
+        // the same data stored as public is found at a different address
+        let public_xorurl = safe.store_public_bytes(data, None).await?;
+        assert_ne!(
+            SafeUrl::from_url(&public_xorurl)?.xorname(),
+            SafeUrl::from_url(&file_xorurl)?.xorname()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_store_private_blob_dry_run_without_connection() -> Result<()> {
+        let safe = Safe {
+            dry_run_mode: true,
+            ..Default::default()
+        };
+
+        // the address of private data depends on the keys of the client storing it
+        assert_matches!(
+            safe.store_private_bytes(Bytes::from("private"), None).await,
+            Err(Error::ConnectionError(_))
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_files_container_create_from_file() -> Result<()> {
         let mut safe = new_safe_instance().await?;
@@ -1372,6 +1713,38 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_files_container_create_private_from_file() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let filename = Path::new("./testdata/test.md");
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create_private_from(
+                &filename.display().to_string(),
+                None,
+                false,
+                false,
+            )
+            .await?;
+
+        assert_eq!(SafeUrl::from_url(&xorurl)?.scope(), Scope::Private);
+        assert_eq!(processed_files.len(), 1);
+        let link = &files_map["/test.md"][PREDICATE_LINK];
+        assert_eq!(SafeUrl::from_url(link)?.scope(), Scope::Private);
+
+        // the owner can read both the container and the file it links to
+        let (_, fetched_files_map) =
+            single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
+        assert_eq!(fetched_files_map, files_map);
+        match retry_loop!(safe.fetch(&format!("{}/test.md", xorurl), None)) {
+            SafeData::PrivateBlob { data, .. } => {
+                assert_eq!(data, Bytes::from(fs::read(filename)?));
+            }
+            other => bail!("Content fetched is not a PrivateBlob: {:?}", other),
+        }
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_files_container_create_from_dry_run() -> Result<()> {
         let mut safe = new_safe_instance().await?;
@@ -1599,8 +1972,7 @@ mod tests {
         let mut safe = new_safe_instance().await?;
         let (xorurl, processed_files, _) = new_files_container_from_testdata(&mut safe).await?;
 
-        let (version0, _) = retry_loop!(safe.files_container_get(&xorurl))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
 
         let (content, new_processed_files) = safe
             .files_container_sync("./testdata/subfolder/", &xorurl, true, true, false, false)
@@ -1819,8 +2191,7 @@ mod tests {
         let (xorurl, _, files_map) = new_files_container_from_testdata(&mut safe).await?;
 
         let _ = retry_loop!(safe.fetch(&xorurl, None));
-        let (version0, _) = retry_loop!(safe.files_container_get(&xorurl))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
 
         let (version1_content, new_processed_files) = safe
             .files_container_sync(
@@ -1979,8 +2350,7 @@ mod tests {
         let mut safe = new_safe_instance().await?;
         let (xorurl, _, _) = new_files_container_from_testdata(&mut safe).await?;
 
-        let (version0, _) = retry_loop!(safe.files_container_get(&xorurl))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
 
         let nrsurl = random_nrs_name();
         let mut safe_url = SafeUrl::from_url(&xorurl)?;
@@ -2002,7 +2372,7 @@ mod tests {
 
         // wait for it
         retry_loop_for_pattern!(safe
-            .files_container_get(&safe_url.to_string()), Ok(Some((version, _))) if *version == version1)?;
+            .files_container_get(&safe_url.to_string()), Ok(Some((versions, _))) if versions.contains(&version1))?;
 
         let mut safe_url = SafeUrl::from_url(&xorurl)?;
         safe_url.set_content_version(Some(version1));
@@ -2150,8 +2520,8 @@ mod tests {
         let mut safe = new_safe_instance().await?;
         let (xorurl, _, files_map) = new_files_container_from_testdata(&mut safe).await?;
 
-        let (_, fetched_files_map) = retry_loop!(safe.files_container_get(&xorurl))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (_, fetched_files_map) =
+            single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
 
         assert_eq!(fetched_files_map.len(), TESTDATA_PUT_FILESMAP_COUNT);
         assert_eq!(files_map.len(), fetched_files_map.len());
@@ -2171,8 +2541,7 @@ mod tests {
         let mut safe = new_safe_instance().await?;
         let (xorurl, _, _) = new_files_container_from_testdata(&mut safe).await?;
 
-        let (version0, _) = retry_loop!(safe.files_container_get(&xorurl))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
 
         let (version1_content, _) = retry_loop!(safe.files_container_sync(
             "./testdata/subfolder/",
@@ -2189,8 +2558,8 @@ mod tests {
 
         let mut safe_url = SafeUrl::from_url(&xorurl)?;
         safe_url.set_content_version(None);
-        let (version, _) = retry_loop_for_pattern!(safe
-            .files_container_get(&safe_url.to_string()), Ok(Some((version, _))) if *version == version1)?.ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version, _) = single_version(retry_loop_for_pattern!(safe
+            .files_container_get(&safe_url.to_string()), Ok(Some((versions, _))) if versions.contains(&version1))?)?;
         assert_eq!(version, version1);
 
         Ok(())
@@ -2201,8 +2570,7 @@ mod tests {
         let mut safe = new_safe_instance().await?;
         let (xorurl, _, files_map) = new_files_container_from_testdata(&mut safe).await?;
 
-        let (version0, _) = retry_loop!(safe.files_container_get(&xorurl))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
 
         // let's create a new version of the files container
         let (version1_content, _) = retry_loop!(safe.files_container_sync(
@@ -2219,8 +2587,8 @@ mod tests {
         // let's fetch version 0
         let mut safe_url = SafeUrl::from_url(&xorurl)?;
         safe_url.set_content_version(Some(version0));
-        let (version, v0_files_map) = retry_loop!(safe.files_container_get(&safe_url.to_string()))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version, v0_files_map) =
+            single_version(retry_loop!(safe.files_container_get(&safe_url.to_string())))?;
 
         assert_eq!(version, version0);
         assert_eq!(files_map, v0_files_map);
@@ -2233,8 +2601,8 @@ mod tests {
 
         // let's fetch version1
         safe_url.set_content_version(Some(version1));
-        let (version, v1_files_map) = retry_loop_for_pattern!(safe
-                .files_container_get(&safe_url.to_string()), Ok(Some((version, _))) if *version == version1)?.ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version, v1_files_map) = single_version(retry_loop_for_pattern!(safe
+                .files_container_get(&safe_url.to_string()), Ok(Some((versions, _))) if versions.contains(&version1))?)?;
 
         assert_eq!(version, version1);
         assert_eq!(new_files_map, v1_files_map);
@@ -2285,8 +2653,8 @@ mod tests {
         let mut safe = new_safe_instance().await?;
         let (xorurl, _, files_map) = new_files_container_from_testdata(&mut safe).await?;
 
-        let (_, files_map_get) = retry_loop!(safe.files_container_get(&xorurl.to_string()))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (_, files_map_get) =
+            single_version(retry_loop!(safe.files_container_get(&xorurl.to_string())))?;
 
         assert_eq!(files_map, files_map_get);
         assert_eq!(files_map_get["/emptyfolder"], files_map["/emptyfolder"]);
@@ -2305,8 +2673,7 @@ mod tests {
         let (xorurl, _, _) =
             retry_loop!(safe.files_container_create_from("./testdata/test.md", None, false, true,));
         let _ = retry_loop!(safe.fetch(&xorurl, None));
-        let (version0, _) = retry_loop!(safe.files_container_get(&xorurl))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
 
         let nrsurl = random_nrs_name();
         let mut safe_url = SafeUrl::from_url(&xorurl)?;
@@ -2346,7 +2713,7 @@ mod tests {
         // └── test.md
         //
         // So, we have 6 items.
-        let (_, fetched_files_map) = retry_loop_for_pattern!(safe.files_container_get(&xorurl), Ok(Some((version, _))) if *version == version2)?.ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (_, fetched_files_map) = retry_loop_for_pattern!(safe.files_container_get(&xorurl), Ok(Some((versions, _))) if versions.contains(&version2))?.ok_or(anyhow!("files container was unexpectedly empty"))?;
         assert_eq!(fetched_files_map.len(), 6);
 
         Ok(())
@@ -2364,8 +2731,7 @@ mod tests {
         assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         let _ = retry_loop!(safe.fetch(&xorurl, None));
-        let (version0, _) = retry_loop!(safe.files_container_get(&xorurl))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
 
         let mut url_with_path = SafeUrl::from_xorurl(&xorurl)?;
         url_with_path.set_path("/new_filename_test.md");
@@ -2515,8 +2881,7 @@ mod tests {
         assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
 
         let _ = retry_loop!(safe.fetch(&xorurl, None));
-        let (version0, _) = retry_loop!(safe.files_container_get(&xorurl))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
 
         let mut url_with_path = SafeUrl::from_xorurl(&xorurl)?;
         url_with_path.set_path("/sub2.md");
@@ -2653,8 +3018,7 @@ mod tests {
         assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         let _ = retry_loop!(safe.fetch(&xorurl, None));
-        let (version0, _) = retry_loop!(safe.files_container_get(&xorurl))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
 
         let data = Bytes::from("0123456789");
         let file_xorurl = retry_loop!(safe.store_public_bytes(data.clone(), None));
@@ -2743,8 +3107,7 @@ mod tests {
         assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         let _ = retry_loop!(safe.fetch(&xorurl, None));
-        let (version0, _) = retry_loop!(safe.files_container_get(&xorurl))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
 
         let data = Bytes::from("0123456789");
         let new_filename = Path::new("/new_filename_test.md");
@@ -2796,8 +3159,7 @@ mod tests {
         let mut safe = new_safe_instance().await?;
         let (xorurl, _, files_map) = new_files_container_from_testdata(&mut safe).await?;
 
-        let (version0, _) = retry_loop!(safe.files_container_get(&xorurl))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
 
         let mut url_with_path = SafeUrl::from_xorurl(&xorurl)?;
         url_with_path.set_path("/test.md");
@@ -2846,4 +3208,96 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_merge_files_maps() -> Result<()> {
+        let version1 = VersionHash::from(&EntryHash([1; 32]));
+        let version2 = VersionHash::from(&EntryHash([2; 32]));
+        let file_info = |link: &str, modified: &str| -> FileInfo {
+            vec![
+                (PREDICATE_TYPE.to_string(), "text/plain".to_string()),
+                (PREDICATE_LINK.to_string(), link.to_string()),
+                (PREDICATE_MODIFIED.to_string(), modified.to_string()),
+            ]
+            .into_iter()
+            .collect()
+        };
+
+        let mut files_map1 = FilesMap::new();
+        files_map1.insert("/same.md".to_string(), file_info("safe://same", "1"));
+        files_map1.insert("/only1.md".to_string(), file_info("safe://only1", "1"));
+        files_map1.insert("/both.md".to_string(), file_info("safe://older", "1"));
+        let mut files_map2 = FilesMap::new();
+        files_map2.insert("/same.md".to_string(), file_info("safe://same", "2"));
+        files_map2.insert("/only2.md".to_string(), file_info("safe://only2", "1"));
+        files_map2.insert("/both.md".to_string(), file_info("safe://newer", "2"));
+
+        let merged = merge_files_maps(&[(version1, files_map1), (version2, files_map2)]);
+
+        let conflict_path = format!("/both.md.conflict-{}", &version1.to_string()[..8]);
+        let mut expected: Vec<_> = vec![
+            "/same.md",
+            "/only1.md",
+            "/only2.md",
+            "/both.md",
+            conflict_path.as_str(),
+        ];
+        expected.sort_unstable();
+        assert_eq!(merged.keys().collect::<Vec<_>>(), expected);
+
+        // same content, the most recently modified metadata is kept
+        assert_eq!(merged["/same.md"][PREDICATE_MODIFIED], "2");
+        // conflicting content, the most recently modified keeps the path
+        assert_eq!(merged["/both.md"][PREDICATE_LINK], "safe://newer");
+        assert_eq!(merged[&conflict_path][PREDICATE_LINK], "safe://older");
+        assert_eq!(
+            merged[&conflict_path][PREDICATE_CONFLICT],
+            version1.to_string()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_merge() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, _, files_map) = new_files_container_from_testdata(&mut safe).await?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
+
+        // fork the FilesContainer with two versions, each adding a different file
+        let mut fork_versions = vec![];
+        for path in ["/fork1.md", "/fork2.md"] {
+            let mut forked_map = files_map.clone();
+            let _ = forked_map.insert(path.to_string(), files_map["/test.md"].clone());
+            let files_map_xorurl = safe.store_files_map(&forked_map, Scope::Public).await?;
+            let hash = safe
+                .register_write(
+                    &xorurl,
+                    files_map_xorurl.as_bytes().to_vec(),
+                    vec![version0.entry_hash()].into_iter().collect(),
+                )
+                .await?;
+            fork_versions.push(VersionHash::from(&hash));
+        }
+
+        // reading the forked FilesContainer merges both versions
+        let (versions, forked_map) = retry_loop!(safe.files_container_get(&xorurl))
+            .ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+        assert_eq!(versions, fork_versions.iter().copied().collect());
+        assert!(forked_map.contains_key("/fork1.md"));
+        assert!(forked_map.contains_key("/fork2.md"));
+
+        // merging writes a single version on top of both
+        let (merged_version, merged_map) = safe
+            .files_container_merge(&xorurl)
+            .await?
+            .ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+        assert_eq!(merged_map, forked_map);
+        assert_eq!(merged_map.len(), files_map.len() + 2);
+
+        let (version, _) = single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
+        assert_eq!(version, merged_version);
+
+        Ok(())
+    }
 }
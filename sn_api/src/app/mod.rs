@@ -13,7 +13,7 @@ mod helpers;
 mod keys;
 mod safe_client;
 #[cfg(test)]
-mod test_helpers;
+pub(crate) mod test_helpers;
 
 use super::{common, constants, Result};
 use rand::rngs::OsRng;
@@ -22,6 +22,7 @@ use safe_network::client::DEFAULT_QUERY_TIMEOUT;
 use safe_network::types::Keypair;
 
 use std::time::Duration;
+use tokio::sync::broadcast;
 
 // The following is what's meant to be the public API
 
@@ -33,6 +34,7 @@ pub mod resolver;
 pub use crate::safeurl::*;
 pub use consts::DEFAULT_XORURL_BASE;
 pub use helpers::parse_tokens_amount;
+pub use safe_network::client::{ChunkCacheConfig, ChunkCacheEviction, UploadEvent, UploadProgress};
 pub use xor_name::{XorName, XOR_NAME_LEN};
 
 #[derive(Clone)]
@@ -40,6 +42,7 @@ pub struct Safe {
     safe_client: SafeAppClient,
     pub xorurl_base: XorUrlBase,
     pub dry_run_mode: bool,
+    pub resume_uploads_mode: bool,
 }
 
 impl Default for Safe {
@@ -54,6 +57,7 @@ impl Safe {
             safe_client: SafeAppClient::new(timeout),
             xorurl_base: xorurl_base.unwrap_or(DEFAULT_XORURL_BASE),
             dry_run_mode: false,
+            resume_uploads_mode: false,
         }
     }
 
@@ -68,4 +72,16 @@ impl Safe {
     pub fn get_my_keypair(&self) -> Result<Keypair> {
         self.safe_client.keypair()
     }
+
+    /// Subscribe to the progress events of the uploads made from now on, e.g. of the files
+    /// stored by `files_container_create_from`, to report their progress.
+    pub fn upload_events(&self) -> Result<broadcast::Receiver<UploadEvent>> {
+        self.safe_client.upload_events()
+    }
+
+    /// Keep the chunks read in an on-disk cache across runs, or only in memory if `None`.
+    /// Only applies to the connections made from now on.
+    pub fn set_chunk_cache(&mut self, chunk_cache: Option<ChunkCacheConfig>) {
+        self.safe_client.set_chunk_cache(chunk_cache);
+    }
 }
@@ -169,6 +169,104 @@ impl Safe {
         Ok(get_versioned_nrs_url(url_str, entry_hash)?)
     }
 
+    /// # Merges the conflicting entries of an NRS Map Container
+    /// When several clients associate the same public name concurrently, the NRS Map Container
+    /// ends up with several links for it. For each such public name, this associates again the
+    /// most recent of its conflicting links, superseding all of them.
+    /// Conflicting links to different versions of the same content are ordered by the history
+    /// of that content, the most recent being the last one written to it. Any other conflicting
+    /// links are ordered by the hash of their NRS entries, so every client picks the same one.
+    /// Returns the NrsMap without conflicts, along with the versioned NRS SafeUrl of the last
+    /// association made, if there was any conflict to merge.
+    pub async fn nrs_merge(&self, top_name: &str) -> Result<(NrsMap, Option<SafeUrl>)> {
+        info!("Merging conflicting entries of NRS map for: {}", top_name);
+
+        let url_str = validate_nrs_top_name(top_name)?;
+        let safe_url = Safe::parse_url(&url_str)?;
+        let multimap_keyvals = match self.fetch_multimap_values(&safe_url).await {
+            Ok(keyvals) => keyvals,
+            Err(Error::EmptyContent(_)) => BTreeSet::new(),
+            Err(e) => return Err(e),
+        };
+
+        let mut links_by_subname = BTreeMap::<Vec<u8>, Vec<(EntryHash, SafeUrl)>>::new();
+        for (hash, (subname_bytes, url_bytes)) in multimap_keyvals {
+            let url = SafeUrl::from_url(str::from_utf8(&url_bytes)?)?;
+            links_by_subname
+                .entry(subname_bytes)
+                .or_default()
+                .push((hash, url));
+        }
+
+        let mut merged_url = None;
+        for (subname_bytes, links) in links_by_subname {
+            if links.len() < 2 {
+                continue;
+            }
+            debug!(
+                "Merging {} conflicting links for subname {:?}",
+                links.len(),
+                str::from_utf8(&subname_bytes)
+            );
+
+            let link = match self.most_recent_nrs_link(&links).await {
+                Some(link) => link,
+                None => continue,
+            };
+            let current_versions = links.iter().map(|(hash, _)| *hash).collect();
+            let entry = (subname_bytes, link.to_string().as_bytes().to_vec());
+            let entry_hash = self
+                .multimap_insert(&url_str, entry, current_versions)
+                .await?;
+            merged_url = Some(get_versioned_nrs_url(url_str.clone(), entry_hash)?);
+        }
+
+        let nrs_map = self.nrs_get_subnames_map(top_name, None).await?;
+        Ok((nrs_map, merged_url))
+    }
+
+    // Picks the most recent of conflicting NRS links, see `nrs_merge`
+    async fn most_recent_nrs_link(&self, links: &[(EntryHash, SafeUrl)]) -> Option<SafeUrl> {
+        // fallback on the link of the greatest entry
+        let fallback = links.iter().max_by_key(|(hash, _)| *hash)?.1.clone();
+
+        // the links are versions of the same content only if they all point to it
+        let mut content_url = fallback.clone();
+        content_url.set_content_version(None);
+        let content_version = |url: &SafeUrl| {
+            let mut unversioned = url.clone();
+            unversioned.set_content_version(None);
+            url.content_version()
+                .filter(|_| unversioned.to_string() == content_url.to_string())
+        };
+        let versions: Vec<_> = links.iter().map(|(_, url)| content_version(url)).collect();
+        if versions.iter().any(Option::is_none) {
+            return Some(fallback);
+        }
+
+        let history = match self.register_history(&content_url.to_string()).await {
+            Ok(history) => history,
+            Err(err) => {
+                debug!(
+                    "Couldn't get the history of {}, ordering conflicting links by hash: {}",
+                    content_url, err
+                );
+                return Some(fallback);
+            }
+        };
+
+        links
+            .iter()
+            .zip(versions)
+            .max_by_key(|((hash, _), version)| {
+                let position = history
+                    .iter()
+                    .position(|item| version.map(|v| v.entry_hash()) == Some(item.hash));
+                (position, *hash)
+            })
+            .map(|((_, url), _)| url.clone())
+    }
+
     /// # Gets a public name's associated link
     /// If no version is specified, returns the latest.
     /// The top name of the input public name needs to be registered first with `nrs_create`
@@ -366,10 +464,11 @@ fn sanitised_url(name: &str) -> String {
 mod tests {
     use super::*;
     use crate::{
-        app::test_helpers::{new_safe_instance, random_nrs_name},
+        app::test_helpers::{new_safe_instance, random_nrs_name, single_version},
         retry_loop, retry_loop_for_pattern, Error, SafeUrl,
     };
     use anyhow::{anyhow, bail, Result};
+    use bytes::Bytes;
     use rand::Rng;
     use std::{matches, str::FromStr};
 
@@ -402,8 +501,7 @@ mod tests {
         let (link, _, _) = safe
             .files_container_create_from(TEST_DATA_FILE, None, false, false)
             .await?;
-        let (version0, _) = retry_loop!(safe.files_container_get(&link))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&link)))?;
 
         let mut url_v0 = SafeUrl::from_url(&link)?;
         url_v0.set_content_version(Some(version0));
@@ -440,8 +538,7 @@ mod tests {
         let (link, _, _) = safe
             .files_container_create_from(TEST_DATA_FILE, None, false, false)
             .await?;
-        let (version0, _) = retry_loop!(safe.files_container_get(&link))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&link)))?;
 
         let mut url_v0 = SafeUrl::from_url(&link)?;
         url_v0.set_content_version(Some(version0));
@@ -504,8 +601,7 @@ mod tests {
         let (link, _, _) = safe
             .files_container_create_from(TEST_DATA_FILE, None, false, false)
             .await?;
-        let (version0, _) = retry_loop!(safe.files_container_get(&link))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&link)))?;
 
         // associate a first name
         let mut url_v0 = SafeUrl::from_url(&link)?;
@@ -563,8 +659,7 @@ mod tests {
         let (link, _, _) = safe
             .files_container_create_from(TEST_DATA_FILE, None, false, false)
             .await?;
-        let (version0, _) = retry_loop!(safe.files_container_get(&link))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&link)))?;
 
         let mut url_v0 = SafeUrl::from_url(&link)?;
         url_v0.set_content_version(Some(version0));
@@ -618,8 +713,7 @@ mod tests {
         let (link, _, _) = safe
             .files_container_create_from(TEST_DATA_FILE, None, false, false)
             .await?;
-        let (version0, _) = retry_loop!(safe.files_container_get(&link))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&link)))?;
 
         // associate a first name
         let mut valid_link = SafeUrl::from_url(&link)?;
@@ -679,4 +773,59 @@ mod tests {
         let _ = retry_loop_for_pattern!(safe.nrs_get(&site_name, None), Ok((res_url, _)) if res_url == &valid_link)?;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_nrs_merge() -> Result<()> {
+        let site_name = random_nrs_name();
+        let mut safe = new_safe_instance().await?;
+
+        // two versions of the same files container, the second one written on top of the first
+        let (link, _, _) = safe
+            .files_container_create_from(TEST_DATA_FILE, None, false, false)
+            .await?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&link)))?;
+        let (version1, _) = safe
+            .files_container_add_from_raw(
+                Bytes::from("new file"),
+                &format!("{}/new.md", link),
+                false,
+                false,
+            )
+            .await?
+            .0
+            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+
+        let mut old_link = SafeUrl::from_url(&link)?;
+        old_link.set_content_version(Some(version0));
+        let mut new_link = SafeUrl::from_url(&link)?;
+        new_link.set_content_version(Some(version1));
+
+        // associate the site concurrently to both versions
+        let _ = retry_loop!(safe.nrs_add(&site_name, &new_link));
+        let url_str = validate_nrs_top_name(&site_name)?;
+        let entry = (
+            "".as_bytes().to_vec(),
+            old_link.to_string().as_bytes().to_vec(),
+        );
+        let _ = safe
+            .multimap_insert(&url_str, entry, BTreeSet::new())
+            .await?;
+        let _ = retry_loop_for_pattern!(
+            safe.nrs_get(&site_name, None),
+            Err(Error::ConflictingNrsEntries { .. })
+        );
+
+        // merging keeps the most recent version of the files container
+        let (nrs_map, merged_url) = safe.nrs_merge(&site_name).await?;
+        assert!(merged_url.is_some());
+        assert_eq!(nrs_map.get(&site_name)?, new_link);
+
+        let _ = retry_loop_for_pattern!(safe.nrs_get(&site_name, None), Ok((res_url, _)) if res_url == &new_link)?;
+
+        // nothing left to merge
+        let (_, merged_url) = safe.nrs_merge(&site_name).await?;
+        assert!(merged_url.is_none());
+
+        Ok(())
+    }
 }
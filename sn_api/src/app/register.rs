@@ -7,12 +7,15 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-pub use safe_network::types::register::{Entry, EntryHash};
+pub use safe_network::types::register::{
+    Action, Entry, EntryHash, HistoryEntry, Permissions, Policy, PrivatePermissions, PrivatePolicy,
+    PublicPermissions, PublicPolicy, User,
+};
 
 use crate::safeurl::{ContentType, SafeUrl, XorUrl};
 use crate::{Error, Result, Safe};
 use log::debug;
-use safe_network::types::{DataAddress, RegisterAddress, Scope};
+use safe_network::types::{DataAddress, PublicKey, RegisterAddress, Scope};
 use std::collections::BTreeSet;
 use xor_name::XorName;
 
@@ -60,6 +63,40 @@ impl Safe {
         self.register_fetch_entry(&safeurl, hash).await
     }
 
+    /// Read the history of a Register on the network, oldest entries first.
+    /// If the URL carries a version hash, the history ends with that entry,
+    /// i.e. it's the content of the Register as of that version.
+    pub async fn register_history(&self, url: &str) -> Result<Vec<HistoryEntry>> {
+        debug!("Getting Register history from: {:?}", url);
+        let safeurl = self.parse_and_resolve_url(url).await?;
+
+        self.register_fetch_history(&safeurl).await
+    }
+
+    /// Read the children of an entry of a Register on the network,
+    /// i.e. the entries it was written on top of
+    pub async fn register_read_children(
+        &self,
+        url: &str,
+        hash: EntryHash,
+    ) -> Result<BTreeSet<(EntryHash, Entry)>> {
+        debug!(
+            "Getting children of Register entry {:?} from: {:?}",
+            hash, url
+        );
+        let safeurl = self.parse_and_resolve_url(url).await?;
+        let address = self.get_register_address(&safeurl)?;
+
+        self.safe_client.get_register_children(address, hash).await
+    }
+
+    /// Fetch the history of a Register from a SafeUrl without performing any type of URL resolution
+    pub(crate) async fn register_fetch_history(&self, url: &SafeUrl) -> Result<Vec<HistoryEntry>> {
+        let from = url.content_version().map(|v| v.entry_hash());
+        let address = self.get_register_address(url)?;
+        self.safe_client.get_register_history(address, from).await
+    }
+
     /// Fetch a Register from a SafeUrl without performing any type of URL resolution
     /// Supports version hashes:
     /// e.g. safe://mysafeurl?v=ce56a3504c8f27bfeb13bdf9051c2e91409230ea
@@ -133,6 +170,75 @@ impl Safe {
         Ok(entry_hash)
     }
 
+    /// Write value to a Register on the network on top of all its current entries,
+    /// thus merging any concurrent forks of it into the new entry.
+    /// Returns the hash of the new entry along with the entries it superseded.
+    pub async fn register_merge(
+        &self,
+        url: &str,
+        entry: Entry,
+    ) -> Result<(EntryHash, BTreeSet<(EntryHash, Entry)>)> {
+        let mut reg_url = self.parse_and_resolve_url(url).await?;
+        reg_url.set_content_version(None);
+        let current = self.register_fetch_entries(&reg_url).await?;
+        debug!(
+            "Merging {} entries of Register at {}",
+            current.len(),
+            reg_url
+        );
+
+        let parents = current.iter().map(|(hash, _)| *hash).collect();
+        let entry_hash = self
+            .register_write(&reg_url.to_string(), entry, parents)
+            .await?;
+
+        Ok((entry_hash, current))
+    }
+
+    /// Read the policy of a Register on the network, i.e. its owner and its users' permissions
+    pub async fn register_policy(&self, url: &str) -> Result<Policy> {
+        debug!("Getting Register policy from: {:?}", url);
+        let safeurl = self.parse_and_resolve_url(url).await?;
+        let address = self.get_register_address(&safeurl)?;
+
+        self.safe_client.get_register_policy(address).await
+    }
+
+    /// Set the policy of a Register on the network, e.g. to add or remove writers,
+    /// or to grant read access to a private Register.
+    /// Only its owner can do it, and the policy must keep the same owner.
+    pub async fn register_set_policy(&self, url: &str, policy: Policy) -> Result<()> {
+        let reg_url = self.parse_and_resolve_url(url).await?;
+        let address = self.get_register_address(&reg_url)?;
+        let op_batch = self
+            .safe_client
+            .set_register_policy(address, policy)
+            .await?;
+
+        if !self.dry_run_mode {
+            self.safe_client.apply_register_ops(op_batch).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Transfer the ownership of a Register on the network to another key.
+    /// Only its current owner can do it.
+    pub async fn register_transfer_ownership(&self, url: &str, new_owner: PublicKey) -> Result<()> {
+        let reg_url = self.parse_and_resolve_url(url).await?;
+        let address = self.get_register_address(&reg_url)?;
+        let op_batch = self
+            .safe_client
+            .transfer_register_ownership(address, new_owner)
+            .await?;
+
+        if !self.dry_run_mode {
+            self.safe_client.apply_register_ops(op_batch).await?;
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn get_register_address(&self, url: &SafeUrl) -> Result<RegisterAddress> {
         let address = match url.address() {
             DataAddress::Register(reg_address) => reg_address,
@@ -150,8 +256,12 @@ impl Safe {
 
 #[cfg(test)]
 mod tests {
+    use super::{Policy, PrivatePermissions};
     use crate::app::test_helpers::new_safe_instance;
-    use anyhow::Result;
+    use crate::{retry_loop, retry_loop_for_pattern, SafeUrl, VersionHash};
+    use anyhow::{bail, Result};
+    use rand::rngs::OsRng;
+    use safe_network::types::Keypair;
 
     #[tokio::test]
     async fn test_register_create() -> Result<()> {
@@ -180,6 +290,85 @@ mod tests {
         assert_eq!(received_entry, initial_data.clone());
         assert_eq!(received_entry_priv, initial_data);
 
+        let next_data = "next data bytes".as_bytes().to_vec();
+        let next_hash = safe
+            .register_write(&xorurl, next_data.clone(), vec![hash].into_iter().collect())
+            .await?;
+
+        let children = safe.register_read_children(&xorurl, next_hash).await?;
+        assert_eq!(children, vec![(hash, initial_data)].into_iter().collect());
+        assert!(safe.register_read_children(&xorurl, hash).await?.is_empty());
+
+        let history = safe.register_history(&xorurl).await?;
+        let hashes: Vec<_> = history.iter().map(|h| h.hash).collect();
+        assert_eq!(hashes, vec![hash, next_hash]);
+
+        // the history as of the first entry
+        let mut url = SafeUrl::from_url(&xorurl)?;
+        url.set_content_version(Some(VersionHash::from(&hash)));
+        let history = safe.register_history(&url.to_string()).await?;
+        let hashes: Vec<_> = history.iter().map(|h| h.hash).collect();
+        assert_eq!(hashes, vec![hash]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_merge() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let xorurl = safe.register_create(None, 25_000, false).await?;
+
+        // two concurrent writes fork the register
+        let data1 = b"first fork".to_vec();
+        let data2 = b"second fork".to_vec();
+        let hash1 = safe
+            .register_write(&xorurl, data1.clone(), Default::default())
+            .await?;
+        let hash2 = safe
+            .register_write(&xorurl, data2.clone(), Default::default())
+            .await?;
+        assert_eq!(safe.register_read(&xorurl).await?.len(), 2);
+
+        let merged = b"merged".to_vec();
+        let (merged_hash, superseded) = safe.register_merge(&xorurl, merged.clone()).await?;
+        assert_eq!(
+            superseded,
+            vec![(hash1, data1), (hash2, data2)].into_iter().collect()
+        );
+
+        let received_data = safe.register_read(&xorurl).await?;
+        assert_eq!(
+            received_data,
+            vec![(merged_hash, merged)].into_iter().collect()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_policy() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let xorurl = safe.register_create(None, 25_000, true).await?;
+
+        let reader = Keypair::new_ed25519(&mut OsRng).public_key();
+        let mut policy = retry_loop!(safe.register_policy(&xorurl));
+        match &mut policy {
+            Policy::Private(policy) => {
+                let _ = policy
+                    .permissions
+                    .insert(reader, PrivatePermissions::new(true, false));
+            }
+            Policy::Public(_) => bail!("Policy of a private Register is public: {:?}", policy),
+        }
+        safe.register_set_policy(&xorurl, policy.clone()).await?;
+        let _ = retry_loop_for_pattern!(safe.register_policy(&xorurl), Ok(current) if *current == policy)?;
+
+        let new_owner = Keypair::new_ed25519(&mut OsRng).public_key();
+        safe.register_transfer_ownership(&xorurl, new_owner).await?;
+        let _ = retry_loop_for_pattern!(safe.register_policy(&xorurl), Ok(current) if *current.owner() == new_owner)?;
+        // the previous owner can't change the policy anymore
+        assert!(safe.register_set_policy(&xorurl, policy).await.is_err());
+
         Ok(())
     }
 }
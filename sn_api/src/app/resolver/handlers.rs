@@ -4,10 +4,9 @@ use crate::app::{
     multimap::MultimapKeyValues,
     DataType, Safe, SafeUrl,
 };
-use crate::{Error, Result};
+use crate::{Error, Result, Scope};
 use bytes::Bytes;
 use log::{debug, warn};
-use safe_network::types::BytesAddress;
 use std::collections::BTreeSet;
 
 impl Safe {
@@ -173,8 +172,12 @@ impl Safe {
         ensure_no_subnames(&input_url, "file container")?;
 
         // Fetch files container
+        // a FilesContainer with concurrent versions has no single version for its merged FilesMap
         let (version, files_map) = match self.fetch_files_container(&input_url).await? {
-            Some((version, files_map)) => (Some(version), files_map),
+            Some((versions, files_map)) if versions.len() == 1 => {
+                (versions.into_iter().next(), files_map)
+            }
+            Some((_, files_map)) => (None, files_map),
             None => (None, FilesMap::default()),
         };
 
@@ -256,20 +259,32 @@ impl Safe {
         };
 
         let data = if retrieve_data {
-            self.safe_client
-                .get_bytes(BytesAddress::Public(input_url.xorname()), range)
-                .await?
+            self.fetch_data(input_url, range).await?
         } else {
             Bytes::new()
         };
 
-        let safe_data = SafeData::PublicBlob {
-            xorurl: input_url.to_xorurl_string(),
-            xorname: input_url.xorname(),
-            data,
-            media_type,
-            metadata: metadata.clone(),
-            resolved_from: input_url.to_string(),
+        let xorurl = input_url.to_xorurl_string();
+        let xorname = input_url.xorname();
+        let metadata = metadata.clone();
+        let resolved_from = input_url.to_string();
+        let safe_data = match input_url.scope() {
+            Scope::Public => SafeData::PublicBlob {
+                xorurl,
+                xorname,
+                data,
+                media_type,
+                metadata,
+                resolved_from,
+            },
+            Scope::Private => SafeData::PrivateBlob {
+                xorurl,
+                xorname,
+                data,
+                media_type,
+                metadata,
+                resolved_from,
+            },
         };
 
         Ok(safe_data)
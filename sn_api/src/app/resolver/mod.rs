@@ -243,7 +243,11 @@ impl Safe {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{app::files, app::test_helpers::new_safe_instance, retry_loop, SafeUrl, Scope};
+    use crate::{
+        app::files,
+        app::test_helpers::{new_safe_instance, single_version},
+        retry_loop, SafeUrl, Scope,
+    };
     use anyhow::{anyhow, bail, Context, Result};
     use bytes::Bytes;
     use rand::{distributions::Alphanumeric, thread_rng, Rng};
@@ -259,8 +263,7 @@ mod tests {
 
         let safe_url = SafeUrl::from_url(&fc_xorurl)?;
         let content = retry_loop!(safe.fetch(&fc_xorurl, None));
-        let (version0, _) = retry_loop!(safe.files_container_get(&fc_xorurl))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&fc_xorurl)))?;
 
         match content.clone() {
             SafeData::FilesContainer {
@@ -310,8 +313,7 @@ mod tests {
             .files_container_create_from("./testdata/", None, true, false)
             .await?;
         let _ = retry_loop!(safe.fetch(&xorurl, None));
-        let (version0, _) = retry_loop!(safe.files_container_get(&xorurl))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
 
         // link to an nrs map
         let mut safe_url = SafeUrl::from_url(&xorurl)?;
@@ -370,8 +372,7 @@ mod tests {
             .files_container_create_from("./testdata/", None, true, false)
             .await?;
         let _ = retry_loop!(safe.fetch(&xorurl, None));
-        let (version0, _) = retry_loop!(safe.files_container_get(&xorurl))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
 
         // link to an nrs map
         let mut safe_url = SafeUrl::from_url(&xorurl)?;
@@ -510,8 +511,7 @@ mod tests {
             .files_container_create_from("./testdata/", None, true, false)
             .await?;
         let _ = retry_loop!(safe.fetch(&xorurl, None));
-        let (version0, _) = retry_loop!(safe.files_container_get(&xorurl))
-            .ok_or(anyhow!("files container was unexpectedly empty"))?;
+        let (version0, _) = single_version(retry_loop!(safe.files_container_get(&xorurl)))?;
 
         // map to nrs name
         let mut safe_url = SafeUrl::from_url(&xorurl)?;
@@ -33,7 +33,9 @@ pub enum SafeData {
         xorurl: String,
         xorname: XorName,
         type_tag: u64,
-        version: Option<VersionHash>, // this is set to None if the container is found empty
+        // this is set to None if the container is found empty, or with several concurrent
+        // versions, whose FilesMaps are merged
+        version: Option<VersionHash>,
         files_map: FilesMap,
         data_type: DataType,
         metadata: Option<FileInfo>,
@@ -48,6 +50,14 @@ pub enum SafeData {
         metadata: Option<FileInfo>,
         resolved_from: String,
     },
+    PrivateBlob {
+        xorurl: String,
+        xorname: XorName,
+        data: Bytes,
+        media_type: Option<String>,
+        metadata: Option<FileInfo>,
+        resolved_from: String,
+    },
     NrsMapContainer {
         public_name: Option<String>,
         xorurl: String,
@@ -89,6 +99,7 @@ impl SafeData {
             SafeKey { xorurl, .. }
             | FilesContainer { xorurl, .. }
             | PublicBlob { xorurl, .. }
+            | PrivateBlob { xorurl, .. }
             | NrsMapContainer { xorurl, .. }
             | Multimap { xorurl, .. }
             | PublicRegister { xorurl, .. }
@@ -102,6 +113,7 @@ impl SafeData {
             SafeKey { resolved_from, .. }
             | FilesContainer { resolved_from, .. }
             | PublicBlob { resolved_from, .. }
+            | PrivateBlob { resolved_from, .. }
             | NrsMapContainer { resolved_from, .. }
             | Multimap { resolved_from, .. }
             | PublicRegister { resolved_from, .. }
@@ -114,6 +126,7 @@ impl SafeData {
         match self {
             SafeKey { .. }
             | PublicBlob { .. }
+            | PrivateBlob { .. }
             | Multimap { .. }
             | PublicRegister { .. }
             | PrivateRegister { .. } => None,
@@ -131,7 +144,9 @@ impl SafeData {
             | PublicRegister { .. }
             | PrivateRegister { .. }
             | NrsMapContainer { .. } => None,
-            FilesContainer { metadata, .. } | PublicBlob { metadata, .. } => metadata.clone(),
+            FilesContainer { metadata, .. }
+            | PublicBlob { metadata, .. }
+            | PrivateBlob { metadata, .. } => metadata.clone(),
         }
     }
 }
@@ -12,25 +12,35 @@ use crate::{ipc::NodeConfig, Error, Result};
 use bytes::Bytes;
 use hex::encode;
 use log::{debug, info};
-use safe_network::client::{Client, ClientConfig, Error as ClientError, RegisterWriteAheadLog};
+use safe_network::client::{
+    ChunkCacheConfig, Client, ClientConfig, Error as ClientError, RegisterWriteAheadLog,
+    UploadEvent,
+};
 use safe_network::types::{
-    register::{Entry, EntryHash, PrivatePermissions, PublicPermissions, User},
-    BytesAddress, Error as SafeNdError, Keypair, RegisterAddress, Scope,
+    register::{
+        Entry, EntryHash, HistoryEntry, Policy, PrivatePermissions, PublicPermissions, User,
+    },
+    BytesAddress, Error as SafeNdError, Keypair, PublicKey, RegisterAddress, Scope,
 };
 use std::{
     collections::{BTreeMap, BTreeSet},
     path::{Path, PathBuf},
     time::Duration,
 };
+use tokio::{io::AsyncWrite, sync::broadcast};
 use xor_name::XorName;
 
 const APP_NOT_CONNECTED: &str = "Application is not connected to the network";
+const PRIVATE_DRY_RUN_NOT_CONNECTED: &str = "The address of private content depends on the keys \
+    of the client storing it, so it can only be calculated, even in dry-run mode, once connected \
+    to the network";
 
 #[derive(Default, Clone)]
 pub struct SafeAppClient {
     safe_client: Option<Client>,
     config_path: Option<PathBuf>,
     timeout: Duration,
+    chunk_cache: Option<ChunkCacheConfig>,
 }
 
 impl SafeAppClient {
@@ -42,14 +52,27 @@ impl SafeAppClient {
         }
     }
 
+    // Private helper to obtain the Safe Client instance whose keys private content is
+    // encrypted with, to calculate its address in dry-run mode
+    fn get_private_content_owner(&self) -> Result<Client> {
+        self.get_safe_client()
+            .map_err(|_| Error::ConnectionError(PRIVATE_DRY_RUN_NOT_CONNECTED.to_string()))
+    }
+
     pub fn new(timeout: Duration) -> Self {
         Self {
             safe_client: None,
             config_path: None,
             timeout,
+            chunk_cache: None,
         }
     }
 
+    // Set the on-disk chunk cache of the clients connected from now on
+    pub fn set_chunk_cache(&mut self, chunk_cache: Option<ChunkCacheConfig>) {
+        self.chunk_cache = chunk_cache;
+    }
+
     // Connect to the SAFE Network using the keypair if provided. Contacts list
     // are overriden if a 'bootstrap_config' is provided.
     pub async fn connect(
@@ -68,7 +91,7 @@ impl SafeAppClient {
         );
         debug!("Bootstrap contacts list set to: {:?}", node_config);
 
-        let config = ClientConfig::new(
+        let mut config = ClientConfig::new(
             None,
             None,
             node_config.0,
@@ -77,6 +100,7 @@ impl SafeAppClient {
             None,
         )
         .await;
+        config.chunk_cache = self.chunk_cache;
         let client = Client::new(config, node_config.1, app_keypair)
             .await
             .map_err(|err| {
@@ -94,20 +118,77 @@ impl SafeAppClient {
         Ok(client.keypair())
     }
 
+    pub fn upload_events(&self) -> Result<broadcast::Receiver<UploadEvent>> {
+        let client = self.get_safe_client()?;
+        Ok(client.upload_events())
+    }
+
     //
     // Blob operations
     //
-    pub async fn store_bytes(&self, bytes: Bytes, dry_run: bool) -> Result<XorName> {
+    pub async fn store_bytes(&self, bytes: Bytes, scope: Scope, dry_run: bool) -> Result<XorName> {
         let address = if dry_run {
             debug!(
                 "Calculating network address for {} bytes of data",
                 bytes.len()
             );
-            Client::calculate_address(bytes, Scope::Public)?
+            match scope {
+                Scope::Public => Client::calculate_address(bytes, scope)?,
+                // the address of private data depends on the keys of the client storing it
+                Scope::Private => {
+                    self.get_private_content_owner()?
+                        .chunk_bytes(bytes, scope)?
+                        .0
+                }
+            }
         } else {
             debug!("Storing {} bytes of data", bytes.len());
             let client = self.get_safe_client()?;
-            client.upload_and_verify(bytes, Scope::Public).await?
+            client.upload_and_verify(bytes, scope).await?
+        };
+
+        Ok(*address.name())
+    }
+
+    pub async fn store_file(
+        &self,
+        path: &Path,
+        scope: Scope,
+        dry_run: bool,
+        resume: bool,
+    ) -> Result<XorName> {
+        let address = if dry_run {
+            debug!("Calculating network address for file {}", path.display());
+            match scope {
+                Scope::Public => Client::calculate_address_from_path(path, scope).await?,
+                // the address of private data depends on the keys of the client storing it
+                Scope::Private => {
+                    self.get_private_content_owner()?
+                        .calculate_own_address_from_path(path, scope)
+                        .await?
+                }
+            }
+        } else if resume {
+            debug!("Resuming upload of file {}", path.display());
+            let client = self.get_safe_client()?;
+            let journal_id = Client::upload_journal_id(path, scope)?;
+            let address = match client.resume_upload(journal_id).await {
+                Err(ClientError::NoSuchUploadJournal(_)) => {
+                    debug!("No upload to resume found, storing file {}", path.display());
+                    client.upload_from_path(path, scope).await?
+                }
+                other => other?,
+            };
+
+            // let's now try to retrieve it, without keeping it in memory
+            let _ = client
+                .read_to_writer(address, &mut tokio::io::sink())
+                .await?;
+            address
+        } else {
+            debug!("Storing file {}", path.display());
+            let client = self.get_safe_client()?;
+            client.upload_from_path_and_verify(path, scope).await?
         };
 
         Ok(*address.name())
@@ -137,6 +218,26 @@ impl SafeAppClient {
         Ok(data)
     }
 
+    pub async fn get_bytes_to_writer<W>(&self, address: BytesAddress, writer: &mut W) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        debug!("Attempting to stream data from {:?}", address.name());
+        let client = self.get_safe_client()?;
+        let bytes_written = client
+            .read_to_writer(address, writer)
+            .await
+            .map_err(|e| Error::NetDataError(format!("Failed to GET Blob: {:?}", e)))?;
+
+        debug!(
+            "{} bytes of data successfully streamed from: {:?}",
+            bytes_written,
+            address.name()
+        );
+
+        Ok(bytes_written)
+    }
+
     // === Register data operations ===
     /// Low level method to create a register
     /// Returns a register operation batch that can be used to apply changes on the network.
@@ -252,6 +353,59 @@ impl SafeAppClient {
         Ok(entry)
     }
 
+    /// Low level method to read the history of a register, oldest entries first,
+    /// optionally ending with the given entry
+    pub async fn get_register_history(
+        &self,
+        address: RegisterAddress,
+        from: Option<EntryHash>,
+    ) -> Result<Vec<HistoryEntry>> {
+        debug!("Fetching Register history from {:?} at {:?}", from, address);
+
+        let client = self.get_safe_client()?;
+        client
+            .get_register_history(address, from)
+            .await
+            .map_err(|err| match (err, from) {
+                (ClientError::NetworkDataError(SafeNdError::NoSuchEntry), Some(hash)) => {
+                    Error::HashNotFound(hash)
+                }
+                (err, _) => Error::NetDataError(format!(
+                    "Failed to retrieve history from Register data: {:?}",
+                    err
+                )),
+            })
+    }
+
+    /// Low level method to read the children of a register entry,
+    /// i.e. the entries it was written on top of
+    pub async fn get_register_children(
+        &self,
+        address: RegisterAddress,
+        hash: EntryHash,
+    ) -> Result<BTreeSet<(EntryHash, Entry)>> {
+        debug!(
+            "Fetching children of Register hash {:?} at {:?}",
+            hash, address
+        );
+
+        let client = self.get_safe_client()?;
+        client
+            .get_register_children(address, hash)
+            .await
+            .map_err(|err| {
+                if let ClientError::NetworkDataError(SafeNdError::NoSuchEntry) = err {
+                    Error::HashNotFound(hash)
+                } else {
+                    Error::NetDataError(format!(
+                        "Failed to retrieve children of entry with hash '{}' from Register data: {:?}",
+                        encode(hash.0),
+                        err
+                    ))
+                }
+            })
+    }
+
     /// Low level method to write to register
     /// Returns a register operation batch that can be used to apply changes on the network.
     /// Nothing is sent to the network, without applying the batch, it's pretty much a dry run.
@@ -275,6 +429,61 @@ impl SafeAppClient {
             })
     }
 
+    /// Low level method to read the policy of a register, i.e. its owner and users' permissions
+    pub async fn get_register_policy(&self, address: RegisterAddress) -> Result<Policy> {
+        debug!("Fetching Register policy at {:?}", address);
+        let client = self.get_safe_client()?;
+
+        client.get_register_policy(address).await.map_err(|err| {
+            Error::NetDataError(format!("Failed to retrieve Register policy: {:?}", err))
+        })
+    }
+
+    /// Low level method to set the policy of a register
+    /// Returns a register operation batch that can be used to apply changes on the network.
+    pub async fn set_register_policy(
+        &self,
+        address: RegisterAddress,
+        policy: Policy,
+    ) -> Result<RegisterWriteAheadLog> {
+        debug!("Setting policy of Register at {:?}", address);
+        let client = self.get_safe_client()?;
+
+        client
+            .set_register_policy(address, policy)
+            .await
+            .map_err(|err| {
+                Error::NetDataError(format!(
+                    "Failed to prepare set Register policy operation: {:?}",
+                    err
+                ))
+            })
+    }
+
+    /// Low level method to transfer the ownership of a register
+    /// Returns a register operation batch that can be used to apply changes on the network.
+    pub async fn transfer_register_ownership(
+        &self,
+        address: RegisterAddress,
+        new_owner: PublicKey,
+    ) -> Result<RegisterWriteAheadLog> {
+        debug!(
+            "Transferring ownership of Register at {:?} to {:?}",
+            address, new_owner
+        );
+        let client = self.get_safe_client()?;
+
+        client
+            .transfer_register_ownership(address, new_owner)
+            .await
+            .map_err(|err| {
+                Error::NetDataError(format!(
+                    "Failed to prepare transfer Register ownership operation: {:?}",
+                    err
+                ))
+            })
+    }
+
     /// Low level method to apply register operations batches and send them to the network
     pub async fn apply_register_ops(&self, batch: RegisterWriteAheadLog) -> Result<()> {
         let client = self.get_safe_client()?;
@@ -7,11 +7,17 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use crate::{ipc::NodeConfig, Safe};
+use crate::{files::FilesMap, ipc::NodeConfig, Safe, VersionHash};
 use anyhow::{bail, Context, Result};
 use rand::{distributions::Alphanumeric, rngs::OsRng, thread_rng, Rng};
 use safe_network::types::{Keypair, PublicKey};
-use std::{collections::BTreeSet, env::var, fs, net::SocketAddr, sync::Once};
+use std::{
+    collections::{BTreeSet, HashSet},
+    env::var,
+    fs,
+    net::SocketAddr,
+    sync::Once,
+};
 use tracing_subscriber::{fmt, EnvFilter};
 
 // Environment variable which can be set with the auth credentials
@@ -39,6 +45,19 @@ fn init_logger() {
     });
 }
 
+// Returns the only latest version of a FilesContainer fetched with `files_container_get`,
+// failing if it was empty or has several concurrent versions
+pub fn single_version(
+    found: Option<(HashSet<VersionHash>, FilesMap)>,
+) -> Result<(VersionHash, FilesMap)> {
+    let (versions, files_map) = found.context("files container was unexpectedly empty")?;
+    if versions.len() != 1 {
+        bail!("files container has {} latest versions", versions.len());
+    }
+    let version = versions.into_iter().next().context("no version")?;
+    Ok((version, files_map))
+}
+
 // Instantiate a Safe instance
 pub async fn new_safe_instance() -> Result<Safe> {
     init_logger();
@@ -98,7 +117,7 @@ fn read_default_peers_from_file() -> Result<(String, BTreeSet<SocketAddr>)> {
     Ok(info)
 }
 
-fn get_bootstrap_contacts() -> Result<NodeConfig> {
+pub(crate) fn get_bootstrap_contacts() -> Result<NodeConfig> {
     let (genesis_key_hex, bootstrap_contacts) = match var(TEST_BOOTSTRAPPING_PEERS) {
         Ok(val) => serde_json::from_str(&val).with_context(|| {
             format!(
@@ -0,0 +1,215 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{common::auth_types::AuthedApp, ipc::req::AuthReq, Error, Result};
+use bytes::Bytes;
+use safe_network::types::Keypair;
+use serde::{Deserialize, Serialize};
+use std::collections::{btree_map::Entry, BTreeMap};
+
+/// Content of a Safe, i.e. the applications authorised by its owner along
+/// with the keypair generated for each of them.
+///
+/// A snapshot of it is encrypted and stored on the network every time it changes,
+/// with the Safe's Register pointing to the latest snapshot/s.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(super) struct SafeAccount {
+    apps: BTreeMap<String, AppEntry>,
+}
+
+/// Information the Safe keeps about an authorised application.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(super) struct AppEntry {
+    /// The application friendly-name.
+    pub(super) name: String,
+    /// The application provider/vendor.
+    pub(super) vendor: String,
+    /// The keypair generated for the application to sign its requests with.
+    pub(super) keypair: Keypair,
+    /// Whether the application's access was revoked. A revoked application
+    /// is assigned a new keypair if it's authorised again.
+    pub(super) revoked: bool,
+}
+
+impl AppEntry {
+    pub(super) fn new(auth_req: &AuthReq, keypair: Keypair) -> Self {
+        Self {
+            name: auth_req.app_name.clone(),
+            vendor: auth_req.app_vendor.clone(),
+            keypair,
+            revoked: false,
+        }
+    }
+
+    // Resolves concurrent changes made to the same application's entry. The revocation
+    // of a keypair always prevails, while between different keypairs the one still
+    // in use is kept, falling back to the public key order so all replicas agree.
+    fn supersedes(&self, other: &Self) -> bool {
+        if self.keypair == other.keypair {
+            self.revoked && !other.revoked
+        } else if self.revoked != other.revoked {
+            !self.revoked
+        } else {
+            self.keypair.public_key() > other.keypair.public_key()
+        }
+    }
+}
+
+impl SafeAccount {
+    /// Returns the entry of an application which is currently authorised.
+    pub(super) fn authorised_app(&self, app_id: &str) -> Option<&AppEntry> {
+        self.apps.get(app_id).filter(|entry| !entry.revoked)
+    }
+
+    /// Inserts (or replaces) the entry of an application.
+    pub(super) fn insert_app(&mut self, app_id: &str, entry: AppEntry) {
+        let _ = self.apps.insert(app_id.to_string(), entry);
+    }
+
+    /// Flags an application as revoked, returning `false` if the application is unknown.
+    pub(super) fn revoke_app(&mut self, app_id: &str) -> bool {
+        match self.apps.get_mut(app_id) {
+            Some(entry) => {
+                entry.revoked = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// List of the applications currently authorised.
+    pub(super) fn authed_apps(&self) -> Vec<AuthedApp> {
+        self.apps
+            .iter()
+            .filter(|(_, entry)| !entry.revoked)
+            .map(|(id, entry)| AuthedApp {
+                id: id.clone(),
+                name: entry.name.clone(),
+                vendor: entry.vendor.clone(),
+            })
+            .collect()
+    }
+
+    /// Merges the content of a concurrent version of the Safe.
+    pub(super) fn merge(&mut self, other: Self) {
+        for (app_id, other_entry) in other.apps {
+            match self.apps.entry(app_id) {
+                Entry::Vacant(vacant) => {
+                    let _ = vacant.insert(other_entry);
+                }
+                Entry::Occupied(mut occupied) => {
+                    if other_entry.supersedes(occupied.get()) {
+                        let _ = occupied.insert(other_entry);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Serialises and encrypts the content so it can be stored on the network.
+    pub(super) fn encrypt(&self, encryption_key: &bls::PublicKey) -> Result<Bytes> {
+        let serialised = bincode::serialize(self).map_err(|err| {
+            Error::Serialisation(format!("Failed to serialise Safe content: {:?}", err))
+        })?;
+        let ciphertext = encryption_key.encrypt(serialised);
+        let bytes = bincode::serialize(&ciphertext).map_err(|err| {
+            Error::Serialisation(format!(
+                "Failed to serialise encrypted Safe content: {:?}",
+                err
+            ))
+        })?;
+
+        Ok(Bytes::from(bytes))
+    }
+
+    /// Decrypts and deserialises content previously encrypted with `encrypt`.
+    pub(super) fn decrypt(bytes: &[u8], decryption_key: &bls::SecretKey) -> Result<Self> {
+        let ciphertext: bls::Ciphertext = bincode::deserialize(bytes).map_err(|err| {
+            Error::Serialisation(format!(
+                "Failed to deserialise encrypted Safe content: {:?}",
+                err
+            ))
+        })?;
+        let serialised = decryption_key.decrypt(&ciphertext).ok_or_else(|| {
+            Error::AuthenticatorError("Failed to decrypt Safe content".to_string())
+        })?;
+
+        bincode::deserialize(&serialised).map_err(|err| {
+            Error::Serialisation(format!("Failed to deserialise Safe content: {:?}", err))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rand::rngs::OsRng;
+
+    fn new_entry(app_name: &str) -> AppEntry {
+        AppEntry {
+            name: app_name.to_string(),
+            vendor: "vendor".to_string(),
+            keypair: Keypair::new_ed25519(&mut OsRng),
+            revoked: false,
+        }
+    }
+
+    #[test]
+    fn safe_account_encryption_roundtrip() -> Result<()> {
+        let mut account = SafeAccount::default();
+        account.insert_app("app_id", new_entry("app"));
+
+        let sk = bls::SecretKey::random();
+        let encrypted = account.encrypt(&sk.public_key())?;
+        assert_eq!(SafeAccount::decrypt(&encrypted, &sk)?, account);
+
+        let other_sk = bls::SecretKey::random();
+        assert!(SafeAccount::decrypt(&encrypted, &other_sk).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn safe_account_merge_keeps_revocations() {
+        let entry = new_entry("app");
+        let mut account = SafeAccount::default();
+        account.insert_app("app_id", entry.clone());
+
+        let mut revoked = account.clone();
+        assert!(revoked.revoke_app("app_id"));
+        assert!(!revoked.revoke_app("unknown_app_id"));
+        revoked.insert_app("other_app_id", new_entry("other app"));
+
+        account.merge(revoked.clone());
+        revoked.merge(SafeAccount {
+            apps: vec![("app_id".to_string(), entry)].into_iter().collect(),
+        });
+
+        assert_eq!(account, revoked);
+        assert!(account.authorised_app("app_id").is_none());
+        assert!(account.authorised_app("other_app_id").is_some());
+        assert_eq!(account.authed_apps().len(), 1);
+    }
+
+    #[test]
+    fn safe_account_merge_is_commutative_on_concurrent_authorisations() {
+        let mut first = SafeAccount::default();
+        first.insert_app("app_id", new_entry("app"));
+        let mut second = SafeAccount::default();
+        second.insert_app("app_id", new_entry("app"));
+
+        let mut merged_first = first.clone();
+        merged_first.merge(second.clone());
+        let mut merged_second = second;
+        merged_second.merge(first);
+
+        assert_eq!(merged_first, merged_second);
+    }
+}
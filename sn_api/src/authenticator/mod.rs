@@ -7,19 +7,36 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
+mod account;
+
 use crate::{
-    ipc::{req::AuthReq, resp::AuthGranted},
-    Result, SafeAuthReq,
+    common::auth_types::AuthedAppsList,
+    ipc::{
+        req::{AuthReq, IpcReq},
+        resp::{AuthGranted, IpcResp},
+        IpcError, IpcMsg, NodeConfig,
+    },
+    Error, Result, SafeAuthReq,
 };
+use account::{AppEntry, SafeAccount};
 use hmac::Hmac;
-use rand::rngs::StdRng;
+use log::{debug, info, trace};
+use rand::{
+    rngs::{OsRng, StdRng},
+    Rng,
+};
 use rand_core::SeedableRng;
-use safe_network::client::client_api::Client;
-use safe_network::types::{Keypair, RegisterAddress};
+use safe_network::client::{
+    ChunkCacheConfig, Client, ClientConfig, Error as ClientError, ErrorMessage,
+    DEFAULT_QUERY_TIMEOUT,
+};
+use safe_network::types::{
+    register::{EntryHash, PrivatePermissions},
+    BytesAddress, Error as SafeNdError, Keypair, RegisterAddress, Scope,
+};
 use sha3::Sha3_256;
 use std::{
-    collections::HashSet,
-    net::SocketAddr,
+    collections::{BTreeMap, BTreeSet},
     path::{Path, PathBuf},
 };
 use tiny_keccak::{Hasher, Sha3};
@@ -27,10 +44,12 @@ use xor_name::{XorName, XOR_NAME_LEN};
 
 const SHA3_512_HASH_LEN: usize = 64;
 
-// Type tag value used for the Map which holds the Safe's content on the network.
-#[allow(dead_code)]
+// Type tag value used for the Register which holds the Safe's content on the network.
 const SAFE_TYPE_TAG: u64 = 1_300;
 
+// Appended to the keypair's seed to obtain the seed of the Safe's encryption key.
+const ENCRYPTION_KEY_SEED_SUFFIX: &[u8] = b"safe-encryption-key";
+
 /// Derive Passphrase, Password and Salt (in order).
 pub fn derive_secrets(acc_passphrase: &[u8], acc_password: &[u8]) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
     let mut passphrase_hasher = Sha3::v512();
@@ -65,17 +84,39 @@ fn create_ed25519_keypair_from_seed(seeder: &[u8]) -> Keypair {
     Keypair::new_ed25519(&mut rng)
 }
 
+/// Create a new BLS secret key from seed
+fn create_bls_secret_key_from_seed(seeder: &[u8]) -> bls::SecretKey {
+    let mut hasher = Sha3::v256();
+    let mut seed = [0; 32];
+    hasher.update(seeder);
+    hasher.finalize(&mut seed);
+    let mut rng = StdRng::from_seed(seed);
+    rng.gen()
+}
+
 /// Perform all derivations and seeding to deterministically obtain location and Keypair from input
 pub fn derive_location_and_keypair(passphrase: &str, password: &str) -> Result<(XorName, Keypair)> {
+    let (location, keypair, _) = derive_safe_secrets(passphrase, password)?;
+    Ok((location, keypair))
+}
+
+// Same as `derive_location_and_keypair`, but also derives the key used to encrypt the Safe's content
+fn derive_safe_secrets(
+    passphrase: &str,
+    password: &str,
+) -> Result<(XorName, Keypair, bls::SecretKey)> {
     let (passphrase, password, salt) = derive_secrets(passphrase.as_bytes(), password.as_bytes());
 
-    let map_data_location = generate_network_address(&passphrase, &salt)?;
+    let register_location = generate_network_address(&passphrase, &salt)?;
 
     let mut seed = password;
     seed.extend(salt.iter());
     let keypair = create_ed25519_keypair_from_seed(&seed);
 
-    Ok((map_data_location, keypair))
+    seed.extend(ENCRYPTION_KEY_SEED_SUFFIX);
+    let encryption_key = create_bls_secret_key_from_seed(&seed);
+
+    Ok((register_location, keypair, encryption_key))
 }
 
 /// Generates User's Identity for the network using supplied credentials in
@@ -94,28 +135,37 @@ pub fn generate_network_address(passphrase: &[u8], salt: &[u8]) -> Result<XorNam
 #[derive(Default)]
 pub struct SafeAuthenticator {
     // We keep the client instantiated with the derived keypair, along
-    // with the address of the Map which holds its Safe on the network.
-    #[allow(dead_code)]
-    safe: Option<(Client, RegisterAddress)>,
-    #[allow(dead_code)]
+    // with the address of the Register which holds its Safe on the network.
+    safe: Option<UnlockedSafe>,
     config_path: Option<PathBuf>,
-    #[allow(dead_code)]
-    bootstrap_contacts: Option<HashSet<SocketAddr>>,
+    node_config: Option<NodeConfig>,
+    chunk_cache: Option<ChunkCacheConfig>,
+}
+
+// A Safe which is currently unlocked
+struct UnlockedSafe {
+    client: Client,
+    address: RegisterAddress,
+    // Key used to encrypt/decrypt the Safe's content
+    encryption_key: bls::SecretKey,
 }
 
 impl SafeAuthenticator {
-    pub fn new(
-        _config_dir_path: Option<&Path>,
-        _bootstrap_contacts: Option<HashSet<SocketAddr>>,
-    ) -> Self {
-        unimplemented!("Authenticator hasn't yet been updated to work with the new Safe Network");
-        // let config_path = config_dir_path.map(|p| p.to_path_buf());
-
-        // Self {
-        //     safe: None,
-        //     config_path,
-        //     bootstrap_contacts,
-        // }
+    pub fn new(config_dir_path: Option<&Path>, node_config: Option<NodeConfig>) -> Self {
+        let config_path = config_dir_path.map(|p| p.to_path_buf());
+
+        Self {
+            safe: None,
+            config_path,
+            node_config,
+            chunk_cache: None,
+        }
+    }
+
+    /// Keep the chunks read in an on-disk cache across runs, or only in memory if `None`.
+    /// Only applies to the Safes unlocked from now on.
+    pub fn set_chunk_cache(&mut self, chunk_cache: Option<ChunkCacheConfig>) {
+        self.chunk_cache = chunk_cache;
     }
 
     /// # Create Safe
@@ -130,15 +180,14 @@ impl SafeAuthenticator {
     /// ## Example
     /// ```ignore
     /// use sn_api::SafeAuthenticator;
-    /// let mut safe_auth = SafeAuthenticator::new(None);
+    /// let mut safe_auth = SafeAuthenticator::new(None, Some(node_config));
     /// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
     /// let my_secret = "mysecretstring";
     /// let my_password = "mypassword";
     /// # let my_secret = &(random_str());
     /// # let my_password = &(random_str());
-    /// # let sk = "83c055c5efdc483bd967adba5c1769daee0a17bc5fa2b6e129cd6b596c217617";
     /// # async_std::task::block_on(async {
-    /// let acc_created = safe_auth.create(sk, my_secret, my_password).await;
+    /// let acc_created = safe_auth.create(my_secret, my_password).await;
     /// match acc_created {
     ///    Ok(()) => assert!(true), // This should pass
     ///    Err(_) => assert!(false)
@@ -151,17 +200,16 @@ impl SafeAuthenticator {
     /// the function will return an error:
     /// ```ignore
     /// use sn_api::{SafeAuthenticator, Error};
-    /// let mut safe_auth = SafeAuthenticator::new(None);
+    /// let mut safe_auth = SafeAuthenticator::new(None, Some(node_config));
     /// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
     /// /// Using an already existing Safe's passphrase and password:
     /// let my_secret = "mysecretstring";
     /// let my_password = "mypassword";
     /// # let my_secret = &(random_str());
     /// # let my_password = &(random_str());
-    /// # let sk = "83c055c5efdc483bd967adba5c1769daee0a17bc5fa2b6e129cd6b596c217617";
     /// # async_std::task::block_on(async {
-    /// # safe_auth.create(sk, my_secret, my_password).await.unwrap();
-    /// let acc_not_created = safe_auth.create(sk, my_secret, my_password).await;
+    /// # safe_auth.create(my_secret, my_password).await.unwrap();
+    /// let acc_not_created = safe_auth.create(my_secret, my_password).await;
     /// match acc_not_created {
     ///    Ok(_) => assert!(false), // This should not pass
     ///    Err(Error::AuthError(message)) => {
@@ -171,53 +219,61 @@ impl SafeAuthenticator {
     /// }
     /// # });
     ///```
-    pub async fn create(&mut self, _passphrase: &str, _password: &str) -> Result<()> {
-        unimplemented!("Authenticator hasn't yet been updated to work with the new Safe Network");
-        // debug!("Attempting to create a Safe from provided passphrase and password.");
-
-        // let (location, keypair) = derive_location_and_keypair(passphrase, password)?;
-        // let data_owner = keypair.public_key();
-
-        // debug!("Creating Safe to be owned by PublicKey: {:?}", data_owner);
-
-        // let client = Client::new(
-        //     Some(keypair),
-        //     self.config_path.as_deref(),
-        //     self.bootstrap_contacts.clone(),
-        //     DEFAULT_QUERY_TIMEOUT,
-        // )
-        // .await?;
-        // trace!("Client instantiated properly!");
-
-        // // Create Map data to store the list of keypairs generated for
-        // // each of the user's applications.
-        // let permission_set = MapPermissionSet::new()
-        //     .allow(MapAction::Read)
-        //     .allow(MapAction::Insert)
-        //     .allow(MapAction::Update)
-        //     .allow(MapAction::Delete)
-        //     .allow(MapAction::ManagePermissions);
-
-        // let mut permission_map = BTreeMap::new();
-        // permission_map.insert(data_owner, permission_set);
-
-        // // TODO: encrypt content
-        // let map_address = client
-        //     .store_seq_map(
-        //         location,
-        //         SAFE_TYPE_TAG,
-        //         data_owner,
-        //         None,
-        //         Some(permission_map),
-        //     )
-        //     .await
-        //     .map_err(|err| {
-        //         Error::AuthenticatorError(format!("Failed to store Safe on a Map: {}", err))
-        //     })?;
-        // debug!("Map stored successfully for new Safe!");
-
-        // self.safe = Some((client, map_address));
-        // Ok(())
+    pub async fn create(&mut self, passphrase: &str, password: &str) -> Result<()> {
+        debug!("Attempting to create a Safe from provided passphrase and password.");
+
+        let (location, keypair, encryption_key) = derive_safe_secrets(passphrase, password)?;
+        let data_owner = keypair.public_key();
+
+        debug!("Creating Safe to be owned by PublicKey: {:?}", data_owner);
+
+        let client = self.connect(keypair).await?;
+        trace!("Client instantiated properly!");
+
+        let address = RegisterAddress::Private {
+            name: location,
+            tag: SAFE_TYPE_TAG,
+        };
+        match client.get_register(address).await {
+            Ok(_) => {
+                return Err(Error::AuthError(
+                    "Failed to create a Safe: a Safe already exists with the provided passphrase"
+                        .to_string(),
+                ))
+            }
+            // No Register could be found at the Safe's location. Any other error, e.g. when
+            // no Elder responded, doesn't tell whether a Safe exists, so it's not overwritten.
+            Err(ClientError::ErrorMessage {
+                source: ErrorMessage::DataNotFound(_),
+                ..
+            }) => {}
+            Err(err) => {
+                return Err(Error::AuthError(format!(
+                    "Failed to create a Safe: {}",
+                    err
+                )))
+            }
+        }
+
+        // Create a private Register to keep track of the keypairs
+        // generated for each of the user's applications.
+        let mut permissions = BTreeMap::new();
+        let _ = permissions.insert(data_owner, PrivatePermissions::new(true, true));
+
+        let (address, op_batch) = client
+            .store_private_register(location, SAFE_TYPE_TAG, data_owner, permissions)
+            .await?;
+        client.publish_register_ops(op_batch).await.map_err(|err| {
+            Error::AuthenticatorError(format!("Failed to store Safe on a Register: {}", err))
+        })?;
+        debug!("Register stored successfully for new Safe!");
+
+        self.safe = Some(UnlockedSafe {
+            client,
+            address,
+            encryption_key,
+        });
+        Ok(())
     }
 
     /// # Unlock
@@ -227,16 +283,15 @@ impl SafeAuthenticator {
     /// ## Example
     /// ```ignore
     /// use sn_api::SafeAuthenticator;
-    /// let mut safe_auth = SafeAuthenticator::new(None);
+    /// let mut safe_auth = SafeAuthenticator::new(None, Some(node_config));
     /// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
     /// /// Using an already existing Safe's passphrase and password:
     /// let my_secret = "mysecretstring";
     /// let my_password = "mypassword";
     /// # let my_secret = &(random_str());
     /// # let my_password = &(random_str());
-    /// # let sk = "83c055c5efdc483bd967adba5c1769daee0a17bc5fa2b6e129cd6b596c217617";
     /// # async_std::task::block_on(async {
-    /// # safe_auth.create(sk, my_secret, my_password).await.unwrap();
+    /// # safe_auth.create(my_secret, my_password).await.unwrap();
     /// let logged_in = safe_auth.unlock(my_secret, my_password).await;
     /// match logged_in {
     ///    Ok(()) => assert!(true), // This should pass
@@ -249,7 +304,7 @@ impl SafeAuthenticator {
     /// If the Safe does not exist, the function will return an appropriate error:
     ///```ignore
     /// use sn_api::{SafeAuthenticator, Error};
-    /// let mut safe_auth = SafeAuthenticator::new(None);
+    /// let mut safe_auth = SafeAuthenticator::new(None, Some(node_config));
     /// # async_std::task::block_on(async {
     /// let not_logged_in = safe_auth.unlock("non", "existant").await;
     /// match not_logged_in {
@@ -261,109 +316,120 @@ impl SafeAuthenticator {
     /// }
     /// # });
     ///```
-    pub async fn unlock(&mut self, _passphrase: &str, _password: &str) -> Result<()> {
-        unimplemented!("Authenticator hasn't yet been updated to work with the new Safe Network");
-
-        // debug!("Attempting to unlock a Safe...");
-
-        // let (location, keypair) = derive_location_and_keypair(passphrase, password)?;
-
-        // debug!(
-        //     "Unlocking Safe owned by PublicKey: {:?}",
-        //     keypair.public_key()
-        // );
-
-        // let client = Client::new(
-        //     Some(keypair),
-        //     self.config_path.as_deref(),
-        //     self.bootstrap_contacts.clone(),
-        //     DEFAULT_QUERY_TIMEOUT,
-        // )
-        // .await?;
-        // trace!("Client instantiated properly!");
-
-        // let map_address = MapAddress::Seq {
-        //     name: location,
-        //     tag: SAFE_TYPE_TAG,
-        // };
-
-        // // Attempt to retrieve Map to make sure it actually exists
-        // let _ = client.get_map(map_address).await?;
-        // debug!("Safe unlocked successfully!");
-
-        // self.safe = Some((client, map_address));
-        // Ok(())
+    pub async fn unlock(&mut self, passphrase: &str, password: &str) -> Result<()> {
+        debug!("Attempting to unlock a Safe...");
+
+        let (location, keypair, encryption_key) = derive_safe_secrets(passphrase, password)?;
+
+        debug!(
+            "Unlocking Safe owned by PublicKey: {:?}",
+            keypair.public_key()
+        );
+
+        let client = self.connect(keypair).await?;
+        trace!("Client instantiated properly!");
+
+        let address = RegisterAddress::Private {
+            name: location,
+            tag: SAFE_TYPE_TAG,
+        };
+
+        // Attempt to retrieve the Register to make sure it actually exists
+        let _ = client
+            .get_register(address)
+            .await
+            .map_err(|err| Error::AuthError(format!("Failed to log in: {}", err)))?;
+        debug!("Safe unlocked successfully!");
+
+        self.safe = Some(UnlockedSafe {
+            client,
+            address,
+            encryption_key,
+        });
+        Ok(())
     }
 
     pub fn lock(&mut self) -> Result<()> {
-        unimplemented!("Authenticator hasn't yet been updated to work with the new Safe Network");
-
-        // debug!("Locking Safe...");
-        // self.safe = None;
-        // Ok(())
+        debug!("Locking Safe...");
+        self.safe = None;
+        Ok(())
     }
 
     pub fn is_a_safe_unlocked(&self) -> bool {
-        unimplemented!("Authenticator hasn't yet been updated to work with the new Safe Network");
-
-        // let is_a_safe_unlocked = self.safe.is_some();
-        // debug!(
-        //     "Is there a Safe currently unlocked?: {}",
-        //     is_a_safe_unlocked
-        // );
-        // is_a_safe_unlocked
+        let is_a_safe_unlocked = self.safe.is_some();
+        debug!(
+            "Is there a Safe currently unlocked?: {}",
+            is_a_safe_unlocked
+        );
+        is_a_safe_unlocked
     }
 
-    pub async fn decode_req(&self, _req: &str) -> Result<SafeAuthReq> {
-        unimplemented!("Authenticator hasn't yet been updated to work with the new Safe Network");
-
-        // match IpcMsg::from_string(req) {
-        //     Ok(IpcMsg::Req(IpcReq::Auth(app_auth_req))) => {
-        //         debug!("Auth request string decoded: {:?}", app_auth_req);
-        //         Ok(SafeAuthReq::Auth(app_auth_req))
-        //     }
-        //     Ok(other) => Err(Error::AuthError(format!(
-        //         "Failed to decode string as an authorisation request, it's a: '{:?}'",
-        //         other
-        //     ))),
-        //     Err(error) => Err(Error::AuthenticatorError(format!(
-        //         "Failed to decode request: {:?}",
-        //         error
-        //     ))),
-        // }
+    pub async fn decode_req(&self, req: &str) -> Result<SafeAuthReq> {
+        match IpcMsg::from_string(req) {
+            Ok(IpcMsg::Req(ipc_req)) => {
+                debug!("Auth request string decoded: {:?}", ipc_req);
+                Ok(ipc_req)
+            }
+            Ok(other) => Err(Error::AuthError(format!(
+                "Failed to decode string as an authorisation request, it's a: '{:?}'",
+                other
+            ))),
+            Err(error) => Err(Error::AuthenticatorError(format!(
+                "Failed to decode request: {:?}",
+                error
+            ))),
+        }
     }
 
-    // TODO: update terminology around apps auth here
-    pub async fn revoke_app(&self, _y: &str) -> Result<()> {
-        unimplemented!()
+    /// List of the applications currently authorised in the Safe.
+    pub async fn authed_apps(&self) -> Result<AuthedAppsList> {
+        let safe = self.unlocked_safe()?;
+        let (account, _) = fetch_account(safe).await?;
+        Ok(account.authed_apps())
+    }
+
+    /// Revoke the access of an application. Its keypair is not handed out anymore,
+    /// and a new one is generated for it if it's authorised again.
+    pub async fn revoke_app(&self, app_id: &str) -> Result<()> {
+        debug!("Revoking application: {}", app_id);
+        let safe = self.unlocked_safe()?;
+        let (mut account, latest) = fetch_account(safe).await?;
+
+        if account.authorised_app(app_id).is_none() {
+            return Err(Error::AuthIpcError(IpcError::UnknownApp));
+        }
+
+        let _ = account.revoke_app(app_id);
+        store_account(safe, &account, latest).await?;
+        debug!("Application '{}' revoked", app_id);
+
+        Ok(())
     }
 
     /// Decode requests and trigger application authorisation against the current client
-    pub async fn authorise_app(&self, _req: &str) -> Result<String> {
-        unimplemented!("Authenticator hasn't yet been updated to work with the new Safe Network");
-
-        // let ipc_req = IpcMsg::from_string(req).map_err(|err| {
-        //     Error::AuthenticatorError(format!("Failed to decode authorisation request: {:?}", err))
-        // })?;
-
-        // debug!("Auth request string decoded: {:?}", ipc_req);
-
-        // match ipc_req {
-        //     IpcMsg::Req(IpcReq::Auth(app_auth_req)) => {
-        //         info!("Request was recognised as an application auth request");
-        //         debug!("Decoded request: {:?}", app_auth_req);
-        //         self.gen_auth_response(app_auth_req).await
-        //     }
-        //     IpcMsg::Req(IpcReq::Unregistered(user_data)) => {
-        //         info!("Request was recognised as an unregistered auth request");
-        //         debug!("Decoded request: {:?}", user_data);
-
-        //         self.gen_unreg_auth_response()
-        //     }
-        //     IpcMsg::Resp { .. } | IpcMsg::Err(..) => Err(Error::AuthError(
-        //         "The request was not recognised as a valid auth request".to_string(),
-        //     )),
-        // }
+    pub async fn authorise_app(&self, req: &str) -> Result<String> {
+        let ipc_req = IpcMsg::from_string(req).map_err(|err| {
+            Error::AuthenticatorError(format!("Failed to decode authorisation request: {:?}", err))
+        })?;
+
+        debug!("Auth request string decoded: {:?}", ipc_req);
+
+        match ipc_req {
+            IpcMsg::Req(IpcReq::Auth(app_auth_req)) => {
+                info!("Request was recognised as an application auth request");
+                debug!("Decoded request: {:?}", app_auth_req);
+                self.gen_auth_response(app_auth_req).await
+            }
+            IpcMsg::Req(IpcReq::Unregistered(user_data)) => {
+                info!("Request was recognised as an unregistered auth request");
+                debug!("Decoded request: {:?}", user_data);
+
+                self.gen_unreg_auth_response()
+            }
+            IpcMsg::Resp { .. } | IpcMsg::Err(..) => Err(Error::AuthError(
+                "The request was not recognised as a valid auth request".to_string(),
+            )),
+        }
     }
 
     /// Authenticate an app request.
@@ -371,146 +437,183 @@ impl SafeAuthenticator {
     /// First, this function searches for an app info in the Safe.
     /// If the app is found, then the `AuthGranted` struct is returned based on that information.
     /// If the app is not found in the Safe, then it will be authenticated.
-    pub async fn authenticate(&self, _auth_req: AuthReq) -> Result<AuthGranted> {
-        unimplemented!("Authenticator hasn't yet been updated to work with the new Safe Network");
-
-        // debug!(
-        //     "Retrieving/generating keypair for an application: {:?}",
-        //     auth_req
-        // );
-        // if let Some((client, map_address)) = &self.safe {
-        //     let app_id = auth_req.app_id.as_bytes().to_vec();
-        //     let keypair = match client.get_map_value(*map_address, app_id.clone()).await {
-        //         Ok(value) => {
-        //             // This app already has its own keypair
-        //             trace!(
-        //                 "The app ('{}') already has a Keypair in the Safe",
-        //                 auth_req.app_id
-        //             );
-
-        //             // TODO: support for scenario when app was previously revoked,
-        //             // in which case we should generate a new keypair
-
-        //             let keypair_bytes = match value {
-        //                 MapValue::Seq(seq_value) => seq_value.data,
-        //                 MapValue::Unseq(data) => data,
-        //             };
-        //             let keypair_str = String::from_utf8(keypair_bytes).map_err(|_err| {
-        //                 Error::AuthError(
-        //                     "The Safe contains an invalid keypair associated to this app"
-        //                         .to_string(),
-        //                 )
-        //             })?;
-        //             let keypair: Keypair = serde_json::from_str(&keypair_str).map_err(|_err| {
-        //                 Error::AuthError(
-        //                     "The Safe contains an invalid keypair associated to this app"
-        //                         .to_string(),
-        //                 )
-        //             })?;
-
-        //             debug!(
-        //                 "Keypair for the app being authorised ('{}') retrieved from the Safe: {}",
-        //                 auth_req.app_id,
-        //                 keypair.public_key()
-        //             );
-
-        //             keypair
-        //         }
-        //         Err(ClientError::ErrorMessage {
-        //             source: NoSuchEntry,
-        //             ..
-        //         }) => {
-        //             // This is the first time this app is being authorised,
-        //             // thus let's generate a keypair for it
-        //             trace!(
-        //                 "The app ('{}') was not assigned a Keypair yet in the Safe. Generating one for it...",
-        //                 auth_req.app_id
-        //             );
-        //             let mut rng = OsRng;
-        //             let keypair = Keypair::new_ed25519(&mut rng);
-
-        //             let keypair_str = serde_json::to_string(&keypair).map_err(|err| {
-        //                 Error::AuthError(format!(
-        //                     "Failed to serialised keypair to store it in the Safe: {}",
-        //                     err
-        //                 ))
-        //             })?;
-
-        //             debug!(
-        //                 "New keypair generated for app ('{}') being authorised: {}",
-        //                 auth_req.app_id,
-        //                 keypair.public_key()
-        //             );
-
-        //             // Store the keypair in the Safe, mapped to the app id
-        //             let map_actions =
-        //                 MapSeqEntryActions::new().ins(app_id, keypair_str.as_bytes().to_vec(), 0);
-
-        //             client
-        //                 .edit_map_entries(*map_address, MapEntryActions::Seq(map_actions))
-        //                 .await?;
-
-        //             keypair
-        //         }
-        //         Err(err) => {
-        //             return Err(Error::AuthError(format!(
-        //                 "Failed to retrieve keypair from the Safe: {}",
-        //                 err
-        //             )))
-        //         }
-        //     };
-
-        //     Ok(AuthGranted {
-        //         app_keypair: keypair,
-        //         bootstrap_config: self.bootstrap_contacts.clone(),
-        //     })
-        // } else {
-        //     Err(Error::AuthenticatorError(
-        //         "No Safe is currently unlocked".to_string(),
-        //     ))
-        // }
+    pub async fn authenticate(&self, auth_req: AuthReq) -> Result<AuthGranted> {
+        debug!(
+            "Retrieving/generating keypair for an application: {:?}",
+            auth_req
+        );
+        let safe = self.unlocked_safe()?;
+        let (mut account, latest) = fetch_account(safe).await.map_err(|err| {
+            Error::AuthError(format!("Failed to retrieve keypair from the Safe: {}", err))
+        })?;
+
+        let keypair = match account.authorised_app(&auth_req.app_id) {
+            Some(app_entry) => {
+                // This app already has its own keypair
+                debug!(
+                    "Keypair for the app being authorised ('{}') retrieved from the Safe: {}",
+                    auth_req.app_id,
+                    app_entry.keypair.public_key()
+                );
+
+                app_entry.keypair.clone()
+            }
+            None => {
+                // This is the first time this app is being authorised (or it was
+                // previously revoked), thus let's generate a keypair for it
+                trace!(
+                    "The app ('{}') was not assigned a Keypair yet in the Safe. Generating one for it...",
+                    auth_req.app_id
+                );
+                let keypair = Keypair::new_ed25519(&mut OsRng);
+
+                debug!(
+                    "New keypair generated for app ('{}') being authorised: {}",
+                    auth_req.app_id,
+                    keypair.public_key()
+                );
+
+                // Store the keypair in the Safe, mapped to the app id
+                account.insert_app(&auth_req.app_id, AppEntry::new(&auth_req, keypair.clone()));
+                store_account(safe, &account, latest).await?;
+
+                keypair
+            }
+        };
+
+        Ok(AuthGranted {
+            app_keypair: keypair,
+            bootstrap_config: self.node_config.clone(),
+        })
     }
 
     // Helper function to generate an app authorisation response
-    // async fn gen_auth_response(&self, auth_req: AuthReq) -> Result<String> {
-    //     let auth_granted = self.authenticate(auth_req).await.map_err(|err| {
-    //         Error::AuthenticatorError(format!(
-    //             "Failed to authorise application on the network: {}",
-    //             err
-    //         ))
-    //     })?;
+    async fn gen_auth_response(&self, auth_req: AuthReq) -> Result<String> {
+        let auth_granted = self.authenticate(auth_req).await.map_err(|err| {
+            Error::AuthenticatorError(format!(
+                "Failed to authorise application on the network: {}",
+                err
+            ))
+        })?;
+
+        debug!("Encoding response with auth credentials auth granted...");
+        let resp = IpcMsg::Resp(IpcResp::Auth(Ok(auth_granted)))
+            .to_string()
+            .map_err(|err| {
+                Error::AuthenticatorError(format!("Failed to encode response: {:?}", err))
+            })?;
+
+        debug!("Returning auth response generated");
+
+        Ok(resp)
+    }
+
+    // Helper function to generate an unregistered authorisation response
+    fn gen_unreg_auth_response(&self) -> Result<String> {
+        let node_config = self.node_config.clone().ok_or_else(|| {
+            Error::AuthenticatorError("Bootstrap contacts information not available".to_string())
+        })?;
+
+        debug!("Encoding response... {:?}", node_config);
+        let resp = IpcMsg::Resp(IpcResp::Unregistered(Ok(node_config)))
+            .to_string()
+            .map_err(|err| {
+                Error::AuthenticatorError(format!("Failed to encode response: {:?}", err))
+            })?;
+
+        debug!("Returning unregistered auth response generated: {:?}", resp);
+        Ok(resp)
+    }
 
-    //     debug!("Encoding response with auth credentials auth granted...");
-    //     let resp = serde_json::to_string(&IpcMsg::Resp(IpcResp::Auth(Ok(auth_granted)))).map_err(
-    //         |err| Error::AuthenticatorError(format!("Failed to encode response: {:?}", err)),
-    //     )?;
+    // Helper function to obtain the Safe currently unlocked
+    fn unlocked_safe(&self) -> Result<&UnlockedSafe> {
+        self.safe
+            .as_ref()
+            .ok_or_else(|| Error::AuthenticatorError("No Safe is currently unlocked".to_string()))
+    }
 
-    //     debug!("Returning auth response generated");
+    // Helper function to instantiate a client with the Safe's keypair
+    async fn connect(&self, keypair: Keypair) -> Result<Client> {
+        let (genesis_key, bootstrap_nodes) = self.node_config.clone().ok_or_else(|| {
+            Error::AuthenticatorError("Bootstrap contacts information not available".to_string())
+        })?;
+
+        let mut config = ClientConfig::new(
+            None,
+            None,
+            genesis_key,
+            self.config_path.as_deref(),
+            Some(DEFAULT_QUERY_TIMEOUT),
+            None,
+        )
+        .await;
+        config.chunk_cache = self.chunk_cache;
+
+        Client::new(config, bootstrap_nodes, Some(keypair))
+            .await
+            .map_err(|err| {
+                Error::ConnectionError(format!("Failed to connect to the SAFE Network: {:?}", err))
+            })
+    }
+}
 
-    //     Ok(resp)
-    // }
+// Retrieves the latest content of the Safe, merging any concurrent versions of it,
+// along with the hashes of the Register entries it was read from.
+async fn fetch_account(safe: &UnlockedSafe) -> Result<(SafeAccount, BTreeSet<EntryHash>)> {
+    let entries = match safe.client.read_register(safe.address).await {
+        Ok(entries) => entries,
+        Err(ClientError::NetworkDataError(SafeNdError::NoSuchEntry)) => BTreeSet::new(),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut account = SafeAccount::default();
+    let mut latest = BTreeSet::new();
+    for (hash, entry) in entries {
+        let content_address: BytesAddress = bincode::deserialize(&entry).map_err(|err| {
+            Error::AuthenticatorError(format!(
+                "The Safe contains an invalid entry '{}': {:?}",
+                hex::encode(hash.0),
+                err
+            ))
+        })?;
+        let content = safe.client.read_bytes(content_address).await?;
+        account.merge(SafeAccount::decrypt(&content, &safe.encryption_key)?);
+        let _ = latest.insert(hash);
+    }
 
-    // Helper function to generate an unregistered authorisation response
-    // fn gen_unreg_auth_response(&self) -> Result<String> {
-    //     let bootstrap_contacts = self.bootstrap_contacts.clone().ok_or_else(|| {
-    //         Error::AuthenticatorError("Bootstrap contacts information not available".to_string())
-    //     })?;
-
-    //     debug!("Encoding response... {:?}", bootstrap_contacts);
-    //     let resp =
-    //         serde_json::to_string(&IpcMsg::Resp(IpcResp::Unregistered(Ok(bootstrap_contacts))))
-    //             .map_err(|err| {
-    //                 Error::AuthenticatorError(format!("Failed to encode response: {:?}", err))
-    //             })?;
-
-    //     debug!("Returning unregistered auth response generated: {:?}", resp);
-    //     Ok(resp)
-    // }
+    Ok((account, latest))
+}
+
+// Encrypts and stores the content of the Safe on the network, writing its
+// address onto the Safe's Register as the successor of the `latest` entries.
+async fn store_account(
+    safe: &UnlockedSafe,
+    account: &SafeAccount,
+    latest: BTreeSet<EntryHash>,
+) -> Result<()> {
+    let content = account.encrypt(&safe.encryption_key.public_key())?;
+    let content_address = safe.client.upload(content, Scope::Public).await?;
+    let entry = bincode::serialize(&content_address).map_err(|err| {
+        Error::Serialisation(format!(
+            "Failed to serialise Safe content address: {:?}",
+            err
+        ))
+    })?;
+
+    let (_, op_batch) = safe
+        .client
+        .write_to_register(safe.address, entry, latest)
+        .await?;
+    safe.client.publish_register_ops(op_batch).await?;
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "app")]
+    use crate::{app::test_helpers::get_bootstrap_contacts, retry_loop};
     use anyhow::{Context, Result};
     use proptest::prelude::*;
     use safe_network::types::PublicKey;
@@ -543,4 +646,62 @@ mod tests {
             prop_assert_eq!(keypair, keypair_again);
         }
     }
+
+    #[cfg(feature = "app")]
+    #[tokio::test]
+    async fn test_authenticator_authorise_and_revoke_app() -> Result<()> {
+        let (passphrase, password): (String, String) = (random_str(), random_str());
+        let node_config = get_bootstrap_contacts()?;
+
+        let mut safe_auth = SafeAuthenticator::new(None, Some(node_config.clone()));
+        safe_auth.create(&passphrase, &password).await?;
+        assert!(safe_auth.is_a_safe_unlocked());
+
+        let auth_req_str = IpcMsg::new_auth_req("app_id", "app name", "app vendor").to_string()?;
+        assert!(matches!(
+            safe_auth.decode_req(&auth_req_str).await?,
+            IpcReq::Auth(AuthReq { app_id, .. }) if app_id == "app_id"
+        ));
+
+        let auth_resp_str = retry_loop!(safe_auth.authorise_app(&auth_req_str));
+        let auth_granted = match IpcMsg::from_string(&auth_resp_str)? {
+            IpcMsg::Resp(IpcResp::Auth(Ok(auth_granted))) => auth_granted,
+            other => anyhow::bail!("Unexpected auth response: {:?}", other),
+        };
+        assert_eq!(auth_granted.bootstrap_config, Some(node_config.clone()));
+
+        // A new session on the same Safe gets the same keypair for the app
+        let mut safe_auth = SafeAuthenticator::new(None, Some(node_config));
+        safe_auth.lock()?;
+        assert!(!safe_auth.is_a_safe_unlocked());
+        retry_loop!(safe_auth.unlock(&passphrase, &password));
+        let auth_req = AuthReq {
+            req_id: 1,
+            app_id: "app_id".to_string(),
+            app_name: "app name".to_string(),
+            app_vendor: "app vendor".to_string(),
+        };
+        let granted_again = retry_loop!(safe_auth.authenticate(auth_req.clone()));
+        assert_eq!(granted_again.app_keypair, auth_granted.app_keypair);
+
+        let authed_apps = safe_auth.authed_apps().await?;
+        assert_eq!(authed_apps.len(), 1);
+        assert_eq!(authed_apps[0].id, "app_id");
+
+        // Once revoked, a new keypair is generated for the app
+        safe_auth.revoke_app("app_id").await?;
+        let granted_after_revoke = retry_loop!(safe_auth.authenticate(auth_req.clone()));
+        assert_ne!(granted_after_revoke.app_keypair, auth_granted.app_keypair);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "app")]
+    fn random_str() -> String {
+        use rand::distributions::Alphanumeric;
+        rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(15)
+            .collect()
+    }
 }
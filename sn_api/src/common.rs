@@ -11,6 +11,7 @@ use super::{constants::SN_AUTHD_CONNECTION_IDLE_TIMEOUT, Error, Result};
 use log::info;
 use qjsonrpc::ClientEndpoint;
 use serde::de::DeserializeOwned;
+use std::path::Path;
 use tokio::runtime;
 
 pub mod auth_types {
@@ -93,52 +94,75 @@ where
             paths.push(".safe");
             paths.push("authd");
 
-            let cert_base_path = paths.display().to_string();
-
-            let qjsonrpc_client = ClientEndpoint::new(
-                &cert_base_path,
-                Some(SN_AUTHD_CONNECTION_IDLE_TIMEOUT),
-                false,
+            send_jsonrpc_request(
+                &paths,
+                dst_endpoint,
+                None,
+                method,
+                params,
+                Error::AuthdClientError,
+                Error::AuthdError,
             )
-            .map_err(|err| {
-                Error::AuthdClientError(format!("Failed to create client endpoint: {}", err))
-            })?;
-
-            // We try to obtain current runtime or create a new one if there is none
-            let runtime = match runtime::Handle::try_current() {
-                Ok(r) => r,
-                Err(_) => runtime::Runtime::new()
-                    .map_err(|err| {
-                        Error::AuthdClientError(format!("Failed to create runtime: {}", err))
-                    })?
-                    .handle()
-                    .clone(),
-            };
-
-            let mut outgoing_conn = {
-                let _ = runtime.enter();
-                qjsonrpc_client.bind().map_err(|err| {
-                    Error::AuthdClientError(format!("Failed to bind endpoint: {}", err))
-                })?
-            };
-
-            // Establish a new connection
-            outgoing_conn
-                .connect(dst_endpoint, None)
-                .await
-                .map_err(|err| {
-                    Error::AuthdClientError(format!(
-                        "Failed to establish connection with authd: {}",
-                        err
-                    ))
-                })?
-                // Send request and await for response
-                .send(method, params)
-                .await
-                .map_err(|err| match err {
-                    qjsonrpc::Error::RemoteEndpointError(msg) => Error::AuthdError(msg),
-                    other => Error::AuthdClientError(other.to_string()),
-                })
+            .await
         }
     }
 }
+
+// Send a JSON-RPC request over QUIC to an endpoint whose certificate is found at
+// `cert_base_path`. Errors occurred locally are mapped with `client_err`, while those
+// reported by the remote endpoint are mapped with `remote_err`.
+pub async fn send_jsonrpc_request<T>(
+    cert_base_path: &Path,
+    dst_endpoint: &str,
+    cert_host: Option<&str>,
+    method: &str,
+    params: serde_json::Value,
+    client_err: fn(String) -> Error,
+    remote_err: fn(String) -> Error,
+) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    let cert_base_path = cert_base_path.display().to_string();
+
+    let qjsonrpc_client = ClientEndpoint::new(
+        &cert_base_path,
+        Some(SN_AUTHD_CONNECTION_IDLE_TIMEOUT),
+        false,
+    )
+    .map_err(|err| client_err(format!("Failed to create client endpoint: {}", err)))?;
+
+    // We try to obtain current runtime or create a new one if there is none
+    let runtime = match runtime::Handle::try_current() {
+        Ok(r) => r,
+        Err(_) => runtime::Runtime::new()
+            .map_err(|err| client_err(format!("Failed to create runtime: {}", err)))?
+            .handle()
+            .clone(),
+    };
+
+    let mut outgoing_conn = {
+        let _ = runtime.enter();
+        qjsonrpc_client
+            .bind()
+            .map_err(|err| client_err(format!("Failed to bind endpoint: {}", err)))?
+    };
+
+    // Establish a new connection
+    outgoing_conn
+        .connect(dst_endpoint, cert_host)
+        .await
+        .map_err(|err| {
+            client_err(format!(
+                "Failed to establish connection with {}: {}",
+                dst_endpoint, err
+            ))
+        })?
+        // Send request and await for response
+        .send(method, params)
+        .await
+        .map_err(|err| match err {
+            qjsonrpc::Error::RemoteEndpointError(msg) => remote_err(msg),
+            other => client_err(other.to_string()),
+        })
+}
@@ -38,6 +38,12 @@ pub enum Error {
     /// AuthenticatorError
     #[error("AuthenticatorError: {0}")]
     AuthenticatorError(String),
+    /// NodeAdminClientError
+    #[error("NodeAdminClientError: {0}")]
+    NodeAdminClientError(String),
+    /// NodeAdminError
+    #[error("NodeAdminError: {0}")]
+    NodeAdminError(String),
     /// ConflictingNrsEntries
     #[error("ConflictingNrsEntries: {0}")]
     ConflictingNrsEntries(String, Vec<(String, SafeUrl)>, NrsMap),
@@ -19,6 +19,8 @@ mod authenticator;
 mod common;
 mod constants;
 mod errors;
+#[cfg(feature = "node_admin_client")]
+mod node_admin_client;
 mod safeurl;
 
 // re-export these useful types from sn_data_types
@@ -41,6 +43,9 @@ pub use authenticator::*;
 #[cfg(feature = "authd_client")]
 pub use authd_client::*;
 
+#[cfg(feature = "node_admin_client")]
+pub use node_admin_client::*;
+
 #[cfg(any(feature = "authenticator", feature = "authd_client"))]
 pub use common::auth_types::*;
 
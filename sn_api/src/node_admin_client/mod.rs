@@ -0,0 +1,125 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{common::send_jsonrpc_request, Error, Result};
+use log::{debug, info};
+use safe_network::node::admin::{
+    AdminParams, ADMIN_CERT_DIR_NAME, ADMIN_METHOD_LEAVE, ADMIN_METHOD_SET_JOINS_ALLOWED,
+    ADMIN_METHOD_SET_LOG_LEVEL, ADMIN_METHOD_START_CONNECTIVITY_TEST, ADMIN_METHOD_STATUS,
+};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+use xor_name::XorName;
+
+// Expose the types returned by the node
+pub use safe_network::node::admin::{KnownSection, NodeStatus, PendingDkgSession, SectionMember};
+
+// Hostname the certificate of the node's admin endpoint is issued for
+const NODE_ADMIN_CERT_HOST: &str = "localhost";
+
+// Client of the admin interface of a running node
+pub struct NodeAdminClient {
+    // admin endpoint of the node
+    pub admin_endpoint: String,
+    // directory the node's admin certificate is read from
+    cert_base_path: PathBuf,
+    // token the node's admin interface was configured with, if any
+    token: Option<String>,
+}
+
+impl NodeAdminClient {
+    // Create a client of the admin endpoint at `admin_addr`, for the node
+    // whose root dir is `node_root_dir`, where its admin certificate is read from.
+    // The `token` is sent along with every request, as the node requires if it was
+    // configured with one.
+    pub fn new(admin_addr: SocketAddr, node_root_dir: &Path, token: Option<String>) -> Self {
+        let admin_endpoint = format!("https://{}", admin_addr);
+        debug!(
+            "Creating new node admin client for endpoint {}",
+            admin_endpoint
+        );
+        Self {
+            admin_endpoint,
+            cert_base_path: node_root_dir.join(ADMIN_CERT_DIR_NAME),
+            token,
+        }
+    }
+
+    // Send a request to the node to obtain a status report
+    pub async fn status(&self) -> Result<NodeStatus> {
+        debug!("Attempting to retrieve status report from node...");
+        let status = self
+            .send::<NodeStatus>(ADMIN_METHOD_STATUS, serde_json::Value::Null)
+            .await?;
+
+        info!("Node status report retrieved successfully: {:?}", status);
+        Ok(status)
+    }
+
+    // Send a request to the node to allow, or disallow, new nodes joining its section
+    pub async fn set_joins_allowed(&self, joins_allowed: bool) -> Result<()> {
+        debug!(
+            "Attempting to set joins allowed to {} on node...",
+            joins_allowed
+        );
+        self.send(ADMIN_METHOD_SET_JOINS_ALLOWED, json!(joins_allowed))
+            .await
+    }
+
+    // Send a request to the node for its section to test connectivity to the given node
+    pub async fn start_connectivity_test(&self, name: XorName) -> Result<()> {
+        debug!(
+            "Attempting to start connectivity test to {} on node...",
+            name
+        );
+        self.send(ADMIN_METHOD_START_CONNECTIVITY_TEST, json!(name))
+            .await
+    }
+
+    // Send a request to the node to leave the network
+    pub async fn leave(&self) -> Result<()> {
+        debug!("Attempting to make node leave the network...");
+        self.send(ADMIN_METHOD_LEAVE, serde_json::Value::Null).await
+    }
+
+    // Send a request to the node to change its log filter, e.g. "safe_network=debug"
+    pub async fn set_log_level(&self, directive: &str) -> Result<()> {
+        debug!("Attempting to set log level of node to '{}'...", directive);
+        self.send(ADMIN_METHOD_SET_LOG_LEVEL, json!(directive))
+            .await
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T> {
+        info!(
+            "Sending '{}' request to node admin endpoint on {} ...",
+            method, self.admin_endpoint
+        );
+        send_jsonrpc_request(
+            &self.cert_base_path,
+            &self.admin_endpoint,
+            Some(NODE_ADMIN_CERT_HOST),
+            method,
+            json!(AdminParams {
+                token: self.token.clone(),
+                params,
+            }),
+            Error::NodeAdminClientError,
+            Error::NodeAdminError,
+        )
+        .await
+    }
+}
@@ -13,9 +13,10 @@ use crate::{
     shell,
     subcommands::{
         cat::cat_commander, config::config_commander, dog::dog_commander, files::files_commander,
-        keys::key_commander, networks::networks_commander, node::node_commander,
-        nrs::nrs_commander, setup::setup_commander, update::update_commander,
-        xorurl::xorurl_commander, OutputFmt, SubCommands,
+        keys::key_commander, multimap::multimap_commander, networks::networks_commander,
+        node::node_commander, nrs::nrs_commander, register::register_commander,
+        setup::setup_commander, update::update_commander, xorurl::xorurl_commander, OutputFmt,
+        SubCommands,
     },
 };
 use color_eyre::{eyre::eyre, Result};
@@ -123,7 +124,7 @@ pub async fn run_with(cmd_args: Option<&[&str]>, safe: &mut Safe) -> Result<()>
         }) => xorurl_commander(cmd, location, recursive, follow_links, output_fmt, safe).await,
         Some(SubCommands::Node { cmd }) => {
             let mut launcher = Box::new(SnLaunchToolNetworkLauncher::default());
-            node_commander(cmd, &mut get_config().await?, &mut launcher).await
+            node_commander(cmd, &mut get_config().await?, &mut launcher, output_fmt).await
         }
         Some(other) => {
             // We treat these commands separatelly since we use the credentials if they are
@@ -140,6 +141,8 @@ pub async fn run_with(cmd_args: Option<&[&str]>, safe: &mut Safe) -> Result<()>
                 SubCommands::Dog(cmd) => dog_commander(cmd, output_fmt, safe).await,
                 SubCommands::Files(cmd) => files_commander(cmd, output_fmt, safe).await,
                 SubCommands::Nrs(cmd) => nrs_commander(cmd, output_fmt, safe).await,
+                SubCommands::Register(cmd) => register_commander(cmd, output_fmt, safe).await,
+                SubCommands::Multimap(cmd) => multimap_commander(cmd, output_fmt, safe).await,
                 _ => Err(eyre!("Unknown safe subcommand")),
             }
         }
@@ -62,6 +62,7 @@ pub async fn connect(safe: &mut Safe, config: Config) -> Result<Option<Keypair>>
     }
 
     let (_, bootstrap_contacts) = config.read_current_node_config().await?;
+    safe.set_chunk_cache(config.chunk_cache());
     let client_cfg = client_config_path();
     match safe
         .connect(
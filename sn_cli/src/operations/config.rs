@@ -10,7 +10,7 @@
 use color_eyre::{eyre::bail, eyre::eyre, eyre::WrapErr, Help, Report, Result};
 use prettytable::Table;
 use serde::{Deserialize, Serialize};
-use sn_api::{NodeConfig, PublicKey};
+use sn_api::{ChunkCacheConfig, NodeConfig, PublicKey};
 use std::{
     collections::{BTreeMap, BTreeSet},
     default::Default,
@@ -104,6 +104,9 @@ impl NetworkInfo {
 #[derive(Clone, Deserialize, Debug, Serialize, Default)]
 pub struct Settings {
     networks: BTreeMap<String, NetworkInfo>,
+    /// On-disk cache of the chunks read, kept across runs. Disabled if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    chunk_cache: Option<ChunkCacheConfig>,
 }
 
 #[derive(Clone, Debug)]
@@ -287,6 +290,17 @@ impl Config {
         Ok(())
     }
 
+    pub fn chunk_cache(&self) -> Option<ChunkCacheConfig> {
+        self.settings.chunk_cache
+    }
+
+    pub async fn set_chunk_cache(&mut self, chunk_cache: Option<ChunkCacheConfig>) -> Result<()> {
+        self.settings.chunk_cache = chunk_cache;
+        self.write_settings_to_file().await?;
+        debug!("Chunk cache set in settings: {:?}", chunk_cache);
+        Ok(())
+    }
+
     pub async fn clear(&mut self) -> Result<()> {
         self.settings = Settings::default();
         self.write_settings_to_file().await
@@ -338,6 +352,14 @@ impl Config {
         }
 
         table.printstd();
+
+        match self.chunk_cache() {
+            Some(chunk_cache) => println!(
+                "Chunk cache: up to {} bytes, evicting by {:?}",
+                chunk_cache.capacity, chunk_cache.eviction
+            ),
+            None => println!("Chunk cache: disabled"),
+        }
     }
 
     //
@@ -1105,3 +1127,39 @@ mod add_network {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod set_chunk_cache {
+    use super::Config;
+    use assert_fs::prelude::*;
+    use color_eyre::Result;
+    use sn_api::{ChunkCacheConfig, ChunkCacheEviction};
+    use std::path::PathBuf;
+
+    #[tokio::test]
+    async fn chunk_cache_should_be_kept_in_the_config_file() -> Result<()> {
+        let tmp_dir = assert_fs::TempDir::new()?;
+        let cli_config_file = tmp_dir.child(".safe/cli/config.json");
+        let node_config_file = tmp_dir.child(".safe/node/node_connection_info.config");
+        let mut config = Config::new(
+            PathBuf::from(cli_config_file.path()),
+            PathBuf::from(node_config_file.path()),
+        )
+        .await?;
+        assert_eq!(config.chunk_cache(), None);
+
+        let chunk_cache = ChunkCacheConfig {
+            capacity: 1024,
+            eviction: ChunkCacheEviction::FirstInFirstOut,
+        };
+        config.set_chunk_cache(Some(chunk_cache)).await?;
+
+        let config = Config::new(
+            PathBuf::from(cli_config_file.path()),
+            PathBuf::from(node_config_file.path()),
+        )
+        .await?;
+        assert_eq!(config.chunk_cache(), Some(chunk_cache));
+        Ok(())
+    }
+}
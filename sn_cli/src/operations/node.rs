@@ -18,6 +18,11 @@ use std::{
     path::PathBuf,
     process::{Command, Stdio},
 };
+#[cfg(not(target_os = "windows"))]
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
 use tracing::debug;
 
 #[cfg(not(target_os = "windows"))]
@@ -26,6 +31,15 @@ pub(crate) const SN_NODE_EXECUTABLE: &str = "sn_node";
 #[cfg(target_os = "windows")]
 pub(crate) const SN_NODE_EXECUTABLE: &str = "sn_node.exe";
 
+// How long to wait for the nodes to leave the network gracefully, a bit longer than they give
+// themselves before exiting anyway.
+#[cfg(not(target_os = "windows"))]
+const NODES_LEAVE_TIMEOUT: Duration = Duration::from_secs(40);
+
+// How long to wait for killed nodes to be gone, and so to release their ports and dirs.
+#[cfg(not(target_os = "windows"))]
+const NODES_KILL_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Tries to print the version of the node binary pointed to
 pub fn node_version(node_path: Option<PathBuf>) -> Result<()> {
     let bin_path = get_node_bin_path(node_path)?.join(SN_NODE_EXECUTABLE);
@@ -159,7 +173,7 @@ pub fn node_join(
     Ok(())
 }
 
-pub fn node_shutdown(node_path: Option<PathBuf>) -> Result<()> {
+pub fn node_shutdown(node_path: Option<PathBuf>, graceful: bool) -> Result<()> {
     let node_exec_name = match node_path {
         Some(ref path) => {
             let filepath = path.as_path();
@@ -178,11 +192,18 @@ pub fn node_shutdown(node_path: Option<PathBuf>) -> Result<()> {
         None => SN_NODE_EXECUTABLE,
     };
 
-    debug!(
-        "Killing all running nodes launched with {}...",
-        node_exec_name
-    );
-    kill_nodes(node_exec_name)
+    if graceful {
+        debug!(
+            "Asking all running nodes launched with {} to leave...",
+            node_exec_name
+        );
+    } else {
+        debug!(
+            "Killing all running nodes launched with {}...",
+            node_exec_name
+        );
+    }
+    kill_nodes(node_exec_name, graceful)
 }
 
 fn get_initial_sn_launch_args(
@@ -231,10 +252,17 @@ fn get_node_bin_path(node_path: Option<PathBuf>) -> Result<PathBuf> {
     }
 }
 
+// Nodes handle SIGTERM by leaving the network gracefully, which is sent when `graceful` is set,
+// while SIGKILL stops them right away otherwise. Either way this waits for them to have exited.
 #[cfg(not(target_os = "windows"))]
-fn kill_nodes(exec_name: &str) -> Result<()> {
+fn kill_nodes(exec_name: &str, graceful: bool) -> Result<()> {
+    let (signal, timeout) = if graceful {
+        ("-TERM", NODES_LEAVE_TIMEOUT)
+    } else {
+        ("-KILL", NODES_KILL_TIMEOUT)
+    };
     let output = Command::new("killall")
-        .arg(exec_name)
+        .args(&[signal, exec_name])
         .output()
         .wrap_err_with(|| {
             format!(
@@ -244,10 +272,18 @@ fn kill_nodes(exec_name: &str) -> Result<()> {
         })?;
 
     if output.status.success() {
-        println!(
-            "Success, all processes instances of {} were stopped!",
-            exec_name
-        );
+        wait_for_nodes_to_exit(exec_name, timeout)?;
+        if graceful {
+            println!(
+                "Success, all processes instances of {} left the network!",
+                exec_name
+            );
+        } else {
+            println!(
+                "Success, all processes instances of {} were stopped!",
+                exec_name
+            );
+        }
         Ok(())
     } else {
         Err(eyre!(
@@ -258,8 +294,37 @@ fn kill_nodes(exec_name: &str) -> Result<()> {
     }
 }
 
+#[cfg(not(target_os = "windows"))]
+fn wait_for_nodes_to_exit(exec_name: &str, timeout: Duration) -> Result<()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        // Signal 0 is never delivered, it only checks whether any such process is running
+        let running = Command::new("killall")
+            .args(&["-0", exec_name])
+            .output()
+            .wrap_err_with(|| format!("Error when checking for nodes ({}) processes", exec_name))?
+            .status
+            .success();
+        if !running {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            bail!(
+                "Nodes ({}) processes were still running after {:?}",
+                exec_name,
+                timeout
+            );
+        }
+        sleep(Duration::from_millis(500));
+    }
+}
+
 #[cfg(target_os = "windows")]
-fn kill_nodes(exec_name: &str) -> Result<()> {
+fn kill_nodes(exec_name: &str, graceful: bool) -> Result<()> {
+    if graceful {
+        bail!("Stopping nodes gracefully is not supported on Windows");
+    }
+
     let output = Command::new("taskkill")
         .args(&["/F", "/IM", exec_name])
         .output()
@@ -52,12 +52,6 @@ pub fn shell_run() -> Result<()> {
         0,
         |io, (safe, _sn_authd_client), args| call_cli("files", args, safe, io),
     );
-    shell.new_command(
-        "seq",
-        "Manage Sequences on the Safe Network",
-        0,
-        |io, (safe, _sn_authd_client), args| call_cli("seq", args, safe, io),
-    );
     shell.new_command(
         "keypair",
         "Generate a key pair without creating and/or storing a SafeKey on the network",
@@ -82,6 +76,18 @@ pub fn shell_run() -> Result<()> {
         0,
         |io, (safe, _sn_authd_client), args| call_cli("nrs", args, safe, io),
     );
+    shell.new_command(
+        "register",
+        "Manage Registers on the Safe Network",
+        0,
+        |io, (safe, _sn_authd_client), args| call_cli("register", args, safe, io),
+    );
+    shell.new_command(
+        "multimap",
+        "Manage Multimaps on the Safe Network",
+        0,
+        |io, (safe, _sn_authd_client), args| call_cli("multimap", args, safe, io),
+    );
     shell.new_command(
         "setup",
         "Perform setup tasks",
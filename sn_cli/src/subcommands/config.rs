@@ -8,7 +8,8 @@
 // Software.
 
 use crate::operations::config::{Config, NetworkInfo};
-use color_eyre::Result;
+use color_eyre::{eyre::eyre, Result};
+use sn_api::{ChunkCacheConfig, ChunkCacheEviction};
 use structopt::StructOpt;
 use tracing::debug;
 
@@ -34,6 +35,15 @@ pub enum SettingAddCmd {
         /// Location of the network connection information. If this argument is not passed, it takes current network connection information and caches it
         config_location: Option<String>,
     },
+    #[structopt(name = "chunk-cache")]
+    /// Keep the chunks read in an on-disk cache across runs
+    ChunkCache {
+        /// Upper limit, in bytes, for the size of the chunks cached
+        capacity: u64,
+        /// Which chunks are dropped first once the capacity is reached: [lru, fifo]
+        #[structopt(long, default_value = "lru", parse(try_from_str = parse_eviction))]
+        eviction: ChunkCacheEviction,
+    },
     // #[structopt(name = "contact")]
     // Contact {
     //    /// Contact friendly name
@@ -50,6 +60,9 @@ pub enum SettingRemoveCmd {
         /// Network to remove
         network_name: String,
     },
+    #[structopt(name = "chunk-cache")]
+    /// Stop keeping the chunks read on disk
+    ChunkCache,
     // #[structopt(name = "contact")]
     // Contact {
     //    /// Name of the contact to remove
@@ -70,10 +83,18 @@ pub async fn config_commander(cmd: Option<ConfigSubCommands>, config: &mut Confi
                 )
                 .await?;
         }
+        Some(ConfigSubCommands::Add(SettingAddCmd::ChunkCache { capacity, eviction })) => {
+            config
+                .set_chunk_cache(Some(ChunkCacheConfig { capacity, eviction }))
+                .await?;
+        }
         // Some(ConfigSubCommands::Add(SettingAddCmd::Contact { name, safeid })) => {}
         Some(ConfigSubCommands::Remove(SettingRemoveCmd::Network { network_name })) => {
             config.remove_network(&network_name).await?
         }
+        Some(ConfigSubCommands::Remove(SettingRemoveCmd::ChunkCache)) => {
+            config.set_chunk_cache(None).await?
+        }
         // Some(ConfigSubCommands::Remove(SettingRemoveCmd::Contact { name })) => {}
         Some(ConfigSubCommands::Clear) => {
             config.clear().await?;
@@ -84,3 +105,14 @@ pub async fn config_commander(cmd: Option<ConfigSubCommands>, config: &mut Confi
 
     Ok(())
 }
+
+fn parse_eviction(s: &str) -> Result<ChunkCacheEviction> {
+    match s {
+        "lru" => Ok(ChunkCacheEviction::LeastRecentlyUsed),
+        "fifo" => Ok(ChunkCacheEviction::FirstInFirstOut),
+        other => Err(eyre!(
+            "Unknown eviction policy '{}', expected one of: lru, fifo",
+            other
+        )),
+    }
+}
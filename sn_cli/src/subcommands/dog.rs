@@ -12,9 +12,11 @@ use super::{
     OutputFmt,
 };
 use color_eyre::Result;
+use prettytable::Table;
 use sn_api::{
+    register::HistoryEntry,
     resolver::{ContentType, SafeData},
-    Safe, SafeUrl,
+    Safe, SafeUrl, VersionHash,
 };
 use structopt::StructOpt;
 use tracing::debug;
@@ -23,6 +25,9 @@ use tracing::debug;
 pub struct DogCommands {
     /// The safe:// location to inspect
     location: Option<String>,
+    /// Also show the history of the FilesContainers and NRS Map Containers found while resolving
+    #[structopt(long = "history")]
+    history: bool,
 }
 
 pub async fn dog_commander(cmd: DogCommands, output_fmt: OutputFmt, safe: &mut Safe) -> Result<()> {
@@ -30,8 +35,21 @@ pub async fn dog_commander(cmd: DogCommands, output_fmt: OutputFmt, safe: &mut S
     debug!("Running dog for: {:?}", &url);
 
     let resolved_content = safe.inspect(&url).await?;
+    let histories = if cmd.history {
+        fetch_histories(safe, &resolved_content).await?
+    } else {
+        vec![]
+    };
+
     if OutputFmt::Pretty != output_fmt {
-        println!("{}", serialise_output(&(url, resolved_content), output_fmt));
+        if cmd.history {
+            println!(
+                "{}",
+                serialise_output(&(url, resolved_content, histories), output_fmt)
+            );
+        } else {
+            println!("{}", serialise_output(&(url, resolved_content), output_fmt));
+        }
     } else {
         for (i, ref content) in resolved_content.iter().enumerate() {
             println!();
@@ -63,6 +81,7 @@ pub async fn dog_commander(cmd: DogCommands, output_fmt: OutputFmt, safe: &mut S
                     safeurl.set_content_type(ContentType::Raw)?;
                     println!("Native data XOR-URL: {}", safeurl.to_string());
                     print_nrs_map(nrs_map, public_name);
+                    print_history(&histories, xorurl);
                 }
                 SafeData::FilesContainer {
                     xorurl,
@@ -86,6 +105,7 @@ pub async fn dog_commander(cmd: DogCommands, output_fmt: OutputFmt, safe: &mut S
                     let mut safeurl = SafeUrl::from_url(xorurl)?;
                     safeurl.set_content_type(ContentType::Raw)?;
                     println!("Native data XOR-URL: {}", safeurl.to_string());
+                    print_history(&histories, xorurl);
                 }
                 SafeData::PublicBlob {
                     xorurl,
@@ -93,12 +113,23 @@ pub async fn dog_commander(cmd: DogCommands, output_fmt: OutputFmt, safe: &mut S
                     media_type,
                     resolved_from,
                     ..
+                }
+                | SafeData::PrivateBlob {
+                    xorurl,
+                    xorname,
+                    media_type,
+                    resolved_from,
+                    ..
                 } => {
+                    let data_type = match content {
+                        SafeData::PrivateBlob { .. } => "PrivateBlob",
+                        _ => "PublicBlob",
+                    };
                     println!("Resolved from: {}", resolved_from);
                     println!("= File =");
                     println!("XOR-URL: {}", xorurl);
                     println!("XOR name: 0x{}", xorname_to_hex(xorname));
-                    println!("Native data type: PublicBlob");
+                    println!("Native data type: {}", data_type);
                     println!(
                         "Media type: {}",
                         media_type.clone().unwrap_or_else(|| "Unknown".to_string())
@@ -125,3 +156,52 @@ pub async fn dog_commander(cmd: DogCommands, output_fmt: OutputFmt, safe: &mut S
 
     Ok(())
 }
+
+// Fetches the history of every FilesContainer and NRS Map Container in the resolution chain
+async fn fetch_histories(
+    safe: &Safe,
+    resolved_content: &[SafeData],
+) -> Result<Vec<(String, Vec<HistoryEntry>)>> {
+    let mut histories = vec![];
+    for content in resolved_content {
+        match content {
+            SafeData::FilesContainer { xorurl, .. } | SafeData::NrsMapContainer { xorurl, .. } => {
+                // the whole history is wanted, not only the one up to the resolved version
+                let mut safeurl = SafeUrl::from_url(xorurl)?;
+                safeurl.set_content_version(None);
+                let history = safe.register_history(&safeurl.to_string()).await?;
+                histories.push((xorurl.clone(), history));
+            }
+            _ => {}
+        }
+    }
+    Ok(histories)
+}
+
+fn print_history(histories: &[(String, Vec<HistoryEntry>)], xorurl: &str) {
+    let history = match histories.iter().find(|(url, _)| url == xorurl) {
+        Some((_, history)) => history,
+        None => return,
+    };
+
+    println!("History ({} versions, oldest first):", history.len());
+    let mut table = Table::new();
+    table.add_row(row![bFg->"Version", bFg->"Previous versions", bFg->"Entry"]);
+    for item in history {
+        let previous: Vec<String> = item
+            .children
+            .iter()
+            .map(|hash| VersionHash::from(hash).to_string())
+            .collect();
+        let entry = match std::str::from_utf8(&item.entry) {
+            Ok(text) => text.to_string(),
+            Err(_) => format!("<{} bytes>", item.entry.len()),
+        };
+        table.add_row(row![
+            VersionHash::from(&item.hash),
+            previous.join("\n"),
+            entry
+        ]);
+    }
+    table.printstd();
+}
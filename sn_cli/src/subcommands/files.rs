@@ -10,33 +10,42 @@
 use super::{
     files_get::{process_get_command, FileExistsAction, ProgressIndicator},
     helpers::{
-        gen_processed_files_table, get_from_arg_or_stdin, get_from_stdin, if_tty, notice_dry_run,
-        parse_stdin_arg, pluralize, serialise_output,
+        div_or, gen_processed_files_table, get_from_arg_or_stdin, get_from_stdin, if_tty,
+        notice_dry_run, parse_stdin_arg, pluralize, serialise_output,
     },
     OutputFmt,
 };
 use ansi_term::Colour;
 use bytes::Bytes;
 use color_eyre::{eyre::bail, eyre::eyre, Result};
+use console::Term;
 use prettytable::{format::FormatBuilder, Table};
 use serde::Serialize;
 use sn_api::{
     files::{FilesMap, ProcessedFiles},
     nrs::VersionHash,
     resolver::SafeData,
-    Safe, SafeUrl, XorUrl,
+    Safe, SafeUrl, UploadEvent, UploadProgress, XorUrl,
 };
 use std::{
     collections::{BTreeMap, HashMap},
+    future::Future,
     path::{Component, Path, PathBuf},
 };
 use structopt::StructOpt;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
 use tracing::debug;
+use walkdir::WalkDir;
 
 type FileDetails = BTreeMap<String, String>;
 
 const UNKNOWN_FILE_NAME: &str = "<unknown>";
 
+// Width, in characters, of the bar showing the progress of uploads.
+const PROGRESS_BAR_WIDTH: usize = 30;
+// Depth local folders are walked to when uploading them recursively, as per sn_api.
+const MAX_RECURSIVE_DEPTH: usize = 10_000;
+
 // Differentiates between nodes in a file system.
 #[derive(Debug, Serialize, PartialEq)]
 enum FileTreeNodeType {
@@ -111,6 +120,15 @@ pub enum FilesSubCommands {
         /// Follow symlinks
         #[structopt(short = "l", long = "follow-links")]
         follow_links: bool,
+        /// Resume the upload of files which previously failed halfway, sending only the data which is still missing
+        #[structopt(long = "resume")]
+        resume: bool,
+        /// Create a private FilesContainer, with the files encrypted so only the owner can read them
+        #[structopt(long = "private")]
+        private: bool,
+        /// How to display progress.
+        #[structopt(short = "i", long = "progress", possible_values = &["text", "none"], default_value="text")]
+        progress: ProgressIndicator,
     },
     /// Get a file or folder from the SAFE Network
     Get {
@@ -211,14 +229,56 @@ pub async fn files_commander(
             dst,
             recursive,
             follow_links,
+            resume,
+            private,
+            progress,
         } => {
             // create FilesContainer from a given path to local files/folders
+            if private && safe.dry_run_mode {
+                // there's no connection in dry-run mode, thus no keys to encrypt with
+                bail!(
+                    "The address of private files depends on the keys they're encrypted with, \
+                    thus '--private' can't be used in dry-run mode"
+                );
+            }
             if safe.dry_run_mode && OutputFmt::Pretty == output_fmt {
                 notice_dry_run();
             }
-            let (files_container_xorurl, processed_files, _) = safe
-                .files_container_create_from(&location, dst.as_deref(), recursive, follow_links)
-                .await?;
+            safe.resume_uploads_mode = resume;
+
+            // the progress bar is drawn on stderr, so it doesn't mix with the output
+            let events = match progress {
+                ProgressIndicator::Text if !safe.dry_run_mode && isatty::stderr_isatty() => {
+                    safe.upload_events().ok()
+                }
+                _ => None,
+            };
+            let events = events.map(|events| {
+                let total_bytes = local_files_size(Path::new(&location), recursive, follow_links);
+                (events, total_bytes)
+            });
+
+            let upload = async {
+                if private {
+                    safe.files_container_create_private_from(
+                        &location,
+                        dst.as_deref(),
+                        recursive,
+                        follow_links,
+                    )
+                    .await
+                } else {
+                    safe.files_container_create_from(
+                        &location,
+                        dst.as_deref(),
+                        recursive,
+                        follow_links,
+                    )
+                    .await
+                }
+            };
+            let (files_container_xorurl, processed_files, _) =
+                with_upload_progress_bar(events, upload).await?;
 
             // Now let's just print out a list of the files uploaded/processed
             if OutputFmt::Pretty == output_fmt {
@@ -369,7 +429,7 @@ pub async fn files_commander(
                     let (total, filtered_filesmap) = filter_files_map(&files_map, &target_url)?;
                     (version, filtered_filesmap, total)
                 }
-                SafeData::PublicBlob { metadata, .. } => {
+                SafeData::PublicBlob { metadata, .. } | SafeData::PrivateBlob { metadata, .. } => {
                     if let Some(file_item) = metadata {
                         let mut files_map = FilesMap::new();
                         let name = match file_item.get("name") {
@@ -414,6 +474,77 @@ pub async fn files_commander(
     }
 }
 
+// Drives the upload, drawing a bar with its progress as per the upload events, if any,
+// out of the total size of the files uploaded.
+async fn with_upload_progress_bar<T>(
+    events: Option<(Receiver<UploadEvent>, u64)>,
+    upload: impl Future<Output = T>,
+) -> T {
+    let (mut events, total_bytes) = match events {
+        Some(events) => events,
+        None => return upload.await,
+    };
+    tokio::pin!(upload);
+
+    let term = Term::stderr();
+    let mut progress = UploadProgress::default();
+    let result = loop {
+        tokio::select! {
+            result = &mut upload => break result,
+            event = events.recv() => match event {
+                Ok(event) => {
+                    progress.record(&event);
+                    let _ = term.clear_line();
+                    let _ = term.write_str(&format_upload_progress(&progress, total_bytes));
+                }
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break (&mut upload).await,
+            }
+        }
+    };
+
+    let _ = term.clear_line();
+    result
+}
+
+fn format_upload_progress(progress: &UploadProgress, total_bytes: u64) -> String {
+    // chunks are compressed and encrypted, so their size is only close to that of the files
+    let ratio = div_or(progress.bytes_delivered as f64, total_bytes as f64, 1.0).min(1.0);
+    let filled = (ratio * PROGRESS_BAR_WIDTH as f64) as usize;
+    let mut line = format!(
+        "[{}{}] {:>3.0}% - {} of ~{} bytes, {} {} delivered",
+        "=".repeat(filled),
+        " ".repeat(PROGRESS_BAR_WIDTH - filled),
+        ratio * 100.0,
+        progress.bytes_delivered,
+        total_bytes,
+        progress.chunks_delivered,
+        pluralize("chunk", "chunks", progress.chunks_delivered as u64),
+    );
+    if let Some(concurrency) = progress.concurrency {
+        line.push_str(&format!(", sending {} at once", concurrency));
+    }
+    if progress.chunks_failed > 0 {
+        line.push_str(&format!(", {} failed", progress.chunks_failed));
+    }
+    line
+}
+
+// Size of the files which are uploaded from the location, walking it as `files put` does,
+// i.e. with the same depth, and skipping symlink loops and entries whose name isn't UTF-8.
+fn local_files_size(location: &Path, recursive: bool, follow_links: bool) -> u64 {
+    let max_depth = if recursive { MAX_RECURSIVE_DEPTH } else { 1 };
+    WalkDir::new(location)
+        .follow_links(follow_links)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name().to_str().is_some() && entry.depth() <= max_depth)
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
 // processes the `safe files tree` command.
 async fn process_tree_command(
     safe: &mut Safe,
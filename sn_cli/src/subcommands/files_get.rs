@@ -11,20 +11,17 @@ use super::{
     helpers::{div_or, pluralize, processed_files_err_report, prompt_user},
     OutputFmt,
 };
-use bytes::Buf;
 use color_eyre::{eyre::bail, eyre::eyre, eyre::WrapErr, Result};
 use console::Term;
 use sn_api::{
     files::{FilesMap, GetAttr},
-    resolver::Range,
     resolver::SafeData,
     DataType, Result as ApiResult, Safe, SafeUrl, XorUrl,
 };
-use std::{
-    collections::BTreeMap,
-    fs,
-    io::{BufWriter, Write},
-    path::Path,
+use std::{collections::BTreeMap, fs, path::Path};
+use tokio::{
+    fs::File,
+    io::{AsyncWrite, AsyncWriteExt, BufWriter},
 };
 use tracing::{debug, info, trace, warn};
 
@@ -334,7 +331,7 @@ async fn files_container_get_files(
         SafeData::FilesContainer {
             version, files_map, ..
         } => (version.map_or("".to_string(), |v| v.to_string()), files_map),
-        SafeData::PublicBlob { metadata, .. } => {
+        SafeData::PublicBlob { metadata, .. } | SafeData::PrivateBlob { metadata, .. } => {
             if let Some(file_item) = metadata {
                 let mut files_map = FilesMap::new();
                 files_map.insert("".to_string(), file_item);
@@ -629,54 +626,40 @@ async fn download_file_from_net(
 ) -> Result<u64> {
     debug!("downloading file {} to {}", xorurl, path.display());
 
-    // TODO: download the file by concurrently (spawning tasks/threads) pulling chunks.
-    // The chunk_size can be based on https://stackoverflow.com/questions/8803515/optimal-buffer-size-for-write2
-    // which can be 4096 to match common disk block size, but that seems a bit small for the
-    // network, so I multiplied by 16. Perhaps should make it a param so caller can decide.
-    let mut rcvd: u64 = 0;
-    let mut bytes_written: u64 = 0;
-
-    let fh = file_create(path)?;
+    // The content is written to the file as its chunks are being fetched from the network,
+    // thus the whole file is never held in memory.
+    let fh = file_create(path).await?;
     let mut stream = BufWriter::new(fh);
 
     // gets public or private, based on xorurl type
-    let filedata = files_get_blob(safe, xorurl, None).await?;
-    bytes_written += stream_write(&mut stream, &filedata, path)? as u64;
-    rcvd += filedata.len() as u64;
-    trace!("received {} bytes of {}", rcvd, size,);
+    let bytes_written = files_get_blob_to_writer(safe, xorurl, &mut stream)
+        .await
+        .with_context(|| format!("Error writing to file: \"{}\"", path.display(),))?;
+    trace!("received {} bytes of {}", bytes_written, size);
 
     // Close may generate an error, so we do a flush/sync first to detect such.
     // see https://github.com/rust-lang/rust/pull/63410#issuecomment-519965351
-    let fh = bufwriter_into_inner(stream, path)?;
-    file_sync_all(&fh, path)?;
+    stream
+        .flush()
+        .await
+        .with_context(|| format!("Error flushing file \"{}\"", path.display()))?;
+    file_sync_all(stream.get_ref(), path).await?;
 
-    Ok(bytes_written as u64)
+    Ok(bytes_written)
 }
 
 // syncs file to filesystem.
-fn file_sync_all(f: &fs::File, path: &Path) -> Result<()> {
+async fn file_sync_all(f: &File, path: &Path) -> Result<()> {
     f.sync_all()
+        .await
         .with_context(|| format!("Error syncing file: \"{}\"", path.display(),))
 }
 
-// causes BufWriter to flush() file.
-fn bufwriter_into_inner<W: Write>(w: BufWriter<W>, path: &Path) -> Result<W> {
-    match w.into_inner() {
-        Ok(inner) => Ok(inner),
-        Err(err) => Err(eyre!("Error flushing file \"{}\": {}", path.display(), err)),
-    }
-}
-
-// Writes data to a file/stream.
-fn stream_write(writer: &mut dyn Write, data: &[u8], path: &Path) -> Result<usize> {
-    writer
-        .write(data)
-        .with_context(|| format!("Error writing to file: \"{}\"", path.display(),))
-}
-
 // Creates a file, ready for writing.
-fn file_create(path: &Path) -> Result<fs::File> {
-    fs::File::create(path).with_context(|| format!("Couldn't create file: \"{}\"", path.display(),))
+async fn file_create(path: &Path) -> Result<File> {
+    File::create(path)
+        .await
+        .with_context(|| format!("Couldn't create file: \"{}\"", path.display(),))
 }
 
 // create all directories in path if possible.
@@ -692,12 +675,15 @@ fn create_dir_all(dir_path: &Path) -> Result<()> {
 }
 
 /// # Get Public or Private Blob
-/// Get immutable data blobs from the network.
-pub async fn files_get_blob(safe: &mut Safe, url: &str, range: Range) -> Result<Vec<u8>> {
+/// Get immutable data blobs from the network, writing them to the provided `writer`.
+pub async fn files_get_blob_to_writer<W>(safe: &mut Safe, url: &str, writer: &mut W) -> Result<u64>
+where
+    W: AsyncWrite + Unpin,
+{
     match SafeUrl::from_url(url)?.data_type() {
         DataType::Bytes => {
-            let pub_blob = safe.files_get_public_data(url, range).await?;
-            Ok(pub_blob.chunk().to_vec())
+            let bytes_written = safe.files_get_public_data_to_writer(url, writer).await?;
+            Ok(bytes_written)
         }
         _ => Err(eyre!("URL target is not immutable data")),
     }
@@ -16,13 +16,100 @@ use serde::ser::Serialize;
 use sn_api::{
     files::{FilesMapChange, ProcessedFiles},
     nrs::NrsMap,
+    register::EntryHash,
+    PublicKey, VersionHash,
+};
+use std::{
+    fs,
+    io::{stdin, stdout, Read, Write},
+    str::FromStr,
 };
-use std::io::{stdin, stdout, Read, Write};
 use tracing::debug;
 use xor_name::XorName;
 
 const UNKNOWN_PUBLIC_NAME: &str = "<unknown>";
 
+// How a value given as argument is to be interpreted
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ValueFormat {
+    Utf8,
+    Hex,
+    File,
+}
+
+impl FromStr for ValueFormat {
+    type Err = String;
+    fn from_str(str: &str) -> Result<Self, String> {
+        match str {
+            "utf8" => Ok(Self::Utf8),
+            "hex" => Ok(Self::Hex),
+            "file" => Ok(Self::File),
+            other => Err(format!(
+                "Value format '{}' not supported. Supported values are utf8, hex, and file",
+                other
+            )),
+        }
+    }
+}
+
+// Get the bytes of a value given as argument, or read from STDIN if not provided
+pub fn get_value_from_arg_or_stdin(arg: Option<String>, fmt: ValueFormat) -> Result<Vec<u8>> {
+    match arg {
+        Some(value) if !value.is_empty() => parse_value(&value, fmt),
+        // raw bytes can be piped in as they are
+        _ if fmt == ValueFormat::Utf8 => get_from_stdin(Some("...awaiting value from STDIN")),
+        _ => {
+            let value = get_from_arg_or_stdin(None, Some("...awaiting value from STDIN"))?;
+            parse_value(&value, fmt)
+        }
+    }
+}
+
+// Get the bytes of a value given as argument
+pub fn parse_value(value: &str, fmt: ValueFormat) -> Result<Vec<u8>> {
+    match fmt {
+        ValueFormat::Utf8 => Ok(value.as_bytes().to_vec()),
+        ValueFormat::Hex => hex::decode(value.trim().trim_start_matches("0x"))
+            .wrap_err_with(|| format!("Value '{}' is not a valid hex string", value)),
+        ValueFormat::File => {
+            fs::read(value.trim()).wrap_err_with(|| format!("Failed to read file '{}'", value))
+        }
+    }
+}
+
+// Render a value as a string, either hex encoded or as lossy UTF-8
+pub fn display_value(value: &[u8], as_hex: bool) -> String {
+    if as_hex {
+        hex::encode(value)
+    } else {
+        String::from_utf8_lossy(value).to_string()
+    }
+}
+
+// Parse an entry hash given in the same encoding as the versions of Safe-URLs
+pub fn parse_entry_hash(hash: &str) -> Result<EntryHash> {
+    let version = VersionHash::from_str(hash)
+        .map_err(|err| eyre!("Invalid entry hash '{}': {}", hash, err))?;
+    Ok(version.entry_hash())
+}
+
+// Render an entry hash in the same encoding as the versions of Safe-URLs
+pub fn entry_hash_to_string(hash: &EntryHash) -> String {
+    VersionHash::from(hash).to_string()
+}
+
+// Parse a hex encoded Ed25519 or BLS public key
+pub fn parse_public_key(pk: &str) -> Result<PublicKey> {
+    PublicKey::ed25519_from_hex(pk)
+        .or_else(|_| PublicKey::bls_from_hex(pk))
+        .map_err(|_| eyre!("Invalid (Ed25519/BLS) public key bytes: {}", pk))
+}
+
+// Render a public key hex encoded, as parsed by `parse_public_key`
+pub fn public_key_to_hex(pk: &PublicKey) -> String {
+    hex::encode(pk.to_bytes())
+}
+
 // Warn the user about a dry-run being performed
 pub fn notice_dry_run() {
     println!("NOTE the operation is being performed in dry-run mode, therefore no changes are committed to the network.");
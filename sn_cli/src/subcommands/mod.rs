@@ -14,9 +14,11 @@ pub mod files;
 mod files_get;
 mod helpers;
 pub mod keys;
+pub mod multimap;
 pub mod networks;
 pub mod node;
 pub mod nrs;
+pub mod register;
 pub mod safe_id;
 pub mod setup;
 pub mod update;
@@ -108,6 +110,20 @@ pub enum SubCommands {
     )]
     /// Manage public names on the SAFE Network
     Nrs(nrs::NrsSubCommands),
+    #[structopt(
+        name = "register",
+        no_version,
+        global_settings(&[AppSettings::DisableVersion]),
+    )]
+    /// Manage Registers on the SAFE Network
+    Register(register::RegisterSubCommands),
+    #[structopt(
+        name = "multimap",
+        no_version,
+        global_settings(&[AppSettings::DisableVersion]),
+    )]
+    /// Manage Multimaps on the SAFE Network
+    Multimap(multimap::MultimapSubCommands),
     #[structopt(
         name = "keys",
         no_version,
@@ -0,0 +1,208 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    helpers::{
+        display_value, entry_hash_to_string, get_value_from_arg_or_stdin, notice_dry_run,
+        parse_entry_hash, parse_value, serialise_output, ValueFormat,
+    },
+    register::DEFAULT_REGISTER_TYPE_TAG,
+    OutputFmt,
+};
+use color_eyre::{eyre::bail, Result};
+use prettytable::Table;
+use sn_api::{multimap::MultimapKeyValues, register::EntryHash, Error, Safe};
+use std::collections::BTreeSet;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub enum MultimapSubCommands {
+    #[structopt(name = "create")]
+    /// Create a new Multimap
+    Create {
+        /// The type tag of the Multimap
+        #[structopt(long = "type-tag", default_value = DEFAULT_REGISTER_TYPE_TAG)]
+        type_tag: u64,
+        /// Create a private Multimap, only readable by its owner
+        #[structopt(long = "private")]
+        private: bool,
+    },
+    #[structopt(name = "insert")]
+    /// Insert a key-value pair into a Multimap, replacing the current values of the key
+    Insert {
+        /// The safe:// URL of the Multimap
+        url: String,
+        /// The key to insert
+        key: String,
+        /// The value to insert. If not provided it will be read from STDIN
+        value: Option<String>,
+        /// How to interpret the key: utf8, hex, or file to read it from a local file
+        #[structopt(long = "key-format", default_value = "utf8")]
+        key_format: ValueFormat,
+        /// How to interpret the value: utf8, hex, or file to read it from a local file
+        #[structopt(long = "value-format", default_value = "utf8")]
+        value_format: ValueFormat,
+    },
+    #[structopt(name = "remove")]
+    /// Remove all the values of a key from a Multimap
+    Remove {
+        /// The safe:// URL of the Multimap
+        url: String,
+        /// The key to remove
+        key: String,
+        /// How to interpret the key: utf8, hex, or file to read it from a local file
+        #[structopt(long = "key-format", default_value = "utf8")]
+        key_format: ValueFormat,
+    },
+    #[structopt(name = "get")]
+    /// Get the values of a key from a Multimap, or the key-value pair with the given hash
+    Get {
+        /// The safe:// URL of the Multimap
+        url: String,
+        /// The key to get the values of
+        #[structopt(required_unless = "hash")]
+        key: Option<String>,
+        /// How to interpret the key: utf8, hex, or file to read it from a local file
+        #[structopt(long = "key-format", default_value = "utf8")]
+        key_format: ValueFormat,
+        /// The hash of the key-value pair to get, as shown by the `insert` command
+        #[structopt(long = "hash", conflicts_with = "key")]
+        hash: Option<String>,
+        /// Show the keys and values hex encoded instead of as UTF-8
+        #[structopt(long = "hex")]
+        hex: bool,
+    },
+}
+
+pub async fn multimap_commander(
+    cmd: MultimapSubCommands,
+    output_fmt: OutputFmt,
+    safe: &mut Safe,
+) -> Result<()> {
+    match cmd {
+        MultimapSubCommands::Create { type_tag, private } => {
+            let xorurl = safe.multimap_create(None, type_tag, private).await?;
+            if OutputFmt::Pretty == output_fmt {
+                if safe.dry_run_mode {
+                    notice_dry_run();
+                }
+                println!("Multimap created at: \"{}\"", xorurl);
+            } else {
+                println!("{}", serialise_output(&xorurl, output_fmt));
+            }
+            Ok(())
+        }
+        MultimapSubCommands::Insert {
+            url,
+            key,
+            value,
+            key_format,
+            value_format,
+        } => {
+            let key = parse_value(&key, key_format)?;
+            let value = get_value_from_arg_or_stdin(value, value_format)?;
+            let replace = current_hashes(safe, &url, &key).await?;
+            let hash = safe.multimap_insert(&url, (key, value), replace).await?;
+
+            print_change(
+                safe,
+                &url,
+                "inserted into",
+                &entry_hash_to_string(&hash),
+                output_fmt,
+            );
+            Ok(())
+        }
+        MultimapSubCommands::Remove {
+            url,
+            key,
+            key_format,
+        } => {
+            let key = parse_value(&key, key_format)?;
+            let to_remove = current_hashes(safe, &url, &key).await?;
+            if to_remove.is_empty() {
+                bail!("No values found for the key in Multimap at \"{}\"", url);
+            }
+            let hash = safe.multimap_remove(&url, to_remove).await?;
+
+            print_change(
+                safe,
+                &url,
+                "removed from",
+                &entry_hash_to_string(&hash),
+                output_fmt,
+            );
+            Ok(())
+        }
+        MultimapSubCommands::Get {
+            url,
+            key,
+            key_format,
+            hash,
+            hex,
+        } => {
+            let entries: MultimapKeyValues = match (hash, key) {
+                (Some(hash), _) => {
+                    let hash = parse_entry_hash(&hash)?;
+                    let key_value = safe.multimap_get_by_hash(&url, hash).await?;
+                    vec![(hash, key_value)].into_iter().collect()
+                }
+                (None, Some(key)) => {
+                    let key = parse_value(&key, key_format)?;
+                    safe.multimap_get_by_key(&url, &key).await?
+                }
+                (None, None) => bail!("Either a key or the hash of a key-value pair is needed"),
+            };
+
+            let entries: Vec<_> = entries
+                .iter()
+                .map(|(hash, (key, value))| {
+                    (
+                        entry_hash_to_string(hash),
+                        display_value(key, hex),
+                        display_value(value, hex),
+                    )
+                })
+                .collect();
+
+            if OutputFmt::Pretty == output_fmt {
+                println!("Entries of Multimap at \"{}\":", url);
+                let mut table = Table::new();
+                table.add_row(row![bFg->"Hash", bFg->"Key", bFg->"Value"]);
+                for (hash, key, value) in &entries {
+                    table.add_row(row![hash, key, value]);
+                }
+                table.printstd();
+            } else {
+                println!("{}", serialise_output(&(url, entries), output_fmt));
+            }
+            Ok(())
+        }
+    }
+}
+
+// Hashes of the current key-value pairs for the key
+async fn current_hashes(safe: &Safe, url: &str, key: &[u8]) -> Result<BTreeSet<EntryHash>> {
+    match safe.multimap_get_by_key(url, key).await {
+        Ok(entries) => Ok(entries.into_iter().map(|(hash, _)| hash).collect()),
+        Err(Error::EmptyContent(_)) => Ok(BTreeSet::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn print_change(safe: &Safe, url: &str, change: &str, hash: &str, output_fmt: OutputFmt) {
+    if OutputFmt::Pretty == output_fmt {
+        if safe.dry_run_mode {
+            notice_dry_run();
+        }
+        println!("Entry {} Multimap at \"{}\": {}", change, url, hash);
+    } else {
+        println!("{}", serialise_output(&(url, hash), output_fmt));
+    }
+}
@@ -7,10 +7,16 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
+use super::{helpers::serialise_output, OutputFmt};
 use crate::operations::{config::Config, config::NetworkLauncher, node::*};
 use color_eyre::{eyre::eyre, Help, Result};
-use sn_api::PublicKey;
-use std::{collections::BTreeSet, net::SocketAddr, path::PathBuf};
+use prettytable::Table;
+use sn_api::{NodeAdminClient, PublicKey};
+use std::{
+    collections::BTreeSet,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
 use structopt::StructOpt;
 use tracing::debug;
 
@@ -119,12 +125,31 @@ pub enum NodeSubCommands {
         #[structopt(long = "ip")]
         ip: Option<String>,
     },
+    #[structopt(name = "status")]
+    /// Get a status report of a running node, through its admin endpoint
+    Status {
+        /// Address of the node's admin endpoint, as set with its `--admin-addr` argument
+        #[structopt(long = "admin-addr")]
+        admin_addr: SocketAddr,
+        /// Token the node's admin endpoint was configured with, as set with its `--admin-token`
+        /// argument. The SN_ADMIN_TOKEN env var can be also used to set it
+        #[structopt(long = "admin-token", env = "SN_ADMIN_TOKEN", hide_env_values = true)]
+        admin_token: Option<String>,
+        /// Root directory of the node, where its admin endpoint certificate is read from
+        /// (default is ~/.safe/node/local-node, as used by the `join` command)
+        #[structopt(long = "root-dir")]
+        root_dir: Option<PathBuf>,
+    },
     /// Shutdown all running nodes processes
     #[structopt(name = "killall")]
     Killall {
         /// Path of the sn_node executable used to launch the processes with (default ~/.safe/node/sn_node). The SN_NODE_PATH env var can be also used to set this path
         #[structopt(long = "node-path", env = "SN_NODE_PATH")]
         node_path: Option<PathBuf>,
+        /// Have the nodes leave the network gracefully, handing off the data they hold before
+        /// exiting, rather than killing them right away. Not supported on Windows.
+        #[structopt(long)]
+        graceful: bool,
     },
     #[structopt(name = "update")]
     /// Update to latest sn_node released version
@@ -140,6 +165,7 @@ pub async fn node_commander(
     cmd: Option<NodeSubCommands>,
     config: &mut Config,
     network_launcher: &mut Box<impl NetworkLauncher>,
+    output_fmt: OutputFmt,
 ) -> Result<()> {
     match cmd {
         Some(NodeSubCommands::BinVersion { node_path }) => node_version(node_path),
@@ -247,12 +273,105 @@ pub async fn node_commander(
             config.add_network("baby-fleming", None).await?;
             Ok(())
         }
-        Some(NodeSubCommands::Killall { node_path }) => node_shutdown(node_path),
+        Some(NodeSubCommands::Status {
+            admin_addr,
+            admin_token,
+            root_dir,
+        }) => {
+            let root_dir = root_dir.unwrap_or_else(|| {
+                let mut default_root_dir = config.node_config_path.clone();
+                default_root_dir.pop();
+                default_root_dir.join(LOCAL_NODE_DIR_NAME)
+            });
+            node_status(admin_addr, admin_token, &root_dir, output_fmt).await
+        }
+        Some(NodeSubCommands::Killall {
+            node_path,
+            graceful,
+        }) => node_shutdown(node_path, graceful),
         Some(NodeSubCommands::Update { node_path }) => node_update(node_path),
         None => Err(eyre!("Missing node subcommand")),
     }
 }
 
+async fn node_status(
+    admin_addr: SocketAddr,
+    admin_token: Option<String>,
+    root_dir: &Path,
+    output_fmt: OutputFmt,
+) -> Result<()> {
+    let client = NodeAdminClient::new(admin_addr, root_dir, admin_token);
+    let status = client.status().await?;
+
+    if OutputFmt::Pretty != output_fmt {
+        println!("{}", serialise_output(&status, output_fmt));
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.add_row(row![bFg->"Node status"]);
+    table.add_row(row!["Name", format!("{:x}", status.name)]);
+    table.add_row(row!["Age", status.age]);
+    table.add_row(row!["Connection info", status.connection_info]);
+    table.add_row(row!["Section prefix", format_prefix(&status.prefix)]);
+    table.add_row(row!["Is elder", if status.is_elder { "Yes" } else { "No" }]);
+    table.add_row(row!["Genesis key", status.genesis_key]);
+    table.printstd();
+
+    let mut table = Table::new();
+    table.add_row(row![bFg->"Section members", bFg->"Name", bFg->"Address"]);
+    for (role, members) in [("Elder", &status.elders), ("Adult", &status.adults)] {
+        for member in members {
+            table.add_row(row![role, format!("{:x}", member.name), member.addr]);
+        }
+    }
+    table.printstd();
+
+    let mut table = Table::new();
+    table.add_row(row![bFg->"Known sections", bFg->"Section key", bFg->"Elders"]);
+    for section in &status.prefix_map {
+        table.add_row(row![
+            format_prefix(&section.prefix),
+            section.section_key,
+            section.elders.len()
+        ]);
+    }
+    table.printstd();
+
+    if !status.storage_levels.is_empty() {
+        let mut table = Table::new();
+        table.add_row(row![bFg->"Adult", bFg->"Storage level"]);
+        for (name, level) in &status.storage_levels {
+            table.add_row(row![format!("{:x}", name), level]);
+        }
+        table.printstd();
+    }
+
+    if !status.pending_dkg_sessions.is_empty() {
+        let mut table = Table::new();
+        table.add_row(row![bFg->"Pending DKG sessions", bFg->"Prefix", bFg->"Participants"]);
+        for session in &status.pending_dkg_sessions {
+            table.add_row(row![
+                session.generation,
+                format_prefix(&session.prefix),
+                session.participants.len()
+            ]);
+        }
+        table.printstd();
+    }
+
+    Ok(())
+}
+
+// The root prefix is rendered as an empty string by the node
+fn format_prefix(prefix: &str) -> &str {
+    if prefix.is_empty() {
+        "(root)"
+    } else {
+        prefix
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::operations::config::NetworkLauncher;
@@ -279,7 +398,7 @@ mod test {
 #[cfg(test)]
 mod run_command {
     use super::test::FakeNetworkLauncher;
-    use super::{node_commander, NodeSubCommands, NODES_DATA_DIR_NAME};
+    use super::{node_commander, NodeSubCommands, OutputFmt, NODES_DATA_DIR_NAME};
     use crate::operations::config::{Config, NetworkInfo};
     use crate::operations::node::SN_NODE_EXECUTABLE;
     use assert_fs::prelude::*;
@@ -331,7 +450,7 @@ mod run_command {
             ip: None,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
 
@@ -372,7 +491,7 @@ mod run_command {
             ip: None,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher.launch_args.iter().any(|x| x == "--node-path"));
@@ -412,7 +531,7 @@ mod run_command {
             ip: None,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher.launch_args.iter().any(|x| x == "--nodes-dir"));
@@ -450,7 +569,7 @@ mod run_command {
             ip: None,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher.launch_args.iter().any(|x| x == "--nodes-dir"));
@@ -491,7 +610,7 @@ mod run_command {
             ip: None,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         node_data_dir.assert(predicates::path::is_dir());
@@ -525,7 +644,7 @@ mod run_command {
             ip: None,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher.launch_args.iter().any(|x| x == "--interval"));
@@ -560,7 +679,7 @@ mod run_command {
             ip: None,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher.launch_args.iter().any(|x| x == "--num-nodes"));
@@ -595,7 +714,7 @@ mod run_command {
             ip: Some("10.10.0.1".to_string()),
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher.launch_args.iter().any(|x| x == "--ip"));
@@ -630,7 +749,7 @@ mod run_command {
             ip: None,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher.launch_args.iter().any(|x| x == "--local"));
@@ -666,7 +785,7 @@ mod run_command {
             ip: None,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert_eq!(config.networks_iter().count(), 1);
@@ -694,7 +813,7 @@ mod run_command {
 #[cfg(test)]
 mod join_command {
     use super::test::FakeNetworkLauncher;
-    use super::{node_commander, NodeSubCommands, LOCAL_NODE_DIR_NAME};
+    use super::{node_commander, NodeSubCommands, OutputFmt, LOCAL_NODE_DIR_NAME};
     use crate::operations::config::Config;
     use crate::operations::node::SN_NODE_EXECUTABLE;
     use assert_fs::prelude::*;
@@ -753,7 +872,7 @@ mod join_command {
             skip_auto_port_forwarding: false,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher
@@ -808,7 +927,7 @@ mod join_command {
             skip_auto_port_forwarding: false,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher.launch_args.iter().any(|x| x == "--node-path"));
@@ -854,7 +973,7 @@ mod join_command {
             skip_auto_port_forwarding: false,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher.launch_args.iter().any(|x| x == "--nodes-dir"));
@@ -901,7 +1020,7 @@ mod join_command {
             skip_auto_port_forwarding: false,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher.launch_args.iter().any(|x| x == "--nodes-dir"));
@@ -947,7 +1066,7 @@ mod join_command {
             skip_auto_port_forwarding: true,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher
@@ -990,7 +1109,7 @@ mod join_command {
             skip_auto_port_forwarding: false,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher.launch_args.iter().any(|x| x == "--local-addr"));
@@ -1034,7 +1153,7 @@ mod join_command {
             skip_auto_port_forwarding: false,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher.launch_args.iter().any(|x| x == "--public-addr"));
@@ -1075,7 +1194,7 @@ mod join_command {
             skip_auto_port_forwarding: false,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher.launch_args.iter().any(|x| x == "--clear-data"));
@@ -1115,7 +1234,7 @@ mod join_command {
             skip_auto_port_forwarding: false,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher.launch_args.iter().any(|x| x == "-yyy"));
@@ -1165,7 +1284,7 @@ mod join_command {
             skip_auto_port_forwarding: false,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher
@@ -1228,7 +1347,7 @@ mod join_command {
             skip_auto_port_forwarding: false,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_err());
         let error = result.unwrap_err();
@@ -1273,7 +1392,7 @@ mod join_command {
             skip_auto_port_forwarding: false,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         node_data_dir.assert(predicates::path::is_dir());
@@ -1324,7 +1443,7 @@ mod join_command {
             skip_auto_port_forwarding: false,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_ok());
         assert!(launcher.launch_args.iter().any(|x| x == "--genesis-key"));
@@ -1376,7 +1495,7 @@ mod join_command {
             skip_auto_port_forwarding: false,
         };
 
-        let result = node_commander(Some(cmd), &mut config, &mut launcher).await;
+        let result = node_commander(Some(cmd), &mut config, &mut launcher, OutputFmt::Pretty).await;
 
         assert!(result.is_err());
         let error = result.unwrap_err();
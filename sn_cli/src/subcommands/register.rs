@@ -0,0 +1,359 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    helpers::{
+        display_value, entry_hash_to_string, get_from_arg_or_stdin, get_value_from_arg_or_stdin,
+        notice_dry_run, parse_entry_hash, parse_public_key, public_key_to_hex, serialise_output,
+        ValueFormat,
+    },
+    OutputFmt,
+};
+use color_eyre::{eyre::bail, Result};
+use prettytable::Table;
+use sn_api::{
+    register::{Action, Entry, EntryHash, Policy, PrivatePermissions, PublicPermissions, User},
+    PublicKey, Safe,
+};
+use std::collections::BTreeSet;
+use structopt::StructOpt;
+
+// Type tag used by default for the Registers created
+pub(crate) const DEFAULT_REGISTER_TYPE_TAG: &str = "25000";
+
+#[derive(StructOpt, Debug)]
+pub enum RegisterSubCommands {
+    #[structopt(name = "create")]
+    /// Create a new Register
+    Create {
+        /// The type tag of the Register
+        #[structopt(long = "type-tag", default_value = DEFAULT_REGISTER_TYPE_TAG)]
+        type_tag: u64,
+        /// Create a private Register, only readable by its owner
+        #[structopt(long = "private")]
+        private: bool,
+    },
+    #[structopt(name = "read")]
+    /// Read the latest entries of a Register, or the one with the given hash
+    Read {
+        /// The safe:// URL of the Register. If not provided it will be read from STDIN
+        url: Option<String>,
+        /// The hash of the entry to read, as shown by the `write` and `history` commands
+        #[structopt(long = "hash")]
+        hash: Option<String>,
+        /// Show the values hex encoded instead of as UTF-8
+        #[structopt(long = "hex")]
+        hex: bool,
+    },
+    #[structopt(name = "write")]
+    /// Write an entry to a Register, superseding all its latest entries by default
+    Write {
+        /// The safe:// URL of the Register
+        url: String,
+        /// The value to write. If not provided it will be read from STDIN
+        value: Option<String>,
+        /// How to interpret the value: utf8, hex, or file to read it from a local file
+        #[structopt(long = "value-format", default_value = "utf8")]
+        value_format: ValueFormat,
+        /// The hash of an entry the new one supersedes, instead of all the latest ones.
+        /// Can be repeated to supersede several entries
+        #[structopt(long = "parent", number_of_values = 1)]
+        parents: Vec<String>,
+        /// Don't supersede any entry, the new entry starting a new fork of the Register
+        #[structopt(long = "fork", conflicts_with = "parents")]
+        fork: bool,
+    },
+    #[structopt(name = "history")]
+    /// Show the history of a Register, oldest entries first
+    History {
+        /// The safe:// URL of the Register. If not provided it will be read from STDIN.
+        /// If it has a version hash, the history ends with that entry
+        url: Option<String>,
+        /// Show the values hex encoded instead of as UTF-8
+        #[structopt(long = "hex")]
+        hex: bool,
+    },
+    #[structopt(name = "perms")]
+    /// Show the owner and permissions of a Register, or change them if any option is given
+    Perms {
+        /// The safe:// URL of the Register. If not provided it will be read from STDIN
+        url: Option<String>,
+        /// Public key (hex) of a user to let write to the Register. Can be repeated
+        #[structopt(long = "add-writer", number_of_values = 1)]
+        add_writers: Vec<String>,
+        /// Public key (hex) of a user to deny writing to the Register, even if anyone can write
+        /// to it. Can be repeated
+        #[structopt(long = "remove-writer", number_of_values = 1)]
+        remove_writers: Vec<String>,
+        /// Public key (hex) of a user to let read a private Register. Can be repeated
+        #[structopt(long = "grant-read", number_of_values = 1)]
+        grant_read: Vec<String>,
+        /// Public key (hex) of a user to deny reading a private Register. Can be repeated
+        #[structopt(long = "revoke-read", number_of_values = 1)]
+        revoke_read: Vec<String>,
+        /// Public key (hex) of the user to transfer the ownership of the Register to,
+        /// after applying any other change
+        #[structopt(long = "transfer-to")]
+        new_owner: Option<String>,
+    },
+}
+
+// Changes to the permissions of the users of a Register
+struct PermsChanges {
+    add_writers: Vec<PublicKey>,
+    remove_writers: Vec<PublicKey>,
+    grant_read: Vec<PublicKey>,
+    revoke_read: Vec<PublicKey>,
+}
+
+impl PermsChanges {
+    fn is_empty(&self) -> bool {
+        self.add_writers.is_empty()
+            && self.remove_writers.is_empty()
+            && self.grant_read.is_empty()
+            && self.revoke_read.is_empty()
+    }
+
+    fn apply(&self, policy: &mut Policy) -> Result<()> {
+        match policy {
+            Policy::Public(policy) => {
+                if !self.grant_read.is_empty() || !self.revoke_read.is_empty() {
+                    bail!("Anyone can read a public Register, read permissions only apply to private Registers");
+                }
+                for (keys, write) in [(&self.add_writers, true), (&self.remove_writers, false)] {
+                    for key in keys {
+                        let _ = policy
+                            .permissions
+                            .insert(User::Key(*key), PublicPermissions::new(write));
+                    }
+                }
+            }
+            Policy::Private(policy) => {
+                let changes = [
+                    (&self.add_writers, None, Some(true)),
+                    (&self.remove_writers, None, Some(false)),
+                    (&self.grant_read, Some(true), None),
+                    (&self.revoke_read, Some(false), None),
+                ];
+                for (keys, read, write) in changes {
+                    for key in keys {
+                        let current = policy.permissions.get(key).copied();
+                        let allowed =
+                            |action| current.map_or(false, |perms| perms.is_allowed(action));
+                        let read = read.unwrap_or_else(|| allowed(Action::Read));
+                        let write = write.unwrap_or_else(|| allowed(Action::Write));
+                        if read || write {
+                            let _ = policy
+                                .permissions
+                                .insert(*key, PrivatePermissions::new(read, write));
+                        } else {
+                            let _ = policy.permissions.remove(key);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub async fn register_commander(
+    cmd: RegisterSubCommands,
+    output_fmt: OutputFmt,
+    safe: &mut Safe,
+) -> Result<()> {
+    match cmd {
+        RegisterSubCommands::Create { type_tag, private } => {
+            let xorurl = safe.register_create(None, type_tag, private).await?;
+            if OutputFmt::Pretty == output_fmt {
+                if safe.dry_run_mode {
+                    notice_dry_run();
+                }
+                println!("Register created at: \"{}\"", xorurl);
+            } else {
+                println!("{}", serialise_output(&xorurl, output_fmt));
+            }
+            Ok(())
+        }
+        RegisterSubCommands::Read { url, hash, hex } => {
+            let url = get_from_arg_or_stdin(url, None)?;
+            let entries = match hash {
+                Some(hash) => {
+                    let hash = parse_entry_hash(&hash)?;
+                    let entry = safe.register_read_entry(&url, hash).await?;
+                    vec![(hash, entry)].into_iter().collect()
+                }
+                None => safe.register_read(&url).await?,
+            };
+            print_entries(&url, &entries, hex, output_fmt);
+            Ok(())
+        }
+        RegisterSubCommands::Write {
+            url,
+            value,
+            value_format,
+            parents,
+            fork,
+        } => {
+            let entry = get_value_from_arg_or_stdin(value, value_format)?;
+            let hash = if fork || !parents.is_empty() {
+                let parents = parents
+                    .iter()
+                    .map(|hash| parse_entry_hash(hash))
+                    .collect::<Result<BTreeSet<EntryHash>>>()?;
+                safe.register_write(&url, entry, parents).await?
+            } else {
+                safe.register_merge(&url, entry).await?.0
+            };
+
+            let hash = entry_hash_to_string(&hash);
+            if OutputFmt::Pretty == output_fmt {
+                if safe.dry_run_mode {
+                    notice_dry_run();
+                }
+                println!("Entry written to Register at \"{}\": {}", url, hash);
+            } else {
+                println!("{}", serialise_output(&(url, hash), output_fmt));
+            }
+            Ok(())
+        }
+        RegisterSubCommands::History { url, hex } => {
+            let url = get_from_arg_or_stdin(url, None)?;
+            let history = safe.register_history(&url).await?;
+            let history: Vec<_> = history
+                .into_iter()
+                .map(|item| {
+                    let parents: Vec<_> = item.children.iter().map(entry_hash_to_string).collect();
+                    (
+                        entry_hash_to_string(&item.hash),
+                        parents,
+                        display_value(&item.entry, hex),
+                    )
+                })
+                .collect();
+
+            if OutputFmt::Pretty == output_fmt {
+                println!("History of Register at \"{}\":", url);
+                let mut table = Table::new();
+                table.add_row(row![bFg->"Hash", bFg->"Parents", bFg->"Value"]);
+                for (hash, parents, value) in &history {
+                    table.add_row(row![hash, parents.join("\n"), value]);
+                }
+                table.printstd();
+            } else {
+                println!("{}", serialise_output(&(url, history), output_fmt));
+            }
+            Ok(())
+        }
+        RegisterSubCommands::Perms {
+            url,
+            add_writers,
+            remove_writers,
+            grant_read,
+            revoke_read,
+            new_owner,
+        } => {
+            let url = get_from_arg_or_stdin(url, None)?;
+            let parse_keys = |keys: Vec<String>| {
+                keys.iter()
+                    .map(|key| parse_public_key(key))
+                    .collect::<Result<Vec<_>>>()
+            };
+            let changes = PermsChanges {
+                add_writers: parse_keys(add_writers)?,
+                remove_writers: parse_keys(remove_writers)?,
+                grant_read: parse_keys(grant_read)?,
+                revoke_read: parse_keys(revoke_read)?,
+            };
+            let new_owner = new_owner.map(|key| parse_public_key(&key)).transpose()?;
+
+            let mut policy = safe.register_policy(&url).await?;
+            if !changes.is_empty() {
+                changes.apply(&mut policy)?;
+                safe.register_set_policy(&url, policy.clone()).await?;
+            }
+            if let Some(new_owner) = new_owner {
+                safe.register_transfer_ownership(&url, new_owner).await?;
+                policy.set_owner(new_owner);
+            }
+
+            if OutputFmt::Pretty == output_fmt {
+                if safe.dry_run_mode && (!changes.is_empty() || new_owner.is_some()) {
+                    notice_dry_run();
+                }
+                print_policy(&url, &policy);
+            } else {
+                println!("{}", serialise_output(&(url, policy), output_fmt));
+            }
+            Ok(())
+        }
+    }
+}
+
+fn print_policy(url: &str, policy: &Policy) {
+    println!("Permissions of Register at \"{}\":", url);
+    println!("Owner: {}", public_key_to_hex(policy.owner()));
+
+    let mut table = Table::new();
+    table.add_row(row![bFg->"User", bFg->"Read", bFg->"Write"]);
+    let allowed = |allowed: Option<bool>| match allowed {
+        Some(true) => "yes",
+        Some(false) => "no",
+        None => "as anyone",
+    };
+    match policy {
+        Policy::Public(policy) => {
+            for (user, perms) in &policy.permissions {
+                let user = match user {
+                    User::Anyone => "anyone".to_string(),
+                    User::Key(key) => public_key_to_hex(key),
+                };
+                table.add_row(row![
+                    user,
+                    allowed(perms.is_allowed(Action::Read)),
+                    allowed(perms.is_allowed(Action::Write))
+                ]);
+            }
+        }
+        Policy::Private(policy) => {
+            for (key, perms) in &policy.permissions {
+                table.add_row(row![
+                    public_key_to_hex(key),
+                    allowed(Some(perms.is_allowed(Action::Read))),
+                    allowed(Some(perms.is_allowed(Action::Write)))
+                ]);
+            }
+        }
+    }
+    table.printstd();
+}
+
+fn print_entries(
+    url: &str,
+    entries: &BTreeSet<(EntryHash, Entry)>,
+    hex: bool,
+    output_fmt: OutputFmt,
+) {
+    let entries: Vec<_> = entries
+        .iter()
+        .map(|(hash, entry)| (entry_hash_to_string(hash), display_value(entry, hex)))
+        .collect();
+
+    if OutputFmt::Pretty == output_fmt {
+        println!("Entries of Register at \"{}\":", url);
+        let mut table = Table::new();
+        table.add_row(row![bFg->"Hash", bFg->"Value"]);
+        for (hash, value) in &entries {
+            table.add_row(row![hash, value]);
+        }
+        table.printstd();
+    } else {
+        println!("{}", serialise_output(&(url, entries), output_fmt));
+    }
+}
@@ -8,11 +8,14 @@
 // Software.
 
 use super::{
-    helpers::{gen_processed_files_table, get_from_arg_or_stdin, serialise_output, xorname_to_hex},
+    helpers::{
+        gen_processed_files_table, get_from_arg_or_stdin, parse_public_key, serialise_output,
+        xorname_to_hex,
+    },
     OutputFmt,
 };
-use color_eyre::{eyre::eyre, Result};
-use sn_api::{files::FilesMapChange, PublicKey, Safe, SafeUrl, XorName};
+use color_eyre::Result;
+use sn_api::{files::FilesMapChange, Safe, SafeUrl, XorName};
 use structopt::StructOpt;
 
 // Defines subcommands of 'xorurl'
@@ -75,9 +78,7 @@ pub async fn xorurl_commander(
             }
         }
         Some(XorurlSubCommands::Pk { pk }) => {
-            let public_key = PublicKey::ed25519_from_hex(&pk)
-                .or_else(|_| PublicKey::bls_from_hex(&pk))
-                .map_err(|_| eyre!("Invalid (Ed25519/BLS) public key bytes: {}", pk))?;
+            let public_key = parse_public_key(&pk)?;
 
             let xorname = XorName::from(public_key);
             let xorurl = SafeUrl::encode_safekey(xorname, safe.xorurl_base)?;
@@ -11,7 +11,8 @@ use assert_cmd::prelude::*;
 use assert_fs::prelude::*;
 use color_eyre::{eyre::eyre, Report, Result};
 use predicates::prelude::*;
-use sn_api::{SafeUrl, VersionHash};
+use rand::RngCore;
+use sn_api::{SafeUrl, Scope, VersionHash};
 use sn_cmd_test_utilities::util::{
     get_directory_file_count, get_directory_len, get_file_len, get_random_nrs_string,
     mk_emptyfolder, parse_files_container_output, parse_files_put_or_sync_output,
@@ -19,7 +20,12 @@ use sn_cmd_test_utilities::util::{
     safeurl_from, test_symlinks_are_valid, upload_path, upload_test_symlinks_folder,
     upload_testfolder_trailing_slash, CLI, SAFE_PROTOCOL,
 };
-use std::{path::Path, process::Command, str::FromStr};
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    str::FromStr,
+    time::{Duration, Instant},
+};
 
 const PRETTY_FILES_CREATION_RESPONSE: &str = "FilesContainer created at: ";
 const TEST_FILE: &str = "../resources/testdata/test.md";
@@ -54,6 +60,134 @@ fn calling_safe_files_put() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn calling_safe_files_put_resume() -> Result<()> {
+    // with no previous upload to resume, the files are simply uploaded
+    let content = safe_cmd_stdout(["files", "put", TEST_FILE, "--resume", "--json"], Some(0))?;
+    let (_, processed_files) = parse_files_put_or_sync_output(&content)?;
+    let first_link = processed_files[Path::new(TEST_FILE)]
+        .link()
+        .ok_or_else(|| eyre!("Missing xorurl link of uploaded test file"))?;
+
+    // resuming an upload which already completed results in the same link
+    let content = safe_cmd_stdout(["files", "put", TEST_FILE, "--resume", "--json"], Some(0))?;
+    let (_, processed_files) = parse_files_put_or_sync_output(&content)?;
+    let second_link = processed_files[Path::new(TEST_FILE)]
+        .link()
+        .ok_or_else(|| eyre!("Missing xorurl link of uploaded test file"))?;
+
+    assert_eq!(first_link, second_link);
+    Ok(())
+}
+
+#[test]
+fn calling_safe_files_put_private() -> Result<()> {
+    let content = safe_cmd_stdout(["files", "put", TEST_FILE, "--private", "--json"], Some(0))?;
+    let (container_xorurl, processed_files) = parse_files_put_or_sync_output(&content)?;
+    assert_eq!(
+        SafeUrl::from_url(&container_xorurl)?.scope(),
+        Scope::Private
+    );
+    let link = processed_files[Path::new(TEST_FILE)]
+        .link()
+        .ok_or_else(|| eyre!("Missing xorurl link of uploaded test file"))?;
+    assert_eq!(SafeUrl::from_url(link)?.scope(), Scope::Private);
+
+    // the owner can read the file back, through the container too
+    let contents = std::fs::read_to_string(TEST_FILE)?;
+    let file_cat = safe_cmd_stdout(["cat", link], Some(0))?;
+    assert_eq!(file_cat, contents);
+    let mut safeurl = safeurl_from(&container_xorurl)?;
+    safeurl.set_path("/test.md");
+    let file_cat = safe_cmd_stdout(["cat", &safeurl.to_string()], Some(0))?;
+    assert_eq!(file_cat, contents);
+    Ok(())
+}
+
+#[test]
+fn calling_safe_files_put_private_dry_run() -> Result<()> {
+    // the address of private files depends on the keys they're encrypted with
+    let output = safe_cmd_stderr(
+        ["files", "put", TEST_FILE, "--private", "--dry-run"],
+        Some(1),
+    )?;
+    assert!(output.contains("'--private' can't be used in dry-run mode"));
+    Ok(())
+}
+
+#[test]
+fn calling_safe_files_put_resume_interrupted_upload() -> Result<()> {
+    // large enough to be sent in several batches of chunks
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let file = tmp_data_dir.child("large_file.bin");
+    let mut content = vec![0; 20 * 1024 * 1024];
+    rand::thread_rng().fill_bytes(&mut content);
+    file.write_binary(&content)?;
+    let path = file.path().display().to_string();
+
+    // the link of public content doesn't depend on the client uploading it
+    let content = safe_cmd_stdout(["files", "put", &path, "--dry-run", "--json"], Some(0))?;
+    let (_, processed_files) = parse_files_put_or_sync_output(&content)?;
+    let expected_link = processed_files[file.path()]
+        .link()
+        .ok_or_else(|| eyre!("Missing xorurl link of the large file"))?
+        .clone();
+
+    // interrupt the upload once some of the chunks were stored, as per its journal
+    let mut upload = Command::cargo_bin(CLI)
+        .map_err(|e| eyre!(e.to_string()))?
+        .args(&["files", "put", &path])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let journal = wait_for_upload_journal_update(file.path())?;
+    upload.kill()?;
+    let _ = upload.wait()?;
+    assert!(
+        journal.exists(),
+        "the upload completed before being interrupted"
+    );
+
+    let content = safe_cmd_stdout(["files", "put", &path, "--resume", "--json"], Some(0))?;
+    let (_, processed_files) = parse_files_put_or_sync_output(&content)?;
+    let link = processed_files[file.path()]
+        .link()
+        .ok_or_else(|| eyre!("Missing xorurl link of the resumed upload"))?;
+
+    assert_eq!(link, &expected_link);
+    assert!(!journal.exists());
+    Ok(())
+}
+
+// Waits for the journal of an upload of `source` to record stored chunks, returning its path.
+fn wait_for_upload_journal_update(source: &Path) -> Result<PathBuf> {
+    let journals_dir = dirs_next::home_dir()
+        .ok_or_else(|| eyre!("Home directory not found"))?
+        .join(".safe/client/root_dir/upload_journals");
+    let source = source.canonicalize()?.display().to_string();
+    let start = Instant::now();
+
+    let mut created = None;
+    while start.elapsed() < Duration::from_secs(120) {
+        for entry in std::fs::read_dir(&journals_dir).into_iter().flatten() {
+            let journal = entry?.path();
+            let content = std::fs::read(&journal).unwrap_or_default();
+            if !String::from_utf8_lossy(&content).contains(&source) {
+                continue;
+            }
+            // the journal grows as the chunks stored are recorded
+            match created {
+                Some(size) if content.len() > size => return Ok(journal),
+                Some(_) => {}
+                None => created = Some(content.len()),
+            }
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    Err(eyre!("No chunks of {} were recorded as stored", source))
+}
+
 #[test]
 #[ignore = "dry_run"]
 fn calling_safe_files_put_dry_run() -> Result<()> {
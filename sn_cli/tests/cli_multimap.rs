@@ -0,0 +1,147 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use assert_cmd::prelude::*;
+use color_eyre::{eyre::eyre, Result};
+use predicates::prelude::*;
+use sn_cmd_test_utilities::util::{
+    parse_entry_written_output, parse_multimap_get_output, safe_cmd, safe_cmd_stdout,
+};
+
+fn create_multimap() -> Result<String> {
+    let output = safe_cmd_stdout(["multimap", "create", "--json"], Some(0))?;
+    serde_json::from_str(&output).map_err(|_| {
+        eyre!(
+            "Failed to parse output of `safe multimap create`: {}",
+            output
+        )
+    })
+}
+
+fn insert_into_multimap(url: &str, args: &[&str]) -> Result<String> {
+    let mut cmd_args = vec!["multimap", "insert", url, "--json"];
+    cmd_args.extend(args);
+    let output = safe_cmd_stdout(cmd_args, Some(0))?;
+    let (_, hash) = parse_entry_written_output(&output)?;
+    Ok(hash)
+}
+
+///
+/// `multimap create` subcommand
+///
+
+#[test]
+fn multimap_create_should_create_a_multimap() -> Result<()> {
+    safe_cmd(["multimap", "create", "--private"], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains("Multimap created at: \"safe://"));
+    Ok(())
+}
+
+///
+/// `multimap insert` and `multimap get` subcommands
+///
+
+#[test]
+fn multimap_insert_should_replace_the_values_of_the_key() -> Result<()> {
+    let url = create_multimap()?;
+    let first_hash = insert_into_multimap(&url, &["key", "first value"])?;
+    let second_hash = insert_into_multimap(&url, &["key", "second value"])?;
+    let other_hash = insert_into_multimap(&url, &["other", "other value"])?;
+
+    let output = safe_cmd_stdout(["multimap", "get", &url, "key", "--json"], Some(0))?;
+    let (_, entries) = parse_multimap_get_output(&output)?;
+    assert_eq!(
+        entries,
+        vec![(second_hash, "key".to_string(), "second value".to_string())]
+    );
+
+    // the replaced value can still be read by its hash
+    let output = safe_cmd_stdout(
+        ["multimap", "get", &url, "--hash", &first_hash, "--json"],
+        Some(0),
+    )?;
+    let (_, entries) = parse_multimap_get_output(&output)?;
+    assert_eq!(
+        entries,
+        vec![(first_hash, "key".to_string(), "first value".to_string())]
+    );
+
+    let output = safe_cmd_stdout(["multimap", "get", &url, "other", "--json"], Some(0))?;
+    let (_, entries) = parse_multimap_get_output(&output)?;
+    assert_eq!(
+        entries,
+        vec![(other_hash, "other".to_string(), "other value".to_string())]
+    );
+    Ok(())
+}
+
+#[test]
+fn multimap_insert_should_support_hex_keys_and_values() -> Result<()> {
+    let url = create_multimap()?;
+    let hash = insert_into_multimap(
+        &url,
+        &["0102", "ff", "--key-format", "hex", "--value-format", "hex"],
+    )?;
+
+    let output = safe_cmd_stdout(
+        [
+            "multimap",
+            "get",
+            &url,
+            "0102",
+            "--key-format",
+            "hex",
+            "--hex",
+            "--json",
+        ],
+        Some(0),
+    )?;
+    let (_, entries) = parse_multimap_get_output(&output)?;
+    assert_eq!(entries, vec![(hash, "0102".to_string(), "ff".to_string())]);
+    Ok(())
+}
+
+///
+/// `multimap remove` subcommand
+///
+
+#[test]
+fn multimap_remove_should_remove_the_values_of_the_key() -> Result<()> {
+    let url = create_multimap()?;
+    let _ = insert_into_multimap(&url, &["key", "value"])?;
+    let other_hash = insert_into_multimap(&url, &["other", "other value"])?;
+
+    safe_cmd(["multimap", "remove", &url, "key"], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains("Entry removed from Multimap at"));
+
+    let output = safe_cmd_stdout(["multimap", "get", &url, "key", "--json"], Some(0))?;
+    let (_, entries) = parse_multimap_get_output(&output)?;
+    assert!(entries.is_empty());
+
+    let output = safe_cmd_stdout(["multimap", "get", &url, "other", "--json"], Some(0))?;
+    let (_, entries) = parse_multimap_get_output(&output)?;
+    assert_eq!(
+        entries,
+        vec![(other_hash, "other".to_string(), "other value".to_string())]
+    );
+    Ok(())
+}
+
+#[test]
+fn multimap_remove_should_fail_for_a_missing_key() -> Result<()> {
+    let url = create_multimap()?;
+    let _ = insert_into_multimap(&url, &["key", "value"])?;
+
+    safe_cmd(["multimap", "remove", &url, "missing"], Some(1))?
+        .assert()
+        .stderr(predicate::str::contains("No values found for the key"));
+    Ok(())
+}
@@ -0,0 +1,227 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use color_eyre::{eyre::eyre, Result};
+use predicates::prelude::*;
+use rand::rngs::OsRng;
+use sn_api::{
+    register::{Action, Policy},
+    Keypair, PublicKey, SafeUrl,
+};
+use sn_cmd_test_utilities::util::{
+    parse_entry_written_output, parse_register_entries_output, parse_register_history_output,
+    safe_cmd, safe_cmd_stdout,
+};
+
+fn create_register(private: bool) -> Result<String> {
+    let mut args = vec!["register", "create", "--json"];
+    if private {
+        args.push("--private");
+    }
+    let output = safe_cmd_stdout(args, Some(0))?;
+    serde_json::from_str(&output).map_err(|_| {
+        eyre!(
+            "Failed to parse output of `safe register create`: {}",
+            output
+        )
+    })
+}
+
+fn write_register(url: &str, args: &[&str]) -> Result<String> {
+    let mut cmd_args = vec!["register", "write", url, "--json"];
+    cmd_args.extend(args);
+    let output = safe_cmd_stdout(cmd_args, Some(0))?;
+    let (_, hash) = parse_entry_written_output(&output)?;
+    Ok(hash)
+}
+
+///
+/// `register create` subcommand
+///
+
+#[test]
+fn register_create_should_create_a_register() -> Result<()> {
+    safe_cmd(["register", "create"], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains("Register created at: \"safe://"));
+    Ok(())
+}
+
+#[test]
+fn register_create_should_create_a_private_register_with_the_type_tag() -> Result<()> {
+    let output = safe_cmd_stdout(
+        [
+            "register",
+            "create",
+            "--private",
+            "--type-tag",
+            "30000",
+            "--json",
+        ],
+        Some(0),
+    )?;
+    let url: String = serde_json::from_str(&output)?;
+    let url = SafeUrl::from_url(&url)?;
+    assert_eq!(url.type_tag(), 30_000);
+    Ok(())
+}
+
+///
+/// `register write` and `register read` subcommands
+///
+
+#[test]
+fn register_write_should_supersede_the_latest_entries() -> Result<()> {
+    let url = create_register(false)?;
+    let first_hash = write_register(&url, &["first value"])?;
+    let second_hash = write_register(&url, &["second value"])?;
+
+    let output = safe_cmd_stdout(["register", "read", &url, "--json"], Some(0))?;
+    let (_, entries) = parse_register_entries_output(&output)?;
+    assert_eq!(entries, vec![(second_hash, "second value".to_string())]);
+
+    // the superseded entry can still be read by its hash
+    let output = safe_cmd_stdout(
+        ["register", "read", &url, "--hash", &first_hash, "--json"],
+        Some(0),
+    )?;
+    let (_, entries) = parse_register_entries_output(&output)?;
+    assert_eq!(entries, vec![(first_hash, "first value".to_string())]);
+    Ok(())
+}
+
+#[test]
+fn register_write_should_fork_the_register() -> Result<()> {
+    let url = create_register(true)?;
+    let first_hash = write_register(&url, &["first value"])?;
+    let second_hash = write_register(&url, &["second value", "--fork"])?;
+
+    let output = safe_cmd_stdout(["register", "read", &url, "--json"], Some(0))?;
+    let (_, entries) = parse_register_entries_output(&output)?;
+    assert_eq!(entries.len(), 2);
+    assert!(entries.contains(&(first_hash, "first value".to_string())));
+    assert!(entries.contains(&(second_hash, "second value".to_string())));
+    Ok(())
+}
+
+#[test]
+fn register_write_should_support_hex_and_file_values() -> Result<()> {
+    let url = create_register(false)?;
+    let hex_hash = write_register(&url, &["00ff", "--value-format", "hex"])?;
+
+    let output = safe_cmd_stdout(["register", "read", &url, "--hex", "--json"], Some(0))?;
+    let (_, entries) = parse_register_entries_output(&output)?;
+    assert_eq!(entries, vec![(hex_hash, "00ff".to_string())]);
+
+    let tmp_dir = assert_fs::TempDir::new()?;
+    let file = tmp_dir.child("value.txt");
+    file.write_str("value from a file")?;
+    let file_path = file.path().display().to_string();
+    let file_hash = write_register(&url, &[&file_path, "--value-format", "file"])?;
+
+    let output = safe_cmd_stdout(["register", "read", &url, "--json"], Some(0))?;
+    let (_, entries) = parse_register_entries_output(&output)?;
+    assert_eq!(entries, vec![(file_hash, "value from a file".to_string())]);
+    Ok(())
+}
+
+#[test]
+fn register_write_should_fail_with_an_invalid_parent() -> Result<()> {
+    let url = create_register(false)?;
+    safe_cmd(
+        ["register", "write", &url, "value", "--parent", "invalid"],
+        Some(1),
+    )?
+    .assert()
+    .stderr(predicate::str::contains("Invalid entry hash 'invalid'"));
+    Ok(())
+}
+
+///
+/// `register history` subcommand
+///
+
+#[test]
+fn register_history_should_list_all_the_entries() -> Result<()> {
+    let url = create_register(false)?;
+    let first_hash = write_register(&url, &["first value"])?;
+    let second_hash = write_register(&url, &["second value"])?;
+
+    let output = safe_cmd_stdout(["register", "history", &url, "--json"], Some(0))?;
+    let (_, history) = parse_register_history_output(&output)?;
+    assert_eq!(
+        history,
+        vec![
+            (first_hash.clone(), vec![], "first value".to_string()),
+            (second_hash, vec![first_hash], "second value".to_string()),
+        ]
+    );
+    Ok(())
+}
+
+// `register perms` subcommand
+
+fn register_perms(url: &str, args: &[&str]) -> Result<Policy> {
+    let mut cmd_args = vec!["register", "perms", url, "--json"];
+    cmd_args.extend(args);
+    let output = safe_cmd_stdout(cmd_args, Some(0))?;
+    let (_, policy): (String, Policy) = serde_json::from_str(&output).map_err(|_| {
+        eyre!(
+            "Failed to parse output of `safe register perms`: {}",
+            output
+        )
+    })?;
+    Ok(policy)
+}
+
+fn new_public_key() -> (PublicKey, String) {
+    let public_key = Keypair::new_ed25519(&mut OsRng).public_key();
+    let hex = hex::encode(public_key.to_bytes());
+    (public_key, hex)
+}
+
+#[test]
+fn register_perms_should_grant_read_and_write_access() -> Result<()> {
+    let url = create_register(true)?;
+    let (reader, reader_hex) = new_public_key();
+    let (writer, writer_hex) = new_public_key();
+
+    let policy = register_perms(
+        &url,
+        &["--grant-read", &reader_hex, "--add-writer", &writer_hex],
+    )?;
+    match policy {
+        Policy::Private(policy) => {
+            assert!(policy.permissions[&reader].is_allowed(Action::Read));
+            assert!(!policy.permissions[&reader].is_allowed(Action::Write));
+            assert!(policy.permissions[&writer].is_allowed(Action::Write));
+        }
+        Policy::Public(_) => return Err(eyre!("Policy of a private Register is public")),
+    }
+
+    // the changes are kept on the network
+    let policy = register_perms(&url, &[])?;
+    assert!(matches!(
+        &policy,
+        Policy::Private(policy) if policy.permissions.contains_key(&reader)
+    ));
+    Ok(())
+}
+
+#[test]
+fn register_perms_should_transfer_the_ownership() -> Result<()> {
+    let url = create_register(false)?;
+    let (new_owner, new_owner_hex) = new_public_key();
+
+    let policy = register_perms(&url, &["--transfer-to", &new_owner_hex])?;
+    assert_eq!(*policy.owner(), new_owner);
+    Ok(())
+}
@@ -337,6 +337,37 @@ pub mod util {
             .map_err(|_| eyre!("Failed to parse output of `safe keys create`: {}", output))
     }
 
+    pub fn parse_register_entries_output(output: &str) -> Result<(String, Vec<(String, String)>)> {
+        serde_json::from_str(output)
+            .map_err(|_| eyre!("Failed to parse output of `safe register read`: {}", output))
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn parse_register_history_output(
+        output: &str,
+    ) -> Result<(String, Vec<(String, Vec<String>, String)>)> {
+        serde_json::from_str(output).map_err(|_| {
+            eyre!(
+                "Failed to parse output of `safe register history`: {}",
+                output
+            )
+        })
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn parse_multimap_get_output(
+        output: &str,
+    ) -> Result<(String, Vec<(String, String, String)>)> {
+        serde_json::from_str(output)
+            .map_err(|_| eyre!("Failed to parse output of `safe multimap get`: {}", output))
+    }
+
+    // Parses the output of the commands writing to a Register or Multimap
+    pub fn parse_entry_written_output(output: &str) -> Result<(String, String)> {
+        serde_json::from_str(output)
+            .map_err(|_| eyre!("Failed to parse output of entry written: {}", output))
+    }
+
     /// Runs safe with the arguments specified, with the option to assert on the exit code.
     ///
     /// This was changed to use the assert_cmd crate because the newer version of this crate